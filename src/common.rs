@@ -7,6 +7,7 @@
 //! As the different phases should be isolated as much as possible, this module should be kept small
 //! to avoid heavy coupling.
 
+pub mod diagnostics;
 pub mod multiphase;
 pub mod newlines;
 pub mod peekable_buffer;
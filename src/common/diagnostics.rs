@@ -0,0 +1,76 @@
+//! Problems found in a source, in one form whichever phase found them, so that
+//! tooling can report the lexer's, the parser's, and the lints' problems as a
+//! single stream.
+
+use std::fmt;
+
+use crate::source::Span;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Severity {
+    /// The source can't be compiled.
+    Error,
+
+    /// The source compiles, but probably doesn't do what was meant, as with
+    /// the lints.
+    Warning,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+
+    /// `None` if the problem isn't tied to anywhere in particular, such as the
+    /// lexer thread failing to start.
+    pub span: Option<Span>,
+
+    /// A short, stable name for the kind of problem, such as
+    /// `unexpected-token`, for tooling to filter or look up by.
+    pub code: &'static str,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+            code,
+        }
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+            code,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// As `line:column: severity: message [code]`, leaving off the position if
+/// there's no span.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = self.span {
+            write!(
+                f,
+                "{}:{}: ",
+                span.start.line(),
+                span.start.character_position_in_line()
+            )?;
+        }
+        write!(f, "{}: {} [{}]", self.severity, self.message, self.code)
+    }
+}
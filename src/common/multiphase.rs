@@ -3,8 +3,11 @@
 //! For example, string literals are passed unaltered between the lexer,
 //! the parser, and compiler, and the runtime.
 
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+use crate::source::{Position, Span};
+
 macro_rules! multiphase_string_types {
     ( $( $type: ident ),* ) => {
         $(
@@ -26,22 +29,260 @@ macro_rules! multiphase_string_types {
     }
 }
 
-multiphase_string_types![Identifier, Shebang, SylanString, SyDoc];
+multiphase_string_types![Shebang, SylanString, SyDoc];
+
+/// A name, along with where it was written if it was parsed from a source.
+///
+/// Where it was written plays no part in comparing or hashing it, as the same
+/// name written in two places is still the same name. An identifier is always
+/// a single token, so only its start is kept to keep identifiers small.
+#[derive(Clone, Debug)]
+pub struct Identifier(pub Arc<String>, pub Option<Position>);
+
+impl Identifier {
+    pub fn span(&self) -> Option<Span> {
+        self.1.map(|position| Span {
+            start: position,
+            end: position,
+        })
+    }
+
+    pub fn at(self, position: Position) -> Self {
+        Identifier(self.0, Some(position))
+    }
+}
+
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Identifier) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Identifier {}
+
+impl Hash for Identifier {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl From<String> for Identifier {
+    fn from(string: String) -> Self {
+        Identifier(Arc::new(string), None)
+    }
+}
+
+impl From<&'static str> for Identifier {
+    fn from(string: &'static str) -> Self {
+        Self::from(string.to_owned())
+    }
+}
 
 /// Interpolations are interleaved with string fragments, ready to be glued
-/// together when the runtime knows what the interpolated identifiers resolve
+/// together when the runtime knows what the interpolated expressions evaluate
 /// to.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct InterpolatedString {
     pub string_fragments: Vec<String>,
-    pub interpolations: Vec<Identifier>,
+    pub interpolations: Vec<Interpolation>,
+}
+
+/// The expression between an interpolation's braces, as in the `a + 1` of
+/// `$"{a + 1} items"`. The lexer can't parse expressions, so it keeps the
+/// source written between the braces for the parser to parse later. `offset`
+/// is where that source starts, in characters from the start of the string's
+/// contents, so that errors in it can be pinpointed.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Interpolation {
+    pub source: String,
+    pub offset: usize,
+}
+
+impl Interpolation {
+    pub fn new(source: impl Into<String>, offset: usize) -> Self {
+        Self {
+            source: source.into(),
+            offset,
+        }
+    }
 }
 
 // TODO: implement properly with a multiprecision library.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Number(pub i64, pub u64);
 
+/// A hexadecimal floating-point literal such as `0x1.8p3`, i.e. a hexadecimal
+/// mantissa scaled by a power of two. It's kept as that mantissa and exponent,
+/// rather than converted to a decimal [Number], so that the float it denotes is
+/// exactly the one that was written.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HexFloat {
+    pub negative: bool,
+    pub mantissa: u64,
+    pub exponent: i32,
+}
+
+impl HexFloat {
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.mantissa as f64 * 2f64.powi(self.exponent);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+/// A decimal literal in scientific notation such as `1.5e3`, i.e. a decimal
+/// significand scaled by a power of ten. Like [HexFloat], it's kept as written
+/// rather than converted, with the digits after any decimal point moved into
+/// the significand, so `1.5e3` has a significand of 15 and an exponent of 2.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Scientific {
+    pub negative: bool,
+    pub significand: u64,
+    pub exponent: i32,
+}
+
+impl Scientific {
+    pub fn to_f64(&self) -> f64 {
+        // Parsing rounds correctly, whereas multiplying by a power of ten
+        // could round twice.
+        let magnitude = format!("{}e{}", self.significand, self.exponent)
+            .parse::<f64>()
+            .unwrap_or(f64::NAN);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+/// The explicit type of a numeric literal, given as a suffix such as the `u8`
+/// in `255u8`. Unsuffixed literals leave their type to be inferred.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NumericType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+}
+
+impl NumericType {
+    pub const ALL: [NumericType; 10] = [
+        NumericType::I8,
+        NumericType::I16,
+        NumericType::I32,
+        NumericType::I64,
+        NumericType::U8,
+        NumericType::U16,
+        NumericType::U32,
+        NumericType::U64,
+        NumericType::F32,
+        NumericType::F64,
+    ];
+
+    pub fn from_suffix(suffix: &str) -> Option<NumericType> {
+        NumericType::ALL
+            .iter()
+            .copied()
+            .find(|numeric_type| numeric_type.suffix() == suffix)
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            NumericType::I8 => "i8",
+            NumericType::I16 => "i16",
+            NumericType::I32 => "i32",
+            NumericType::I64 => "i64",
+            NumericType::U8 => "u8",
+            NumericType::U16 => "u16",
+            NumericType::U32 => "u32",
+            NumericType::U64 => "u64",
+            NumericType::F32 => "f32",
+            NumericType::F64 => "f64",
+        }
+    }
+
+    pub fn is_floating(self) -> bool {
+        matches!(self, NumericType::F32 | NumericType::F64)
+    }
+
+    /// The numeric type of the standard library's numeric classes, such as
+    /// `Int32` or `Byte`.
+    pub fn from_type_name(name: &str) -> Option<NumericType> {
+        Some(match name {
+            "Int8" => NumericType::I8,
+            "Short" => NumericType::I16,
+            "Int32" => NumericType::I32,
+            "Long" => NumericType::I64,
+            "Byte" => NumericType::U8,
+            "UInt16" => NumericType::U16,
+            "UInt32" => NumericType::U32,
+            "UInt64" => NumericType::U64,
+            "Float" => NumericType::F32,
+            "Double" => NumericType::F64,
+            _ => return None,
+        })
+    }
+
+    /// The smallest and largest values of an integer type, or `None` for
+    /// floating types. `u64` is capped to what a `Number` can currently hold.
+    pub fn integer_range(self) -> Option<(i64, i64)> {
+        Some(match self {
+            NumericType::I8 => (i8::MIN.into(), i8::MAX.into()),
+            NumericType::I16 => (i16::MIN.into(), i16::MAX.into()),
+            NumericType::I32 => (i32::MIN.into(), i32::MAX.into()),
+            NumericType::I64 => (i64::MIN, i64::MAX),
+            NumericType::U8 => (0, u8::MAX.into()),
+            NumericType::U16 => (0, u16::MAX.into()),
+            NumericType::U32 => (0, u32::MAX.into()),
+            NumericType::U64 => (0, i64::MAX),
+            NumericType::F32 | NumericType::F64 => return None,
+        })
+    }
+
+    /// Check that a literal number fits in this type, describing why not
+    /// otherwise. Integer types only accept whole numbers within their range;
+    /// floating types accept any number.
+    pub fn check_bounds(self, number: &Number) -> Result<(), String> {
+        let (min, max) = match self.integer_range() {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+        let Number(real, fractional) = *number;
+        if fractional != 0 {
+            Err(format!(
+                "{}.{} is not a whole number, which {} requires",
+                real,
+                fractional,
+                self.suffix()
+            ))
+        } else if real < min || max < real {
+            Err(format!(
+                "{} is out of range for {}, which holds {} to {}",
+                real,
+                self.suffix(),
+                min,
+                max
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// How widely an item can be seen, ordered from least to most open, so an
+/// exported item's signature can be checked for mentioning anything less
+/// visible than the item with `signature_type < item`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Accessibility {
     Private,
     Internal,
@@ -71,6 +312,12 @@ pub enum OverloadableInfixOperator {
     // See: https://docs.microsoft.com/en-us/dotnet/fsharp/language-reference/functions/#function-composition-and-pipelining
     Compose,
 
+    // Whole division between two integers, truncating towards zero so that
+    // `7 / 2` is `3`, and decimal division if either side is a decimal, so
+    // that `7 / 2f64` is `3.5`. Python's `//` for whole division isn't an
+    // option, as it starts a comment. Dividing by zero fails either way,
+    // rather than yielding an infinity or a NaN. `Modulo` follows the same
+    // rules.
     Divide,
     Equals,
     GreaterThan,
@@ -185,6 +432,10 @@ pub enum OverloadableSliceOperator {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum PostfixOperator {
     Bind,
+
+    /// `a!` yields the value within the optional `a`, failing if `a` is
+    /// `Null`.
+    ForceUnwrap,
 }
 
 /// They act as symbols but have two distinguishing properties:
@@ -222,3 +473,37 @@ pub enum PseudoIdentifier {
     /// transforms an invocation into a partial application.
     PlaceholderIdentifier,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessibility_orders_by_openness() {
+        let mut levels = vec![
+            Accessibility::Public,
+            Accessibility::Private,
+            Accessibility::Internal,
+        ];
+        levels.sort();
+        assert_eq!(
+            levels,
+            vec![
+                Accessibility::Private,
+                Accessibility::Internal,
+                Accessibility::Public,
+            ]
+        );
+
+        // A public item can only expose types at least as visible as itself.
+        let item = Accessibility::Public;
+        let exposes = |r#type: Accessibility| item <= r#type;
+        assert!(exposes(Accessibility::Public));
+        assert!(!exposes(Accessibility::Internal));
+        assert!(!exposes(Accessibility::Private));
+        assert_eq!(
+            Accessibility::Internal.max(Accessibility::Private),
+            Accessibility::Internal
+        );
+    }
+}
@@ -26,7 +26,7 @@ where
 
     /// Get an immutable view of the `n`th next element in the buffer, where `n` is zero indexed.
     fn peek_nth(&mut self, n: usize) -> Option<&T> {
-        self.peek_many(n).and_then(|tokens| tokens.last())
+        self.peek_many(n + 1).and_then(|tokens| tokens.last())
     }
 
     /// Consume an item from the buffer and return it.
@@ -1,6 +1,6 @@
 /// Versioning across Sylan is done consistently with [Semantic Versioning](https://semver.org), aka
 /// "semver".
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Version {
     pub major: u64,
     pub minor: u64,
@@ -0,0 +1,1836 @@
+//! # Sylan's interpreter
+//!
+//! The interpreter is intended to eventually run Sylan IL, as outlined in `main.rs`. Until the IL
+//! stage exists, it instead walks the AST directly, once whatever simplification exists so far has
+//! been applied. That's enough to drive a REPL and to try out the language's semantics, but only a
+//! subset of the language is supported so far; anything else fails with an error saying so rather
+//! than misbehaving.
+//!
+//! Values live in [Scope]s, which chain outwards to the root scope. The root scope holds the
+//! builtins and, in a REPL, everything bound at the prompt.
+//!
+//! Being a tree-walker, each Sylan call nests several calls in the interpreter itself. Calls are
+//! therefore limited to a maximum depth, failing with a stack overflow error rather than
+//! overflowing the host's own stack and taking the whole process down with it.
+//!
+//! A `for` loop is reiterated by calling its label or `continue`, which unwinds back to the loop as
+//! an error. The loop catches it and starts its next iteration without nesting any deeper.
+//!
+//! There's only ever one process, so a `select` only sees messages [Interpreter::send]s queued
+//! before it runs. If none of them match, nothing else can run to send one while it waits, so it
+//! waits out its timeout on the interpreter's [Clock] and then runs the timeout's body.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+use std::result;
+use std::thread;
+use std::time::Duration;
+
+use crate::common::multiphase::{
+    Identifier, InterpolatedString, Number, NumericType, OverloadableInfixOperator,
+    PostfixOperator, PseudoIdentifier,
+};
+use crate::intepreter::scope::{Cleanup, Scope};
+use crate::intepreter::values::{Builtin, Closure, Value};
+use crate::parsing::nodes::{
+    Binding, Block, BranchingAndJumping, CallArguments, Case, Expression, For, Item, Literal,
+    MainFile, Node, Operator, Pattern, PatternItem, Select, SlicePattern, Symbol, SymbolLookup,
+    Throw, TypeReference, WhileVar,
+};
+use crate::parsing::parse_expression;
+use crate::simplification;
+use crate::source::Position;
+
+pub mod scope;
+pub mod values;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Described(String),
+
+    /// A value thrown with `throw` that nothing caught, along with the value
+    /// given as its cause, if any.
+    Thrown {
+        value: Value,
+        cause: Option<Value>,
+    },
+
+    /// Calls were nested more deeply than the interpreter's maximum depth.
+    StackOverflow {
+        max_depth: usize,
+    },
+
+    /// A `for` loop's next iteration was requested, unwinding back to that
+    /// loop to start it.
+    Reiterate {
+        loop_id: usize,
+        arguments: Vec<Value>,
+    },
+
+    /// A `break`, unwinding to the innermost block with its label, which then
+    /// yields its value.
+    Break {
+        label: Identifier,
+        value: Value,
+    },
+
+    /// A `switch` arm's `fallthrough`, unwinding to the `switch` to run its
+    /// next arm.
+    Fallthrough,
+
+    /// A `???` was evaluated, at the position it was written if known.
+    ReachedHole(Option<Position>),
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Described(description) => write!(f, "{}", description),
+            Error::Thrown { value, cause } => {
+                write!(f, "uncaught throw of {}", value)?;
+                match cause {
+                    Some(cause) => write!(f, ", caused by {}", cause),
+                    None => Ok(()),
+                }
+            }
+            Error::StackOverflow { max_depth } => {
+                write!(
+                    f,
+                    "stack overflow: calls nested more than {} deep",
+                    max_depth
+                )
+            }
+            Error::Reiterate { .. } => write!(f, "continue called outside of its loop"),
+            Error::Break { label, .. } => {
+                write!(f, "break {} called outside of its labelled block", label.0)
+            }
+            Error::Fallthrough => write!(f, "fallthrough used outside of a switch arm"),
+            Error::ReachedHole(Some(position)) => write!(
+                f,
+                "reached a hole at {}:{}",
+                position.line(),
+                position.character_position_in_line()
+            ),
+            Error::ReachedHole(None) => write!(f, "reached a hole"),
+        }
+    }
+}
+
+fn fail<T>(description: impl Into<String>) -> Result<T> {
+    Err(Error::Described(description.into()))
+}
+
+fn unsupported<T>(feature: &str) -> Result<T> {
+    fail(format!(
+        "{} aren't supported by the interpreter yet",
+        feature
+    ))
+}
+
+fn println(arguments: Vec<Value>) -> Result<Value> {
+    let line = arguments
+        .iter()
+        .map(|argument| match argument {
+            Value::String(string) => string.clone(),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!("{}", line);
+    Ok(Value::Void)
+}
+
+fn number(Number(real, fractional): &Number, numeric_type: Option<NumericType>) -> Result<Value> {
+    let is_floating = matches!(numeric_type, Some(numeric_type) if numeric_type.is_floating());
+    if *fractional == 0 && !is_floating {
+        Ok(Value::Integer(*real))
+    } else {
+        format!("{}.{}", real, fractional)
+            .parse()
+            .map(Value::Decimal)
+            .or_else(|_| fail(format!("invalid number: {}.{}", real, fractional)))
+    }
+}
+
+fn boolean(value: Value, context: &str) -> Result<bool> {
+    match value {
+        Value::Boolean(boolean) => Ok(boolean),
+        other => fail(format!(
+            "{} must be a Boolean, not a {}",
+            context,
+            other.type_name()
+        )),
+    }
+}
+
+/// Integers stay integers unless combined with a decimal, so division between two integers
+/// truncates. Dividing by zero fails for decimals too, instead of yielding an infinity or a NaN.
+fn arithmetic(operator: &OverloadableInfixOperator, left: Value, right: Value) -> Result<Value> {
+    use OverloadableInfixOperator::*;
+
+    let overflow = || Error::Described("integer overflow".to_owned());
+    match (left, right) {
+        (Value::Integer(left), Value::Integer(right)) => match operator {
+            Add => left.checked_add(right).ok_or_else(overflow),
+            Subtract => left.checked_sub(right).ok_or_else(overflow),
+            Multiply => left.checked_mul(right).ok_or_else(overflow),
+            Divide | Modulo if right == 0 => fail("division by zero"),
+            Divide => left.checked_div(right).ok_or_else(overflow),
+            Modulo => left.checked_rem(right).ok_or_else(overflow),
+            Power if right < 0 => return Ok(Value::Decimal((left as f64).powf(right as f64))),
+            Power => u32::try_from(right)
+                .ok()
+                .and_then(|right| left.checked_pow(right))
+                .ok_or_else(overflow),
+            _ => unreachable!(),
+        }
+        .map(Value::Integer),
+        (left @ Value::Integer(_), right @ Value::Decimal(_))
+        | (left @ Value::Decimal(_), right @ Value::Integer(_))
+        | (left @ Value::Decimal(_), right @ Value::Decimal(_)) => {
+            let as_decimal = |value| match value {
+                Value::Integer(integer) => integer as f64,
+                Value::Decimal(decimal) => decimal,
+                _ => unreachable!(),
+            };
+            let (left, right) = (as_decimal(left), as_decimal(right));
+            if let (Divide | Modulo, 0.0) = (operator, right) {
+                return fail("division by zero");
+            }
+            Ok(Value::Decimal(match operator {
+                Add => left + right,
+                Subtract => left - right,
+                Multiply => left * right,
+                Divide => left / right,
+                Modulo => left % right,
+                Power => left.powf(right),
+                _ => unreachable!(),
+            }))
+        }
+        (Value::String(left), Value::String(right)) if *operator == Add => {
+            Ok(Value::String(left + &right))
+        }
+        (left, right) => fail(format!(
+            "cannot apply {:?} to a {} and a {}",
+            operator,
+            left.type_name(),
+            right.type_name()
+        )),
+    }
+}
+
+fn compare(operator: &OverloadableInfixOperator, left: Value, right: Value) -> Result<Value> {
+    use std::cmp::Ordering;
+    use OverloadableInfixOperator::*;
+
+    let ordering = match (&left, &right) {
+        (Value::Integer(left), Value::Integer(right)) => left.partial_cmp(right),
+        (Value::Integer(left), Value::Decimal(right)) => (*left as f64).partial_cmp(right),
+        (Value::Decimal(left), Value::Integer(right)) => left.partial_cmp(&(*right as f64)),
+        (Value::Decimal(left), Value::Decimal(right)) => left.partial_cmp(right),
+        (Value::Char(left), Value::Char(right)) => left.partial_cmp(right),
+        (Value::String(left), Value::String(right)) => left.partial_cmp(right),
+        _ => {
+            return fail(format!(
+                "cannot compare a {} with a {}",
+                left.type_name(),
+                right.type_name()
+            ))
+        }
+    };
+    Ok(Value::Boolean(match (operator, ordering) {
+        (_, None) => false,
+        (LessThan, Some(ordering)) => ordering == Ordering::Less,
+        (LessThanOrEqual, Some(ordering)) => ordering != Ordering::Greater,
+        (GreaterThan, Some(ordering)) => ordering == Ordering::Greater,
+        (GreaterThanOrEqual, Some(ordering)) => ordering != Ordering::Less,
+        _ => unreachable!(),
+    }))
+}
+
+/// Deep enough for reasonable recursion while staying well within the stack of a thread spawned
+/// with Rust's default size.
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// How the interpreter waits, so that tests can pass time without really waiting.
+pub trait Clock {
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+fn closer(resource: &Value) -> Option<Value> {
+    match resource {
+        Value::Record(fields) => fields
+            .iter()
+            .find(|(name, _)| name.0.as_str() == "close")
+            .map(|(_, close)| close.clone()),
+        _ => None,
+    }
+}
+
+/// Classes aren't supported yet, so a value is only of the builtin type it's named after.
+fn is_of_type(value: &Value, type_reference: &TypeReference) -> bool {
+    let name = match &type_reference.symbol {
+        Symbol::Absolute(SymbolLookup(lookup)) | Symbol::Relative(SymbolLookup(lookup)) => {
+            lookup.last()
+        }
+        _ => None,
+    };
+    matches!(name, Some(name) if name.0.as_str() == value.type_name())
+}
+
+pub struct Interpreter {
+    root: Scope,
+    depth: Cell<usize>,
+    max_depth: usize,
+    loops: Cell<usize>,
+    mailbox: RefCell<VecDeque<Value>>,
+    clock: Box<dyn Clock>,
+}
+
+fn builtins() -> Vec<(&'static str, Value)> {
+    vec![
+        ("True", Value::Boolean(true)),
+        ("False", Value::Boolean(false)),
+        ("Null", Value::Null),
+        (
+            "println",
+            Value::Builtin(Rc::new(Builtin {
+                name: "println",
+                call: println,
+            })),
+        ),
+    ]
+}
+
+/// The names the root scope defines before anything runs, for checking code
+/// that the interpreter is going to run.
+pub fn builtin_names() -> Vec<Identifier> {
+    builtins()
+        .into_iter()
+        .map(|(name, _)| Identifier::from(name))
+        .collect()
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        let root = Scope::root();
+        for (name, value) in builtins() {
+            root.define(Identifier::from(name), value).unwrap();
+        }
+        Self {
+            root,
+            depth: Cell::new(0),
+            max_depth: DEFAULT_MAX_DEPTH,
+            loops: Cell::new(0),
+            mailbox: RefCell::new(VecDeque::new()),
+            clock: Box::new(SystemClock),
+        }
+    }
+}
+
+impl Interpreter {
+    /// Limit how deeply calls can nest before failing with a stack overflow. Higher limits need a
+    /// correspondingly larger stack for the thread running the interpreter.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Wait for `select` timeouts on the given clock rather than the system's.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Queue a message for a later `select` to receive.
+    pub fn send(&self, message: Value) {
+        self.mailbox.borrow_mut().push_back(message);
+    }
+
+    /// Run a whole file, yielding the value of its last top-level expression.
+    pub fn run(&self, file: &MainFile) -> Result<Value> {
+        let file = simplification::lower_conds(file);
+        for item in &file.package.package.items {
+            self.item(item, &self.root)?;
+        }
+        let result = self.block_contents(&file.package.block, &self.root);
+        self.clean_up(&self.root, result)
+    }
+
+    /// Interpret a node in the root scope, as a REPL does with each line. Bindings persist for
+    /// later nodes and yield `Void`.
+    pub fn interpret(&self, node: &Node) -> Result<Value> {
+        match &simplification::lower_node_conds(node) {
+            Node::Item(item) => {
+                self.item(item, &self.root)?;
+                Ok(Value::Void)
+            }
+            Node::Expression(expression) => self.expression(expression, &self.root),
+        }
+    }
+
+    fn item(&self, item: &Item, scope: &Scope) -> Result<()> {
+        match item {
+            Item::Var(binding) | Item::Final(binding) | Item::Const(binding) => {
+                self.binding(binding, scope)
+            }
+            _ => unsupported("items other than bindings"),
+        }
+    }
+
+    fn binding(&self, binding: &Binding, scope: &Scope) -> Result<()> {
+        let value = self.expression(&binding.value, scope)?;
+        if self.pattern(&binding.pattern, &value, scope)? {
+            Ok(())
+        } else {
+            fail(format!(
+                "the value {} did not match the binding's pattern",
+                value
+            ))
+        }
+    }
+
+    /// Match a value against a pattern, binding the pattern's names in the
+    /// scope if it matches.
+    fn pattern(&self, pattern: &Pattern, value: &Value, scope: &Scope) -> Result<bool> {
+        let matched = match &pattern.item {
+            PatternItem::Identifier(name) => {
+                scope.define(name.clone(), value.clone()).or_else(fail)?;
+                true
+            }
+            PatternItem::Ignored => true,
+            PatternItem::Literal(literal) => self.literal(literal, scope)? == *value,
+            PatternItem::Range(range) => {
+                let start = self.literal(&range.start, scope)?;
+                let end = self.literal(&range.end, scope)?;
+                let comparable = matches!(
+                    (value, &start),
+                    (Value::Char(_), Value::Char(_))
+                        | (
+                            Value::Integer(_) | Value::Decimal(_),
+                            Value::Integer(_) | Value::Decimal(_)
+                        )
+                );
+                use OverloadableInfixOperator::{GreaterThanOrEqual, LessThanOrEqual};
+                comparable
+                    && compare(&GreaterThanOrEqual, value.clone(), start)? == Value::Boolean(true)
+                    && compare(&LessThanOrEqual, value.clone(), end)? == Value::Boolean(true)
+            }
+            PatternItem::BoundSymbol(symbol) => self.symbol(symbol, scope)? == *value,
+            PatternItem::Tuple(patterns) => match value {
+                Value::Tuple(values) if values.len() == patterns.len() => {
+                    let mut matched = true;
+                    for (pattern, value) in patterns.iter().zip(values) {
+                        if !self.pattern(pattern, value, scope)? {
+                            matched = false;
+                            break;
+                        }
+                    }
+                    matched
+                }
+                _ => false,
+            },
+            PatternItem::Composite(_) => unsupported("composite patterns")?,
+            PatternItem::Slice(slice) => match value {
+                Value::List(values) => self.slice_pattern(slice, values, scope)?,
+                _ => false,
+            },
+        };
+        match &pattern.bound_match {
+            Some(bound_match) if matched => self.pattern(bound_match, value, scope),
+            _ => Ok(matched),
+        }
+    }
+
+    fn slice_pattern(&self, slice: &SlicePattern, values: &[Value], scope: &Scope) -> Result<bool> {
+        let fixed = slice.leading.len() + slice.trailing.len();
+        let fits = match slice.rest {
+            Some(_) => fixed <= values.len(),
+            None => fixed == values.len(),
+        };
+        if !fits {
+            return Ok(false);
+        }
+
+        let rest_end = values.len() - slice.trailing.len();
+        let (leading, rest) = values[..rest_end].split_at(slice.leading.len());
+        let trailing = &values[rest_end..];
+        let elements = slice
+            .leading
+            .iter()
+            .zip(leading)
+            .chain(slice.trailing.iter().zip(trailing));
+        for (pattern, value) in elements {
+            if !self.pattern(pattern, value, scope)? {
+                return Ok(false);
+            }
+        }
+        match &slice.rest {
+            Some(pattern) => self.pattern(pattern, &Value::List(rest.to_vec()), scope),
+            None => Ok(true),
+        }
+    }
+
+    fn block(&self, block: &Block, scope: &Scope) -> Result<Value> {
+        let inner = scope.within();
+        let result = self.block_contents(block, &inner);
+        self.clean_up(&inner, result)
+    }
+
+    /// Acquire a resource to be closed when the current block exits. Only records with a `close`
+    /// field can be closed until classes are supported.
+    fn r#use(&self, resource: &Expression, scope: &Scope) -> Result<Value> {
+        let resource = self.expression(resource, scope)?;
+        if closer(&resource).is_none() {
+            fail(format!(
+                "only values with a `close` to call can be used, not a {}",
+                resource.type_name()
+            ))?;
+        }
+        scope.acquire(resource.clone());
+        Ok(resource)
+    }
+
+    /// Close the resources acquired in a scope that's exiting with the given result, and run the
+    /// blocks deferred in it, most recent first. They all run even if some fail, but a failure
+    /// doesn't replace an error the scope was already exiting with.
+    fn clean_up(&self, scope: &Scope, mut result: Result<Value>) -> Result<Value> {
+        for cleanup in scope.release() {
+            let cleaned = match cleanup {
+                Cleanup::Close(resource) => match closer(&resource) {
+                    Some(close) => self.call(close, vec![]),
+                    None => continue,
+                },
+                Cleanup::Run(block) => self.block(&block, scope),
+            };
+            if let (Ok(_), Err(error)) = (&result, cleaned) {
+                result = Err(error);
+            }
+        }
+        result
+    }
+
+    /// Evaluate a block's bindings and then its expressions directly in the
+    /// given scope, yielding the value of the last expression.
+    fn block_contents(&self, block: &Block, scope: &Scope) -> Result<Value> {
+        for binding in &block.bindings {
+            self.binding(binding, scope)?;
+        }
+        let mut last = Value::Void;
+        for expression in &block.expressions {
+            last = self.expression(expression, scope)?;
+        }
+        Ok(last)
+    }
+
+    fn throw(&self, throw: &Throw, scope: &Scope) -> Result<Value> {
+        let value = self.expression(&throw.exception, scope)?;
+        let cause = match &throw.cause {
+            Some(cause) => Some(self.expression(cause, scope)?),
+            None => None,
+        };
+        Err(Error::Thrown { value, cause })
+    }
+
+    fn symbol(&self, symbol: &Symbol, scope: &Scope) -> Result<Value> {
+        let name = match symbol {
+            Symbol::Relative(SymbolLookup(lookup)) if lookup.len() == 1 => lookup[0].clone(),
+            Symbol::Pseudo(PseudoIdentifier::It) => Identifier::from("it"),
+            Symbol::Pseudo(PseudoIdentifier::Continue) => Identifier::from("continue"),
+            Symbol::Pseudo(_) => unsupported("pseudoidentifiers other than `it` and `continue`")?,
+            _ => unsupported("qualified symbols")?,
+        };
+        scope
+            .lookup(&name)
+            .map(Ok)
+            .unwrap_or_else(|| fail(format!("unknown name: {}", name.0)))
+    }
+
+    fn literal(&self, literal: &Literal, scope: &Scope) -> Result<Value> {
+        match literal {
+            Literal::Char(c) => Ok(Value::Char(*c)),
+            Literal::Number(n, numeric_type) => number(n, *numeric_type),
+            Literal::HexFloat(float) => Ok(Value::Decimal(float.to_f64())),
+            Literal::Scientific(scientific) => Ok(Value::Decimal(scientific.to_f64())),
+            Literal::String(string) => Ok(Value::String(string.0.to_string())),
+            Literal::InterpolatedString(string) => self.interpolate(string, scope),
+            Literal::Record(record) => {
+                let mut fields = vec![];
+                for field in &record.0 {
+                    fields.push((field.name.clone(), self.expression(&field.value, scope)?));
+                }
+                Ok(Value::Record(fields))
+            }
+            Literal::Tuple(tuple) => {
+                let mut elements = vec![];
+                for element in &tuple.0 {
+                    elements.push(self.expression(element, scope)?);
+                }
+                Ok(Value::Tuple(elements))
+            }
+            // Nothing can mutate a list yet, so mutable lists are evaluated
+            // the same way.
+            Literal::List(list) => {
+                let mut elements = vec![];
+                for element in &list.elements {
+                    elements.push(self.expression(element, scope)?);
+                }
+                Ok(Value::List(elements))
+            }
+            Literal::Lambda(lambda) => Ok(Value::Closure(Rc::new(Closure {
+                lambda: lambda.clone(),
+                scope: scope.clone(),
+            }))),
+        }
+    }
+
+    fn interpolate(&self, string: &InterpolatedString, scope: &Scope) -> Result<Value> {
+        let unescape = |fragment: &str| fragment.replace("{{", "{").replace("}}", "}");
+
+        let mut result = String::new();
+        for (index, fragment) in string.string_fragments.iter().enumerate() {
+            result.push_str(&unescape(fragment));
+            if let Some(interpolation) = string.interpolations.get(index) {
+                let expression = match parse_expression(&interpolation.source) {
+                    Ok(expression) => expression,
+                    Err(_) => fail(format!("malformed interpolation: {}", interpolation.source))?,
+                };
+                match self.expression(&expression, scope)? {
+                    Value::String(string) => result.push_str(&string),
+                    value => result.push_str(&value.to_string()),
+                }
+            }
+        }
+        Ok(Value::String(result))
+    }
+
+    fn expression(&self, expression: &Expression, scope: &Scope) -> Result<Value> {
+        match expression {
+            Expression::Literal(literal) => self.literal(literal, scope),
+            Expression::Symbol(symbol) => self.symbol(symbol, scope),
+            Expression::Context(block) => self.block(block, scope),
+            Expression::Operator(Operator::OverloadableInfix(left, operator, right)) => {
+                self.infix(left, operator, right, scope)
+            }
+            Expression::Operator(Operator::Postfix(operand, PostfixOperator::ForceUnwrap)) => {
+                match self.expression(operand, scope)? {
+                    Value::Null => fail("`!` unwrapped a Null"),
+                    value => Ok(value),
+                }
+            }
+            Expression::Operator(_) => unsupported("postfix and slice operators"),
+            Expression::Throw(throw) => self.throw(throw, scope),
+            Expression::BranchingAndJumping(branching) => self.branching(branching, scope),
+            Expression::Use(r#use) => self.r#use(&r#use.0, scope),
+            Expression::Defer(defer) => {
+                scope.defer(defer.0.clone());
+                Ok(Value::Void)
+            }
+            Expression::Hole(position) => Err(Error::ReachedHole(*position)),
+            _ => unsupported("member handles, updates, reader macros, and spawns"),
+        }
+    }
+
+    fn infix(
+        &self,
+        left: &Expression,
+        operator: &OverloadableInfixOperator,
+        right: &Expression,
+        scope: &Scope,
+    ) -> Result<Value> {
+        use OverloadableInfixOperator::*;
+
+        // Logical operators short-circuit, so the right is evaluated lazily.
+        if let And | Or = operator {
+            let left = boolean(self.expression(left, scope)?, "a logical operand")?;
+            return if left == (*operator == Or) {
+                Ok(Value::Boolean(left))
+            } else {
+                let right = boolean(self.expression(right, scope)?, "a logical operand")?;
+                Ok(Value::Boolean(right))
+            };
+        }
+
+        let left = self.expression(left, scope)?;
+        let right = self.expression(right, scope)?;
+        match operator {
+            Add | Subtract | Multiply | Divide | Modulo | Power => {
+                arithmetic(operator, left, right)
+            }
+            Equals => Ok(Value::Boolean(left == right)),
+            NotEqual => Ok(Value::Boolean(left != right)),
+            LessThan | LessThanOrEqual | GreaterThan | GreaterThanOrEqual => {
+                compare(operator, left, right)
+            }
+            Pipe => self.call(right, vec![(None, left)]),
+            _ => unsupported(&format!("{:?} operators", operator)),
+        }
+    }
+
+    fn arguments(
+        &self,
+        arguments: &CallArguments,
+        scope: &Scope,
+    ) -> Result<Vec<(Option<Identifier>, Value)>> {
+        let mut values = vec![];
+        for argument in &arguments.arguments {
+            // The parser ensures a spread is the unlabelled final argument, so
+            // its elements fill the remaining positional parameters in order.
+            if argument.is_spread {
+                match self.expression(&argument.value, scope)? {
+                    Value::List(elements) => {
+                        values.extend(elements.into_iter().map(|element| (None, element)))
+                    }
+                    other => fail(format!("a {} cannot be spread", other.type_name()))?,
+                }
+                continue;
+            }
+            values.push((
+                argument.label.clone(),
+                self.expression(&argument.value, scope)?,
+            ));
+        }
+        Ok(values)
+    }
+
+    fn call(&self, callee: Value, arguments: Vec<(Option<Identifier>, Value)>) -> Result<Value> {
+        match callee {
+            Value::Builtin(builtin) => {
+                if arguments.iter().any(|(label, _)| label.is_some()) {
+                    fail(format!("{} doesn't take labelled arguments", builtin.name))
+                } else {
+                    (builtin.call)(arguments.into_iter().map(|(_, value)| value).collect())
+                }
+            }
+            Value::Closure(closure) => {
+                let depth = self.depth.get();
+                if self.max_depth <= depth {
+                    return Err(Error::StackOverflow {
+                        max_depth: self.max_depth,
+                    });
+                }
+                self.depth.set(depth + 1);
+                let result = self.call_closure(&closure, arguments);
+                self.depth.set(depth);
+                result
+            }
+            Value::Reiteration(loop_id) => {
+                if arguments.iter().any(|(label, _)| label.is_some()) {
+                    fail("continuing a loop doesn't take labelled arguments")
+                } else {
+                    Err(Error::Reiterate {
+                        loop_id,
+                        arguments: arguments.into_iter().map(|(_, value)| value).collect(),
+                    })
+                }
+            }
+            other => fail(format!("a {} cannot be called", other.type_name())),
+        }
+    }
+
+    /// Run a `for` loop, yielding its last iteration's value or, for a comprehension, a list of
+    /// every iteration's yielded value.
+    fn r#for(&self, r#for: &For, scope: &Scope) -> Result<Value> {
+        let loop_id = self.loops.get();
+        self.loops.set(loop_id + 1);
+
+        // An unlabelled loop is reiterated with `continue`, which can't otherwise be used as a
+        // name, so it's bound as one.
+        let reiteration_symbol = r#for
+            .reiteration_symbol
+            .clone()
+            .unwrap_or_else(|| Identifier::from("continue"));
+
+        let mut values = vec![];
+        for binding in &r#for.bindings {
+            values.push(self.expression(&binding.value, scope)?);
+        }
+
+        let mut yielded = vec![];
+        loop {
+            if values.len() != r#for.bindings.len() {
+                fail(format!(
+                    "the loop has {} bindings but was continued with {} values",
+                    r#for.bindings.len(),
+                    values.len()
+                ))?;
+            }
+            let iteration_scope = scope.within();
+            for (binding, value) in r#for.bindings.iter().zip(&values) {
+                if !self.pattern(&binding.pattern, value, &iteration_scope)? {
+                    fail(format!(
+                        "the value {} did not match the loop binding's pattern",
+                        value
+                    ))?;
+                }
+            }
+            iteration_scope
+                .define(reiteration_symbol.clone(), Value::Reiteration(loop_id))
+                .or_else(fail)?;
+
+            if let Some(yield_expression) = &r#for.yield_expression {
+                yielded.push(self.expression(yield_expression, &iteration_scope)?);
+            }
+            match self.block(&r#for.scope, &iteration_scope) {
+                Err(Error::Reiterate {
+                    loop_id: id,
+                    arguments,
+                }) if id == loop_id => values = arguments,
+                Err(Error::Break { label, value })
+                    if Some(&label) == r#for.reiteration_symbol.as_ref() =>
+                {
+                    return Ok(value)
+                }
+                Ok(_) if r#for.is_comprehension() => return Ok(Value::List(yielded)),
+                Ok(last) => {
+                    return match &r#for.else_clause {
+                        Some(else_clause) => self.block(else_clause, scope),
+                        None => Ok(last),
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Run a `while var` loop until its value no longer matches its pattern, rebinding the
+    /// pattern in a fresh scope each iteration.
+    fn while_var(&self, while_var: &WhileVar, scope: &Scope) -> Result<Value> {
+        loop {
+            let value = self.expression(&while_var.binding.value, scope)?;
+            let iteration_scope = scope.within();
+            if !self.pattern(&while_var.binding.pattern, &value, &iteration_scope)? {
+                return Ok(Value::Void);
+            }
+            self.block(&while_var.scope, &iteration_scope)?;
+        }
+    }
+
+    fn call_closure(
+        &self,
+        closure: &Closure,
+        mut arguments: Vec<(Option<Identifier>, Value)>,
+    ) -> Result<Value> {
+        let scope = closure.scope.within();
+        let parameters = &closure.lambda.signature.value_parameters;
+
+        // A lambda without parameters can still take a single argument as `it`.
+        if parameters.is_empty() {
+            match arguments.len() {
+                0 => {}
+                1 if arguments[0].0.is_none() => {
+                    let (_, it) = arguments.remove(0);
+                    scope.define(Identifier::from("it"), it).or_else(fail)?;
+                }
+                _ => fail("a lambda without parameters takes at most one argument as `it`")?,
+            }
+        }
+
+        for parameter in parameters {
+            let label = parameter
+                .label
+                .clone()
+                .or_else(|| match &parameter.pattern.item {
+                    PatternItem::Identifier(name) => Some(name.clone()),
+                    _ => None,
+                });
+            let labelled = arguments.iter().position(|(argument_label, _)| {
+                argument_label.is_some() && *argument_label == label
+            });
+            let positional = || arguments.iter().position(|(label, _)| label.is_none());
+
+            let value = match labelled.or_else(positional) {
+                Some(index) => arguments.remove(index).1,
+                None => match &parameter.default_value {
+                    Some(default_value) => self.expression(default_value, &scope)?,
+                    None => fail("missing an argument for a lambda parameter")?,
+                },
+            };
+            if !self.pattern(&parameter.pattern, &value, &scope)? {
+                fail(format!(
+                    "the argument {} did not match its parameter",
+                    value
+                ))?;
+            }
+        }
+
+        if !arguments.is_empty() {
+            fail("too many arguments")?;
+        }
+        self.block(&closure.lambda.block, &scope)
+    }
+
+    fn branching(&self, branching: &BranchingAndJumping, scope: &Scope) -> Result<Value> {
+        match branching {
+            BranchingAndJumping::Call(call) => {
+                let callee = self.symbol(&call.target, scope)?;
+                let arguments = self.arguments(&call.arguments, scope)?;
+                self.call(callee, arguments)
+            }
+            BranchingAndJumping::ExpressionCall(call) => {
+                let callee = self.expression(&call.target, scope)?;
+                let arguments = self.arguments(&call.arguments, scope)?;
+                self.call(callee, arguments)
+            }
+            BranchingAndJumping::If(r#if) => {
+                if boolean(self.expression(&r#if.condition, scope)?, "an if condition")? {
+                    self.block(&r#if.then, scope)
+                } else {
+                    match &r#if.else_clause {
+                        Some(else_clause) => self.block(else_clause, scope),
+                        None => Ok(Value::Void),
+                    }
+                }
+            }
+            // Conds are lowered into ifs before anything is interpreted.
+            BranchingAndJumping::Cond(_) => unreachable!(),
+            BranchingAndJumping::Switch(switch) => {
+                let value = self.expression(&switch.expression, scope)?;
+                let switch_scope;
+                let scope = match &switch.binding {
+                    Some(name) => {
+                        switch_scope = scope.within();
+                        switch_scope
+                            .define(name.clone(), value.clone())
+                            .or_else(fail)?;
+                        &switch_scope
+                    }
+                    None => scope,
+                };
+                for (index, case) in switch.cases.iter().enumerate() {
+                    if let Some(case_scope) = self.case(case, &value, scope)? {
+                        let mut result = self.block(&case.body, &case_scope);
+                        for next in &switch.cases[index + 1..] {
+                            match result {
+                                // The parser rejects these, but ASTs can be built
+                                // without it.
+                                Err(Error::Fallthrough) if next.binds_names() => {
+                                    return fail(
+                                        "can't fall through into an arm that binds names, as nothing would bind them",
+                                    );
+                                }
+                                Err(Error::Fallthrough) => {
+                                    result = self.block(&next.body, &scope.within())
+                                }
+                                _ => break,
+                            }
+                        }
+                        return result;
+                    }
+                }
+                fail(format!("no switch case matched {}", value))
+            }
+            BranchingAndJumping::For(r#for) => self.r#for(r#for, scope),
+            BranchingAndJumping::Select(select) => self.select(select, scope),
+            BranchingAndJumping::LabelledBlock(labelled) => {
+                match self.block(&labelled.scope, scope) {
+                    Err(Error::Break { label, value }) if label == labelled.label => Ok(value),
+                    result => result,
+                }
+            }
+            BranchingAndJumping::Fallthrough => Err(Error::Fallthrough),
+            BranchingAndJumping::Break(r#break) => {
+                let value = match &r#break.value {
+                    Some(value) => self.expression(value, scope)?,
+                    None => Value::Void,
+                };
+                Err(Error::Break {
+                    label: r#break.label.clone(),
+                    value,
+                })
+            }
+            BranchingAndJumping::WhileVar(while_var) => self.while_var(while_var, scope),
+            _ => unsupported("while loops and `if var`"),
+        }
+    }
+
+    /// The scope to run a case's body in if one of its matches accepts the value.
+    fn case(&self, case: &Case, value: &Value, scope: &Scope) -> Result<Option<Scope>> {
+        for case_match in &case.matches {
+            let case_scope = scope.within();
+            if !self.pattern(&case_match.pattern, value, &case_scope)? {
+                continue;
+            }
+            let guarded = match &case_match.guard {
+                Some(guard) => boolean(self.expression(guard, &case_scope)?, "a guard")?,
+                None => true,
+            };
+            if guarded {
+                return Ok(Some(case_scope));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Receive the oldest queued message that a case accepts, leaving the others queued.
+    fn select(&self, select: &Select, scope: &Scope) -> Result<Value> {
+        let queued = self.mailbox.borrow().len();
+        for index in 0..queued {
+            let message = self.mailbox.borrow()[index].clone();
+            for select_case in &select.cases {
+                if !is_of_type(&message, &select_case.message_type) {
+                    continue;
+                }
+                if let Some(case_scope) = self.case(&select_case.case, &message, scope)? {
+                    self.mailbox.borrow_mut().remove(index);
+                    return self.block(&select_case.case.body, &case_scope);
+                }
+            }
+        }
+
+        match &select.timeout {
+            Some(timeout) => {
+                let nanoseconds = match self.expression(&timeout.nanoseconds, scope)? {
+                    Value::Integer(nanoseconds) if 0 <= nanoseconds => nanoseconds as u64,
+                    other => {
+                        return fail(format!(
+                            "a select timeout must be a non-negative number of nanoseconds, not {}",
+                            other
+                        ))
+                    }
+                };
+                self.clock.sleep(Duration::from_nanos(nanoseconds));
+                self.block(&timeout.body, scope)
+            }
+            None => fail("a select without a timeout would wait forever, as no other process can send it a message"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn run(source: &str) -> Result<Value> {
+        Interpreter::default().run(&parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            run("package main\n var x = 2\n x * 3 + 1"),
+            Ok(Value::Integer(7))
+        );
+        assert_eq!(run("package main\n 10 - 2 - 3"), Ok(Value::Integer(5)));
+        assert_eq!(run("package main\n 2 + 3 * 4"), Ok(Value::Integer(14)));
+        assert_eq!(run("package main\n 2 ** 3 ** 2"), Ok(Value::Integer(512)));
+        assert_eq!(run("package main\n 1 + 1 == 2"), Ok(Value::Boolean(true)));
+        assert_eq!(run("package main\n 1.5 * 2"), Ok(Value::Decimal(3.0)));
+        assert_eq!(run("package main\n 3f64"), Ok(Value::Decimal(3.0)));
+        assert_eq!(
+            run("package main\n 1 / 0"),
+            Err(Error::Described("division by zero".to_owned()))
+        );
+        assert_eq!(
+            run("package main\n 2 ** 4294967296"),
+            Err(Error::Described("integer overflow".to_owned()))
+        );
+    }
+
+    #[test]
+    fn division() {
+        let divided = |source: &str| run(&format!("package main\n {}", source));
+        assert_eq!(divided("7 / 2"), Ok(Value::Integer(3)));
+        assert_eq!(divided("-7 / 2"), Ok(Value::Integer(-3)));
+        assert_eq!(divided("-7 % 2"), Ok(Value::Integer(-1)));
+        assert_eq!(divided("7 / 2f64"), Ok(Value::Decimal(3.5)));
+        assert_eq!(divided("7.5 / 2"), Ok(Value::Decimal(3.75)));
+        assert_eq!(divided("7.5 % 2"), Ok(Value::Decimal(1.5)));
+
+        // The second slash starts a comment rather than whole division.
+        assert_eq!(divided("7.5 / 2 // 2"), Ok(Value::Decimal(3.75)));
+
+        for source in &["7 / 0", "7 % 0", "1.5 / 0", "1.5 / 0f64", "0f64 % 0"] {
+            assert_eq!(
+                divided(source),
+                Err(Error::Described("division by zero".to_owned())),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn conditionals() {
+        assert_eq!(
+            run("package main\n var x = 3\n var size = x > 2 ? \"big\" : \"small\"\n size"),
+            Ok(Value::String("big".to_owned()))
+        );
+        assert_eq!(
+            run("package main\n switch { False { 1 }\n True, False { 2 } }"),
+            Ok(Value::Integer(2))
+        );
+        assert_eq!(
+            run("package main\n switch { False { 1 }\n else { 3 } }"),
+            Ok(Value::Integer(3))
+        );
+    }
+
+    #[test]
+    fn bodyless_cond_arms_yield_their_condition() {
+        let source = "package main
+            var x = 3
+            switch {
+                x < 0 { False }
+                x > 10, x == 3:
+                else { False }
+            }";
+        assert_eq!(run(source), Ok(Value::Boolean(true)));
+        assert_eq!(
+            run("package main\n switch { False:\n else { 3 } }"),
+            Ok(Value::Integer(3))
+        );
+    }
+
+    #[test]
+    fn blocks_yield_their_tail_branch() {
+        let source = |x| {
+            format!(
+                "package main
+                var x = {}
+                var size = with {{
+                    var limit = 10
+                    if x < 0 {{
+                        \"negative\"
+                    }} else if limit < x {{
+                        \"big\"
+                    }} else {{
+                        switch x {{
+                            0 {{ \"zero\" }}
+                            _ {{ \"small\" }}
+                        }}
+                    }}
+                }}
+                size",
+                x
+            )
+        };
+        let sizes = ["-1", "11", "0", "5"]
+            .iter()
+            .map(|x| run(&source(x)))
+            .collect::<Vec<_>>();
+        let expected = ["negative", "big", "zero", "small"]
+            .iter()
+            .map(|size| Ok(Value::String(size.to_string())))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, expected);
+    }
+
+    #[test]
+    fn closures() {
+        let source = "package main
+            var add = -> x, y { x + y }
+            var twice = -> f {
+                var g = -> x { f(f(x)) }
+                g
+            }
+            twice(-> { it + add(x: 1, y: 2) })(1)";
+        assert_eq!(run(source), Ok(Value::Integer(7)));
+    }
+
+    #[test]
+    fn spread_arguments_fill_the_remaining_parameters() {
+        let source = "package main
+            var sum = -> x, y, z { x * 100 + y * 10 + z }
+            var rest = [2, 3]
+            sum(1, ..rest)";
+        assert_eq!(run(source), Ok(Value::Integer(123)));
+        assert_eq!(
+            run("package main\n var f = -> x { x }\n f(..1)"),
+            Err(Error::Described("a Number cannot be spread".to_owned()))
+        );
+    }
+
+    #[test]
+    fn var_parameters_are_rebound_and_outer_names_shadowed() {
+        let source = "package main
+            var step = -> var count {
+                var count = count + 1
+                count
+            }
+            var x = 1
+            var shadowed = if True {
+                var x = 10
+                x
+            } else {
+                0
+            }
+            step(step(x)) + shadowed";
+        assert_eq!(run(source), Ok(Value::Integer(13)));
+        assert_eq!(
+            run("package main\n var x = 1\n var x = 2\n x"),
+            Err(Error::Described("x is already bound".to_owned()))
+        );
+    }
+
+    #[test]
+    fn first_matching_case_wins() {
+        let source = "package main
+            switch 5 {
+                n if n > 1 { #{ arm: 1 } }
+                n if n > 2 { #{ arm: 2 } }
+                _ { #{ arm: 3 } }
+            }";
+        assert_eq!(run(source).unwrap().to_string(), "#{ arm: 1 }");
+
+        let source = "package main
+            switch {
+                1 < 2 { \"first\" }
+                2 < 3 { \"second\" }
+            }";
+        assert_eq!(run(source), Ok(Value::String("first".to_owned())));
+    }
+
+    #[test]
+    fn ranges_match_their_members() {
+        let source = |value: &str| {
+            format!(
+                "package main
+                switch {} {{
+                    1..3 {{ \"low\" }}
+                    4..10 {{ \"high\" }}
+                    _ {{ \"out of range\" }}
+                }}",
+                value
+            )
+        };
+        assert_eq!(run(&source("10")), Ok(Value::String("high".to_owned())));
+        assert_eq!(run(&source("1")), Ok(Value::String("low".to_owned())));
+        assert_eq!(
+            run(&source("11")),
+            Ok(Value::String("out of range".to_owned()))
+        );
+        assert_eq!(
+            run(&source("\"4\"")),
+            Ok(Value::String("out of range".to_owned()))
+        );
+    }
+
+    #[test]
+    fn fallthrough_runs_the_next_arm() {
+        let source = |value: &str| {
+            format!(
+                "package main
+                switch {} {{
+                    1 {{ fallthrough }}
+                    2 {{
+                        var ignored = \"two\"
+                        fallthrough
+                    }}
+                    3 {{ \"three\" }}
+                    _ {{ \"other\" }}
+                }}",
+                value
+            )
+        };
+        for value in &["1", "2", "3"] {
+            assert_eq!(run(&source(value)), Ok(Value::String("three".to_owned())));
+        }
+        assert_eq!(run(&source("4")), Ok(Value::String("other".to_owned())));
+    }
+
+    #[test]
+    fn fallthrough_never_reaches_arms_binding_names() {
+        fn switch(file: &mut MainFile) -> &mut crate::parsing::nodes::Switch {
+            match &mut file.package.block.expressions[0] {
+                Expression::BranchingAndJumping(BranchingAndJumping::Switch(switch)) => switch,
+                other => panic!("expected a switch: {:?}", other),
+            }
+        }
+
+        // The parser won't fall through into `n`, so the arms are spliced.
+        let mut file =
+            parse_str("package main\n switch 1 { 1 { fallthrough }\n _ { 2 } }").unwrap();
+        let mut binding = parse_str("package main\n switch 1 { n { n } }").unwrap();
+        switch(&mut file).cases[1] = switch(&mut binding).cases.remove(0);
+        match Interpreter::default().run(&file) {
+            Err(Error::Described(message)) => assert!(message.contains("binds names")),
+            other => panic!("expected falling through to fail: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn switch_arms_bind_with_as() {
+        let source = "package main
+            var size = (3, 4)
+            switch size {
+                (0, _) as empty { empty }
+                (width, height) as whole {
+                    var result = (whole, width * height)
+                    result
+                }
+            }";
+        assert_eq!(
+            run(source),
+            Ok(Value::Tuple(vec![
+                Value::Tuple(vec![Value::Integer(3), Value::Integer(4)]),
+                Value::Integer(12),
+            ]))
+        );
+    }
+
+    #[test]
+    fn differently_composed_names_are_the_same() {
+        let source = "package main
+            var caf\u{e9} = 42
+            cafe\u{301}";
+        assert_eq!(run(source), Ok(Value::Integer(42)));
+    }
+
+    #[test]
+    fn switches_bind_their_scrutinee() {
+        let source = "package main
+            var count = 3
+            switch count * 2 as doubled {
+                0 { 0 }
+                n if doubled > 5 { doubled + n }
+                _ { doubled }
+            }";
+        assert_eq!(run(source), Ok(Value::Integer(12)));
+
+        let source = "package main
+            switch 4 as n {
+                4 { fallthrough }
+                _ { n * 10 }
+            }";
+        assert_eq!(run(source), Ok(Value::Integer(40)));
+    }
+
+    #[test]
+    fn tuples_destructure() {
+        let source = "package main
+            var divide = -> x, y {
+                var result = (x / y, x % y)
+                result
+            }
+            var (quotient, remainder) = divide(7, 2)
+            #{ quotient: quotient, remainder: remainder }";
+        assert_eq!(
+            run(source).unwrap().to_string(),
+            "#{ quotient: 3, remainder: 1 }"
+        );
+
+        let source = "package main
+            switch divide(7, 2) {
+                (_, 0) { \"even\" }
+                (_, r) { r }
+            }
+            var divide = -> x, y {
+                var result = (x / y, x % y)
+                result
+            }";
+        assert_eq!(run(source), Ok(Value::Integer(1)));
+    }
+
+    #[test]
+    fn list_literals_destructure() {
+        let source = "package main
+            var [first, ..rest] = [1, 2 + 3, 4]
+            var mutable = var [first]
+            #{ rest: rest, mutable: mutable }";
+        assert_eq!(
+            run(source).unwrap().to_string(),
+            "#{ rest: [5, 4], mutable: [1] }"
+        );
+    }
+
+    #[test]
+    fn tuples_must_match_in_length() {
+        let source = "package main\n var (a, b) = (1, 2, 3)";
+        assert!(run(source).is_err());
+    }
+
+    #[test]
+    fn unbounded_recursion_overflows_cleanly() {
+        let source = "package main\n var count = -> n { count(n + 1) }\n count(0)";
+        assert_eq!(
+            run(source),
+            Err(Error::StackOverflow {
+                max_depth: DEFAULT_MAX_DEPTH
+            })
+        );
+
+        let down = "package main\n var down = -> n { if n == 0 { 0 } else { down(n - 1) } }\n";
+        let run_limited = |call: &str| {
+            let file = parse_str(&format!("{}{}", down, call)).unwrap();
+            Interpreter::default().with_max_depth(11).run(&file)
+        };
+        assert_eq!(run_limited("down(10)"), Ok(Value::Integer(0)));
+        assert_eq!(
+            run_limited("down(11)"),
+            Err(Error::StackOverflow { max_depth: 11 })
+        );
+    }
+
+    thread_local! {
+        static CLOSED: RefCell<Vec<Value>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record_close(arguments: Vec<Value>) -> Result<Value> {
+        CLOSED.with(|closed| closed.borrow_mut().extend(arguments));
+        Ok(Value::Void)
+    }
+
+    /// Run a file with a `record` builtin for resources to call when closed,
+    /// yielding what was recorded along with the result.
+    fn run_recording_closes(source: &str) -> (Result<Value>, Vec<Value>) {
+        let interpreter = Interpreter::default();
+        let record = Builtin {
+            name: "record",
+            call: record_close,
+        };
+        interpreter
+            .root
+            .define(Identifier::from("record"), Value::Builtin(Rc::new(record)))
+            .unwrap();
+        CLOSED.with(|closed| closed.borrow_mut().clear());
+        let result = interpreter.run(&parse_str(source).unwrap());
+        (result, CLOSED.with(|closed| closed.take()))
+    }
+
+    const RESOURCE: &str = "package main
+        var resource = -> name { #{ name: name, close: -> { record(name) } } }
+        ";
+
+    #[test]
+    fn used_resources_are_closed_when_their_block_exits() {
+        let (result, closed) = run_recording_closes(&format!(
+            "{}
+            var answer = with {{
+                var first = use resource(\"first\")
+                var second = use resource(\"second\")
+                record(\"body\")
+                42
+            }}
+            answer",
+            RESOURCE
+        ));
+        assert_eq!(result, Ok(Value::Integer(42)));
+        let names = ["body", "second", "first"];
+        assert_eq!(
+            closed,
+            names
+                .iter()
+                .map(|&name| Value::String(name.to_owned()))
+                .collect::<Vec<_>>()
+        );
+
+        let (result, _) = run_recording_closes("package main\n var number = use 42");
+        assert_eq!(
+            result,
+            Err(Error::Described(
+                "only values with a `close` to call can be used, not a Number".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn used_resources_are_closed_when_thrown_past() {
+        let (result, closed) = run_recording_closes(&format!(
+            "{}
+            with {{
+                var file = use resource(\"file\")
+                throw \"failed\"
+            }}",
+            RESOURCE
+        ));
+        assert_eq!(
+            result,
+            Err(Error::Thrown {
+                value: Value::String("failed".to_owned()),
+                cause: None,
+            })
+        );
+        assert_eq!(closed, vec![Value::String("file".to_owned())]);
+    }
+
+    #[test]
+    fn deferred_blocks_run_in_reverse_when_their_block_exits() {
+        let (result, recorded) = run_recording_closes(&format!(
+            "{}
+            var answer = with {{
+                var file = use resource(\"file\")
+                var name = \"second\"
+                defer {{ record(\"first\") }}
+                defer {{ record(name) }}
+                record(\"body\")
+                42
+            }}
+            answer",
+            RESOURCE
+        ));
+        assert_eq!(result, Ok(Value::Integer(42)));
+        let names = ["body", "second", "first", "file"];
+        assert_eq!(
+            recorded,
+            names
+                .iter()
+                .map(|&name| Value::String(name.to_owned()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn deferred_blocks_run_in_reverse_when_thrown_past() {
+        let (result, recorded) = run_recording_closes(
+            "package main
+            with {
+                defer { record(\"first\") }
+                defer { record(\"second\") }
+                throw \"failed\"
+                record(\"unreachable\")
+            }",
+        );
+        assert_eq!(
+            result,
+            Err(Error::Thrown {
+                value: Value::String("failed".to_owned()),
+                cause: None,
+            })
+        );
+        assert_eq!(
+            recorded,
+            vec![
+                Value::String("second".to_owned()),
+                Value::String("first".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn force_unwrapping() {
+        assert_eq!(
+            run("package main\n var x = 3\n var y = x!\n y + 1"),
+            Ok(Value::Integer(4))
+        );
+        assert_eq!(
+            run("package main\n var x = Null\n x!"),
+            Err(Error::Described("`!` unwrapped a Null".to_owned()))
+        );
+    }
+
+    #[test]
+    fn holes_fail_when_reached() {
+        let result = run("package main\n var f = -> { ??? }\n if False { f() } else { 1 }");
+        assert_eq!(result, Ok(Value::Integer(1)));
+
+        let error = run("package main\n var f = -> { ??? }\n f()").unwrap_err();
+        assert!(matches!(error, Error::ReachedHole(Some(_))));
+        assert_eq!(error.to_string(), "reached a hole at 2:15");
+    }
+
+    #[test]
+    fn thrown_values_carry_their_causes() {
+        let source = "package main
+            var original = \"disk full\"
+            throw \"save failed\" caused by original";
+        match run(source) {
+            Err(error) => {
+                assert_eq!(
+                    error,
+                    Error::Thrown {
+                        value: Value::String("save failed".to_owned()),
+                        cause: Some(Value::String("disk full".to_owned())),
+                    }
+                );
+                assert_eq!(
+                    error.to_string(),
+                    "uncaught throw of \"save failed\", caused by \"disk full\""
+                );
+            }
+            other => panic!("expected a throw: {:?}", other),
+        }
+    }
+
+    /// Passes time without waiting, recording how long it was asked to wait in total.
+    struct MockClock(Rc<Cell<Duration>>);
+
+    impl Clock for MockClock {
+        fn sleep(&self, duration: Duration) {
+            self.0.set(self.0.get() + duration);
+        }
+    }
+
+    const SELECT: &str = "package main
+        select {
+            Number n if n > 10 {
+                n * 2
+            }
+            String s {
+                s
+            }
+            timeout 5000 {
+                \"timed out\"
+            }
+        }";
+
+    #[test]
+    fn selects_without_messages_time_out() {
+        let slept = Rc::new(Cell::new(Duration::from_nanos(0)));
+        let interpreter = Interpreter::default().with_clock(MockClock(slept.clone()));
+        let file = parse_str(SELECT).unwrap();
+
+        assert_eq!(
+            interpreter.run(&file),
+            Ok(Value::String("timed out".to_owned()))
+        );
+        assert_eq!(slept.get(), Duration::from_nanos(5000));
+
+        // Messages no case accepts don't stop it timing out.
+        interpreter.send(Value::Integer(3));
+        interpreter.send(Value::Boolean(true));
+        assert_eq!(
+            interpreter.run(&file),
+            Ok(Value::String("timed out".to_owned()))
+        );
+        assert_eq!(slept.get(), Duration::from_nanos(10000));
+
+        assert_eq!(
+            run("package main\n select { Number n { n } }"),
+            Err(Error::Described(
+                "a select without a timeout would wait forever, as no other process can send it a message"
+                    .to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn for_else_runs_when_the_loop_finishes_without_breaking() {
+        let search = |target: &str| {
+            format!(
+                "package main
+                var found = for search var i = 0 {{
+                    if i == {} {{
+                        break search i * 10
+                    }} else if i < 5 {{
+                        search(i + 1)
+                    }}
+                }} else {{
+                    -1
+                }}
+                found",
+                target
+            )
+        };
+        assert_eq!(run(&search("3")), Ok(Value::Integer(30)));
+        assert_eq!(run(&search("0")), Ok(Value::Integer(0)));
+        assert_eq!(run(&search("7")), Ok(Value::Integer(-1)));
+    }
+
+    #[test]
+    fn for_bindings_destructure_each_iteration() {
+        assert_eq!(
+            run("package main
+                for (key, amount) = (1, 10), sum = 0 {
+                    if key < 3 {
+                        continue((key + 1, amount * 2), sum + key * amount)
+                    } else {
+                        sum + key * amount
+                    }
+                }"),
+            Ok(Value::Integer(170))
+        );
+        assert_eq!(
+            run("package main\n for (key, amount) = 1 { key }"),
+            Err(Error::Described(
+                "the value 1 did not match the loop binding's pattern".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn while_var_loops_until_the_pattern_fails() {
+        let interpreter = Interpreter::default().with_clock(MockClock(Default::default()));
+        let file = parse_str(
+            "package main
+            var stop = (\"stop\", 0)
+            while var (\"next\", n) = select {
+                Number n {
+                    var received = (\"next\", n)
+                    received
+                }
+                String s { stop }
+                timeout 0 { stop }
+            } {
+                // Only the messages before the string should be received.
+                if n > 3 { missing } else { n }
+            }
+            select { Number n { n } timeout 0 { 0 } }",
+        )
+        .unwrap();
+
+        for n in 1..=3 {
+            interpreter.send(Value::Integer(n));
+        }
+        interpreter.send(Value::String("stop".to_owned()));
+        interpreter.send(Value::Integer(4));
+        assert_eq!(interpreter.run(&file), Ok(Value::Integer(4)));
+
+        // With nothing to receive, the first pattern fails straight away.
+        let interpreter = Interpreter::default().with_clock(MockClock(Default::default()));
+        assert_eq!(interpreter.run(&file), Ok(Value::Integer(0)));
+
+        assert_eq!(
+            run("package main\n while var (\"next\", n) = (\"stop\", 0) { n }\n n"),
+            Err(Error::Described("unknown name: n".to_owned()))
+        );
+    }
+
+    #[test]
+    fn selects_receive_the_oldest_accepted_message() {
+        let slept = Rc::new(Cell::new(Duration::from_nanos(0)));
+        let interpreter = Interpreter::default().with_clock(MockClock(slept.clone()));
+        let file = parse_str(SELECT).unwrap();
+
+        interpreter.send(Value::Integer(3));
+        interpreter.send(Value::Integer(21));
+        interpreter.send(Value::String("hello".to_owned()));
+        assert_eq!(interpreter.run(&file), Ok(Value::Integer(42)));
+        assert_eq!(
+            interpreter.run(&file),
+            Ok(Value::String("hello".to_owned()))
+        );
+        assert_eq!(
+            interpreter.run(&file),
+            Ok(Value::String("timed out".to_owned()))
+        );
+        assert_eq!(slept.get(), Duration::from_nanos(5000));
+
+        // The unaccepted message is still queued.
+        assert_eq!(interpreter.mailbox.borrow().len(), 1);
+    }
+
+    #[test]
+    fn for_comprehensions_yield_lists() {
+        assert_eq!(
+            run("package main
+                for var n = 1 yield n * n {
+                    if n < 4 {
+                        continue(n + 1)
+                    }
+                }"),
+            Ok(Value::List(
+                [1, 4, 9, 16].iter().copied().map(Value::Integer).collect()
+            ))
+        );
+
+        assert_eq!(
+            run("package main
+                for outer var n = 0, sum = 0 {
+                    if n < 5 {
+                        outer(n + 1, sum + n)
+                    } else {
+                        sum
+                    }
+                }"),
+            Ok(Value::Integer(10))
+        );
+    }
+
+    #[test]
+    fn inner_loops_reiterate_outer_ones_by_label() {
+        // The inner loop counts up to the outer loop's `i`, then starts the
+        // outer loop's next iteration, abandoning its own.
+        assert_eq!(
+            run("package main
+                for outer var i = 0, pairs = 0 {
+                    if i < 4 {
+                        for var j = 0 {
+                            if j < i {
+                                continue(j + 1)
+                            } else {
+                                outer(i + 1, pairs + j)
+                            }
+                        }
+                    } else {
+                        pairs
+                    }
+                }"),
+            Ok(Value::Integer(6))
+        );
+    }
+
+    #[test]
+    fn breaks_exit_labelled_blocks() {
+        assert_eq!(
+            run("package main
+                var root = search: {
+                    for var n = 0 {
+                        if 20 < n * n {
+                            break search n
+                        } else {
+                            continue(n + 1)
+                        }
+                    }
+                }
+                root"),
+            Ok(Value::Integer(5))
+        );
+
+        assert_eq!(
+            run("package main
+                outer: {
+                    inner: {
+                        break outer
+                    }
+                    1
+                }"),
+            Ok(Value::Void)
+        );
+        assert_eq!(run("package main\n finished: { 1 }"), Ok(Value::Integer(1)));
+    }
+
+    #[test]
+    fn slice_patterns_bind_rests() {
+        let run_with_numbers = |pattern: &str, result: &str| {
+            run(&format!(
+                "package main
+                var {} = for var n = 1 yield n {{
+                    if n < 4 {{
+                        continue(n + 1)
+                    }}
+                }}
+                {}",
+                pattern, result
+            ))
+            .map(|value| value.to_string())
+        };
+
+        assert_eq!(
+            run_with_numbers("[first, ..tail]", "#{ first: first, tail: tail }"),
+            Ok("#{ first: 1, tail: [2, 3, 4] }".to_owned())
+        );
+        assert_eq!(
+            run_with_numbers("[_, ..middle, last]", "#{ middle: middle, last: last }"),
+            Ok("#{ middle: [2, 3], last: 4 }".to_owned())
+        );
+        assert_eq!(
+            run_with_numbers("[a, b, c, d, ..rest]", "rest"),
+            Ok("[]".to_owned())
+        );
+        assert!(run_with_numbers("[a, b]", "a").is_err());
+        assert!(run_with_numbers("[a, b, c, d, e, ..rest]", "a").is_err());
+    }
+
+    #[test]
+    fn unknown_names() {
+        assert_eq!(
+            run("package main\n x + 1"),
+            Err(Error::Described("unknown name: x".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::common::multiphase::Identifier;
+use crate::intepreter::values::Value;
+use crate::parsing::nodes::Block;
+
+/// What to do when a scope exits.
+pub enum Cleanup {
+    /// Close a resource acquired with `use`.
+    Close(Value),
+
+    /// Run a block deferred with `defer`.
+    Run(Block),
+}
+
+struct Frame {
+    bindings: HashMap<Identifier, Value>,
+    cleanups: Vec<Cleanup>,
+    parent: Option<Scope>,
+}
+
+/// A lexical environment. Scopes are shared rather than copied, so that
+/// closures see bindings made in their enclosing scope after they were created,
+/// and so that a REPL can keep one scope alive across lines.
+#[derive(Clone)]
+pub struct Scope(Rc<RefCell<Frame>>);
+
+impl Scope {
+    pub fn root() -> Self {
+        Scope(Rc::new(RefCell::new(Frame {
+            bindings: HashMap::new(),
+            cleanups: vec![],
+            parent: None,
+        })))
+    }
+
+    pub fn within(&self) -> Self {
+        Scope(Rc::new(RefCell::new(Frame {
+            bindings: HashMap::new(),
+            cleanups: vec![],
+            parent: Some(self.clone()),
+        })))
+    }
+
+    /// Bind a name in this scope, shadowing any binding of it in an outer
+    /// one. It fails if the name is already bound in this scope itself.
+    ///
+    /// A function's body is a scope within its parameters', so this is also
+    /// how a `var` parameter is rebound at the top of the body. Checking that
+    /// only `var` parameters are rebound is left to the parser's
+    /// `final_parameters` pass.
+    pub fn define(&self, name: Identifier, value: Value) -> Result<(), String> {
+        match self.0.borrow_mut().bindings.entry(name) {
+            Entry::Occupied(occupied) => Err(format!("{} is already bound", occupied.key().0)),
+            Entry::Vacant(vacant) => {
+                vacant.insert(value);
+                Ok(())
+            }
+        }
+    }
+
+    /// Hold a resource acquired with `use` until this scope exits.
+    pub fn acquire(&self, resource: Value) {
+        self.0.borrow_mut().cleanups.push(Cleanup::Close(resource));
+    }
+
+    /// Hold a block deferred with `defer` until this scope exits.
+    pub fn defer(&self, block: Block) {
+        self.0.borrow_mut().cleanups.push(Cleanup::Run(block));
+    }
+
+    /// Take the cleanups for this scope to run them, most recent first.
+    pub fn release(&self) -> Vec<Cleanup> {
+        let mut cleanups = std::mem::take(&mut self.0.borrow_mut().cleanups);
+        cleanups.reverse();
+        cleanups
+    }
+
+    pub fn lookup(&self, name: &Identifier) -> Option<Value> {
+        let frame = self.0.borrow();
+        match frame.bindings.get(name) {
+            Some(value) => Some(value.clone()),
+            None => frame.parent.as_ref().and_then(|parent| parent.lookup(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inner_scopes_see_outer_bindings() {
+        let outer = Scope::root();
+        let inner = outer.within();
+        outer
+            .define(Identifier::from("x"), Value::Integer(1))
+            .unwrap();
+
+        assert_eq!(
+            inner.lookup(&Identifier::from("x")),
+            Some(Value::Integer(1))
+        );
+
+        inner.define(Identifier::from("y"), Value::Void).unwrap();
+        assert_eq!(outer.lookup(&Identifier::from("y")), None);
+    }
+
+    #[test]
+    fn inner_scopes_shadow_but_cannot_rebind() {
+        let outer = Scope::root();
+        let inner = outer.within();
+        let x = || Identifier::from("x");
+        outer.define(x(), Value::Integer(1)).unwrap();
+
+        inner.define(x(), Value::Integer(2)).unwrap();
+        assert_eq!(inner.lookup(&x()), Some(Value::Integer(2)));
+        assert_eq!(outer.lookup(&x()), Some(Value::Integer(1)));
+        assert!(inner.define(x(), Value::Void).is_err());
+    }
+}
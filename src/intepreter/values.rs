@@ -0,0 +1,223 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::common::multiphase::Identifier;
+use crate::intepreter::scope::Scope;
+use crate::intepreter::Result;
+use crate::parsing::nodes::Lambda;
+
+/// A lambda along with the scope it was created in.
+pub struct Closure {
+    pub lambda: Lambda,
+    pub scope: Scope,
+}
+
+/// A function implemented by the interpreter itself rather than in Sylan.
+pub struct Builtin {
+    pub name: &'static str,
+    pub call: fn(Vec<Value>) -> Result<Value>,
+}
+
+#[derive(Clone)]
+pub enum Value {
+    Void,
+
+    /// The absence of a value in an optional, as in `Int?`.
+    Null,
+
+    Boolean(bool),
+    Integer(i64),
+    Decimal(f64),
+    Char(char),
+    String(String),
+    Record(Vec<(Identifier, Value)>),
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    Closure(Rc<Closure>),
+    Builtin(Rc<Builtin>),
+
+    /// Calling this starts the next iteration of the `for` loop with the
+    /// given identifier.
+    Reiteration(usize),
+}
+
+impl PartialEq for Value {
+    /// Functions are only ever equal to themselves.
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Void, Value::Void) | (Value::Null, Value::Null) => true,
+            (Value::Boolean(x), Value::Boolean(y)) => x == y,
+            (Value::Integer(x), Value::Integer(y)) => x == y,
+            (Value::Decimal(x), Value::Decimal(y)) => x == y,
+            (Value::Integer(x), Value::Decimal(y)) | (Value::Decimal(y), Value::Integer(x)) => {
+                (*x as f64) == *y
+            }
+            (Value::Char(x), Value::Char(y)) => x == y,
+            (Value::String(x), Value::String(y)) => x == y,
+            (Value::Record(x), Value::Record(y)) => x == y,
+            (Value::Tuple(x), Value::Tuple(y)) => x == y,
+            (Value::List(x), Value::List(y)) => x == y,
+            (Value::Reiteration(x), Value::Reiteration(y)) => x == y,
+            (Value::Closure(x), Value::Closure(y)) => Rc::ptr_eq(x, y),
+            (Value::Builtin(x), Value::Builtin(y)) => Rc::ptr_eq(x, y),
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Void => "Void",
+            Value::Null => "Null",
+            Value::Boolean(_) => "Boolean",
+            Value::Integer(_) | Value::Decimal(_) => "Number",
+            Value::Char(_) => "Char",
+            Value::String(_) => "String",
+            Value::Record(_) => "Record",
+            Value::Tuple(_) => "Tuple",
+            Value::List(_) => "List",
+            Value::Closure(_) | Value::Builtin(_) | Value::Reiteration(_) => "Function",
+        }
+    }
+}
+
+/// The base in which integers are displayed. Decimals are always displayed in
+/// base ten.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Radix {
+    Decimal,
+    Hexadecimal,
+    Binary,
+}
+
+impl Value {
+    pub fn to_string_in(&self, radix: Radix) -> String {
+        match radix {
+            Radix::Decimal => format!("{}", self),
+            Radix::Hexadecimal => format!("{:x}", self),
+            Radix::Binary => format!("{:b}", self),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, radix: Radix) -> fmt::Result {
+        match self {
+            Value::Void => write!(f, "Void"),
+            Value::Null => write!(f, "Null"),
+            Value::Boolean(true) => write!(f, "True"),
+            Value::Boolean(false) => write!(f, "False"),
+            Value::Integer(integer) => {
+                let sign = if *integer < 0 { "-" } else { "" };
+                match radix {
+                    Radix::Decimal => write!(f, "{}", integer),
+                    Radix::Hexadecimal => write!(f, "{}{:#x}", sign, integer.unsigned_abs()),
+                    Radix::Binary => write!(f, "{}{:#b}", sign, integer.unsigned_abs()),
+                }
+            }
+            Value::Decimal(decimal) => write!(f, "{:?}", decimal),
+            Value::Char(c) => write!(f, "{:?}", c),
+            Value::String(string) => write!(f, "{:?}", string),
+            Value::Record(fields) if fields.is_empty() => write!(f, "#{{}}"),
+            Value::Record(fields) => {
+                write!(f, "#{{ ")?;
+                for (index, (name, value)) in fields.iter().enumerate() {
+                    if 0 < index {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", name.0)?;
+                    value.write(f, radix)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Tuple(elements) => {
+                write!(f, "(")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if 0 < index {
+                        write!(f, ", ")?;
+                    }
+                    element.write(f, radix)?;
+                }
+                write!(f, "{})", if elements.len() == 1 { "," } else { "" })
+            }
+            Value::List(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if 0 < index {
+                        write!(f, ", ")?;
+                    }
+                    element.write(f, radix)?;
+                }
+                write!(f, "]")
+            }
+            Value::Closure(_) => write!(f, "<lambda>"),
+            Value::Builtin(builtin) => write!(f, "<builtin {}>", builtin.name),
+            Value::Reiteration(_) => write!(f, "<continue>"),
+        }
+    }
+}
+
+/// Values are displayed as they'd be written in Sylan where possible.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Radix::Decimal)
+    }
+}
+
+impl fmt::LowerHex for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Radix::Hexadecimal)
+    }
+}
+
+impl fmt::Binary for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Radix::Binary)
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::multiphase::Number;
+    use crate::lexing::lexer::Lexer;
+    use crate::lexing::tokens::{Literal, Token};
+    use crate::source::in_memory::Source;
+
+    #[test]
+    fn integers_in_other_radixes() {
+        let value = Value::Integer(255);
+        assert_eq!(value.to_string_in(Radix::Decimal), "255");
+        assert_eq!(value.to_string_in(Radix::Hexadecimal), "0xff");
+        assert_eq!(value.to_string_in(Radix::Binary), "0b11111111");
+        assert_eq!(
+            Value::Integer(-42).to_string_in(Radix::Hexadecimal),
+            "-0x2a"
+        );
+
+        let tuple = Value::Tuple(vec![Value::Integer(16), Value::Decimal(1.5)]);
+        assert_eq!(tuple.to_string_in(Radix::Decimal), "(16, 1.5)");
+        assert_eq!(tuple.to_string_in(Radix::Hexadecimal), "(0x10, 1.5)");
+    }
+
+    #[test]
+    fn integers_in_other_radixes_lex_back() {
+        for integer in &[0, 255, -42, i64::MAX, i64::MIN] {
+            for radix in &[Radix::Decimal, Radix::Hexadecimal, Radix::Binary] {
+                let written = Value::Integer(*integer).to_string_in(*radix);
+                let mut lexer = Lexer::from(Source::from(written.chars().collect::<Vec<_>>()));
+                match lexer.lex_next().map(|lexed| lexed.token) {
+                    Ok(Token::Literal(Literal::Number(Number(lexed, 0), None))) => {
+                        assert_eq!(lexed, *integer, "{}", written)
+                    }
+                    other => panic!("expected {} to lex back: {:?}", written, other),
+                }
+            }
+        }
+    }
+}
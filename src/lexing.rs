@@ -12,18 +12,27 @@
 //! The lexer is encapsulated in a `LexerTask` that runs concurrently in its own thread. That lexer
 //! task is then hidden behind a `PeekableBuffer`. This allows consumers to treat it as a buffer
 //! without even considering the concurrency that backs the implementation.
+//!
+//! The lexer carries on past tokens it can't lex, sending error tokens in their place. `Tokens`
+//! sets those aside rather than handing them to the parser, and reports them all once the lexer
+//! thread is joined.
 
 use std::io;
+use std::mem;
 use std::ops::Index;
+use std::sync::mpsc::RecvError;
 
 use crate::common::peekable_buffer::PeekableBuffer;
 use crate::lexing::lexer::{LexedToken, Lexer, LexerTask, LexerTaskError};
+use crate::lexing::tokens::Token;
 
 mod char_escapes;
 mod keywords;
 mod non_word_chars;
+mod normalisation;
 
 pub mod lexer;
+pub mod lints;
 pub mod tokens;
 
 const MAX_TOKEN_LOOKAHEAD: usize = 5;
@@ -32,11 +41,22 @@ pub struct Tokens {
     lookahead: [LexedToken; MAX_TOKEN_LOOKAHEAD],
     lookahead_len: usize,
     lexer_task: LexerTask,
+    errors: Vec<lexer::Error>,
 }
 
 impl Tokens {
     pub fn from(lexer: Lexer) -> io::Result<Self> {
-        lexer.lex().map(|lexer_task| Self {
+        lexer.lex().map(Self::from_task)
+    }
+
+    /// Tokens that were already lexed, such as a slice of a source lexed up
+    /// front, which should end with an EOF token like any other stream.
+    pub fn from_lexed(lexed: Vec<LexedToken>) -> Self {
+        Self::from_task(LexerTask::from_lexed(lexed))
+    }
+
+    fn from_task(lexer_task: LexerTask) -> Self {
+        Self {
             lookahead: [
                 Default::default(),
                 Default::default(),
@@ -46,11 +66,48 @@ impl Tokens {
             ],
             lookahead_len: 0,
             lexer_task,
-        })
+            errors: vec![],
+        }
     }
 
-    pub fn join_lexer_thread(self) -> Result<(), LexerTaskError> {
-        self.lexer_task.join()
+    pub fn join_lexer_thread(mut self) -> Result<(), LexerTaskError> {
+        // The consumer might have stopped early, so pick up any errors it didn't get to. This
+        // ends once the lexer thread finishes and drops its end of the channel.
+        while self.recv().is_ok() {}
+
+        match self.lexer_task.join() {
+            Ok(()) if self.errors.is_empty() => Ok(()),
+            Ok(()) => Err(LexerTaskError::Invalid(self.errors)),
+            Err(LexerTaskError::Lexer(err)) if !self.errors.is_empty() => {
+                self.errors.push(err);
+                Err(LexerTaskError::Invalid(self.errors))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Drain the tokens left, starting with any already peeked, and then join
+    /// the lexer thread as [Tokens::join_lexer_thread] does.
+    pub fn into_vec(mut self) -> Result<Vec<LexedToken>, LexerTaskError> {
+        let mut drained = self.lookahead[..self.lookahead_len]
+            .iter_mut()
+            .map(mem::take)
+            .collect::<Vec<LexedToken>>();
+        self.lookahead_len = 0;
+        while let Ok(token) = self.recv() {
+            drained.push(token);
+        }
+        self.join_lexer_thread().map(|()| drained)
+    }
+
+    fn recv(&mut self) -> Result<LexedToken, RecvError> {
+        loop {
+            let lexed = self.lexer_task.recv()?;
+            match lexed.token {
+                Token::Error(err) => self.errors.push(err),
+                _ => break Ok(lexed),
+            }
+        }
     }
 }
 
@@ -67,50 +124,32 @@ impl Index<usize> for LexedTokenReadMany {
 
 impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for Tokens {
     fn peek_many(&mut self, n: usize) -> Option<&[LexedToken]> {
-        let lexer = &self.lexer_task;
-
-        // Expand and the lookahead if it's not big enough.
-        let pending_peeks = n - self.lookahead_len;
-        let mut n = self.lookahead_len;
-        let m = self.lookahead_len + pending_peeks;
-        let ok = loop {
-            if m <= n {
-                break true;
+        // Expand the lookahead if it's not big enough.
+        while self.lookahead_len < n {
+            match self.recv() {
+                Ok(token) => {
+                    self.lookahead[self.lookahead_len] = token;
+                    self.lookahead_len += 1;
+                }
+                Err(_) => return None,
             }
-            self.lookahead[n] = match lexer.recv() {
-                Ok(token) => token,
-                Err(_) => break false,
-            };
-            n += 1;
-        };
-        self.lookahead_len += pending_peeks;
-
-        if ok {
-            // The lookahead now covers the range requested, so slice it.
-            Some(&self.lookahead[..(self.lookahead_len)])
-        } else {
-            None
         }
+
+        // The lookahead now covers the range requested, so slice it.
+        Some(&self.lookahead[..n])
     }
 
     fn read_many(&mut self, n: usize) -> Option<LexedTokenReadMany> {
         let lookahead_to_consume = self.lookahead_len.min(n);
         let mut non_lookahead_to_consume = n - lookahead_to_consume;
 
-        // First consume the lookahead.
-        let mut read_tokens = (0..lookahead_to_consume)
-            .zip(lookahead_to_consume..(lookahead_to_consume + self.lookahead_len))
-            .enumerate()
-            .map(|(i, (destination, source))| {
-                // TODO: work out how to do a `swap_remove` on a slice to avoid
-                // a heap allocation and copying the already allocated string in
-                // the lexed token.
-                let token = self.lookahead[i].clone();
-
-                self.lookahead.swap(destination, source);
-                token
-            })
+        // First consume the lookahead, shifting the unconsumed remainder to
+        // the front.
+        let mut read_tokens = self.lookahead[..lookahead_to_consume]
+            .iter_mut()
+            .map(mem::take)
             .collect::<Vec<LexedToken>>();
+        self.lookahead[..self.lookahead_len].rotate_left(lookahead_to_consume);
         self.lookahead_len -= lookahead_to_consume;
 
         // Having exhausted the lookahead, the remaining reads are from the
@@ -119,7 +158,7 @@ impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for Tokens {
             if non_lookahead_to_consume == 0 {
                 break true;
             }
-            match self.lexer_task.recv() {
+            match self.recv() {
                 Ok(token) => read_tokens.push(token),
                 Err(_) => break false,
             }
@@ -135,20 +174,18 @@ impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for Tokens {
 
     fn discard_many(&mut self, n: usize) -> bool {
         let lookahead_to_discard = self.lookahead_len.min(n);
-        let mut non_lookahead_to_discard = -((self.lookahead_len as isize) - (n as isize));
+        let mut non_lookahead_to_discard = n - lookahead_to_discard;
 
         // First discard the lookahead.
-        (0..lookahead_to_discard)
-            .zip(lookahead_to_discard..(lookahead_to_discard + self.lookahead_len))
-            .for_each(|(destination, source)| self.lookahead.swap(destination, source));
+        self.lookahead[..self.lookahead_len].rotate_left(lookahead_to_discard);
         self.lookahead_len -= lookahead_to_discard;
 
         // Now the lookahead is consumed, discard from the token channel.
         loop {
-            if non_lookahead_to_discard <= 0 {
+            if non_lookahead_to_discard == 0 {
                 break true;
             }
-            match self.lexer_task.recv() {
+            match self.recv() {
                 Ok(_) => {}
                 Err(_) => break false,
             }
@@ -161,7 +198,7 @@ impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for Tokens {
 mod tests {
     use std::fmt::Debug;
 
-    use crate::common::multiphase::{Identifier, Number};
+    use crate::common::multiphase::{Identifier, Number, OverloadableInfixOperator};
     use crate::lexing::tokens::{Grouping, Literal, Token};
     use crate::source::in_memory::Source;
 
@@ -194,6 +231,82 @@ mod tests {
         test(|tokens| assert_eq!(f(tokens), *x))
     }
 
+    #[test]
+    fn trivia_only_source_is_just_eof() {
+        let source = Source::from(
+            "  // Nothing.\n/* Still nothing. */\n"
+                .chars()
+                .collect::<Vec<char>>(),
+        );
+        let mut tokens = Tokens::from(Lexer::from(source)).unwrap();
+        assert_eq!(tokens.read().unwrap().token, Token::Eof);
+        assert!(tokens.read().is_none());
+        tokens.lexer_task.join().unwrap();
+    }
+
+    #[test]
+    fn lexing_recovers_from_invalid_tokens() {
+        let source = Source::from(
+            "first \u{7} second '\\q' third"
+                .chars()
+                .collect::<Vec<char>>(),
+        );
+        let mut tokens = Tokens::from(Lexer::from(source)).unwrap();
+        for expected in &["first", "second", "third"] {
+            assert_eq!(
+                tokens.read().unwrap().token,
+                Token::Identifier(Identifier::from(*expected))
+            );
+        }
+        assert_eq!(tokens.read().unwrap().token, Token::Eof);
+
+        match tokens.join_lexer_thread() {
+            Err(LexerTaskError::Invalid(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("expected two lexing errors: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn into_vec() {
+        let source = Source::from(TEST_SOURCE.chars().collect::<Vec<char>>());
+        let mut tokens = Tokens::from(Lexer::from(source)).unwrap();
+        // Leave some tokens in the lookahead, which must come before the rest.
+        tokens.discard();
+        tokens.peek_many(3).unwrap();
+
+        let drained = tokens
+            .into_vec()
+            .unwrap()
+            .into_iter()
+            .map(|lexed| lexed.token)
+            .collect::<Vec<Token>>();
+        let identifier = |name| Token::Identifier(Identifier::from(name));
+        let number = |n| Token::Literal(Literal::Number(Number(n, 0), None));
+        assert_eq!(
+            drained,
+            vec![
+                Token::Grouping(Grouping::OpenParentheses),
+                number(1),
+                Token::SubItemSeparator,
+                number(2),
+                Token::SubItemSeparator,
+                number(3),
+                Token::Grouping(Grouping::CloseParentheses),
+                Token::Dot,
+                identifier("forEach"),
+                Token::Grouping(Grouping::OpenParentheses),
+                identifier("n"),
+                Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade),
+                identifier("println"),
+                Token::Grouping(Grouping::OpenParentheses),
+                identifier("{n}"),
+                Token::Grouping(Grouping::CloseParentheses),
+                Token::Grouping(Grouping::CloseParentheses),
+                Token::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn peek() {
         assert_next(
@@ -216,7 +329,7 @@ mod tests {
             &vec![
                 Token::Identifier(Identifier::from("List")),
                 Token::Grouping(Grouping::OpenParentheses),
-                Token::Literal(Literal::Number(Number(1, 0))),
+                Token::Literal(Literal::Number(Number(1, 0), None)),
                 Token::SubItemSeparator,
             ],
         )
@@ -227,7 +340,7 @@ mod tests {
         assert_next(
             |tokens| {
                 tokens.discard_many(5);
-                tokens.peek_nth(5).unwrap().token.clone()
+                tokens.peek_nth(4).unwrap().token.clone()
             },
             &Token::Identifier(Identifier::from("forEach")),
         );
@@ -242,7 +355,7 @@ mod tests {
                 tokens.peek().unwrap();
                 tokens.read().unwrap().token
             },
-            &Token::Literal(Literal::Number(Number(1, 0))),
+            &Token::Literal(Literal::Number(Number(1, 0), None)),
         )
     }
 
@@ -274,7 +387,7 @@ mod tests {
                 tokens.discard();
                 tokens.read().unwrap().token
             },
-            &Token::Literal(Literal::Number(Number(2, 0))),
+            &Token::Literal(Literal::Number(Number(2, 0), None)),
         )
     }
 
@@ -292,8 +405,8 @@ mod tests {
     #[test]
     fn match_nth() {
         test(|tokens| {
-            assert!(tokens.match_nth(3, |lexed| lexed.token
-                == Token::Literal(Literal::Number(Number(1, 0)))))
+            assert!(tokens.match_nth(2, |lexed| lexed.token
+                == Token::Literal(Literal::Number(Number(1, 0), None))))
         })
     }
 
@@ -305,7 +418,7 @@ mod tests {
         "#,
         );
         assert_next(
-            |tokens| tokens.peek().unwrap().clone().trivia.unwrap(),
+            |tokens| tokens.peek().unwrap().clone().trivia.unwrap().text(),
             &trivia_to_match,
         );
     }
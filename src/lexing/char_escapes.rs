@@ -1,5 +1,8 @@
 //! Character escapes allow developers to enter characters into Sylan strings that can otherwise
 //! awkward to encode in the UTF-8 source in some contexts. Examples include newlines and hard tabs.
+//!
+//! Beyond the single-character escapes, any character can be written by number: `\xNN` for ASCII
+//! and `\u{...}` for any Unicode scalar value.
 
 use std::collections::HashMap;
 
@@ -14,6 +17,77 @@ pub fn new() -> HashMap<char, char> {
         ('t', '\t'),
         ('\\', '\\'),
         ('\'', '\''),
+        // Delimiters of strings, interpolated strings, and quoted identifiers.
+        ('"', '"'),
+        ('`', '`'),
     ]);
     map
 }
+
+/// Decode the two hex digits of a `\xNN` escape. These are limited to ASCII, `\x00` to `\x7F`, as
+/// anything higher would be ambiguous between a raw byte and a Unicode code point; Sylan strings are
+/// always Unicode, so `\u{...}` must be used beyond ASCII.
+pub fn decode_hex(digits: &str) -> Result<char, String> {
+    let escape = format!("\\x{}", digits);
+    if digits.len() != 2 {
+        return Err(format!("{} must have exactly two hex digits", escape));
+    }
+    u8::from_str_radix(digits, 16)
+        .ok()
+        .filter(|&code| code <= 0x7F)
+        .map(char::from)
+        .ok_or_else(|| format!("{} is not an ASCII character", escape))
+}
+
+/// Decode the one to six hex digits of a `\u{...}` escape. Any Unicode scalar value is allowed,
+/// which rules out surrogates and anything beyond `\u{10FFFF}`.
+pub fn decode_unicode(digits: &str) -> Result<char, String> {
+    let escape = format!("\\u{{{}}}", digits);
+    if digits.is_empty() || (6 < digits.len()) {
+        return Err(format!("{} must have one to six hex digits", escape));
+    }
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(std::char::from_u32)
+        .ok_or_else(|| format!("{} is not a Unicode scalar value", escape))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex() {
+        assert_eq!(decode_hex("41"), Ok('A'));
+        assert_eq!(decode_hex("00"), Ok('\0'));
+        assert_eq!(decode_hex("7f"), Ok('\x7F'));
+    }
+
+    #[test]
+    fn hex_out_of_range() {
+        assert_eq!(
+            decode_hex("80"),
+            Err("\\x80 is not an ASCII character".to_owned())
+        );
+        assert!(decode_hex("4").is_err());
+        assert!(decode_hex("zz").is_err());
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(decode_unicode("41"), Ok('A'));
+        assert_eq!(decode_unicode("1F600"), Ok('😀'));
+        assert_eq!(decode_unicode("10FFFF"), Ok('\u{10FFFF}'));
+    }
+
+    #[test]
+    fn unicode_out_of_range() {
+        assert_eq!(
+            decode_unicode("D800"),
+            Err("\\u{D800} is not a Unicode scalar value".to_owned())
+        );
+        assert!(decode_unicode("110000").is_err());
+        assert!(decode_unicode("").is_err());
+        assert!(decode_unicode("0000041").is_err());
+    }
+}
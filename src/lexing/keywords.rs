@@ -5,14 +5,40 @@
 //! ambiguities. Some of the keywords here are reserved but not used. They are
 //! reserved to avoid their use as symbols in source files so that they can
 //! potentially be used in the future without breaking existing code.
+//!
+//! Builds of the language can reserve more words than these, such as an
+//! experimental build claiming words for features it's trying out. See
+//! [Reservation].
 
 use std::collections::HashMap;
 
 use crate::common::multiphase::{Accessibility, PseudoIdentifier};
+use crate::common::version::Version;
 use crate::lexing::tokens::{
     Binding, BranchingAndJumping, DeclarationHead, Macros, Modifier, ModuleDefinitions, Token,
 };
 
+/// A word reserved for future use on top of the language's own reserved words.
+/// Sources declaring a version before `since` can still use it as a name, so
+/// reserving it doesn't break code written for earlier versions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Reservation {
+    pub word: &'static str,
+    pub since: Version,
+}
+
+impl Reservation {
+    /// Whether the word is reserved for a source declaring the given version,
+    /// if it declares one at all. Sources that don't are assumed to be
+    /// written for the latest version.
+    pub fn applies_to(&self, declared: Option<Version>) -> bool {
+        match declared {
+            Some(version) => self.since <= version,
+            None => true,
+        }
+    }
+}
+
 pub fn new() -> HashMap<&'static str, Token> {
     let mut map = HashMap::new();
     map.extend(vec![
@@ -35,11 +61,19 @@ pub fn new() -> HashMap<&'static str, Token> {
         // Used
         //
         ("as", Token::Binding(Binding::As)),
+        (
+            "break",
+            Token::BranchingAndJumping(BranchingAndJumping::Break),
+        ),
+        ("caused", Token::Caused),
         ("class", Token::DeclarationHead(DeclarationHead::Class)),
+        ("const", Token::Binding(Binding::Const)),
+        ("defer", Token::Defer),
         (
             "else",
             Token::BranchingAndJumping(BranchingAndJumping::Else),
         ),
+        ("embeds", Token::DeclarationHead(DeclarationHead::Embeds)),
         ("extend", Token::DeclarationHead(DeclarationHead::Extend)),
         ("extends", Token::Extends),
         (
@@ -47,6 +81,10 @@ pub fn new() -> HashMap<&'static str, Token> {
             Token::ModuleDefinitions(ModuleDefinitions::Exports),
         ),
         ("extern", Token::DeclarationHead(DeclarationHead::Extern)),
+        (
+            "fallthrough",
+            Token::BranchingAndJumping(BranchingAndJumping::Fallthrough),
+        ),
         ("final", Token::Binding(Binding::Final)),
         ("for", Token::BranchingAndJumping(BranchingAndJumping::For)),
         ("fun", Token::DeclarationHead(DeclarationHead::Fun)),
@@ -102,6 +140,10 @@ pub fn new() -> HashMap<&'static str, Token> {
             "while",
             Token::BranchingAndJumping(BranchingAndJumping::While),
         ),
+        (
+            "yield",
+            Token::BranchingAndJumping(BranchingAndJumping::Yield),
+        ),
         //
         // Reserved, but not yet used
         //
@@ -174,7 +216,6 @@ pub fn new() -> HashMap<&'static str, Token> {
         ("unchecked", Token::ReservedKeyword),
         ("unsafe", Token::ReservedKeyword),
         ("unllvm", Token::ReservedKeyword),
-        ("yield", Token::ReservedKeyword),
         ("value", Token::ReservedKeyword),
         ("virtual", Token::ReservedKeyword),
         ("where", Token::ReservedKeyword),
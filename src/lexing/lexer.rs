@@ -1,21 +1,26 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::io;
+use std::num::{IntErrorKind, ParseIntError};
 use std::sync::mpsc::{channel, Receiver, RecvError, SendError};
 use std::thread::{self, JoinHandle};
 
+use crate::common::diagnostics::Diagnostic;
 use crate::common::multiphase::{
-    self, Identifier, InterpolatedString, Number, OverloadableInfixOperator,
-    OverloadableSliceOperator, PostfixOperator, PseudoIdentifier, SylanString,
+    self, HexFloat, Identifier, InterpolatedString, Interpolation, Number, NumericType,
+    OverloadableInfixOperator, OverloadableSliceOperator, PostfixOperator, PseudoIdentifier,
+    Scientific, SylanString,
 };
 use crate::common::newlines::{check_newline, NewLine};
 use crate::common::peekable_buffer::PeekableBuffer;
 use crate::common::string_matches_char_slice;
 use crate::common::version::Version;
-use crate::lexing::tokens::{Binding, Grouping, Literal, Macros, Token};
-use crate::lexing::{char_escapes, keywords, non_word_chars};
+use crate::lexing::keywords::Reservation;
+use crate::lexing::tokens::{Binding, Grouping, Literal, Macros, ModuleDefinitions, Token};
+use crate::lexing::{char_escapes, keywords, non_word_chars, normalisation};
 use crate::source::in_memory::Source;
-use crate::source::Position;
+use crate::source::{Position, Span};
 
 const LEXER_THREAD_NAME: &str = "Sylan Lexer";
 
@@ -23,14 +28,107 @@ const LEXER_THREAD_NAME: &str = "Sylan Lexer";
 /// on either side. Tracking this allows tooling to pull apart code, refactor
 /// it, and then put it back together without breaking whitespace formatting in
 /// the existing source.
+///
+/// Equality is exact, comparing positions too. Use [LexedToken::same_token] or
+/// [LexedToken::same_token_and_trivia] to compare what was lexed regardless of
+/// where it was.
 #[derive(Clone, Eq, Debug, Default, PartialEq)]
 pub struct LexedToken {
     pub position: Position,
-    pub trivia: Option<String>,
+    pub trivia: Option<Trivia>,
     pub token: Token,
 }
 
-#[derive(Debug)]
+/// A run of whitespace or a single comment, kept verbatim with any comment
+/// delimiters. SyDocs aren't trivia, being lexed as tokens of their own.
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub enum TriviaItem {
+    Whitespace(String),
+    LineComment(String),
+    BlockComment(String),
+}
+
+/// The trivia before a token, in source order.
+#[derive(Clone, Eq, Debug, Default, PartialEq)]
+pub struct Trivia(pub Vec<TriviaItem>);
+
+impl Trivia {
+    /// The trivia as it appeared in the source.
+    pub fn text(&self) -> String {
+        self.0
+            .iter()
+            .map(|item| match item {
+                TriviaItem::Whitespace(text)
+                | TriviaItem::LineComment(text)
+                | TriviaItem::BlockComment(text) => text.as_str(),
+            })
+            .collect()
+    }
+
+    fn push_whitespace(&mut self, c: char) {
+        match self.0.last_mut() {
+            Some(TriviaItem::Whitespace(whitespace)) => whitespace.push(c),
+            _ => self.0.push(TriviaItem::Whitespace(c.to_string())),
+        }
+    }
+}
+
+/// A comment found in a token's trivia, verbatim with its delimiters.
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub struct TriviaComment {
+    pub text: String,
+
+    /// Whether a newline came before the comment within the trivia, i.e.
+    /// whether it started on a line of its own rather than trailing the
+    /// previous token.
+    pub follows_newline: bool,
+}
+
+impl LexedToken {
+    /// Whether both are the same token, wherever they are and whatever trivia
+    /// precedes them.
+    pub fn same_token(&self, other: &LexedToken) -> bool {
+        self.token == other.token
+    }
+
+    /// Whether both are the same token preceded by the same trivia, wherever
+    /// they are.
+    pub fn same_token_and_trivia(&self, other: &LexedToken) -> bool {
+        self.same_token(other) && (self.trivia == other.trivia)
+    }
+
+    /// Pull the comments out of the token's trivia, in source order.
+    pub fn comments(&self) -> Vec<TriviaComment> {
+        let items = match &self.trivia {
+            Some(Trivia(items)) => items,
+            None => return vec![],
+        };
+
+        let mut comments = vec![];
+        let mut follows_newline = false;
+        for item in items {
+            match item {
+                TriviaItem::Whitespace(whitespace) => {
+                    if whitespace.contains(['\n', '\r']) {
+                        follows_newline = true;
+                    }
+                }
+                TriviaItem::LineComment(text) | TriviaItem::BlockComment(text) => {
+                    comments.push(TriviaComment {
+                        text: text.clone(),
+                        follows_newline,
+                    });
+                    // Only a newline after this comment puts the next one on
+                    // a line of its own.
+                    follows_newline = false;
+                }
+            }
+        }
+        comments
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ErrorDescription {
     Described(String),
     Expected(char),
@@ -40,15 +138,43 @@ pub enum ErrorDescription {
     MalformedNumber(String),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Error {
     position: Position,
     description: ErrorDescription,
 }
 
+impl Error {
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        let (code, message) = match &error.description {
+            ErrorDescription::Described(description) => ("lexer", description.clone()),
+            ErrorDescription::Expected(c) => ("expected-char", format!("expected {:?}", c)),
+            ErrorDescription::Unexpected(c) => ("unexpected-char", format!("unexpected {:?}", c)),
+            ErrorDescription::PrematureEof => {
+                ("premature-eof", "the source ended early".to_owned())
+            }
+            ErrorDescription::ChannelFailure(description) => ("lexer-channel", description.clone()),
+            ErrorDescription::MalformedNumber(description) => {
+                ("malformed-number", description.clone())
+            }
+        };
+        Diagnostic::error(code, message, Some(Span::at(error.position)))
+    }
+}
+
 #[derive(Debug)]
 pub enum LexerTaskError {
     Lexer(Error),
+
+    /// Lexing finished, but some tokens couldn't be lexed along the way.
+    Invalid(Vec<Error>),
+
     Task(Box<dyn Any + Send + 'static>),
 }
 
@@ -59,12 +185,31 @@ type LexedTokenResult = Result<LexedToken, Error>;
 /// combined with a join handle on the underlying thread.
 pub struct LexerTask {
     tokens: Receiver<LexedToken>,
-    lexer_handle: JoinHandle<Result<(), Error>>,
+
+    /// Tasks over tokens that were already lexed have no thread behind them.
+    lexer_handle: Option<JoinHandle<Result<(), Error>>>,
 }
 
 impl LexerTask {
+    /// A task replaying tokens that were already lexed, such as part of a
+    /// source lexed up front. Joining it always succeeds.
+    pub fn from_lexed(lexed: Vec<LexedToken>) -> Self {
+        let (tx, rx) = channel();
+        for token in lexed {
+            // The receiver is held right here, so sending can't fail.
+            tx.send(token).unwrap();
+        }
+        Self {
+            tokens: rx,
+            lexer_handle: None,
+        }
+    }
+
     pub fn join(self) -> Result<(), LexerTaskError> {
-        let joined = self.lexer_handle.join();
+        let joined = match self.lexer_handle {
+            Some(handle) => handle.join(),
+            None => return Ok(()),
+        };
         match joined {
             Ok(result) => match result {
                 Ok(()) => Ok(()),
@@ -83,6 +228,69 @@ fn is_start_of_literal_with_escapes(c: char) -> bool {
     (c == '\'') || (c == '"') || (c == '$') || (c == '`')
 }
 
+/// The integer with the magnitude written in another radix, if it fits. The
+/// magnitude of the most negative integer is one more than the most positive,
+/// so it's negated as a wider integer first.
+fn signed_integer(negative: bool, magnitude: u64) -> Option<Number> {
+    let magnitude = i128::from(magnitude);
+    i64::try_from(if negative { -magnitude } else { magnitude })
+        .ok()
+        .map(|integer| Number(integer, 0))
+}
+
+/// Tracks how the lines of a multiline string are indented, so that stripping
+/// their common indentation stays well defined. Every line must be indented
+/// with the same character, whether tabs or spaces, as otherwise a tab could
+/// stand for any number of spaces.
+struct MultilineIndentation {
+    /// The character the first indented line starts with, and its line.
+    established: Option<(char, usize)>,
+
+    /// Whether the source is still within the current line's indentation.
+    /// The first line follows the opening delimiter, so it has none.
+    indenting: bool,
+}
+
+impl MultilineIndentation {
+    fn new() -> Self {
+        Self {
+            established: None,
+            indenting: false,
+        }
+    }
+
+    /// Observe the next character of the string as written in the source,
+    /// before any escapes are processed.
+    fn observe(&mut self, c: char, line: usize) -> Result<(), String> {
+        let name = |c| if c == '\t' { "tabs" } else { "spaces" };
+        match c {
+            '\n' | '\r' => self.indenting = true,
+            ' ' | '\t' if self.indenting => match self.established {
+                Some((established, _)) if established == c => {}
+                Some((_, established_line)) if established_line == line => {
+                    return Err(format!(
+                        "line {} of a multiline string mixes tabs and spaces in its indentation",
+                        line
+                    ))
+                }
+                Some((established, established_line)) => {
+                    return Err(format!(
+                        "line {} of a multiline string is indented with {} but line {} with {}; \
+                         indent every line with one or the other",
+                        line,
+                        name(c),
+                        established_line,
+                        name(established)
+                    ))
+                }
+                None => self.established = Some((c, line)),
+            },
+            _ => self.indenting = false,
+        }
+        Ok(())
+    }
+}
+
 struct CachedStringPrefixes {
     package_prefix_str: Vec<char>,
     module_prefix_str: Vec<char>,
@@ -95,11 +303,42 @@ struct LexerCache {
     non_word_chars: HashSet<char>,
 }
 
+/// Options for how a lexer treats its source. Every option is off by default,
+/// which suits compiling Sylan; tooling such as editors and formatters may want
+/// otherwise.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LexerConfig {
+    /// Emit each newline as a `Token::NewLine` rather than folding it into the
+    /// following token's trivia. Newlines inside comments stay as trivia.
+    pub significant_newlines: bool,
+
+    /// Skip a byte order mark at the very start of the source, as some editors
+    /// on Windows add one. Otherwise it's lexed like any other character.
+    pub strip_byte_order_mark: bool,
+
+    /// The most characters an identifier can have, if there's a limit.
+    pub max_identifier_length: Option<usize>,
+
+    /// Words to reserve for future use beyond those the language reserves.
+    pub reserved_keywords: &'static [Reservation],
+}
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
 /// A lexer that is used by a `LexerTask` to produce a stream of tokens. Each lexer has a source
 /// code to lex, and a set of character escapes and known keyword mappings to use.
 pub struct Lexer {
     source: Source,
     cache: LexerCache,
+    config: LexerConfig,
+
+    /// The language version the source states at its start, if any.
+    declared_version: Option<Version>,
+
+    /// Whether the lexer is within a module's `requires` clause, where a
+    /// number such as `1.2.0` is a version to constrain the module by.
+    /// Elsewhere, it's a decimal followed by a lookup.
+    in_requirement: bool,
 }
 
 impl From<Source> for Lexer {
@@ -115,11 +354,57 @@ impl From<Source> for Lexer {
                     module_prefix_str: ".module".chars().collect(),
                 },
             },
+            config: Default::default(),
+            declared_version: None,
+            in_requirement: false,
         }
     }
 }
 
 impl Lexer {
+    pub fn with_config(mut self, config: LexerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// A shorthand for configuring [LexerConfig::significant_newlines].
+    pub fn with_significant_newlines(mut self) -> Self {
+        self.config.significant_newlines = true;
+        self
+    }
+
+    fn is_trivial_whitespace(&self, c: char) -> bool {
+        c.is_whitespace() && !(self.config.significant_newlines && ((c == '\n') || (c == '\r')))
+    }
+
+    /// Identifiers, with the configured limit on their length.
+    fn identifier(&self, name: String) -> TokenResult {
+        let name = normalisation::partial_nfc(name);
+        let length = name.chars().count();
+        match self.config.max_identifier_length {
+            Some(max) if max < length => self.fail(format!(
+                "identifiers can be at most {} characters long, but this one is {}",
+                max, length
+            )),
+            _ => Ok(Token::Identifier(Identifier::from(name))),
+        }
+    }
+
+    /// Whether a configured reservation applies to a word that the language
+    /// itself doesn't reserve.
+    fn is_reserved_by_config(&self, word: &str) -> bool {
+        self.config.reserved_keywords.iter().any(|reservation| {
+            reservation.word == word && reservation.applies_to(self.declared_version)
+        })
+    }
+
+    fn reserved(&self, word: &str) -> TokenResult {
+        self.fail(format!(
+            "`{}` is reserved for future use; quote it with backticks to use it as a name",
+            word
+        ))
+    }
+
     /// Fail at lexing, describing the reason why.
     fn fail<T>(&self, description: impl Into<String>) -> Result<T, Error> {
         Err(Error {
@@ -239,19 +524,21 @@ impl Lexer {
 
     fn lex_single_line_comment(&mut self, buffer: &mut String) {
         self.source.discard_many(2);
-        while let Some(c) = self.source.read() {
-            if (c == '\n') || ((c == '\r') && !self.source.next_is('\n')) {
-                break;
-            } else if (c == '\r') && self.source.next_is('\n') {
-                self.source.discard();
+        buffer.push_str("//");
+
+        // Leave the terminating newline in the source; it's whitespace trivia
+        // in its own right.
+        while let Some(&c) = self.source.peek() {
+            if (c == '\n') || (c == '\r') {
                 break;
             } else {
                 buffer.push(c);
+                self.source.discard();
             }
         }
     }
 
-    fn lex_trivia(&mut self) -> Result<Option<String>, Error> {
+    fn lex_trivia(&mut self) -> Result<Option<Trivia>, Error> {
         let is_empty = {
             let c = self.source.peek().cloned();
 
@@ -260,14 +547,14 @@ impl Lexer {
                     self.source.nth_is(1, '*') && !self.source.nth_is(2, '*');
                 !(is_multiline_comment || self.source.nth_is(1, '/'))
             } else {
-                c.filter(|x| x.is_whitespace()).is_none()
+                c.filter(|&x| self.is_trivial_whitespace(x)).is_none()
             }
         };
 
         if is_empty {
             Ok(None)
         } else {
-            let mut trivia = String::new();
+            let mut trivia = Trivia::default();
             loop {
                 let next_char = self.source.peek().cloned();
 
@@ -278,13 +565,20 @@ impl Lexer {
                     && self.source.nth_is(1, '*')
                     && !self.source.nth_is(2, '*')
                 {
-                    if let Some(err) = self.lex_multi_line_comment(&mut trivia) {
+                    let mut comment = String::from("/*");
+                    if let Some(err) = self.lex_multi_line_comment(&mut comment) {
                         break Err(err);
                     }
+                    comment.push_str("*/");
+                    trivia.0.push(TriviaItem::BlockComment(comment));
                 } else if (next_char == Some('/')) && self.source.nth_is(1, '/') {
-                    self.lex_single_line_comment(&mut trivia)
-                } else if let Some((c, true)) = next_char.map(|x| (x, x.is_whitespace())) {
-                    trivia.push(c);
+                    let mut comment = String::new();
+                    self.lex_single_line_comment(&mut comment);
+                    trivia.0.push(TriviaItem::LineComment(comment));
+                } else if let Some((c, true)) =
+                    next_char.map(|x| (x, self.is_trivial_whitespace(x)))
+                {
+                    trivia.push_whitespace(c);
                     self.source.discard();
                 } else {
                     break Ok(Some(trivia));
@@ -293,6 +587,16 @@ impl Lexer {
         }
     }
 
+    fn lex_newline(&mut self) -> Token {
+        // Discard a CRLF in one go so it's tracked as a single line break.
+        if self.source.next_is('\r') && self.source.nth_is(1, '\n') {
+            self.source.discard_many(2);
+        } else {
+            self.source.discard();
+        }
+        Token::NewLine
+    }
+
     fn lex_version(&mut self) -> TokenResult {
         self.source.discard();
 
@@ -300,23 +604,364 @@ impl Lexer {
             .map(|Number(real, fractional)| {
                 // TODO: lex this properly. Unlike an absolute number, it must support more than one
                 // decimal place.
-                Token::Version(Version {
+                let version = Version {
                     major: real as u64,
                     minor: fractional,
                     patch: 0,
-                })
+                };
+                self.declared_version = Some(version);
+                Token::Version(version)
             })
             .map(Ok)
             .unwrap_or_else(|_| self.fail("invalid version number"))
     }
 
     fn lex_number(&mut self) -> TokenResult {
-        self.lex_absolute_number()
-            .map(|Number(real, fractional)| {
-                Token::Literal(Literal::Number(Number(real, fractional)))
-            })
-            .map(Ok)
-            .unwrap_or_else(|_| self.fail("invalid number"))
+        let sign_length = if self.source.next_is('-') || self.source.next_is('+') {
+            1
+        } else {
+            0
+        };
+        if self.source.nth_is(sign_length, '0') && self.source.nth_is(sign_length + 1, 'x') {
+            return self.lex_hexadecimal();
+        }
+        if self.source.nth_is(sign_length, '0') && self.source.nth_is(sign_length + 1, 'b') {
+            return self.lex_binary();
+        }
+
+        // C reads `0123` as octal, so it's rejected rather than silently
+        // meaning something else to those used to that. A lone `0`, or one
+        // before a decimal point as in `0.5`, is fine.
+        let has_leading_zero = self.source.nth_is(sign_length, '0')
+            && matches!(self.source.peek_nth(sign_length + 1), Some(c) if c.is_ascii_digit());
+        if has_leading_zero {
+            return Err(self.error(ErrorDescription::MalformedNumber(
+                "decimal numbers can't have leading zeros; C would read them as octal".to_owned(),
+            )));
+        }
+
+        if sign_length == 0 && self.in_requirement && self.next_is_dotted_version() {
+            return self.lex_dotted_version();
+        }
+        if self.next_is_scientific(sign_length) {
+            return self.lex_scientific();
+        }
+
+        let number = match self.lex_absolute_number() {
+            Ok(number) => number,
+            Err(
+                error @ Error {
+                    description: ErrorDescription::MalformedNumber(_),
+                    ..
+                },
+            ) => return Err(error),
+            Err(_) => return self.fail("invalid number"),
+        };
+        let numeric_type = self.lex_numeric_type_suffix(&number)?;
+        Ok(Token::Literal(Literal::Number(number, numeric_type)))
+    }
+
+    /// Numbers with three dotted components, such as `1.2.0`, are versions
+    /// rather than decimals within the constraints of a module's requirements.
+    fn next_is_dotted_version(&mut self) -> bool {
+        let mut n = 0;
+        for component in 0..3 {
+            if 0 < component {
+                if !self.source.nth_is(n, '.') {
+                    return false;
+                }
+                n += 1;
+            }
+            let start = n;
+            while matches!(self.source.peek_nth(n), Some(c) if c.is_ascii_digit()) {
+                n += 1;
+            }
+            if n == start {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn lex_dotted_version(&mut self) -> TokenResult {
+        let mut components = [0u64; 3];
+        for (i, component) in components.iter_mut().enumerate() {
+            if 0 < i {
+                self.source.discard();
+            }
+            let mut digits = String::new();
+            while let Some(&c) = self.source.peek().filter(|c| c.is_ascii_digit()) {
+                self.source.discard();
+                digits.push(c);
+            }
+            *component = match digits.parse() {
+                Ok(parsed) => parsed,
+                Err(_) => return self.fail(format!("version component {} is too large", digits)),
+            };
+        }
+        let [major, minor, patch] = components;
+        Ok(Token::Version(Version {
+            major,
+            minor,
+            patch,
+        }))
+    }
+
+    /// Whether a number in scientific notation, such as `1.5e-3`, is next. An
+    /// `e` or `E` after a number only starts an exponent if a digit follows it,
+    /// optionally after a sign; otherwise it's lexed as a type suffix would be.
+    fn next_is_scientific(&mut self, sign_length: usize) -> bool {
+        let is_digit =
+            |lexer: &mut Self, n| matches!(lexer.source.peek_nth(n), Some(c) if c.is_ascii_digit());
+
+        let mut n = sign_length;
+        while is_digit(self, n) {
+            n += 1;
+        }
+        if self.source.nth_is(n, '.') && !self.source.nth_is(n + 1, '.') {
+            n += 1;
+            while is_digit(self, n) {
+                n += 1;
+            }
+        }
+        if !matches!(self.source.peek_nth(n), Some(&('e' | 'E'))) {
+            return false;
+        }
+        n += 1;
+        if matches!(self.source.peek_nth(n), Some(&('-' | '+'))) {
+            n += 1;
+        }
+        is_digit(self, n)
+    }
+
+    /// Lex a decimal in scientific notation, such as `-1.5E+3`. The exponent is
+    /// in decimal and can be signed, and either case of `e` can introduce it.
+    /// As with any decimal, a point must have digits on both sides, so neither
+    /// `.5e3` nor `1.e3` is accepted.
+    fn lex_scientific(&mut self) -> TokenResult {
+        let negative = self.source.next_is('-');
+        if negative || self.source.next_is('+') {
+            self.source.discard();
+        }
+
+        let malformed = |lexer: &Self, description: &str| {
+            Err(lexer.error(ErrorDescription::MalformedNumber(format!(
+                "malformed scientific notation: {}",
+                description
+            ))))
+        };
+
+        let mut digits = String::new();
+        self.lex_digits(&mut digits);
+        let mut fractional_digits = 0;
+        if self.source.next_is('.') {
+            self.source.discard();
+            let before = digits.len();
+            self.lex_digits(&mut digits);
+            fractional_digits = digits.len() - before;
+            if fractional_digits == 0 {
+                return malformed(self, "a digit must follow the decimal point, as in `1.0e3`");
+            }
+        }
+
+        // Skip the `e` or `E`, which is known to be there.
+        self.source.discard();
+        let mut written_exponent = String::new();
+        if let Some(&sign @ ('-' | '+')) = self.source.peek() {
+            written_exponent.push(sign);
+            self.source.discard();
+        }
+        self.lex_digits(&mut written_exponent);
+
+        let significand = match digits.parse() {
+            Ok(significand) => significand,
+            Err(_) => return malformed(self, "too many digits in the significand"),
+        };
+        let exponent = match written_exponent.parse::<i32>().ok().and_then(|written| {
+            i32::try_from(fractional_digits)
+                .ok()
+                .and_then(|fractional_digits| written.checked_sub(fractional_digits))
+        }) {
+            Some(exponent) => exponent,
+            None => return malformed(self, "the exponent is out of range"),
+        };
+
+        // A range such as `1e3..1e6` can follow, but not a decimal point.
+        let starts_range = self.source.nth_is(1, '.');
+        match self.source.peek() {
+            Some(&c) if c.is_alphanumeric() || (c == '.' && !starts_range) => {
+                malformed(self, &format!("unexpected `{}`", c))
+            }
+            _ => Ok(Token::Literal(Literal::Scientific(Scientific {
+                negative,
+                significand,
+                exponent,
+            }))),
+        }
+    }
+
+    fn lex_digits(&mut self, buffer: &mut String) {
+        while let Some(&c) = self.source.peek().filter(|c| c.is_ascii_digit()) {
+            self.source.discard();
+            buffer.push(c);
+        }
+    }
+
+    /// Lex a hexadecimal integer such as `0xff`, or a hexadecimal float such as
+    /// `0x1.8p3`. A float's mantissa is in hexadecimal and the exponent
+    /// following the `p` is in decimal, scaling the mantissa by that power of
+    /// two. Without a point or an exponent, it's an integer.
+    fn lex_hexadecimal(&mut self) -> TokenResult {
+        let negative = self.source.next_is('-');
+        if negative || self.source.next_is('+') {
+            self.source.discard();
+        }
+        self.source.discard_many(2);
+
+        let malformed = |lexer: &Self, description: &str| {
+            Err(lexer.error(ErrorDescription::MalformedNumber(format!(
+                "malformed hexadecimal number: {}",
+                description
+            ))))
+        };
+
+        let mut mantissa = 0u64;
+        let mut exponent = 0i32;
+        let mut digits = 0;
+        let mut point_consumed = false;
+        loop {
+            match self.source.peek().cloned() {
+                // As with decimals, a second dot makes it a range instead.
+                Some('.') if !point_consumed && !self.source.nth_is(1, '.') => {
+                    point_consumed = true;
+                    self.source.discard();
+                }
+                Some(c) if c.is_ascii_hexdigit() => {
+                    self.source.discard();
+                    let digit = u64::from(c.to_digit(16).unwrap());
+                    mantissa = match mantissa.checked_mul(16).and_then(|m| m.checked_add(digit)) {
+                        Some(mantissa) => mantissa,
+                        None => return malformed(self, "too many digits in the mantissa"),
+                    };
+                    if point_consumed {
+                        exponent -= 4;
+                    }
+                    digits += 1;
+                }
+                _ => break,
+            }
+        }
+        if digits == 0 {
+            return malformed(self, "the mantissa has no digits");
+        }
+
+        if !point_consumed && !self.source.next_is('p') {
+            return match signed_integer(negative, mantissa) {
+                Some(number) => {
+                    let numeric_type = self.lex_numeric_type_suffix(&number)?;
+                    Ok(Token::Literal(Literal::Number(number, numeric_type)))
+                }
+                None => malformed(self, "too many digits to fit in 64 bits"),
+            };
+        }
+        if !self.source.next_is('p') {
+            return malformed(self, "missing the `p` exponent, as in `0x1.8p3`");
+        }
+        self.source.discard();
+        let mut written_exponent = String::new();
+        if let Some(&sign @ ('-' | '+')) = self.source.peek() {
+            written_exponent.push(sign);
+            self.source.discard();
+        }
+        while let Some(&c) = self.source.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            written_exponent.push(c);
+            self.source.discard();
+        }
+        let exponent = match written_exponent
+            .parse::<i32>()
+            .ok()
+            .and_then(|written| exponent.checked_add(written))
+        {
+            Some(exponent) => exponent,
+            None => return malformed(self, "invalid exponent"),
+        };
+
+        match self.source.peek() {
+            Some(&c) if c.is_alphanumeric() || c == '.' => {
+                malformed(self, &format!("unexpected `{}`", c))
+            }
+            _ => Ok(Token::Literal(Literal::HexFloat(HexFloat {
+                negative,
+                mantissa,
+                exponent,
+            }))),
+        }
+    }
+
+    /// Lex a binary integer such as `0b101`.
+    fn lex_binary(&mut self) -> TokenResult {
+        let negative = self.source.next_is('-');
+        if negative || self.source.next_is('+') {
+            self.source.discard();
+        }
+        self.source.discard_many(2);
+
+        let malformed = |lexer: &Self, description: &str| {
+            Err(lexer.error(ErrorDescription::MalformedNumber(format!(
+                "malformed binary number: {}",
+                description
+            ))))
+        };
+
+        let mut magnitude = 0u64;
+        let mut digits = 0;
+        while let Some(&c @ ('0' | '1')) = self.source.peek() {
+            self.source.discard();
+            let digit = u64::from(c == '1');
+            magnitude = match magnitude.checked_mul(2).and_then(|m| m.checked_add(digit)) {
+                Some(magnitude) => magnitude,
+                None => return malformed(self, "too many digits to fit in 64 bits"),
+            };
+            digits += 1;
+        }
+        if digits == 0 {
+            return malformed(self, "there are no digits");
+        }
+        match self.source.peek().cloned() {
+            Some(c) if c.is_ascii_digit() || (c == '.' && !self.source.nth_is(1, '.')) => {
+                malformed(self, &format!("unexpected `{}`", c))
+            }
+            _ => match signed_integer(negative, magnitude) {
+                Some(number) => {
+                    let numeric_type = self.lex_numeric_type_suffix(&number)?;
+                    Ok(Token::Literal(Literal::Number(number, numeric_type)))
+                }
+                None => malformed(self, "too many digits to fit in 64 bits"),
+            },
+        }
+    }
+
+    /// Lex an optional type suffix directly following a number, such as the
+    /// `i32` in `42i32`, checking that the number fits in that type.
+    fn lex_numeric_type_suffix(&mut self, number: &Number) -> Result<Option<NumericType>, Error> {
+        match self.source.peek() {
+            Some(c) if c.is_alphabetic() => {}
+            _ => return Ok(None),
+        }
+        let mut suffix = String::new();
+        self.lex_rest_of_word(&mut suffix);
+
+        let numeric_type = match NumericType::from_suffix(&suffix) {
+            Some(numeric_type) => numeric_type,
+            None => return self.fail(format!("invalid numeric type suffix: {}", suffix)),
+        };
+        if let Err(description) = numeric_type.check_bounds(number) {
+            return Err(self.error(ErrorDescription::MalformedNumber(description)));
+        }
+        Ok(Some(numeric_type))
     }
 
     fn lex_rest_of_word(&mut self, buffer: &mut String) {
@@ -334,7 +979,7 @@ impl Lexer {
     fn lex_multiphase_identifier(&mut self) -> multiphase::Identifier {
         let mut word = String::new();
         self.lex_rest_of_word(&mut word);
-        multiphase::Identifier::from(word)
+        multiphase::Identifier::from(normalisation::partial_nfc(word))
     }
 
     fn lex_identifier(&mut self) -> Token {
@@ -345,6 +990,22 @@ impl Lexer {
         self.source.discard();
 
         match self.source.read() {
+            Some('x') => {
+                let digits = self.lex_escape_digits(2)?;
+                char_escapes::decode_hex(&digits).or_else(|err| self.fail(err))
+            }
+            Some('u') => {
+                self.expect_and_discard('{')?;
+                let mut digits = String::new();
+                loop {
+                    match self.source.read() {
+                        Some('}') => break,
+                        Some(c) => digits.push(c),
+                        None => return Err(self.premature_eof()),
+                    }
+                }
+                char_escapes::decode_unicode(&digits).or_else(|err| self.fail(err))
+            }
             Some(escaped) => self
                 .cache
                 .char_escapes
@@ -356,6 +1017,47 @@ impl Lexer {
         }
     }
 
+    /// A backslash ending a line in an escaping string continues the string
+    /// onto the next line without a newline, so `"abc\<newline>def"` is
+    /// `"abcdef"`. Returns whether one was discarded.
+    fn discard_line_continuation(&mut self) -> bool {
+        let length = if self.source.nth_is(1, '\r') && self.source.nth_is(2, '\n') {
+            3
+        } else if self.source.nth_is(1, '\n') || self.source.nth_is(1, '\r') {
+            2
+        } else {
+            return false;
+        };
+        self.source.discard_many(length)
+    }
+
+    fn lex_escape_digits(&mut self, count: usize) -> Result<String, Error> {
+        let mut digits = String::new();
+        for _ in 0..count {
+            match self.source.read() {
+                Some(c) => digits.push(c),
+                None => return Err(self.premature_eof()),
+            }
+        }
+        Ok(digits)
+    }
+
+    /// Only strings with custom delimiters, such as `"""`, are multiline.
+    fn observe_indentation(
+        &mut self,
+        delimiter_count: usize,
+        indentation: &mut MultilineIndentation,
+    ) -> Result<(), Error> {
+        if delimiter_count < 3 {
+            return Ok(());
+        }
+        let line = self.source.position.line();
+        match self.source.peek().copied() {
+            Some(c) => indentation.observe(c, line).or_else(|err| self.fail(err)),
+            None => Ok(()),
+        }
+    }
+
     fn lex_string_content(
         &mut self,
         delimiter: char,
@@ -363,7 +1065,9 @@ impl Lexer {
         escaping: bool,
     ) -> Result<String, Error> {
         let mut string = String::new();
+        let mut indentation = MultilineIndentation::new();
         loop {
+            self.observe_indentation(delimiter_count, &mut indentation)?;
             match self.source.peek() {
                 Some(&c) if c == delimiter => {
                     self.source.discard();
@@ -383,6 +1087,9 @@ impl Lexer {
                 }
                 Some(&c) => {
                     let maybe_escaped = if (c == '\\') && escaping {
+                        if self.discard_line_continuation() {
+                            continue;
+                        }
                         self.lex_escape_char_in_string_or_char()?
                     } else {
                         self.source.discard();
@@ -404,8 +1111,11 @@ impl Lexer {
         let mut string_fragments = vec!["".to_owned()];
         let mut interpolations = Vec::new();
         let mut start_new_fragment = false;
+        let contents_start = self.source.position.absolute_character_index();
+        let mut indentation = MultilineIndentation::new();
 
         loop {
+            self.observe_indentation(delimiter_count, &mut indentation)?;
             match self.source.peek() {
                 Some(&c) if c == delimiter => {
                     self.source.discard();
@@ -446,15 +1156,18 @@ impl Lexer {
                         last_fragment.push('{');
                     } else {
                         self.source.discard();
-
-                        let identifier = self.lex_multiphase_identifier();
-                        self.expect_and_discard('}')?;
-                        interpolations.push(identifier);
+                        let offset =
+                            self.source.position.absolute_character_index() - contents_start;
+                        let source = self.lex_interpolation_source()?;
+                        interpolations.push(Interpolation::new(source, offset));
                         start_new_fragment = true;
                     }
                 }
                 Some(&c) => {
                     let maybe_escaped = if (c == '\\') && escaping {
+                        if self.discard_line_continuation() {
+                            continue;
+                        }
                         self.lex_escape_char_in_string_or_char()?
                     } else {
                         self.source.discard();
@@ -471,16 +1184,40 @@ impl Lexer {
         }
     }
 
+    /// Lex the source of an interpolated expression up to the brace closing
+    /// it, leaving it for the parser. Braces within it must be balanced, so
+    /// that records and blocks can be interpolated.
+    fn lex_interpolation_source(&mut self) -> Result<String, Error> {
+        let mut source = String::new();
+        let mut depth = 0;
+        loop {
+            match self.source.read() {
+                Some('}') if depth == 0 => break Ok(source),
+                Some(c) => {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    source.push(c);
+                }
+                None => break Err(self.premature_eof()),
+            }
+        }
+    }
+
     fn lex_string(&mut self, escaping: bool) -> TokenResult {
         self.source.discard();
         let string = self.lex_string_content('"', 1, escaping)?;
         Ok(Token::Literal(Literal::String(SylanString::from(string))))
     }
 
+    /// Quoted identifiers are never looked up as keywords, so reserved words can still be used as
+    /// names. This matters for FFI and for code written before a word became reserved.
     fn lex_quoted_identifier(&mut self, escaping: bool) -> TokenResult {
         self.source.discard();
         let string = self.lex_string_content('`', 1, escaping)?;
-        Ok(Token::Identifier(Identifier::from(string)))
+        self.identifier(string)
     }
 
     fn lex_interpolated_string(&mut self, escaping: bool) -> TokenResult {
@@ -654,8 +1391,10 @@ impl Lexer {
     fn lex_phrase(&mut self, word: String) -> TokenResult {
         match self.cache.keywords.get(&word[..]) {
             Some(Token::PseudoIdentifier(PseudoIdentifier::This)) => self.lex_rest_of_this(),
+            Some(Token::ReservedKeyword) => self.reserved(&word),
             Some(token) => Ok(token.clone()),
-            None => Ok(Token::Identifier(multiphase::Identifier::from(word))),
+            None if self.is_reserved_by_config(&word) => self.reserved(&word),
+            None => self.identifier(word),
         }
     }
 
@@ -686,6 +1425,8 @@ impl Lexer {
         Ok(result)
     }
 
+    /// Each component's digits are buffered and parsed once they've all been
+    /// read, keeping lexing linear in the number's length, however long.
     fn lex_absolute_number(&mut self) -> Result<Number, Error> {
         match self.source.read() {
             Some(c) if c.is_digit(10) || (c == '-') || (c == '+') => {
@@ -696,7 +1437,9 @@ impl Lexer {
                 let mut decimal_place_consumed = false;
                 loop {
                     match self.source.peek().cloned() {
-                        Some('.') if !decimal_place_consumed => {
+                        // A second dot makes it a range, such as `1..10`, rather than a
+                        // decimal.
+                        Some('.') if !decimal_place_consumed && !self.source.nth_is(1, '.') => {
                             decimal_place_consumed = true;
                             self.source.discard();
                         }
@@ -715,22 +1458,38 @@ impl Lexer {
                     fractional_to_parse.push('0')
                 }
 
+                // Echoing an overlong component back would bloat the error,
+                // which could otherwise be as long as the source itself.
+                let too_large = |lexer: &Self, component: &str, digits: &str| {
+                    lexer.error(ErrorDescription::MalformedNumber(format!(
+                        "the {} component has {} digits, too many to fit in 64 bits",
+                        component,
+                        digits.trim_start_matches(&['-', '+'][..]).len()
+                    )))
+                };
+
                 real_to_parse
                     .parse()
-                    .map_err(|err| {
-                        self.error(ErrorDescription::MalformedNumber(format!(
+                    .map_err(|err: ParseIntError| match err.kind() {
+                        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                            too_large(self, "real", &real_to_parse)
+                        }
+                        _ => self.error(ErrorDescription::MalformedNumber(format!(
                             "lexed real number component {} failed to parse: {}",
                             real_to_parse, err
-                        )))
+                        ))),
                     })
                     .and_then(|real| {
                         fractional_to_parse
                             .parse()
-                            .map_err(|err| {
-                                self.error(ErrorDescription::MalformedNumber(format!(
+                            .map_err(|err: ParseIntError| match err.kind() {
+                                IntErrorKind::PosOverflow => {
+                                    too_large(self, "fractional", &fractional_to_parse)
+                                }
+                                _ => self.error(ErrorDescription::MalformedNumber(format!(
                                     "lexed fractional number component {} failed to parse: {}",
                                     real_to_parse, err
-                                )))
+                                ))),
                             })
                             .map(|fractional| Number(real, fractional))
                     })
@@ -739,10 +1498,25 @@ impl Lexer {
         }
     }
 
+    /// Operators are lexed by maximal munch: the longest operator starting at the current
+    /// character is always taken, even if taking a shorter one would let the rest lex as a longer
+    /// operator. That keeps each adjacency predictable as operators are added; `a-->b` is `-` then
+    /// `->`, `|||` is `||` then `|`, and `>>=` is `>>` then `=` rather than `>` then `>=`.
+    /// Identifiers can contain operator characters, so this only concerns runs of operators
+    /// separated from identifiers by whitespace or grouping, such as `a -->b`.
+    ///
+    /// The exception is `-` or `+` directly before a digit, which starts a negative or positive
+    /// number rather than an operator. By the time this is called, that has already been ruled out
+    /// for the current character but not for any following it, so `a --1` is `-` then `-1`.
     fn lex_symbolic(&mut self) -> TokenResult {
         if let Some(c) = self.source.peek().cloned() {
             match c {
                 // The Infix Operators
+                '.' if matches!(self.source.peek_nth(1), Some(c) if c.is_ascii_digit()) => {
+                    Err(self.error(ErrorDescription::MalformedNumber(
+                        "a number needs a digit before its decimal point, as in `0.5`".to_owned(),
+                    )))
+                }
                 '.' => Ok(self.lex_with_leading_dot()),
                 '<' => Ok(self.lex_with_leading_left_angle_bracket()),
                 '=' => Ok(self.lex_with_leading_equals()),
@@ -781,14 +1555,15 @@ impl Lexer {
                         OverloadableInfixOperator::Modulo,
                     ))
                 }
-                '*' => {
-                    self.source.discard();
-                    Ok(self.lex_with_leading_asterisk())
-                }
+                '*' => Ok(self.lex_with_leading_asterisk()),
                 ',' => {
                     self.source.discard();
                     Ok(Token::SubItemSeparator)
                 }
+                '?' if self.source.nth_is(1, '?') && self.source.nth_is(2, '?') => {
+                    self.source.discard_many(3);
+                    Ok(Token::Hole)
+                }
                 '?' => {
                     self.source.discard();
                     Ok(Token::PostfixOperator(PostfixOperator::Bind))
@@ -808,6 +1583,12 @@ impl Lexer {
                 '|' => Ok(self.lex_with_leading_pipe()),
 
                 // Grouping tokens.
+                c if c.is_control() => self.unexpected(c),
+
+                '#' if self.source.nth_is(1, '{') => {
+                    self.source.discard_many(2);
+                    Ok(Token::Grouping(Grouping::OpenRecord))
+                }
                 '{' => {
                     self.source.discard();
                     Ok(Token::Grouping(Grouping::OpenBrace))
@@ -869,14 +1650,12 @@ impl Lexer {
     fn lex_with_leading_left_angle_bracket(&mut self) -> Token {
         self.source.discard();
         if self.source.next_is('<') {
-            self.source.discard();
             self.source.discard();
             Token::OverloadableInfixOperator(OverloadableInfixOperator::LeftShift)
         } else if self.source.next_is('=') {
             self.source.discard();
             Token::OverloadableInfixOperator(OverloadableInfixOperator::LessThanOrEqual)
         } else {
-            self.source.discard();
             Token::OverloadableInfixOperator(OverloadableInfixOperator::LessThan)
         }
     }
@@ -938,6 +1717,7 @@ impl Lexer {
                 Token::OverloadableInfixOperator(OverloadableInfixOperator::RightShift)
             }
         } else if self.source.next_is('=') {
+            self.source.discard();
             Token::OverloadableInfixOperator(OverloadableInfixOperator::GreaterThanOrEqual)
         } else {
             Token::OverloadableInfixOperator(OverloadableInfixOperator::GreaterThan)
@@ -966,8 +1746,14 @@ impl Lexer {
 
     fn lex_with_leading_exclamation_mark(&mut self) -> TokenResult {
         self.source.discard();
-        self.expect_and_discard('=')
-            .map(|_| Token::OverloadableInfixOperator(OverloadableInfixOperator::NotEqual))
+        if self.source.next_is('=') {
+            self.source.discard();
+            Ok(Token::OverloadableInfixOperator(
+                OverloadableInfixOperator::NotEqual,
+            ))
+        } else {
+            Ok(Token::PostfixOperator(PostfixOperator::ForceUnwrap))
+        }
     }
 
     fn lex_with_leading_at(&mut self) -> TokenResult {
@@ -1022,7 +1808,22 @@ impl Lexer {
         ))
     }
 
+    /// Lex the next token, keeping track of whether it's within a `requires`
+    /// clause. The clause runs from the keyword through the module's dotted
+    /// name and the constraint's comparison to the version.
     fn lex_non_trivia(&mut self) -> TokenResult {
+        let token = self.lex_any_non_trivia();
+        self.in_requirement = match &token {
+            Ok(Token::ModuleDefinitions(ModuleDefinitions::Requires)) => true,
+            Ok(Token::Identifier(_)) | Ok(Token::Dot) | Ok(Token::OverloadableInfixOperator(_)) => {
+                self.in_requirement
+            }
+            _ => false,
+        };
+        token
+    }
+
+    fn lex_any_non_trivia(&mut self) -> TokenResult {
         match self.source.peek() {
             None => Ok(Token::Eof),
             Some(&c) => {
@@ -1074,6 +1875,7 @@ impl Lexer {
                             }
                         }
                         '\'' => self.lex_char(true),
+                        '\n' | '\r' if self.config.significant_newlines => Ok(self.lex_newline()),
 
                         _ => {
                             let next = self.source.peek_nth(1).cloned();
@@ -1172,34 +1974,62 @@ impl Lexer {
         }
     }
 
-    pub fn lex_version_or_next_non_trivia(&mut self) -> Option<LexedTokenResult> {
-        match self.lex_trivia() {
-            Ok(trivia) => {
-                if let Some(&c) = self.source.peek() {
-                    let token = if (c == 'v') && self.source.match_nth(1, |c| c.is_digit(10)) {
-                        self.lex_version()
-                    } else {
-                        self.lex_non_trivia()
-                    };
-                    Some(token.map(|t| LexedToken {
-                        token: t,
-                        position: self.source.position,
-                        trivia,
-                    }))
-                } else {
-                    None
-                }
+    /// Like `lex_next`, except that a token that fails to lex becomes a `Token::Error` rather than
+    /// aborting. The lexer skips to the next plausible token boundary and carries on from there.
+    /// Only errors in trivia, such as unterminated comments, can't be recovered from.
+    pub fn lex_next_recovering(&mut self) -> LexedTokenResult {
+        let trivia = self.lex_trivia()?;
+        let position = self.source.position;
+        let token = self.lex_non_trivia();
+        Ok(LexedToken {
+            token: self.recover(position, token),
+            position,
+            trivia,
+        })
+    }
+
+    pub fn lex_version_or_next_non_trivia(&mut self) -> LexedTokenResult {
+        let trivia = self.lex_trivia()?;
+        let position = self.source.position;
+        let token = if self.source.next_is('v') && self.source.match_nth(1, |c| c.is_digit(10)) {
+            self.lex_version()
+        } else {
+            self.lex_non_trivia()
+        };
+        Ok(LexedToken {
+            token: self.recover(position, token),
+            position,
+            trivia,
+        })
+    }
+
+    fn recover(&mut self, start: Position, token: TokenResult) -> Token {
+        token.unwrap_or_else(|err| {
+            // Skip at least one character, otherwise a sub-lexer that failed without consuming
+            // anything would fail at the same place forever.
+            if self.source.position == start {
+                self.source.discard();
             }
-            Err(err) => Some(Err(err)),
-        }
+            let mut skipped = String::new();
+            self.lex_rest_of_word(&mut skipped);
+            Token::Error(err)
+        })
     }
 
     pub fn lex_shebang_at_start_of_source(&mut self) -> Option<LexedTokenResult> {
+        if self.config.strip_byte_order_mark
+            && self.source.at_start()
+            && self.source.next_is(BYTE_ORDER_MARK)
+        {
+            self.source.discard();
+        }
+
         if let Some('#') = self.source.peek() {
+            let position = self.source.position;
             match self.lex_shebang() {
                 Ok(shebang) => Some(Ok(LexedToken {
-                    token: shebang.clone(),
-                    position: self.source.position,
+                    token: shebang,
+                    position,
                     trivia: None,
                 })),
                 Err(err) => Some(Err(err)),
@@ -1215,43 +2045,52 @@ impl Lexer {
         let (tx, rx) = channel();
         let thread = thread::Builder::new().name(LEXER_THREAD_NAME.to_string());
 
-        let handle = thread.spawn(move || loop {
+        let handle = thread.spawn(move || {
+            // Shebangs and versions can only appear at the very start of a source.
             if let Some(shebang_result) = self.lex_shebang_at_start_of_source() {
                 let shebang = shebang_result?;
                 tx.send(shebang.clone())
                     .map_err(|err| self.send_error(&shebang, &err))?;
             }
 
-            if let Some(version_result) = self.lex_version_or_next_non_trivia() {
-                let version = version_result?;
-                tx.send(version.clone())
-                    .map_err(|err| self.send_error(&version, &err))?;
+            let first = self.lex_version_or_next_non_trivia()?;
+            let is_eof = first.token == Token::Eof;
+            tx.send(first.clone())
+                .map_err(|err| self.send_error(&first, &err))?;
+            if is_eof {
+                return Ok(());
             }
 
-            match self.lex_next() {
-                Ok(token) => {
-                    let is_eof = token.token == Token::Eof;
-                    tx.send(token.clone())
-                        .map_err(|err| self.send_error(&token, &err))?;
-                    if is_eof {
-                        break Ok(());
+            loop {
+                match self.lex_next_recovering() {
+                    Ok(token) => {
+                        let is_eof = token.token == Token::Eof;
+                        tx.send(token.clone())
+                            .map_err(|err| self.send_error(&token, &err))?;
+                        if is_eof {
+                            break Ok(());
+                        }
                     }
+                    Err(e) => break Err(e),
                 }
-                Err(e) => break Err(e),
             }
         });
 
         handle.map(|h| LexerTask {
             tokens: rx,
-            lexer_handle: h,
+            lexer_handle: Some(h),
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Instant;
+
     use super::*;
-    use crate::common::multiphase::{Identifier, InterpolatedString, Shebang, SyDoc};
+    use crate::common::multiphase::{
+        Identifier, InterpolatedString, Interpolation, Shebang, SyDoc,
+    };
     use crate::lexing::tokens::{
         BranchingAndJumping, DeclarationHead, Modifier, ModuleDefinitions,
     };
@@ -1279,7 +2118,7 @@ mod tests {
     }
 
     fn check_version_or_next_non_trivial(lexer: &mut Lexer, token: &Token) -> bool {
-        if let Some(Ok(LexedToken { token: t, .. })) = lexer.lex_version_or_next_non_trivia() {
+        if let Ok(LexedToken { token: t, .. }) = lexer.lex_version_or_next_non_trivia() {
             t == *token
         } else {
             false
@@ -1292,6 +2131,16 @@ mod tests {
         assert_next(&mut lexer, &Token::Eof);
     }
 
+    #[test]
+    fn labelled_identifier() {
+        let mut lexer = test_lexer("up(to: n)");
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("up")));
+        assert_next(&mut lexer, &Token::Grouping(Grouping::OpenParentheses));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("to")));
+        assert_next(&mut lexer, &Token::Colon);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("n")));
+    }
+
     #[test]
     fn identifier() {
         let mut lexer = test_lexer(
@@ -1318,6 +2167,69 @@ mod tests {
         assert_next(&mut lexer, &Token::Identifier(Identifier::from("abc")));
     }
 
+    #[test]
+    fn quoted_keywords_are_identifiers() {
+        let mut lexer = test_lexer("class `class` ```this``` r`final`");
+        assert_next(&mut lexer, &Token::DeclarationHead(DeclarationHead::Class));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("class")));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("this")));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("final")));
+    }
+
+    #[test]
+    fn reserved_keywords() {
+        fn is_reserved(result: LexedTokenResult, word: &str) -> bool {
+            matches!(
+                result,
+                Err(Error {
+                    description: ErrorDescription::Described(description),
+                    ..
+                }) if description.starts_with(&format!("`{}` is reserved for future use", word))
+            )
+        }
+
+        assert!(is_reserved(test_lexer("total").lex_next(), "total"));
+        assert_next(
+            &mut test_lexer("totally"),
+            &Token::Identifier(Identifier::from("totally")),
+        );
+        assert_next(
+            &mut test_lexer("`total`"),
+            &Token::Identifier(Identifier::from("total")),
+        );
+
+        const EXPERIMENTAL: &[Reservation] = &[Reservation {
+            word: "async",
+            since: Version {
+                major: 2,
+                minor: 0,
+                patch: 0,
+            },
+        }];
+        let experimental = |source| {
+            test_lexer(source).with_config(LexerConfig {
+                reserved_keywords: EXPERIMENTAL,
+                ..LexerConfig::default()
+            })
+        };
+
+        assert_next(
+            &mut test_lexer("async"),
+            &Token::Identifier(Identifier::from("async")),
+        );
+        assert!(is_reserved(experimental("async").lex_next(), "async"));
+
+        // Sources written for versions before the reservation can still use
+        // the word as a name.
+        let mut lexer = experimental("v1.5 async");
+        lexer.lex_version_or_next_non_trivia().unwrap();
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("async")));
+
+        let mut lexer = experimental("v2.0 async");
+        lexer.lex_version_or_next_non_trivia().unwrap();
+        assert!(is_reserved(lexer.lex_next(), "async"));
+    }
+
     #[test]
     fn psuedo_identifiers() {
         let mut lexer =
@@ -1387,15 +2299,363 @@ mod tests {
     #[test]
     fn numbers() {
         let mut lexer = test_lexer("    23  \t  -34   \t\t\n   23   +32 0.32    \t123123123.32");
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(23, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(-34, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(23, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(32, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(0, 32))));
         assert_next(
             &mut lexer,
-            &Token::Literal(Literal::Number(Number(123_123_123, 32))),
+            &Token::Literal(Literal::Number(Number(23, 0), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(-34, 0), None)),
         );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(23, 0), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(32, 0), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 32), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(123_123_123, 32), None)),
+        );
+    }
+
+    #[test]
+    fn numeric_type_suffixes() {
+        let mut lexer = test_lexer("42i32 255u8 -128i8 3.0f64 0.5f32 7u64 12");
+        for (number, numeric_type) in &[
+            (Number(42, 0), NumericType::I32),
+            (Number(255, 0), NumericType::U8),
+            (Number(-128, 0), NumericType::I8),
+            (Number(3, 0), NumericType::F64),
+            (Number(0, 5), NumericType::F32),
+            (Number(7, 0), NumericType::U64),
+        ] {
+            assert_next(
+                &mut lexer,
+                &Token::Literal(Literal::Number(number.clone(), Some(*numeric_type))),
+            );
+        }
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(12, 0), None)),
+        );
+        assert_next(&mut lexer, &Token::Eof);
+    }
+
+    #[test]
+    fn hex_floats() {
+        let mut lexer = test_lexer("0x1.8p3 -0x1p-2 0xA.Bp0 0x10p+1");
+        for expected in &[12.0, -0.25, 10.6875, 32.0] {
+            match lexer.lex_next() {
+                Ok(LexedToken {
+                    token: Token::Literal(Literal::HexFloat(float)),
+                    ..
+                }) => assert_eq!(float.to_f64(), *expected),
+                other => panic!("expected a hexadecimal float: {:?}", other),
+            }
+        }
+        assert_next(&mut lexer, &Token::Eof);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        let mut lexer = test_lexer("0 0.5 -0 0x0p0");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 0), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 5), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 0), None)),
+        );
+        match lexer.lex_next() {
+            Ok(LexedToken {
+                token: Token::Literal(Literal::HexFloat(float)),
+                ..
+            }) => assert_eq!(float.to_f64(), 0.0),
+            other => panic!("expected a hexadecimal float: {:?}", other),
+        }
+        assert_next(&mut lexer, &Token::Eof);
+
+        for source in &["0123", "-007", "00", "01.5"] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::MalformedNumber(description),
+                    ..
+                }) => assert!(description.contains("leading zeros")),
+                other => panic!("expected {} to be rejected: {:?}", source, other),
+            }
+        }
+
+        // The zero of `0x0` isn't a leading one.
+        let mut lexer = test_lexer("0x0 -0x00");
+        for _ in 0..2 {
+            assert_next(
+                &mut lexer,
+                &Token::Literal(Literal::Number(Number(0, 0), None)),
+            );
+        }
+        assert_next(&mut lexer, &Token::Eof);
+    }
+
+    #[test]
+    fn hex_integers() {
+        let mut lexer = test_lexer("0xff -0x2A 0x7Fi8 0x1..0x3");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(255, 0), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(-42, 0), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(127, 0), Some(NumericType::I8))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(1, 0), None)),
+        );
+        assert_next(&mut lexer, &Token::Rest);
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(3, 0), None)),
+        );
+        assert_next(&mut lexer, &Token::Eof);
+
+        for source in &["0x80i8", "0x10000000000000000"] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::MalformedNumber(_),
+                    ..
+                }) => {}
+                other => panic!("expected {} to be rejected: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn binary_integers() {
+        let mut lexer = test_lexer("0b101 -0b11 0b0 0b1111u8");
+        for (number, numeric_type) in &[
+            (Number(5, 0), None),
+            (Number(-3, 0), None),
+            (Number(0, 0), None),
+            (Number(15, 0), Some(NumericType::U8)),
+        ] {
+            assert_next(
+                &mut lexer,
+                &Token::Literal(Literal::Number(number.clone(), *numeric_type)),
+            );
+        }
+        assert_next(&mut lexer, &Token::Eof);
+
+        for source in &["0b", "0b102", "0b1.1", "0b100000000u8"] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::MalformedNumber(_),
+                    ..
+                }) => {}
+                other => panic!("expected {} to be rejected: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_hex_floats() {
+        for source in &["0x1.8", "0x.p3", "0x1.8p", "0x1.8px", "0x1p3q", "0x1.8.1p3"] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::MalformedNumber(_),
+                    ..
+                }) => {}
+                other => panic!("expected {} to be malformed: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn scientific_notation() {
+        let scientific = |negative, significand, exponent| {
+            Token::Literal(Literal::Scientific(Scientific {
+                negative,
+                significand,
+                exponent,
+            }))
+        };
+        for (source, token, value) in &[
+            ("1e5", scientific(false, 1, 5), 1e5),
+            ("1e+5", scientific(false, 1, 5), 1e5),
+            ("1e-5", scientific(false, 1, -5), 1e-5),
+            ("1E-0", scientific(false, 1, 0), 1.0),
+            ("2.5E3", scientific(false, 25, 2), 2500.0),
+            ("-2.50e-3", scientific(true, 250, -5), -0.0025),
+            ("+0.1e1", scientific(false, 1, 0), 1.0),
+            ("0e0", scientific(false, 0, 0), 0.0),
+        ] {
+            let mut lexer = test_lexer(source);
+            match lexer.lex_next() {
+                Ok(LexedToken {
+                    token: Token::Literal(Literal::Scientific(actual)),
+                    ..
+                }) => {
+                    assert_eq!(Token::Literal(Literal::Scientific(actual.clone())), *token);
+                    assert_eq!(actual.to_f64(), *value, "{}", source);
+                }
+                other => panic!("expected {} to be scientific: {:?}", source, other),
+            }
+            assert_next(&mut lexer, &Token::Eof);
+        }
+
+        // An `e` without digits after it isn't an exponent.
+        let mut lexer = test_lexer("1e..2");
+        assert!(lexer.lex_next().is_err());
+
+        let mut lexer = test_lexer("1e3..2e3");
+        assert_next(&mut lexer, &scientific(false, 1, 3));
+        assert_next(&mut lexer, &Token::Rest);
+        assert_next(&mut lexer, &scientific(false, 2, 3));
+
+        for (source, description) in &[
+            (
+                ".5e3",
+                "a number needs a digit before its decimal point, as in `0.5`",
+            ),
+            (
+                ".5",
+                "a number needs a digit before its decimal point, as in `0.5`",
+            ),
+            (
+                "1.e3",
+                "malformed scientific notation: a digit must follow the decimal point, as in `1.0e3`",
+            ),
+            ("1e3x", "malformed scientific notation: unexpected `x`"),
+            ("1e3.5", "malformed scientific notation: unexpected `.`"),
+            (
+                "1e99999999999",
+                "malformed scientific notation: the exponent is out of range",
+            ),
+            (
+                "99999999999999999999e1",
+                "malformed scientific notation: too many digits in the significand",
+            ),
+        ] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::MalformedNumber(actual),
+                    ..
+                }) => assert_eq!(actual, *description),
+                other => panic!("expected {} to be rejected: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn very_long_numbers() {
+        const DIGITS: usize = 1_000_000;
+
+        let source = format!("1.{}", "0".repeat(DIGITS));
+        let mut lexer = test_lexer(&source);
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(1, 0), None)),
+        );
+        assert_next(&mut lexer, &Token::Eof);
+
+        for (source, description) in &[
+            (
+                format!("-{}", "9".repeat(DIGITS)),
+                format!(
+                    "the real component has {} digits, too many to fit in 64 bits",
+                    DIGITS
+                ),
+            ),
+            (
+                format!("0.{}", "1".repeat(DIGITS)),
+                format!(
+                    "the fractional component has {} digits, too many to fit in 64 bits",
+                    DIGITS
+                ),
+            ),
+        ] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::MalformedNumber(actual),
+                    ..
+                }) => assert_eq!(actual, *description),
+                other => panic!("expected an overlong number to be rejected: {:?}", other),
+            }
+        }
+    }
+
+    /// Check that lexing numbers stays linear in their length by timing ten
+    /// times as many digits. Run it with
+    /// `cargo test --release -- --ignored --nocapture number_lexing_scales_linearly`.
+    #[test]
+    #[ignore]
+    fn number_lexing_scales_linearly() {
+        let time = |digits: usize| {
+            let source = format!("1.{}", "0".repeat(digits));
+            let start = Instant::now();
+            test_lexer(&source).lex_next().unwrap();
+            start.elapsed()
+        };
+
+        let shorter = time(1_000_000);
+        let longer = time(10_000_000);
+        println!("1M digits: {:?}, 10M digits: {:?}", shorter, longer);
+        assert!(longer < shorter * 30);
+    }
+
+    #[test]
+    fn invalid_numeric_type_suffixes() {
+        for (source, description) in &[
+            (
+                "42q",
+                ErrorDescription::Described("invalid numeric type suffix: q".to_owned()),
+            ),
+            (
+                "1i3",
+                ErrorDescription::Described("invalid numeric type suffix: i3".to_owned()),
+            ),
+            (
+                "256u8",
+                ErrorDescription::MalformedNumber(
+                    "256 is out of range for u8, which holds 0 to 255".to_owned(),
+                ),
+            ),
+            (
+                "-1u32",
+                ErrorDescription::MalformedNumber(
+                    "-1 is out of range for u32, which holds 0 to 4294967295".to_owned(),
+                ),
+            ),
+            (
+                "1.5i64",
+                ErrorDescription::MalformedNumber(
+                    "1.5 is not a whole number, which i64 requires".to_owned(),
+                ),
+            ),
+        ] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: actual,
+                    ..
+                }) => assert_eq!(actual, *description),
+                unexpected => panic!("expected a suffix error but got {:?}", unexpected),
+            }
+        }
     }
 
     #[test]
@@ -1420,6 +2680,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn escaped_delimiters() {
+        let mut lexer = test_lexer(r#"$"say \"hi\" to `{name}` \`now\`" `odd\`name` "\"""#);
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["say \"hi\" to `".to_owned(), "` `now`".to_owned()],
+                interpolations: vec![Interpolation::new("name", 16)],
+            })),
+        );
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("odd`name")));
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("\""))),
+        );
+        assert_next(&mut lexer, &Token::Eof);
+    }
+
+    #[test]
+    fn numeric_escapes() {
+        let mut lexer = test_lexer(r#"'\x41' '\u{1F600}' "a\x2Db\u{e9}""#);
+        assert_next(&mut lexer, &Token::Literal(Literal::Char('A')));
+        assert_next(&mut lexer, &Token::Literal(Literal::Char('😀')));
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("a-bé"))),
+        );
+    }
+
+    #[test]
+    fn out_of_range_numeric_escapes() {
+        for (source, description) in &[
+            (r"'\x80'", r"\x80 is not an ASCII character"),
+            (r"'\u{D800}'", r"\u{D800} is not a Unicode scalar value"),
+            (r"'\u{110000}'", r"\u{110000} is not a Unicode scalar value"),
+        ] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::Described(actual),
+                    ..
+                }) => assert_eq!(actual, *description),
+                unexpected => panic!("expected an escape error but got {:?}", unexpected),
+            }
+        }
+    }
+
     #[test]
     fn raw_strings() {
         let mut lexer = test_lexer("  r\"abc\\ndef\"   \t \n\n\nr\"\"\"\"'123'\"\"\"\"");
@@ -1433,6 +2739,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn line_continuations() {
+        let mut lexer = test_lexer(
+            "\"abc\\\ndef\" \"one\\\r\n two\" $\"{x}\\\n!\" r\"abc\\\ndef\" \"a\\\\\nb\"",
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("abcdef"))),
+        );
+        // Indentation on the next line is kept.
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("one two"))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["".to_owned(), "!".to_owned()],
+                interpolations: vec![Interpolation::new("x", 1)],
+            })),
+        );
+        // Raw strings keep the backslash and the newline.
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("abc\\\ndef"))),
+        );
+        // An escaped backslash before a newline doesn't continue the line.
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("a\\\nb"))),
+        );
+        assert_next(&mut lexer, &Token::Eof);
+    }
+
+    #[test]
+    fn consistently_indented_multiline_strings() {
+        let mut lexer = test_lexer(
+            "\"\"\"first\n\t\tsecond\n\n\tthird \t \"\"\" $\"\"\"a\n  {b}\n    c\"\"\" \"one\n\ttwo\n  three\"",
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from(
+                "first\n\t\tsecond\n\n\tthird \t ",
+            ))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["a\n  ".to_owned(), "\n    c".to_owned()],
+                interpolations: vec![Interpolation::new("b", 5)],
+            })),
+        );
+        // Strings with single delimiters aren't multiline strings.
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("one\n\ttwo\n  three"))),
+        );
+    }
+
+    #[test]
+    fn inconsistently_indented_multiline_strings() {
+        for (source, description) in &[
+            (
+                "\"\"\"a\n\tb\n    c\"\"\"",
+                "line 3 of a multiline string is indented with spaces but line 2 with tabs; \
+                 indent every line with one or the other",
+            ),
+            (
+                "r\"\"\"\"a\n  b\n\t\tc\"\"\"\"",
+                "line 3 of a multiline string is indented with tabs but line 2 with spaces; \
+                 indent every line with one or the other",
+            ),
+            (
+                "$\"\"\"{a}\n \tb\"\"\"",
+                "line 2 of a multiline string mixes tabs and spaces in its indentation",
+            ),
+        ] {
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::Described(actual),
+                    ..
+                }) => assert_eq!(actual, *description),
+                unexpected => panic!("expected an indentation error but got {:?}", unexpected),
+            }
+        }
+    }
+
     #[test]
     fn interpolated_strings() {
         let mut lexer = test_lexer(
@@ -1443,7 +2836,7 @@ mod tests {
             &mut lexer,
             &Token::Literal(Literal::InterpolatedString(InterpolatedString {
                 string_fragments: vec!["1".to_owned(), "{{23".to_owned()],
-                interpolations: vec![Identifier::from("x")],
+                interpolations: vec![Interpolation::new("x", 2)],
             })),
         );
 
@@ -1454,7 +2847,7 @@ mod tests {
                     "ab{{notInterpolated}}c\"\"\t".to_owned(),
                     r#"""" "#.to_owned(),
                 ],
-                interpolations: vec![Identifier::from("foobar")],
+                interpolations: vec![Interpolation::new("foobar", 27)],
             })),
         );
     }
@@ -1524,17 +2917,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ambiguous_operator_adjacencies() {
+        use OverloadableInfixOperator::*;
+
+        let op = Token::OverloadableInfixOperator;
+        let a = || Token::Identifier(Identifier::from("a"));
+        let b = || Token::Identifier(Identifier::from("b"));
+        let cases = vec![
+            ("a --> b", vec![a(), op(Subtract), op(Cascade), b()]),
+            ("a ->> b", vec![a(), op(Cascade), op(GreaterThan), b()]),
+            (
+                "a --1",
+                vec![
+                    a(),
+                    op(Subtract),
+                    Token::Literal(Literal::Number(Number(-1, 0), None)),
+                ],
+            ),
+            ("a < b", vec![a(), op(LessThan), b()]),
+            (
+                "a <<= b",
+                vec![a(), op(LeftShift), Token::Binding(Binding::Assign), b()],
+            ),
+            ("a <<< b", vec![a(), op(LeftShift), op(LessThan), b()]),
+            ("a >= b", vec![a(), op(GreaterThanOrEqual), b()]),
+            (
+                "a >>= b",
+                vec![a(), op(RightShift), Token::Binding(Binding::Assign), b()],
+            ),
+            (
+                "a >>>> b",
+                vec![a(), op(UnsignedRightShift), op(GreaterThan), b()],
+            ),
+            (
+                "a === b",
+                vec![a(), op(Equals), Token::Binding(Binding::Assign), b()],
+            ),
+            (
+                "a => b",
+                vec![a(), Token::Binding(Binding::Assign), op(GreaterThan), b()],
+            ),
+            ("a &&& b", vec![a(), op(And), op(Ampersand), b()]),
+            ("a ||| b", vec![a(), op(Or), op(BitwiseOr), b()]),
+            ("a |>> b", vec![a(), op(Pipe), op(GreaterThan), b()]),
+            (
+                "a ||]",
+                vec![a(), op(Or), Token::Grouping(Grouping::CloseSquareBracket)],
+            ),
+            ("a ^^^ b", vec![a(), op(Xor), op(BitwiseXor), b()]),
+            ("a *** b", vec![a(), op(Power), op(Multiply), b()]),
+            (
+                "a! != b",
+                vec![
+                    a(),
+                    Token::PostfixOperator(PostfixOperator::ForceUnwrap),
+                    op(NotEqual),
+                    b(),
+                ],
+            ),
+            (
+                "a ???? b",
+                vec![
+                    a(),
+                    Token::Hole,
+                    Token::PostfixOperator(PostfixOperator::Bind),
+                    b(),
+                ],
+            ),
+            ("a @*** b", vec![a(), op(MatrixPower), op(Multiply), b()]),
+            (
+                "a ..... b",
+                vec![
+                    a(),
+                    Token::PseudoIdentifier(PseudoIdentifier::Ellipsis),
+                    Token::Rest,
+                    b(),
+                ],
+            ),
+        ];
+
+        for (source, expected) in cases {
+            let mut lexer = test_lexer(source);
+            let mut tokens = vec![];
+            loop {
+                match lexer.lex_next() {
+                    Ok(LexedToken {
+                        token: Token::Eof, ..
+                    }) => break,
+                    Ok(lexed) => tokens.push(lexed.token),
+                    Err(error) => panic!("{} failed to lex: {:?}", source, error),
+                }
+            }
+            assert_eq!(tokens, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn asterisks() {
+        let mut lexer = test_lexer("a * b **c.*,");
+        for token in &[
+            Token::Identifier(Identifier::from("a")),
+            Token::OverloadableInfixOperator(OverloadableInfixOperator::Multiply),
+            Token::Identifier(Identifier::from("b")),
+            Token::OverloadableInfixOperator(OverloadableInfixOperator::Power),
+            Token::Identifier(Identifier::from("c")),
+            Token::Dot,
+            Token::OverloadableInfixOperator(OverloadableInfixOperator::Multiply),
+            Token::SubItemSeparator,
+        ] {
+            assert_next(&mut lexer, token);
+        }
+    }
+
     #[test]
     fn postfix_operators() {
-        let mut lexer = test_lexer("   ?      ");
+        let mut lexer = test_lexer("   ?   !   ");
         assert_next(&mut lexer, &Token::PostfixOperator(PostfixOperator::Bind));
+        assert_next(
+            &mut lexer,
+            &Token::PostfixOperator(PostfixOperator::ForceUnwrap),
+        );
     }
 
     #[test]
     fn identifiers() {
         let mut lexer = test_lexer(" FOObar  ab!    ");
         assert_next(&mut lexer, &Token::Identifier(Identifier::from("FOObar")));
-        assert_next(&mut lexer, &Token::Identifier(Identifier::from("ab!")));
+
+        // `!` ends a word so that it can force-unwrap a name.
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("ab")));
+        assert_next(
+            &mut lexer,
+            &Token::PostfixOperator(PostfixOperator::ForceUnwrap),
+        );
+    }
+
+    #[test]
+    fn identifiers_are_normalised() {
+        // The first spells the `é` as one character and the second as an `e`
+        // with a combining acute accent. The quoted one combines a Hangul
+        // syllable from its letters.
+        let mut lexer = test_lexer(" caf\u{e9} cafe\u{301} `\u{1112}\u{1161}\u{11ab}`");
+        let composed = Identifier::from("caf\u{e9}");
+        assert_next(&mut lexer, &Token::Identifier(composed.clone()));
+        assert_next(&mut lexer, &Token::Identifier(composed));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("\u{d55c}")));
     }
 
     #[test]
@@ -1549,6 +3077,133 @@ mod tests {
         assert_next(&mut lexer, &Token::Eof);
     }
 
+    #[test]
+    fn tokens_compare_regardless_of_position() {
+        let mut lexer = test_lexer("x  x\n  x 1");
+        let first = lexer.lex_next().unwrap();
+        let second = lexer.lex_next().unwrap();
+        let third = lexer.lex_next().unwrap();
+        let number = lexer.lex_next().unwrap();
+
+        assert_ne!(first.position, second.position);
+        assert_ne!(first, second);
+        assert!(first.same_token(&second));
+        assert!(!first.same_token_and_trivia(&second));
+
+        assert_ne!(second.position.line(), third.position.line());
+        assert!(second.same_token(&third));
+        assert!(!second.same_token_and_trivia(&third));
+
+        let moved = LexedToken {
+            position: third.position,
+            ..second.clone()
+        };
+        assert!(moved.same_token_and_trivia(&second));
+        assert_ne!(moved, second);
+        assert_eq!(moved, moved.clone());
+
+        assert!(!third.same_token(&number));
+    }
+
+    #[test]
+    fn structured_trivia() {
+        let mut lexer = test_lexer("a // Line.\n\n  /* Block /* nested */ */\t/** Doc. */ b");
+        lexer.lex_next().unwrap();
+
+        let doc = lexer.lex_next().unwrap();
+        assert_eq!(
+            doc.trivia,
+            Some(Trivia(vec![
+                TriviaItem::Whitespace(" ".to_owned()),
+                TriviaItem::LineComment("// Line.".to_owned()),
+                TriviaItem::Whitespace("\n\n  ".to_owned()),
+                TriviaItem::BlockComment("/* Block /* nested */ */".to_owned()),
+                TriviaItem::Whitespace("\t".to_owned()),
+            ]))
+        );
+        assert_eq!(doc.token, Token::SyDoc(SyDoc::from(" Doc. ")));
+        assert_eq!(
+            doc.comments(),
+            vec![
+                TriviaComment {
+                    text: "// Line.".to_owned(),
+                    follows_newline: false,
+                },
+                TriviaComment {
+                    text: "/* Block /* nested */ */".to_owned(),
+                    follows_newline: true,
+                },
+            ]
+        );
+
+        let b = lexer.lex_next().unwrap();
+        assert_eq!(
+            b.trivia,
+            Some(Trivia(vec![TriviaItem::Whitespace(" ".to_owned())]))
+        );
+    }
+
+    #[test]
+    fn comments_sharing_a_line() {
+        let mut lexer = test_lexer("a\n/* One. */ /* Two. */\n/* Three. */ b");
+        lexer.lex_next().unwrap();
+        let follows_newlines = lexer
+            .lex_next()
+            .unwrap()
+            .comments()
+            .iter()
+            .map(|comment| comment.follows_newline)
+            .collect::<Vec<_>>();
+        assert_eq!(follows_newlines, vec![true, false, true]);
+    }
+
+    #[test]
+    fn dotted_versions() {
+        let mut lexer = test_lexer("requires a.b >= 1.2.0 requires c == 10.0.12 1.2 0.5..1");
+        for (major, minor, patch) in [(1, 2, 0), (10, 0, 12)].iter().copied() {
+            loop {
+                match lexer.lex_next().unwrap().token {
+                    Token::Version(version) => {
+                        assert_eq!(
+                            version,
+                            Version {
+                                major,
+                                minor,
+                                patch,
+                            }
+                        );
+                        break;
+                    }
+                    Token::Literal(literal) => panic!("expected a version: {:?}", literal),
+                    _ => {}
+                }
+            }
+        }
+        // Anything but three components is a number either way.
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(1, 2), None)),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 5), None)),
+        );
+
+        // Outside of a requirement, it's a decimal with a stray `.0` after it.
+        let mut lexer = test_lexer("1.2.0");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(1, 2), None)),
+        );
+        match lexer.lex_next() {
+            Err(Error {
+                description: ErrorDescription::MalformedNumber(_),
+                ..
+            }) => {}
+            other => panic!("expected `.0` to be malformed: {:?}", other),
+        }
+    }
+
     #[test]
     fn booleans() {
         let mut lexer = test_lexer("  True False   \n\t   /* ");
@@ -1609,7 +3264,7 @@ mod tests {
         assert!(start_is_shebang(&mut lexer3, &shebang3));
         assert_next(
             &mut lexer3,
-            &Token::Literal(Literal::Number(Number(123, 0))),
+            &Token::Literal(Literal::Number(Number(123, 0), None)),
         );
 
         let mut failing_lexer = test_lexer("/usr/local/bin/env sylan\n123 321");
@@ -1658,4 +3313,96 @@ mod tests {
             &Token::PseudoIdentifier(PseudoIdentifier::ThisModule),
         );
     }
+
+    #[test]
+    fn significant_newlines() {
+        let source = "var x = 1 // one\nvar y = 2\n\n  x /* a\nb */ + y\n";
+        let lex_all = |mut lexer: Lexer| {
+            let mut tokens = vec![];
+            loop {
+                let lexed = lexer.lex_next().unwrap();
+                if lexed.token == Token::Eof {
+                    break tokens;
+                }
+                tokens.push(lexed);
+            }
+        };
+
+        let folded = lex_all(test_lexer(source));
+        let significant = lex_all(test_lexer(source).with_significant_newlines());
+        assert!(folded.iter().all(|lexed| lexed.token != Token::NewLine));
+
+        let newlines = significant
+            .iter()
+            .filter(|lexed| lexed.token == Token::NewLine)
+            .map(|lexed| lexed.position.line())
+            .collect::<Vec<_>>();
+        assert_eq!(newlines, vec![1, 2, 3, 5]);
+
+        let without_newlines = significant
+            .into_iter()
+            .filter(|lexed| lexed.token != Token::NewLine)
+            .collect::<Vec<_>>();
+        assert_eq!(without_newlines.len(), folded.len());
+        for (significant, folded) in without_newlines.iter().zip(&folded) {
+            assert_eq!(significant.token, folded.token);
+            assert_eq!(significant.position, folded.position);
+        }
+
+        let crlf = lex_all(test_lexer("a\r\nb").with_significant_newlines());
+        let tokens = crlf.iter().map(|lexed| &lexed.token).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                &Token::Identifier(Identifier::from("a")),
+                &Token::NewLine,
+                &Token::Identifier(Identifier::from("b")),
+            ]
+        );
+        assert_eq!(crlf[2].position.line(), 2);
+    }
+
+    #[test]
+    fn custom_configuration() {
+        let source = "\u{feff}package main\nvar longName = 1";
+        let lex_all = |lexer: Lexer| {
+            let task = lexer.lex().unwrap();
+            let mut tokens = vec![];
+            while let Ok(lexed) = task.recv() {
+                tokens.push(lexed.token);
+            }
+            task.join().unwrap();
+            tokens
+        };
+
+        let default = lex_all(test_lexer(source));
+        assert_ne!(default[0], Token::DeclarationHead(DeclarationHead::Package));
+        assert_eq!(
+            lex_all(test_lexer(source).with_config(LexerConfig::default())),
+            default
+        );
+
+        let configured = lex_all(test_lexer(source).with_config(LexerConfig {
+            significant_newlines: true,
+            strip_byte_order_mark: true,
+            max_identifier_length: Some(4),
+            reserved_keywords: &[],
+        }));
+        assert_eq!(
+            configured[..4],
+            [
+                Token::DeclarationHead(DeclarationHead::Package),
+                Token::Identifier(Identifier::from("main")),
+                Token::NewLine,
+                Token::Binding(Binding::Var),
+            ]
+        );
+        assert!(matches!(
+            &configured[4],
+            Token::Error(Error {
+                description: ErrorDescription::Described(description),
+                ..
+            }) if description == "identifiers can be at most 4 characters long, but this one is 8"
+        ));
+    }
 }
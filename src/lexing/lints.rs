@@ -0,0 +1,341 @@
+//! Lints that only need the token stream, such as those concerned with whitespace. None of these
+//! are run by default; tooling opts into each one.
+
+use crate::common::diagnostics::Diagnostic;
+use crate::common::multiphase::Identifier;
+use crate::common::newlines::{check_newline, NewLine};
+use crate::lexing::lexer::LexedToken;
+use crate::lexing::tokens::Token;
+use crate::source::{Position, Span};
+
+/// A line whose leading whitespace mixes tabs and spaces. Sylan doesn't yet give indentation any
+/// meaning, but this is flagged in case multiline strings or blocks ever depend on it; mixed
+/// indentation looks identical to consistent indentation in many editors while meaning something
+/// else entirely.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MixedIndentation {
+    /// The line's indentation, from the start of the line to its last tab or space.
+    pub span: Span,
+}
+
+/// Find lines in the token stream that mix tabs and spaces in their indentation. The tokens must
+/// be the whole stream from the start of the source, as indentation lives in each token's trivia.
+pub fn mixed_indentation<'a>(
+    tokens: impl IntoIterator<Item = &'a LexedToken>,
+) -> Vec<MixedIndentation> {
+    let mut found = vec![];
+    for (index, lexed) in tokens.into_iter().enumerate() {
+        let (length, lines) = match &lexed.trivia {
+            Some(trivia) => trivia_lines(&trivia.text()),
+            None => continue,
+        };
+
+        // The trivia's first line only starts a line if it starts the whole source; otherwise it
+        // trails the previous token.
+        let skip = if index == 0 { 0 } else { 1 };
+        let last_line = lexed.position.line();
+        let first_line = last_line + 1 - lines.len();
+
+        // The trivia ends where the token starts, so each line's start is counted back from there.
+        let trivia_start = lexed.position.absolute_character_index() - length;
+
+        for (offset, (start, line)) in lines.iter().enumerate().skip(skip) {
+            let indentation = line
+                .iter()
+                .take_while(|&&c| (c == ' ') || (c == '\t'))
+                .collect::<Vec<_>>();
+            if indentation.contains(&&' ') && indentation.contains(&&'\t') {
+                let line_start = Position::start_of_line(first_line + offset, trivia_start + start);
+                found.push(MixedIndentation {
+                    span: Span {
+                        start: line_start,
+                        end: line_start.along_line(indentation.len() - 1),
+                    },
+                });
+            }
+        }
+    }
+    found
+}
+
+impl From<&MixedIndentation> for Diagnostic {
+    fn from(mixed: &MixedIndentation) -> Self {
+        Diagnostic::warning(
+            "mixed-indentation",
+            format!(
+                "line {} mixes tabs and spaces in its indentation",
+                mixed.span.start.line()
+            ),
+            Some(mixed.span),
+        )
+    }
+}
+
+/// The scripts whose letters are most easily mistaken for one another. Letters of other scripts
+/// aren't checked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Script> {
+        match c {
+            'a'..='z' | 'A'..='Z' | '\u{c0}'..='\u{24f}' | '\u{1e00}'..='\u{1eff}' => {
+                Some(Script::Latin)
+            }
+            '\u{370}'..='\u{3ff}' | '\u{1f00}'..='\u{1fff}' if c.is_alphabetic() => {
+                Some(Script::Greek)
+            }
+            '\u{400}'..='\u{52f}' if c.is_alphabetic() => Some(Script::Cyrillic),
+            _ => None,
+        }
+    }
+}
+
+/// Greek and Cyrillic letters that look like Latin ones in most fonts.
+const LATIN_LOOKALIKES: &str = "ΑΒΕΖΗΙΚΜΝΟΡΤΥΧικνορυχ\
+                                АВЕКМНОРСТУХІЈЅаеорсухіјѕԁһԛԝ";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Confusion {
+    /// Letters from more than one script, in the order they first appear, as in a Latin `p`
+    /// followed by a Cyrillic `а`.
+    MixedScripts(Vec<Script>),
+
+    /// Letters all from one script other than Latin, but each looking like a Latin letter, as in
+    /// a Cyrillic `сор` passing for `cop`.
+    LooksLatin(Script),
+}
+
+/// An identifier that could be mistaken for another, which can hide what code really refers to.
+/// An attacker could, for example, bind a name that reads the same as a trusted one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfusableIdentifier {
+    pub identifier: Identifier,
+    pub position: Position,
+    pub confusion: Confusion,
+}
+
+/// Find identifiers mixing Latin, Greek, and Cyrillic letters, or written entirely in Greek or
+/// Cyrillic letters that look Latin. Digits, punctuation, and combining marks belong to no script
+/// here, so they never count as mixing.
+pub fn confusable_identifiers<'a>(
+    tokens: impl IntoIterator<Item = &'a LexedToken>,
+) -> Vec<ConfusableIdentifier> {
+    let mut found = vec![];
+    for lexed in tokens {
+        let identifier = match &lexed.token {
+            Token::Identifier(identifier) => identifier,
+            _ => continue,
+        };
+
+        let mut scripts = vec![];
+        for script in identifier.0.chars().filter_map(Script::of) {
+            if !scripts.contains(&script) {
+                scripts.push(script);
+            }
+        }
+        let confusion = match scripts.as_slice() {
+            [] | [Script::Latin] => continue,
+            [script] => {
+                let all_lookalikes = identifier
+                    .0
+                    .chars()
+                    .filter(|&c| Script::of(c).is_some())
+                    .all(|c| LATIN_LOOKALIKES.contains(c));
+                if !all_lookalikes {
+                    continue;
+                }
+                Confusion::LooksLatin(*script)
+            }
+            _ => Confusion::MixedScripts(scripts),
+        };
+        found.push(ConfusableIdentifier {
+            identifier: identifier.clone(),
+            position: lexed.position,
+            confusion,
+        });
+    }
+    found
+}
+
+impl From<&ConfusableIdentifier> for Diagnostic {
+    fn from(confusable: &ConfusableIdentifier) -> Self {
+        let message = match &confusable.confusion {
+            Confusion::MixedScripts(scripts) => format!(
+                "`{}` mixes {} letters",
+                confusable.identifier.0,
+                scripts
+                    .iter()
+                    .map(|script| format!("{:?}", script))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+            Confusion::LooksLatin(script) => format!(
+                "`{}` is written in {:?} letters that look Latin",
+                confusable.identifier.0, script
+            ),
+        };
+        Diagnostic::warning(
+            "confusable-identifier",
+            message,
+            Some(Span::at(confusable.position)),
+        )
+    }
+}
+
+/// The trivia's length in characters, and its lines, each with the index within the trivia at
+/// which it starts.
+fn trivia_lines(trivia: &str) -> (usize, Vec<(usize, Vec<char>)>) {
+    let chars = trivia.chars().collect::<Vec<char>>();
+    let mut lines = vec![(0, vec![])];
+    let mut index = 0;
+    while index < chars.len() {
+        let current = chars[index];
+        match check_newline(current, chars.get(index + 1).cloned()) {
+            Some(newline) => {
+                if newline == NewLine::CarridgeReturnLineFeed {
+                    index += 1;
+                }
+                lines.push((index + 1, vec![]));
+            }
+            None => lines.last_mut().unwrap().1.push(current),
+        }
+        index += 1;
+    }
+    (chars.len(), lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::diagnostics::Severity;
+    use crate::lexing::lexer::Lexer;
+    use crate::lexing::tokens::Token;
+    use crate::source::in_memory::Source;
+
+    fn lex_all(source: &str) -> Vec<LexedToken> {
+        let mut lexer = Lexer::from(Source::from(source.chars().collect::<Vec<char>>()));
+        let mut tokens = vec![];
+        loop {
+            let lexed = lexer.lex_next().unwrap();
+            let is_eof = lexed.token == Token::Eof;
+            tokens.push(lexed);
+            if is_eof {
+                break tokens;
+            }
+        }
+    }
+
+    /// Each mixed indentation's line, its first and last columns, and its first and last indices.
+    fn indented_lines(tokens: &[LexedToken]) -> Vec<(usize, usize, usize, usize, usize)> {
+        mixed_indentation(tokens)
+            .iter()
+            .map(|mixed| {
+                let Span { start, end } = mixed.span;
+                (
+                    start.line(),
+                    start.character_position_in_line(),
+                    end.character_position_in_line(),
+                    start.absolute_character_index(),
+                    end.absolute_character_index(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mixed_indentation_is_flagged() {
+        let tokens = lex_all("package main\n\n    var x = 1\n \tvar y = 2\n\t // comment\nz");
+        assert_eq!(
+            indented_lines(&tokens),
+            vec![(4, 1, 2, 28, 29), (5, 1, 2, 40, 41)]
+        );
+    }
+
+    #[test]
+    fn mixed_indentation_at_the_start_of_the_source() {
+        let tokens = lex_all("\t package main");
+        assert_eq!(indented_lines(&tokens), vec![(1, 1, 2, 0, 1)]);
+    }
+
+    #[test]
+    fn mixed_script_identifiers_are_flagged() {
+        // The `а` of the first `pаyment` is Cyrillic, and the Greek `Σ` and `ε` of `εΣ` don't
+        // look Latin.
+        let tokens = lex_all("package main\nvar pаyment = payment + `сор` + εΣ");
+        let found = confusable_identifiers(&tokens);
+        assert_eq!(
+            found
+                .iter()
+                .map(|confusable| (
+                    confusable.identifier.0.as_str(),
+                    confusable.position.line(),
+                    &confusable.confusion
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "pаyment",
+                    2,
+                    &Confusion::MixedScripts(vec![Script::Latin, Script::Cyrillic])
+                ),
+                ("сор", 2, &Confusion::LooksLatin(Script::Cyrillic)),
+            ]
+        );
+    }
+
+    #[test]
+    fn lints_become_warnings() {
+        let tokens = lex_all("package main\n \tvar pаyment = `сор`");
+        let diagnostics = mixed_indentation(&tokens)
+            .iter()
+            .map(Diagnostic::from)
+            .chain(confusable_identifiers(&tokens).iter().map(Diagnostic::from))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.severity,
+                    diagnostic.code,
+                    diagnostic.message.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Severity::Warning,
+                    "mixed-indentation",
+                    "line 2 mixes tabs and spaces in its indentation"
+                ),
+                (
+                    Severity::Warning,
+                    "confusable-identifier",
+                    "`pаyment` mixes Latin and Cyrillic letters"
+                ),
+                (
+                    Severity::Warning,
+                    "confusable-identifier",
+                    "`сор` is written in Cyrillic letters that look Latin"
+                ),
+            ]
+        );
+        assert_eq!(diagnostics[0].span.unwrap().start.line(), 2);
+        assert_eq!(diagnostics[1].span.unwrap().start.line(), 2);
+    }
+
+    #[test]
+    fn latin_identifiers_are_not_flagged() {
+        let tokens = lex_all("package main\nvar payment = café + naïve_2 + total_µ");
+        assert!(confusable_identifiers(&tokens).is_empty());
+    }
+
+    #[test]
+    fn consistent_indentation_is_not_flagged() {
+        let tokens = lex_all("package main\n\tvar x = 1\n\t\tvar y = 2\n    z \t w");
+        assert!(mixed_indentation(&tokens).is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+//! Normalises identifiers towards Unicode's Normalization Form C (NFC), so that
+//! two spellings of a name that look the same are the same name. An `é` can be
+//! written either as the single character U+00E9 or as an `e` followed by a
+//! combining acute accent, depending on the editor or keyboard, and without
+//! normalising a program could bind one and fail to find the other.
+//!
+//! Only the compositions of Latin, Greek, and Cyrillic letters with combining
+//! marks are covered, along with Hangul syllables, which are composed
+//! algorithmically. Other scripts are left as they were written, and so could
+//! still be spelled two ways. ASCII is already normalised, so identifiers
+//! written in it are returned straight away.
+
+mod tables;
+
+use tables::{COMBINING_CLASSES, COMPOSITIONS};
+
+const HANGUL_SYLLABLE_BASE: u32 = 0xAC00;
+const HANGUL_LEADING_BASE: u32 = 0x1100;
+const HANGUL_VOWEL_BASE: u32 = 0x1161;
+const HANGUL_TRAILING_BASE: u32 = 0x11A7;
+const HANGUL_LEADING_COUNT: u32 = 19;
+const HANGUL_VOWEL_COUNT: u32 = 21;
+const HANGUL_TRAILING_COUNT: u32 = 28;
+const HANGUL_VOWELS_AND_TRAILING: u32 = HANGUL_VOWEL_COUNT * HANGUL_TRAILING_COUNT;
+const HANGUL_SYLLABLE_COUNT: u32 = HANGUL_LEADING_COUNT * HANGUL_VOWELS_AND_TRAILING;
+
+/// Normalise to NFC as far as the tables cover, which is only Latin, Greek,
+/// and Cyrillic letters with combining marks, and Hangul. This isn't full NFC:
+/// characters outside of those are left as they were written, even where full
+/// NFC would decompose or compose them.
+pub fn partial_nfc(text: String) -> String {
+    if text.is_ascii() {
+        return text;
+    }
+
+    let mut decomposed = vec![];
+    for c in text.chars() {
+        decompose(c, &mut decomposed);
+    }
+    reorder(&mut decomposed);
+    compose(decomposed).into_iter().collect()
+}
+
+fn combining_class(c: char) -> u8 {
+    COMBINING_CLASSES
+        .binary_search_by_key(&c, |&(mark, _)| mark)
+        .map(|index| COMBINING_CLASSES[index].1)
+        .unwrap_or(0)
+}
+
+fn decompose(c: char, decomposed: &mut Vec<char>) {
+    let syllable = (c as u32).wrapping_sub(HANGUL_SYLLABLE_BASE);
+    if syllable < HANGUL_SYLLABLE_COUNT {
+        let leading = HANGUL_LEADING_BASE + syllable / HANGUL_VOWELS_AND_TRAILING;
+        let vowel =
+            HANGUL_VOWEL_BASE + syllable % HANGUL_VOWELS_AND_TRAILING / HANGUL_TRAILING_COUNT;
+        let trailing = HANGUL_TRAILING_BASE + hangul_trailing_index(syllable);
+        decomposed.extend(std::char::from_u32(leading));
+        decomposed.extend(std::char::from_u32(vowel));
+        if trailing != HANGUL_TRAILING_BASE {
+            decomposed.extend(std::char::from_u32(trailing));
+        }
+        return;
+    }
+
+    match COMPOSITIONS.binary_search_by_key(&c, |&(composed, _, _)| composed) {
+        Ok(index) => {
+            let (_, base, mark) = COMPOSITIONS[index];
+            decompose(base, decomposed);
+            decomposed.push(mark);
+        }
+        Err(_) => decomposed.push(c),
+    }
+}
+
+/// Put each run of combining marks into the order of their combining classes,
+/// keeping marks of the same class in the order they were written.
+fn reorder(decomposed: &mut [char]) {
+    let mut start = 0;
+    while start < decomposed.len() {
+        let length = decomposed[start..]
+            .iter()
+            .take_while(|&&c| combining_class(c) != 0)
+            .count();
+        decomposed[start..start + length].sort_by_key(|&c| combining_class(c));
+        start += length.max(1);
+    }
+}
+
+fn compose(decomposed: Vec<char>) -> Vec<char> {
+    let mut composed: Vec<char> = Vec::with_capacity(decomposed.len());
+    let mut starter = None;
+
+    // The class of the last character since the starter, if there's been one.
+    // A mark is blocked from composing with the starter by a character between
+    // them of the same or a higher class, or by another starter.
+    let mut last_class = None;
+
+    for c in decomposed {
+        let class = combining_class(c);
+        if let Some(starter) = starter {
+            let blocked = matches!(last_class, Some(last) if last == 0 || class <= last);
+            if !blocked {
+                if let Some(pair) = compose_pair(composed[starter], c) {
+                    composed[starter] = pair;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(composed.len());
+            last_class = None;
+        } else {
+            last_class = Some(class);
+        }
+        composed.push(c);
+    }
+    composed
+}
+
+/// Which trailing consonant a Hangul syllable ends with, counting from one, or
+/// zero if it has none.
+fn hangul_trailing_index(syllable: u32) -> u32 {
+    syllable % HANGUL_TRAILING_COUNT
+}
+
+fn compose_pair(first: char, second: char) -> Option<char> {
+    let leading = (first as u32).wrapping_sub(HANGUL_LEADING_BASE);
+    let vowel = (second as u32).wrapping_sub(HANGUL_VOWEL_BASE);
+    if leading < HANGUL_LEADING_COUNT && vowel < HANGUL_VOWEL_COUNT {
+        let syllable = (leading * HANGUL_VOWEL_COUNT + vowel) * HANGUL_TRAILING_COUNT;
+        return std::char::from_u32(HANGUL_SYLLABLE_BASE + syllable);
+    }
+
+    let syllable = (first as u32).wrapping_sub(HANGUL_SYLLABLE_BASE);
+    let trailing = (second as u32).wrapping_sub(HANGUL_TRAILING_BASE);
+    if syllable < HANGUL_SYLLABLE_COUNT
+        && hangul_trailing_index(syllable) == 0
+        && 0 < trailing
+        && trailing < HANGUL_TRAILING_COUNT
+    {
+        return std::char::from_u32(first as u32 + trailing);
+    }
+
+    COMPOSITIONS
+        .iter()
+        .find(|&&(_, base, mark)| base == first && mark == second)
+        .map(|&(composed, _, _)| composed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nfc_of(text: &str) -> String {
+        partial_nfc(text.to_owned())
+    }
+
+    #[test]
+    fn combining_marks_compose() {
+        assert_eq!(nfc_of("cafe\u{301}"), "caf\u{e9}");
+        assert_eq!(nfc_of("caf\u{e9}"), "caf\u{e9}");
+        assert_eq!(nfc_of("ascii_only"), "ascii_only");
+
+        // A `u` with a diaeresis and then a macron is a single character, even
+        // when written partly composed.
+        assert_eq!(nfc_of("u\u{308}\u{304}"), "\u{1d6}");
+        assert_eq!(nfc_of("\u{fc}\u{304}"), "\u{1d6}");
+
+        // A dot below has a lower class than a circumflex, so is ordered
+        // first however the two are written.
+        assert_eq!(nfc_of("a\u{302}\u{323}"), "\u{1ead}");
+        assert_eq!(nfc_of("a\u{323}\u{302}"), "\u{1ead}");
+
+        // Marks that can't compose are left in canonical order.
+        assert_eq!(nfc_of("q\u{301}\u{323}"), "q\u{323}\u{301}");
+    }
+
+    #[test]
+    fn hangul_syllables_compose() {
+        assert_eq!(nfc_of("\u{1112}\u{1161}\u{11ab}"), "\u{d55c}");
+        assert_eq!(nfc_of("\u{1100}\u{1173}"), "\u{adf8}");
+        assert_eq!(nfc_of("\u{d55c}"), "\u{d55c}");
+    }
+}
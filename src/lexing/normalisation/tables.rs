@@ -0,0 +1,1019 @@
+//! Tables of canonical compositions and combining classes, selected by hand
+//! from version 14.0.0 of the Unicode Character Database rather than generated
+//! from it. Only the compositions of Latin, Greek, and Cyrillic letters with
+//! combining marks are listed. Singleton and mark-only decompositions, such as
+//! U+0344 into a diaeresis and an acute accent, and every other script, are
+//! missing; adding them means adding their rows here.
+
+/// Characters composed of a base character and a combining mark, ordered by
+/// the composed character.
+pub const COMPOSITIONS: &[(char, char, char)] = &[
+    ('\u{c0}', '\u{41}', '\u{300}'),
+    ('\u{c1}', '\u{41}', '\u{301}'),
+    ('\u{c2}', '\u{41}', '\u{302}'),
+    ('\u{c3}', '\u{41}', '\u{303}'),
+    ('\u{c4}', '\u{41}', '\u{308}'),
+    ('\u{c5}', '\u{41}', '\u{30a}'),
+    ('\u{c7}', '\u{43}', '\u{327}'),
+    ('\u{c8}', '\u{45}', '\u{300}'),
+    ('\u{c9}', '\u{45}', '\u{301}'),
+    ('\u{ca}', '\u{45}', '\u{302}'),
+    ('\u{cb}', '\u{45}', '\u{308}'),
+    ('\u{cc}', '\u{49}', '\u{300}'),
+    ('\u{cd}', '\u{49}', '\u{301}'),
+    ('\u{ce}', '\u{49}', '\u{302}'),
+    ('\u{cf}', '\u{49}', '\u{308}'),
+    ('\u{d1}', '\u{4e}', '\u{303}'),
+    ('\u{d2}', '\u{4f}', '\u{300}'),
+    ('\u{d3}', '\u{4f}', '\u{301}'),
+    ('\u{d4}', '\u{4f}', '\u{302}'),
+    ('\u{d5}', '\u{4f}', '\u{303}'),
+    ('\u{d6}', '\u{4f}', '\u{308}'),
+    ('\u{d9}', '\u{55}', '\u{300}'),
+    ('\u{da}', '\u{55}', '\u{301}'),
+    ('\u{db}', '\u{55}', '\u{302}'),
+    ('\u{dc}', '\u{55}', '\u{308}'),
+    ('\u{dd}', '\u{59}', '\u{301}'),
+    ('\u{e0}', '\u{61}', '\u{300}'),
+    ('\u{e1}', '\u{61}', '\u{301}'),
+    ('\u{e2}', '\u{61}', '\u{302}'),
+    ('\u{e3}', '\u{61}', '\u{303}'),
+    ('\u{e4}', '\u{61}', '\u{308}'),
+    ('\u{e5}', '\u{61}', '\u{30a}'),
+    ('\u{e7}', '\u{63}', '\u{327}'),
+    ('\u{e8}', '\u{65}', '\u{300}'),
+    ('\u{e9}', '\u{65}', '\u{301}'),
+    ('\u{ea}', '\u{65}', '\u{302}'),
+    ('\u{eb}', '\u{65}', '\u{308}'),
+    ('\u{ec}', '\u{69}', '\u{300}'),
+    ('\u{ed}', '\u{69}', '\u{301}'),
+    ('\u{ee}', '\u{69}', '\u{302}'),
+    ('\u{ef}', '\u{69}', '\u{308}'),
+    ('\u{f1}', '\u{6e}', '\u{303}'),
+    ('\u{f2}', '\u{6f}', '\u{300}'),
+    ('\u{f3}', '\u{6f}', '\u{301}'),
+    ('\u{f4}', '\u{6f}', '\u{302}'),
+    ('\u{f5}', '\u{6f}', '\u{303}'),
+    ('\u{f6}', '\u{6f}', '\u{308}'),
+    ('\u{f9}', '\u{75}', '\u{300}'),
+    ('\u{fa}', '\u{75}', '\u{301}'),
+    ('\u{fb}', '\u{75}', '\u{302}'),
+    ('\u{fc}', '\u{75}', '\u{308}'),
+    ('\u{fd}', '\u{79}', '\u{301}'),
+    ('\u{ff}', '\u{79}', '\u{308}'),
+    ('\u{100}', '\u{41}', '\u{304}'),
+    ('\u{101}', '\u{61}', '\u{304}'),
+    ('\u{102}', '\u{41}', '\u{306}'),
+    ('\u{103}', '\u{61}', '\u{306}'),
+    ('\u{104}', '\u{41}', '\u{328}'),
+    ('\u{105}', '\u{61}', '\u{328}'),
+    ('\u{106}', '\u{43}', '\u{301}'),
+    ('\u{107}', '\u{63}', '\u{301}'),
+    ('\u{108}', '\u{43}', '\u{302}'),
+    ('\u{109}', '\u{63}', '\u{302}'),
+    ('\u{10a}', '\u{43}', '\u{307}'),
+    ('\u{10b}', '\u{63}', '\u{307}'),
+    ('\u{10c}', '\u{43}', '\u{30c}'),
+    ('\u{10d}', '\u{63}', '\u{30c}'),
+    ('\u{10e}', '\u{44}', '\u{30c}'),
+    ('\u{10f}', '\u{64}', '\u{30c}'),
+    ('\u{112}', '\u{45}', '\u{304}'),
+    ('\u{113}', '\u{65}', '\u{304}'),
+    ('\u{114}', '\u{45}', '\u{306}'),
+    ('\u{115}', '\u{65}', '\u{306}'),
+    ('\u{116}', '\u{45}', '\u{307}'),
+    ('\u{117}', '\u{65}', '\u{307}'),
+    ('\u{118}', '\u{45}', '\u{328}'),
+    ('\u{119}', '\u{65}', '\u{328}'),
+    ('\u{11a}', '\u{45}', '\u{30c}'),
+    ('\u{11b}', '\u{65}', '\u{30c}'),
+    ('\u{11c}', '\u{47}', '\u{302}'),
+    ('\u{11d}', '\u{67}', '\u{302}'),
+    ('\u{11e}', '\u{47}', '\u{306}'),
+    ('\u{11f}', '\u{67}', '\u{306}'),
+    ('\u{120}', '\u{47}', '\u{307}'),
+    ('\u{121}', '\u{67}', '\u{307}'),
+    ('\u{122}', '\u{47}', '\u{327}'),
+    ('\u{123}', '\u{67}', '\u{327}'),
+    ('\u{124}', '\u{48}', '\u{302}'),
+    ('\u{125}', '\u{68}', '\u{302}'),
+    ('\u{128}', '\u{49}', '\u{303}'),
+    ('\u{129}', '\u{69}', '\u{303}'),
+    ('\u{12a}', '\u{49}', '\u{304}'),
+    ('\u{12b}', '\u{69}', '\u{304}'),
+    ('\u{12c}', '\u{49}', '\u{306}'),
+    ('\u{12d}', '\u{69}', '\u{306}'),
+    ('\u{12e}', '\u{49}', '\u{328}'),
+    ('\u{12f}', '\u{69}', '\u{328}'),
+    ('\u{130}', '\u{49}', '\u{307}'),
+    ('\u{134}', '\u{4a}', '\u{302}'),
+    ('\u{135}', '\u{6a}', '\u{302}'),
+    ('\u{136}', '\u{4b}', '\u{327}'),
+    ('\u{137}', '\u{6b}', '\u{327}'),
+    ('\u{139}', '\u{4c}', '\u{301}'),
+    ('\u{13a}', '\u{6c}', '\u{301}'),
+    ('\u{13b}', '\u{4c}', '\u{327}'),
+    ('\u{13c}', '\u{6c}', '\u{327}'),
+    ('\u{13d}', '\u{4c}', '\u{30c}'),
+    ('\u{13e}', '\u{6c}', '\u{30c}'),
+    ('\u{143}', '\u{4e}', '\u{301}'),
+    ('\u{144}', '\u{6e}', '\u{301}'),
+    ('\u{145}', '\u{4e}', '\u{327}'),
+    ('\u{146}', '\u{6e}', '\u{327}'),
+    ('\u{147}', '\u{4e}', '\u{30c}'),
+    ('\u{148}', '\u{6e}', '\u{30c}'),
+    ('\u{14c}', '\u{4f}', '\u{304}'),
+    ('\u{14d}', '\u{6f}', '\u{304}'),
+    ('\u{14e}', '\u{4f}', '\u{306}'),
+    ('\u{14f}', '\u{6f}', '\u{306}'),
+    ('\u{150}', '\u{4f}', '\u{30b}'),
+    ('\u{151}', '\u{6f}', '\u{30b}'),
+    ('\u{154}', '\u{52}', '\u{301}'),
+    ('\u{155}', '\u{72}', '\u{301}'),
+    ('\u{156}', '\u{52}', '\u{327}'),
+    ('\u{157}', '\u{72}', '\u{327}'),
+    ('\u{158}', '\u{52}', '\u{30c}'),
+    ('\u{159}', '\u{72}', '\u{30c}'),
+    ('\u{15a}', '\u{53}', '\u{301}'),
+    ('\u{15b}', '\u{73}', '\u{301}'),
+    ('\u{15c}', '\u{53}', '\u{302}'),
+    ('\u{15d}', '\u{73}', '\u{302}'),
+    ('\u{15e}', '\u{53}', '\u{327}'),
+    ('\u{15f}', '\u{73}', '\u{327}'),
+    ('\u{160}', '\u{53}', '\u{30c}'),
+    ('\u{161}', '\u{73}', '\u{30c}'),
+    ('\u{162}', '\u{54}', '\u{327}'),
+    ('\u{163}', '\u{74}', '\u{327}'),
+    ('\u{164}', '\u{54}', '\u{30c}'),
+    ('\u{165}', '\u{74}', '\u{30c}'),
+    ('\u{168}', '\u{55}', '\u{303}'),
+    ('\u{169}', '\u{75}', '\u{303}'),
+    ('\u{16a}', '\u{55}', '\u{304}'),
+    ('\u{16b}', '\u{75}', '\u{304}'),
+    ('\u{16c}', '\u{55}', '\u{306}'),
+    ('\u{16d}', '\u{75}', '\u{306}'),
+    ('\u{16e}', '\u{55}', '\u{30a}'),
+    ('\u{16f}', '\u{75}', '\u{30a}'),
+    ('\u{170}', '\u{55}', '\u{30b}'),
+    ('\u{171}', '\u{75}', '\u{30b}'),
+    ('\u{172}', '\u{55}', '\u{328}'),
+    ('\u{173}', '\u{75}', '\u{328}'),
+    ('\u{174}', '\u{57}', '\u{302}'),
+    ('\u{175}', '\u{77}', '\u{302}'),
+    ('\u{176}', '\u{59}', '\u{302}'),
+    ('\u{177}', '\u{79}', '\u{302}'),
+    ('\u{178}', '\u{59}', '\u{308}'),
+    ('\u{179}', '\u{5a}', '\u{301}'),
+    ('\u{17a}', '\u{7a}', '\u{301}'),
+    ('\u{17b}', '\u{5a}', '\u{307}'),
+    ('\u{17c}', '\u{7a}', '\u{307}'),
+    ('\u{17d}', '\u{5a}', '\u{30c}'),
+    ('\u{17e}', '\u{7a}', '\u{30c}'),
+    ('\u{1a0}', '\u{4f}', '\u{31b}'),
+    ('\u{1a1}', '\u{6f}', '\u{31b}'),
+    ('\u{1af}', '\u{55}', '\u{31b}'),
+    ('\u{1b0}', '\u{75}', '\u{31b}'),
+    ('\u{1cd}', '\u{41}', '\u{30c}'),
+    ('\u{1ce}', '\u{61}', '\u{30c}'),
+    ('\u{1cf}', '\u{49}', '\u{30c}'),
+    ('\u{1d0}', '\u{69}', '\u{30c}'),
+    ('\u{1d1}', '\u{4f}', '\u{30c}'),
+    ('\u{1d2}', '\u{6f}', '\u{30c}'),
+    ('\u{1d3}', '\u{55}', '\u{30c}'),
+    ('\u{1d4}', '\u{75}', '\u{30c}'),
+    ('\u{1d5}', '\u{dc}', '\u{304}'),
+    ('\u{1d6}', '\u{fc}', '\u{304}'),
+    ('\u{1d7}', '\u{dc}', '\u{301}'),
+    ('\u{1d8}', '\u{fc}', '\u{301}'),
+    ('\u{1d9}', '\u{dc}', '\u{30c}'),
+    ('\u{1da}', '\u{fc}', '\u{30c}'),
+    ('\u{1db}', '\u{dc}', '\u{300}'),
+    ('\u{1dc}', '\u{fc}', '\u{300}'),
+    ('\u{1de}', '\u{c4}', '\u{304}'),
+    ('\u{1df}', '\u{e4}', '\u{304}'),
+    ('\u{1e0}', '\u{226}', '\u{304}'),
+    ('\u{1e1}', '\u{227}', '\u{304}'),
+    ('\u{1e2}', '\u{c6}', '\u{304}'),
+    ('\u{1e3}', '\u{e6}', '\u{304}'),
+    ('\u{1e6}', '\u{47}', '\u{30c}'),
+    ('\u{1e7}', '\u{67}', '\u{30c}'),
+    ('\u{1e8}', '\u{4b}', '\u{30c}'),
+    ('\u{1e9}', '\u{6b}', '\u{30c}'),
+    ('\u{1ea}', '\u{4f}', '\u{328}'),
+    ('\u{1eb}', '\u{6f}', '\u{328}'),
+    ('\u{1ec}', '\u{1ea}', '\u{304}'),
+    ('\u{1ed}', '\u{1eb}', '\u{304}'),
+    ('\u{1ee}', '\u{1b7}', '\u{30c}'),
+    ('\u{1ef}', '\u{292}', '\u{30c}'),
+    ('\u{1f0}', '\u{6a}', '\u{30c}'),
+    ('\u{1f4}', '\u{47}', '\u{301}'),
+    ('\u{1f5}', '\u{67}', '\u{301}'),
+    ('\u{1f8}', '\u{4e}', '\u{300}'),
+    ('\u{1f9}', '\u{6e}', '\u{300}'),
+    ('\u{1fa}', '\u{c5}', '\u{301}'),
+    ('\u{1fb}', '\u{e5}', '\u{301}'),
+    ('\u{1fc}', '\u{c6}', '\u{301}'),
+    ('\u{1fd}', '\u{e6}', '\u{301}'),
+    ('\u{1fe}', '\u{d8}', '\u{301}'),
+    ('\u{1ff}', '\u{f8}', '\u{301}'),
+    ('\u{200}', '\u{41}', '\u{30f}'),
+    ('\u{201}', '\u{61}', '\u{30f}'),
+    ('\u{202}', '\u{41}', '\u{311}'),
+    ('\u{203}', '\u{61}', '\u{311}'),
+    ('\u{204}', '\u{45}', '\u{30f}'),
+    ('\u{205}', '\u{65}', '\u{30f}'),
+    ('\u{206}', '\u{45}', '\u{311}'),
+    ('\u{207}', '\u{65}', '\u{311}'),
+    ('\u{208}', '\u{49}', '\u{30f}'),
+    ('\u{209}', '\u{69}', '\u{30f}'),
+    ('\u{20a}', '\u{49}', '\u{311}'),
+    ('\u{20b}', '\u{69}', '\u{311}'),
+    ('\u{20c}', '\u{4f}', '\u{30f}'),
+    ('\u{20d}', '\u{6f}', '\u{30f}'),
+    ('\u{20e}', '\u{4f}', '\u{311}'),
+    ('\u{20f}', '\u{6f}', '\u{311}'),
+    ('\u{210}', '\u{52}', '\u{30f}'),
+    ('\u{211}', '\u{72}', '\u{30f}'),
+    ('\u{212}', '\u{52}', '\u{311}'),
+    ('\u{213}', '\u{72}', '\u{311}'),
+    ('\u{214}', '\u{55}', '\u{30f}'),
+    ('\u{215}', '\u{75}', '\u{30f}'),
+    ('\u{216}', '\u{55}', '\u{311}'),
+    ('\u{217}', '\u{75}', '\u{311}'),
+    ('\u{218}', '\u{53}', '\u{326}'),
+    ('\u{219}', '\u{73}', '\u{326}'),
+    ('\u{21a}', '\u{54}', '\u{326}'),
+    ('\u{21b}', '\u{74}', '\u{326}'),
+    ('\u{21e}', '\u{48}', '\u{30c}'),
+    ('\u{21f}', '\u{68}', '\u{30c}'),
+    ('\u{226}', '\u{41}', '\u{307}'),
+    ('\u{227}', '\u{61}', '\u{307}'),
+    ('\u{228}', '\u{45}', '\u{327}'),
+    ('\u{229}', '\u{65}', '\u{327}'),
+    ('\u{22a}', '\u{d6}', '\u{304}'),
+    ('\u{22b}', '\u{f6}', '\u{304}'),
+    ('\u{22c}', '\u{d5}', '\u{304}'),
+    ('\u{22d}', '\u{f5}', '\u{304}'),
+    ('\u{22e}', '\u{4f}', '\u{307}'),
+    ('\u{22f}', '\u{6f}', '\u{307}'),
+    ('\u{230}', '\u{22e}', '\u{304}'),
+    ('\u{231}', '\u{22f}', '\u{304}'),
+    ('\u{232}', '\u{59}', '\u{304}'),
+    ('\u{233}', '\u{79}', '\u{304}'),
+    ('\u{385}', '\u{a8}', '\u{301}'),
+    ('\u{386}', '\u{391}', '\u{301}'),
+    ('\u{388}', '\u{395}', '\u{301}'),
+    ('\u{389}', '\u{397}', '\u{301}'),
+    ('\u{38a}', '\u{399}', '\u{301}'),
+    ('\u{38c}', '\u{39f}', '\u{301}'),
+    ('\u{38e}', '\u{3a5}', '\u{301}'),
+    ('\u{38f}', '\u{3a9}', '\u{301}'),
+    ('\u{390}', '\u{3ca}', '\u{301}'),
+    ('\u{3aa}', '\u{399}', '\u{308}'),
+    ('\u{3ab}', '\u{3a5}', '\u{308}'),
+    ('\u{3ac}', '\u{3b1}', '\u{301}'),
+    ('\u{3ad}', '\u{3b5}', '\u{301}'),
+    ('\u{3ae}', '\u{3b7}', '\u{301}'),
+    ('\u{3af}', '\u{3b9}', '\u{301}'),
+    ('\u{3b0}', '\u{3cb}', '\u{301}'),
+    ('\u{3ca}', '\u{3b9}', '\u{308}'),
+    ('\u{3cb}', '\u{3c5}', '\u{308}'),
+    ('\u{3cc}', '\u{3bf}', '\u{301}'),
+    ('\u{3cd}', '\u{3c5}', '\u{301}'),
+    ('\u{3ce}', '\u{3c9}', '\u{301}'),
+    ('\u{3d3}', '\u{3d2}', '\u{301}'),
+    ('\u{3d4}', '\u{3d2}', '\u{308}'),
+    ('\u{400}', '\u{415}', '\u{300}'),
+    ('\u{401}', '\u{415}', '\u{308}'),
+    ('\u{403}', '\u{413}', '\u{301}'),
+    ('\u{407}', '\u{406}', '\u{308}'),
+    ('\u{40c}', '\u{41a}', '\u{301}'),
+    ('\u{40d}', '\u{418}', '\u{300}'),
+    ('\u{40e}', '\u{423}', '\u{306}'),
+    ('\u{419}', '\u{418}', '\u{306}'),
+    ('\u{439}', '\u{438}', '\u{306}'),
+    ('\u{450}', '\u{435}', '\u{300}'),
+    ('\u{451}', '\u{435}', '\u{308}'),
+    ('\u{453}', '\u{433}', '\u{301}'),
+    ('\u{457}', '\u{456}', '\u{308}'),
+    ('\u{45c}', '\u{43a}', '\u{301}'),
+    ('\u{45d}', '\u{438}', '\u{300}'),
+    ('\u{45e}', '\u{443}', '\u{306}'),
+    ('\u{476}', '\u{474}', '\u{30f}'),
+    ('\u{477}', '\u{475}', '\u{30f}'),
+    ('\u{4c1}', '\u{416}', '\u{306}'),
+    ('\u{4c2}', '\u{436}', '\u{306}'),
+    ('\u{4d0}', '\u{410}', '\u{306}'),
+    ('\u{4d1}', '\u{430}', '\u{306}'),
+    ('\u{4d2}', '\u{410}', '\u{308}'),
+    ('\u{4d3}', '\u{430}', '\u{308}'),
+    ('\u{4d6}', '\u{415}', '\u{306}'),
+    ('\u{4d7}', '\u{435}', '\u{306}'),
+    ('\u{4da}', '\u{4d8}', '\u{308}'),
+    ('\u{4db}', '\u{4d9}', '\u{308}'),
+    ('\u{4dc}', '\u{416}', '\u{308}'),
+    ('\u{4dd}', '\u{436}', '\u{308}'),
+    ('\u{4de}', '\u{417}', '\u{308}'),
+    ('\u{4df}', '\u{437}', '\u{308}'),
+    ('\u{4e2}', '\u{418}', '\u{304}'),
+    ('\u{4e3}', '\u{438}', '\u{304}'),
+    ('\u{4e4}', '\u{418}', '\u{308}'),
+    ('\u{4e5}', '\u{438}', '\u{308}'),
+    ('\u{4e6}', '\u{41e}', '\u{308}'),
+    ('\u{4e7}', '\u{43e}', '\u{308}'),
+    ('\u{4ea}', '\u{4e8}', '\u{308}'),
+    ('\u{4eb}', '\u{4e9}', '\u{308}'),
+    ('\u{4ec}', '\u{42d}', '\u{308}'),
+    ('\u{4ed}', '\u{44d}', '\u{308}'),
+    ('\u{4ee}', '\u{423}', '\u{304}'),
+    ('\u{4ef}', '\u{443}', '\u{304}'),
+    ('\u{4f0}', '\u{423}', '\u{308}'),
+    ('\u{4f1}', '\u{443}', '\u{308}'),
+    ('\u{4f2}', '\u{423}', '\u{30b}'),
+    ('\u{4f3}', '\u{443}', '\u{30b}'),
+    ('\u{4f4}', '\u{427}', '\u{308}'),
+    ('\u{4f5}', '\u{447}', '\u{308}'),
+    ('\u{4f8}', '\u{42b}', '\u{308}'),
+    ('\u{4f9}', '\u{44b}', '\u{308}'),
+    ('\u{1e00}', '\u{41}', '\u{325}'),
+    ('\u{1e01}', '\u{61}', '\u{325}'),
+    ('\u{1e02}', '\u{42}', '\u{307}'),
+    ('\u{1e03}', '\u{62}', '\u{307}'),
+    ('\u{1e04}', '\u{42}', '\u{323}'),
+    ('\u{1e05}', '\u{62}', '\u{323}'),
+    ('\u{1e06}', '\u{42}', '\u{331}'),
+    ('\u{1e07}', '\u{62}', '\u{331}'),
+    ('\u{1e08}', '\u{c7}', '\u{301}'),
+    ('\u{1e09}', '\u{e7}', '\u{301}'),
+    ('\u{1e0a}', '\u{44}', '\u{307}'),
+    ('\u{1e0b}', '\u{64}', '\u{307}'),
+    ('\u{1e0c}', '\u{44}', '\u{323}'),
+    ('\u{1e0d}', '\u{64}', '\u{323}'),
+    ('\u{1e0e}', '\u{44}', '\u{331}'),
+    ('\u{1e0f}', '\u{64}', '\u{331}'),
+    ('\u{1e10}', '\u{44}', '\u{327}'),
+    ('\u{1e11}', '\u{64}', '\u{327}'),
+    ('\u{1e12}', '\u{44}', '\u{32d}'),
+    ('\u{1e13}', '\u{64}', '\u{32d}'),
+    ('\u{1e14}', '\u{112}', '\u{300}'),
+    ('\u{1e15}', '\u{113}', '\u{300}'),
+    ('\u{1e16}', '\u{112}', '\u{301}'),
+    ('\u{1e17}', '\u{113}', '\u{301}'),
+    ('\u{1e18}', '\u{45}', '\u{32d}'),
+    ('\u{1e19}', '\u{65}', '\u{32d}'),
+    ('\u{1e1a}', '\u{45}', '\u{330}'),
+    ('\u{1e1b}', '\u{65}', '\u{330}'),
+    ('\u{1e1c}', '\u{228}', '\u{306}'),
+    ('\u{1e1d}', '\u{229}', '\u{306}'),
+    ('\u{1e1e}', '\u{46}', '\u{307}'),
+    ('\u{1e1f}', '\u{66}', '\u{307}'),
+    ('\u{1e20}', '\u{47}', '\u{304}'),
+    ('\u{1e21}', '\u{67}', '\u{304}'),
+    ('\u{1e22}', '\u{48}', '\u{307}'),
+    ('\u{1e23}', '\u{68}', '\u{307}'),
+    ('\u{1e24}', '\u{48}', '\u{323}'),
+    ('\u{1e25}', '\u{68}', '\u{323}'),
+    ('\u{1e26}', '\u{48}', '\u{308}'),
+    ('\u{1e27}', '\u{68}', '\u{308}'),
+    ('\u{1e28}', '\u{48}', '\u{327}'),
+    ('\u{1e29}', '\u{68}', '\u{327}'),
+    ('\u{1e2a}', '\u{48}', '\u{32e}'),
+    ('\u{1e2b}', '\u{68}', '\u{32e}'),
+    ('\u{1e2c}', '\u{49}', '\u{330}'),
+    ('\u{1e2d}', '\u{69}', '\u{330}'),
+    ('\u{1e2e}', '\u{cf}', '\u{301}'),
+    ('\u{1e2f}', '\u{ef}', '\u{301}'),
+    ('\u{1e30}', '\u{4b}', '\u{301}'),
+    ('\u{1e31}', '\u{6b}', '\u{301}'),
+    ('\u{1e32}', '\u{4b}', '\u{323}'),
+    ('\u{1e33}', '\u{6b}', '\u{323}'),
+    ('\u{1e34}', '\u{4b}', '\u{331}'),
+    ('\u{1e35}', '\u{6b}', '\u{331}'),
+    ('\u{1e36}', '\u{4c}', '\u{323}'),
+    ('\u{1e37}', '\u{6c}', '\u{323}'),
+    ('\u{1e38}', '\u{1e36}', '\u{304}'),
+    ('\u{1e39}', '\u{1e37}', '\u{304}'),
+    ('\u{1e3a}', '\u{4c}', '\u{331}'),
+    ('\u{1e3b}', '\u{6c}', '\u{331}'),
+    ('\u{1e3c}', '\u{4c}', '\u{32d}'),
+    ('\u{1e3d}', '\u{6c}', '\u{32d}'),
+    ('\u{1e3e}', '\u{4d}', '\u{301}'),
+    ('\u{1e3f}', '\u{6d}', '\u{301}'),
+    ('\u{1e40}', '\u{4d}', '\u{307}'),
+    ('\u{1e41}', '\u{6d}', '\u{307}'),
+    ('\u{1e42}', '\u{4d}', '\u{323}'),
+    ('\u{1e43}', '\u{6d}', '\u{323}'),
+    ('\u{1e44}', '\u{4e}', '\u{307}'),
+    ('\u{1e45}', '\u{6e}', '\u{307}'),
+    ('\u{1e46}', '\u{4e}', '\u{323}'),
+    ('\u{1e47}', '\u{6e}', '\u{323}'),
+    ('\u{1e48}', '\u{4e}', '\u{331}'),
+    ('\u{1e49}', '\u{6e}', '\u{331}'),
+    ('\u{1e4a}', '\u{4e}', '\u{32d}'),
+    ('\u{1e4b}', '\u{6e}', '\u{32d}'),
+    ('\u{1e4c}', '\u{d5}', '\u{301}'),
+    ('\u{1e4d}', '\u{f5}', '\u{301}'),
+    ('\u{1e4e}', '\u{d5}', '\u{308}'),
+    ('\u{1e4f}', '\u{f5}', '\u{308}'),
+    ('\u{1e50}', '\u{14c}', '\u{300}'),
+    ('\u{1e51}', '\u{14d}', '\u{300}'),
+    ('\u{1e52}', '\u{14c}', '\u{301}'),
+    ('\u{1e53}', '\u{14d}', '\u{301}'),
+    ('\u{1e54}', '\u{50}', '\u{301}'),
+    ('\u{1e55}', '\u{70}', '\u{301}'),
+    ('\u{1e56}', '\u{50}', '\u{307}'),
+    ('\u{1e57}', '\u{70}', '\u{307}'),
+    ('\u{1e58}', '\u{52}', '\u{307}'),
+    ('\u{1e59}', '\u{72}', '\u{307}'),
+    ('\u{1e5a}', '\u{52}', '\u{323}'),
+    ('\u{1e5b}', '\u{72}', '\u{323}'),
+    ('\u{1e5c}', '\u{1e5a}', '\u{304}'),
+    ('\u{1e5d}', '\u{1e5b}', '\u{304}'),
+    ('\u{1e5e}', '\u{52}', '\u{331}'),
+    ('\u{1e5f}', '\u{72}', '\u{331}'),
+    ('\u{1e60}', '\u{53}', '\u{307}'),
+    ('\u{1e61}', '\u{73}', '\u{307}'),
+    ('\u{1e62}', '\u{53}', '\u{323}'),
+    ('\u{1e63}', '\u{73}', '\u{323}'),
+    ('\u{1e64}', '\u{15a}', '\u{307}'),
+    ('\u{1e65}', '\u{15b}', '\u{307}'),
+    ('\u{1e66}', '\u{160}', '\u{307}'),
+    ('\u{1e67}', '\u{161}', '\u{307}'),
+    ('\u{1e68}', '\u{1e62}', '\u{307}'),
+    ('\u{1e69}', '\u{1e63}', '\u{307}'),
+    ('\u{1e6a}', '\u{54}', '\u{307}'),
+    ('\u{1e6b}', '\u{74}', '\u{307}'),
+    ('\u{1e6c}', '\u{54}', '\u{323}'),
+    ('\u{1e6d}', '\u{74}', '\u{323}'),
+    ('\u{1e6e}', '\u{54}', '\u{331}'),
+    ('\u{1e6f}', '\u{74}', '\u{331}'),
+    ('\u{1e70}', '\u{54}', '\u{32d}'),
+    ('\u{1e71}', '\u{74}', '\u{32d}'),
+    ('\u{1e72}', '\u{55}', '\u{324}'),
+    ('\u{1e73}', '\u{75}', '\u{324}'),
+    ('\u{1e74}', '\u{55}', '\u{330}'),
+    ('\u{1e75}', '\u{75}', '\u{330}'),
+    ('\u{1e76}', '\u{55}', '\u{32d}'),
+    ('\u{1e77}', '\u{75}', '\u{32d}'),
+    ('\u{1e78}', '\u{168}', '\u{301}'),
+    ('\u{1e79}', '\u{169}', '\u{301}'),
+    ('\u{1e7a}', '\u{16a}', '\u{308}'),
+    ('\u{1e7b}', '\u{16b}', '\u{308}'),
+    ('\u{1e7c}', '\u{56}', '\u{303}'),
+    ('\u{1e7d}', '\u{76}', '\u{303}'),
+    ('\u{1e7e}', '\u{56}', '\u{323}'),
+    ('\u{1e7f}', '\u{76}', '\u{323}'),
+    ('\u{1e80}', '\u{57}', '\u{300}'),
+    ('\u{1e81}', '\u{77}', '\u{300}'),
+    ('\u{1e82}', '\u{57}', '\u{301}'),
+    ('\u{1e83}', '\u{77}', '\u{301}'),
+    ('\u{1e84}', '\u{57}', '\u{308}'),
+    ('\u{1e85}', '\u{77}', '\u{308}'),
+    ('\u{1e86}', '\u{57}', '\u{307}'),
+    ('\u{1e87}', '\u{77}', '\u{307}'),
+    ('\u{1e88}', '\u{57}', '\u{323}'),
+    ('\u{1e89}', '\u{77}', '\u{323}'),
+    ('\u{1e8a}', '\u{58}', '\u{307}'),
+    ('\u{1e8b}', '\u{78}', '\u{307}'),
+    ('\u{1e8c}', '\u{58}', '\u{308}'),
+    ('\u{1e8d}', '\u{78}', '\u{308}'),
+    ('\u{1e8e}', '\u{59}', '\u{307}'),
+    ('\u{1e8f}', '\u{79}', '\u{307}'),
+    ('\u{1e90}', '\u{5a}', '\u{302}'),
+    ('\u{1e91}', '\u{7a}', '\u{302}'),
+    ('\u{1e92}', '\u{5a}', '\u{323}'),
+    ('\u{1e93}', '\u{7a}', '\u{323}'),
+    ('\u{1e94}', '\u{5a}', '\u{331}'),
+    ('\u{1e95}', '\u{7a}', '\u{331}'),
+    ('\u{1e96}', '\u{68}', '\u{331}'),
+    ('\u{1e97}', '\u{74}', '\u{308}'),
+    ('\u{1e98}', '\u{77}', '\u{30a}'),
+    ('\u{1e99}', '\u{79}', '\u{30a}'),
+    ('\u{1e9b}', '\u{17f}', '\u{307}'),
+    ('\u{1ea0}', '\u{41}', '\u{323}'),
+    ('\u{1ea1}', '\u{61}', '\u{323}'),
+    ('\u{1ea2}', '\u{41}', '\u{309}'),
+    ('\u{1ea3}', '\u{61}', '\u{309}'),
+    ('\u{1ea4}', '\u{c2}', '\u{301}'),
+    ('\u{1ea5}', '\u{e2}', '\u{301}'),
+    ('\u{1ea6}', '\u{c2}', '\u{300}'),
+    ('\u{1ea7}', '\u{e2}', '\u{300}'),
+    ('\u{1ea8}', '\u{c2}', '\u{309}'),
+    ('\u{1ea9}', '\u{e2}', '\u{309}'),
+    ('\u{1eaa}', '\u{c2}', '\u{303}'),
+    ('\u{1eab}', '\u{e2}', '\u{303}'),
+    ('\u{1eac}', '\u{1ea0}', '\u{302}'),
+    ('\u{1ead}', '\u{1ea1}', '\u{302}'),
+    ('\u{1eae}', '\u{102}', '\u{301}'),
+    ('\u{1eaf}', '\u{103}', '\u{301}'),
+    ('\u{1eb0}', '\u{102}', '\u{300}'),
+    ('\u{1eb1}', '\u{103}', '\u{300}'),
+    ('\u{1eb2}', '\u{102}', '\u{309}'),
+    ('\u{1eb3}', '\u{103}', '\u{309}'),
+    ('\u{1eb4}', '\u{102}', '\u{303}'),
+    ('\u{1eb5}', '\u{103}', '\u{303}'),
+    ('\u{1eb6}', '\u{1ea0}', '\u{306}'),
+    ('\u{1eb7}', '\u{1ea1}', '\u{306}'),
+    ('\u{1eb8}', '\u{45}', '\u{323}'),
+    ('\u{1eb9}', '\u{65}', '\u{323}'),
+    ('\u{1eba}', '\u{45}', '\u{309}'),
+    ('\u{1ebb}', '\u{65}', '\u{309}'),
+    ('\u{1ebc}', '\u{45}', '\u{303}'),
+    ('\u{1ebd}', '\u{65}', '\u{303}'),
+    ('\u{1ebe}', '\u{ca}', '\u{301}'),
+    ('\u{1ebf}', '\u{ea}', '\u{301}'),
+    ('\u{1ec0}', '\u{ca}', '\u{300}'),
+    ('\u{1ec1}', '\u{ea}', '\u{300}'),
+    ('\u{1ec2}', '\u{ca}', '\u{309}'),
+    ('\u{1ec3}', '\u{ea}', '\u{309}'),
+    ('\u{1ec4}', '\u{ca}', '\u{303}'),
+    ('\u{1ec5}', '\u{ea}', '\u{303}'),
+    ('\u{1ec6}', '\u{1eb8}', '\u{302}'),
+    ('\u{1ec7}', '\u{1eb9}', '\u{302}'),
+    ('\u{1ec8}', '\u{49}', '\u{309}'),
+    ('\u{1ec9}', '\u{69}', '\u{309}'),
+    ('\u{1eca}', '\u{49}', '\u{323}'),
+    ('\u{1ecb}', '\u{69}', '\u{323}'),
+    ('\u{1ecc}', '\u{4f}', '\u{323}'),
+    ('\u{1ecd}', '\u{6f}', '\u{323}'),
+    ('\u{1ece}', '\u{4f}', '\u{309}'),
+    ('\u{1ecf}', '\u{6f}', '\u{309}'),
+    ('\u{1ed0}', '\u{d4}', '\u{301}'),
+    ('\u{1ed1}', '\u{f4}', '\u{301}'),
+    ('\u{1ed2}', '\u{d4}', '\u{300}'),
+    ('\u{1ed3}', '\u{f4}', '\u{300}'),
+    ('\u{1ed4}', '\u{d4}', '\u{309}'),
+    ('\u{1ed5}', '\u{f4}', '\u{309}'),
+    ('\u{1ed6}', '\u{d4}', '\u{303}'),
+    ('\u{1ed7}', '\u{f4}', '\u{303}'),
+    ('\u{1ed8}', '\u{1ecc}', '\u{302}'),
+    ('\u{1ed9}', '\u{1ecd}', '\u{302}'),
+    ('\u{1eda}', '\u{1a0}', '\u{301}'),
+    ('\u{1edb}', '\u{1a1}', '\u{301}'),
+    ('\u{1edc}', '\u{1a0}', '\u{300}'),
+    ('\u{1edd}', '\u{1a1}', '\u{300}'),
+    ('\u{1ede}', '\u{1a0}', '\u{309}'),
+    ('\u{1edf}', '\u{1a1}', '\u{309}'),
+    ('\u{1ee0}', '\u{1a0}', '\u{303}'),
+    ('\u{1ee1}', '\u{1a1}', '\u{303}'),
+    ('\u{1ee2}', '\u{1a0}', '\u{323}'),
+    ('\u{1ee3}', '\u{1a1}', '\u{323}'),
+    ('\u{1ee4}', '\u{55}', '\u{323}'),
+    ('\u{1ee5}', '\u{75}', '\u{323}'),
+    ('\u{1ee6}', '\u{55}', '\u{309}'),
+    ('\u{1ee7}', '\u{75}', '\u{309}'),
+    ('\u{1ee8}', '\u{1af}', '\u{301}'),
+    ('\u{1ee9}', '\u{1b0}', '\u{301}'),
+    ('\u{1eea}', '\u{1af}', '\u{300}'),
+    ('\u{1eeb}', '\u{1b0}', '\u{300}'),
+    ('\u{1eec}', '\u{1af}', '\u{309}'),
+    ('\u{1eed}', '\u{1b0}', '\u{309}'),
+    ('\u{1eee}', '\u{1af}', '\u{303}'),
+    ('\u{1eef}', '\u{1b0}', '\u{303}'),
+    ('\u{1ef0}', '\u{1af}', '\u{323}'),
+    ('\u{1ef1}', '\u{1b0}', '\u{323}'),
+    ('\u{1ef2}', '\u{59}', '\u{300}'),
+    ('\u{1ef3}', '\u{79}', '\u{300}'),
+    ('\u{1ef4}', '\u{59}', '\u{323}'),
+    ('\u{1ef5}', '\u{79}', '\u{323}'),
+    ('\u{1ef6}', '\u{59}', '\u{309}'),
+    ('\u{1ef7}', '\u{79}', '\u{309}'),
+    ('\u{1ef8}', '\u{59}', '\u{303}'),
+    ('\u{1ef9}', '\u{79}', '\u{303}'),
+    ('\u{1f00}', '\u{3b1}', '\u{313}'),
+    ('\u{1f01}', '\u{3b1}', '\u{314}'),
+    ('\u{1f02}', '\u{1f00}', '\u{300}'),
+    ('\u{1f03}', '\u{1f01}', '\u{300}'),
+    ('\u{1f04}', '\u{1f00}', '\u{301}'),
+    ('\u{1f05}', '\u{1f01}', '\u{301}'),
+    ('\u{1f06}', '\u{1f00}', '\u{342}'),
+    ('\u{1f07}', '\u{1f01}', '\u{342}'),
+    ('\u{1f08}', '\u{391}', '\u{313}'),
+    ('\u{1f09}', '\u{391}', '\u{314}'),
+    ('\u{1f0a}', '\u{1f08}', '\u{300}'),
+    ('\u{1f0b}', '\u{1f09}', '\u{300}'),
+    ('\u{1f0c}', '\u{1f08}', '\u{301}'),
+    ('\u{1f0d}', '\u{1f09}', '\u{301}'),
+    ('\u{1f0e}', '\u{1f08}', '\u{342}'),
+    ('\u{1f0f}', '\u{1f09}', '\u{342}'),
+    ('\u{1f10}', '\u{3b5}', '\u{313}'),
+    ('\u{1f11}', '\u{3b5}', '\u{314}'),
+    ('\u{1f12}', '\u{1f10}', '\u{300}'),
+    ('\u{1f13}', '\u{1f11}', '\u{300}'),
+    ('\u{1f14}', '\u{1f10}', '\u{301}'),
+    ('\u{1f15}', '\u{1f11}', '\u{301}'),
+    ('\u{1f18}', '\u{395}', '\u{313}'),
+    ('\u{1f19}', '\u{395}', '\u{314}'),
+    ('\u{1f1a}', '\u{1f18}', '\u{300}'),
+    ('\u{1f1b}', '\u{1f19}', '\u{300}'),
+    ('\u{1f1c}', '\u{1f18}', '\u{301}'),
+    ('\u{1f1d}', '\u{1f19}', '\u{301}'),
+    ('\u{1f20}', '\u{3b7}', '\u{313}'),
+    ('\u{1f21}', '\u{3b7}', '\u{314}'),
+    ('\u{1f22}', '\u{1f20}', '\u{300}'),
+    ('\u{1f23}', '\u{1f21}', '\u{300}'),
+    ('\u{1f24}', '\u{1f20}', '\u{301}'),
+    ('\u{1f25}', '\u{1f21}', '\u{301}'),
+    ('\u{1f26}', '\u{1f20}', '\u{342}'),
+    ('\u{1f27}', '\u{1f21}', '\u{342}'),
+    ('\u{1f28}', '\u{397}', '\u{313}'),
+    ('\u{1f29}', '\u{397}', '\u{314}'),
+    ('\u{1f2a}', '\u{1f28}', '\u{300}'),
+    ('\u{1f2b}', '\u{1f29}', '\u{300}'),
+    ('\u{1f2c}', '\u{1f28}', '\u{301}'),
+    ('\u{1f2d}', '\u{1f29}', '\u{301}'),
+    ('\u{1f2e}', '\u{1f28}', '\u{342}'),
+    ('\u{1f2f}', '\u{1f29}', '\u{342}'),
+    ('\u{1f30}', '\u{3b9}', '\u{313}'),
+    ('\u{1f31}', '\u{3b9}', '\u{314}'),
+    ('\u{1f32}', '\u{1f30}', '\u{300}'),
+    ('\u{1f33}', '\u{1f31}', '\u{300}'),
+    ('\u{1f34}', '\u{1f30}', '\u{301}'),
+    ('\u{1f35}', '\u{1f31}', '\u{301}'),
+    ('\u{1f36}', '\u{1f30}', '\u{342}'),
+    ('\u{1f37}', '\u{1f31}', '\u{342}'),
+    ('\u{1f38}', '\u{399}', '\u{313}'),
+    ('\u{1f39}', '\u{399}', '\u{314}'),
+    ('\u{1f3a}', '\u{1f38}', '\u{300}'),
+    ('\u{1f3b}', '\u{1f39}', '\u{300}'),
+    ('\u{1f3c}', '\u{1f38}', '\u{301}'),
+    ('\u{1f3d}', '\u{1f39}', '\u{301}'),
+    ('\u{1f3e}', '\u{1f38}', '\u{342}'),
+    ('\u{1f3f}', '\u{1f39}', '\u{342}'),
+    ('\u{1f40}', '\u{3bf}', '\u{313}'),
+    ('\u{1f41}', '\u{3bf}', '\u{314}'),
+    ('\u{1f42}', '\u{1f40}', '\u{300}'),
+    ('\u{1f43}', '\u{1f41}', '\u{300}'),
+    ('\u{1f44}', '\u{1f40}', '\u{301}'),
+    ('\u{1f45}', '\u{1f41}', '\u{301}'),
+    ('\u{1f48}', '\u{39f}', '\u{313}'),
+    ('\u{1f49}', '\u{39f}', '\u{314}'),
+    ('\u{1f4a}', '\u{1f48}', '\u{300}'),
+    ('\u{1f4b}', '\u{1f49}', '\u{300}'),
+    ('\u{1f4c}', '\u{1f48}', '\u{301}'),
+    ('\u{1f4d}', '\u{1f49}', '\u{301}'),
+    ('\u{1f50}', '\u{3c5}', '\u{313}'),
+    ('\u{1f51}', '\u{3c5}', '\u{314}'),
+    ('\u{1f52}', '\u{1f50}', '\u{300}'),
+    ('\u{1f53}', '\u{1f51}', '\u{300}'),
+    ('\u{1f54}', '\u{1f50}', '\u{301}'),
+    ('\u{1f55}', '\u{1f51}', '\u{301}'),
+    ('\u{1f56}', '\u{1f50}', '\u{342}'),
+    ('\u{1f57}', '\u{1f51}', '\u{342}'),
+    ('\u{1f59}', '\u{3a5}', '\u{314}'),
+    ('\u{1f5b}', '\u{1f59}', '\u{300}'),
+    ('\u{1f5d}', '\u{1f59}', '\u{301}'),
+    ('\u{1f5f}', '\u{1f59}', '\u{342}'),
+    ('\u{1f60}', '\u{3c9}', '\u{313}'),
+    ('\u{1f61}', '\u{3c9}', '\u{314}'),
+    ('\u{1f62}', '\u{1f60}', '\u{300}'),
+    ('\u{1f63}', '\u{1f61}', '\u{300}'),
+    ('\u{1f64}', '\u{1f60}', '\u{301}'),
+    ('\u{1f65}', '\u{1f61}', '\u{301}'),
+    ('\u{1f66}', '\u{1f60}', '\u{342}'),
+    ('\u{1f67}', '\u{1f61}', '\u{342}'),
+    ('\u{1f68}', '\u{3a9}', '\u{313}'),
+    ('\u{1f69}', '\u{3a9}', '\u{314}'),
+    ('\u{1f6a}', '\u{1f68}', '\u{300}'),
+    ('\u{1f6b}', '\u{1f69}', '\u{300}'),
+    ('\u{1f6c}', '\u{1f68}', '\u{301}'),
+    ('\u{1f6d}', '\u{1f69}', '\u{301}'),
+    ('\u{1f6e}', '\u{1f68}', '\u{342}'),
+    ('\u{1f6f}', '\u{1f69}', '\u{342}'),
+    ('\u{1f70}', '\u{3b1}', '\u{300}'),
+    ('\u{1f72}', '\u{3b5}', '\u{300}'),
+    ('\u{1f74}', '\u{3b7}', '\u{300}'),
+    ('\u{1f76}', '\u{3b9}', '\u{300}'),
+    ('\u{1f78}', '\u{3bf}', '\u{300}'),
+    ('\u{1f7a}', '\u{3c5}', '\u{300}'),
+    ('\u{1f7c}', '\u{3c9}', '\u{300}'),
+    ('\u{1f80}', '\u{1f00}', '\u{345}'),
+    ('\u{1f81}', '\u{1f01}', '\u{345}'),
+    ('\u{1f82}', '\u{1f02}', '\u{345}'),
+    ('\u{1f83}', '\u{1f03}', '\u{345}'),
+    ('\u{1f84}', '\u{1f04}', '\u{345}'),
+    ('\u{1f85}', '\u{1f05}', '\u{345}'),
+    ('\u{1f86}', '\u{1f06}', '\u{345}'),
+    ('\u{1f87}', '\u{1f07}', '\u{345}'),
+    ('\u{1f88}', '\u{1f08}', '\u{345}'),
+    ('\u{1f89}', '\u{1f09}', '\u{345}'),
+    ('\u{1f8a}', '\u{1f0a}', '\u{345}'),
+    ('\u{1f8b}', '\u{1f0b}', '\u{345}'),
+    ('\u{1f8c}', '\u{1f0c}', '\u{345}'),
+    ('\u{1f8d}', '\u{1f0d}', '\u{345}'),
+    ('\u{1f8e}', '\u{1f0e}', '\u{345}'),
+    ('\u{1f8f}', '\u{1f0f}', '\u{345}'),
+    ('\u{1f90}', '\u{1f20}', '\u{345}'),
+    ('\u{1f91}', '\u{1f21}', '\u{345}'),
+    ('\u{1f92}', '\u{1f22}', '\u{345}'),
+    ('\u{1f93}', '\u{1f23}', '\u{345}'),
+    ('\u{1f94}', '\u{1f24}', '\u{345}'),
+    ('\u{1f95}', '\u{1f25}', '\u{345}'),
+    ('\u{1f96}', '\u{1f26}', '\u{345}'),
+    ('\u{1f97}', '\u{1f27}', '\u{345}'),
+    ('\u{1f98}', '\u{1f28}', '\u{345}'),
+    ('\u{1f99}', '\u{1f29}', '\u{345}'),
+    ('\u{1f9a}', '\u{1f2a}', '\u{345}'),
+    ('\u{1f9b}', '\u{1f2b}', '\u{345}'),
+    ('\u{1f9c}', '\u{1f2c}', '\u{345}'),
+    ('\u{1f9d}', '\u{1f2d}', '\u{345}'),
+    ('\u{1f9e}', '\u{1f2e}', '\u{345}'),
+    ('\u{1f9f}', '\u{1f2f}', '\u{345}'),
+    ('\u{1fa0}', '\u{1f60}', '\u{345}'),
+    ('\u{1fa1}', '\u{1f61}', '\u{345}'),
+    ('\u{1fa2}', '\u{1f62}', '\u{345}'),
+    ('\u{1fa3}', '\u{1f63}', '\u{345}'),
+    ('\u{1fa4}', '\u{1f64}', '\u{345}'),
+    ('\u{1fa5}', '\u{1f65}', '\u{345}'),
+    ('\u{1fa6}', '\u{1f66}', '\u{345}'),
+    ('\u{1fa7}', '\u{1f67}', '\u{345}'),
+    ('\u{1fa8}', '\u{1f68}', '\u{345}'),
+    ('\u{1fa9}', '\u{1f69}', '\u{345}'),
+    ('\u{1faa}', '\u{1f6a}', '\u{345}'),
+    ('\u{1fab}', '\u{1f6b}', '\u{345}'),
+    ('\u{1fac}', '\u{1f6c}', '\u{345}'),
+    ('\u{1fad}', '\u{1f6d}', '\u{345}'),
+    ('\u{1fae}', '\u{1f6e}', '\u{345}'),
+    ('\u{1faf}', '\u{1f6f}', '\u{345}'),
+    ('\u{1fb0}', '\u{3b1}', '\u{306}'),
+    ('\u{1fb1}', '\u{3b1}', '\u{304}'),
+    ('\u{1fb2}', '\u{1f70}', '\u{345}'),
+    ('\u{1fb3}', '\u{3b1}', '\u{345}'),
+    ('\u{1fb4}', '\u{3ac}', '\u{345}'),
+    ('\u{1fb6}', '\u{3b1}', '\u{342}'),
+    ('\u{1fb7}', '\u{1fb6}', '\u{345}'),
+    ('\u{1fb8}', '\u{391}', '\u{306}'),
+    ('\u{1fb9}', '\u{391}', '\u{304}'),
+    ('\u{1fba}', '\u{391}', '\u{300}'),
+    ('\u{1fbc}', '\u{391}', '\u{345}'),
+    ('\u{1fc1}', '\u{a8}', '\u{342}'),
+    ('\u{1fc2}', '\u{1f74}', '\u{345}'),
+    ('\u{1fc3}', '\u{3b7}', '\u{345}'),
+    ('\u{1fc4}', '\u{3ae}', '\u{345}'),
+    ('\u{1fc6}', '\u{3b7}', '\u{342}'),
+    ('\u{1fc7}', '\u{1fc6}', '\u{345}'),
+    ('\u{1fc8}', '\u{395}', '\u{300}'),
+    ('\u{1fca}', '\u{397}', '\u{300}'),
+    ('\u{1fcc}', '\u{397}', '\u{345}'),
+    ('\u{1fcd}', '\u{1fbf}', '\u{300}'),
+    ('\u{1fce}', '\u{1fbf}', '\u{301}'),
+    ('\u{1fcf}', '\u{1fbf}', '\u{342}'),
+    ('\u{1fd0}', '\u{3b9}', '\u{306}'),
+    ('\u{1fd1}', '\u{3b9}', '\u{304}'),
+    ('\u{1fd2}', '\u{3ca}', '\u{300}'),
+    ('\u{1fd6}', '\u{3b9}', '\u{342}'),
+    ('\u{1fd7}', '\u{3ca}', '\u{342}'),
+    ('\u{1fd8}', '\u{399}', '\u{306}'),
+    ('\u{1fd9}', '\u{399}', '\u{304}'),
+    ('\u{1fda}', '\u{399}', '\u{300}'),
+    ('\u{1fdd}', '\u{1ffe}', '\u{300}'),
+    ('\u{1fde}', '\u{1ffe}', '\u{301}'),
+    ('\u{1fdf}', '\u{1ffe}', '\u{342}'),
+    ('\u{1fe0}', '\u{3c5}', '\u{306}'),
+    ('\u{1fe1}', '\u{3c5}', '\u{304}'),
+    ('\u{1fe2}', '\u{3cb}', '\u{300}'),
+    ('\u{1fe4}', '\u{3c1}', '\u{313}'),
+    ('\u{1fe5}', '\u{3c1}', '\u{314}'),
+    ('\u{1fe6}', '\u{3c5}', '\u{342}'),
+    ('\u{1fe7}', '\u{3cb}', '\u{342}'),
+    ('\u{1fe8}', '\u{3a5}', '\u{306}'),
+    ('\u{1fe9}', '\u{3a5}', '\u{304}'),
+    ('\u{1fea}', '\u{3a5}', '\u{300}'),
+    ('\u{1fec}', '\u{3a1}', '\u{314}'),
+    ('\u{1fed}', '\u{a8}', '\u{300}'),
+    ('\u{1ff2}', '\u{1f7c}', '\u{345}'),
+    ('\u{1ff3}', '\u{3c9}', '\u{345}'),
+    ('\u{1ff4}', '\u{3ce}', '\u{345}'),
+    ('\u{1ff6}', '\u{3c9}', '\u{342}'),
+    ('\u{1ff7}', '\u{1ff6}', '\u{345}'),
+    ('\u{1ff8}', '\u{39f}', '\u{300}'),
+    ('\u{1ffa}', '\u{3a9}', '\u{300}'),
+    ('\u{1ffc}', '\u{3a9}', '\u{345}'),
+];
+
+/// The canonical combining classes of combining marks, ordered by mark. Any
+/// character not listed has a class of zero.
+pub const COMBINING_CLASSES: &[(char, u8)] = &[
+    ('\u{300}', 230),
+    ('\u{301}', 230),
+    ('\u{302}', 230),
+    ('\u{303}', 230),
+    ('\u{304}', 230),
+    ('\u{305}', 230),
+    ('\u{306}', 230),
+    ('\u{307}', 230),
+    ('\u{308}', 230),
+    ('\u{309}', 230),
+    ('\u{30a}', 230),
+    ('\u{30b}', 230),
+    ('\u{30c}', 230),
+    ('\u{30d}', 230),
+    ('\u{30e}', 230),
+    ('\u{30f}', 230),
+    ('\u{310}', 230),
+    ('\u{311}', 230),
+    ('\u{312}', 230),
+    ('\u{313}', 230),
+    ('\u{314}', 230),
+    ('\u{315}', 232),
+    ('\u{316}', 220),
+    ('\u{317}', 220),
+    ('\u{318}', 220),
+    ('\u{319}', 220),
+    ('\u{31a}', 232),
+    ('\u{31b}', 216),
+    ('\u{31c}', 220),
+    ('\u{31d}', 220),
+    ('\u{31e}', 220),
+    ('\u{31f}', 220),
+    ('\u{320}', 220),
+    ('\u{321}', 202),
+    ('\u{322}', 202),
+    ('\u{323}', 220),
+    ('\u{324}', 220),
+    ('\u{325}', 220),
+    ('\u{326}', 220),
+    ('\u{327}', 202),
+    ('\u{328}', 202),
+    ('\u{329}', 220),
+    ('\u{32a}', 220),
+    ('\u{32b}', 220),
+    ('\u{32c}', 220),
+    ('\u{32d}', 220),
+    ('\u{32e}', 220),
+    ('\u{32f}', 220),
+    ('\u{330}', 220),
+    ('\u{331}', 220),
+    ('\u{332}', 220),
+    ('\u{333}', 220),
+    ('\u{334}', 1),
+    ('\u{335}', 1),
+    ('\u{336}', 1),
+    ('\u{337}', 1),
+    ('\u{338}', 1),
+    ('\u{339}', 220),
+    ('\u{33a}', 220),
+    ('\u{33b}', 220),
+    ('\u{33c}', 220),
+    ('\u{33d}', 230),
+    ('\u{33e}', 230),
+    ('\u{33f}', 230),
+    ('\u{340}', 230),
+    ('\u{341}', 230),
+    ('\u{342}', 230),
+    ('\u{343}', 230),
+    ('\u{344}', 230),
+    ('\u{345}', 240),
+    ('\u{346}', 230),
+    ('\u{347}', 220),
+    ('\u{348}', 220),
+    ('\u{349}', 220),
+    ('\u{34a}', 230),
+    ('\u{34b}', 230),
+    ('\u{34c}', 230),
+    ('\u{34d}', 220),
+    ('\u{34e}', 220),
+    ('\u{350}', 230),
+    ('\u{351}', 230),
+    ('\u{352}', 230),
+    ('\u{353}', 220),
+    ('\u{354}', 220),
+    ('\u{355}', 220),
+    ('\u{356}', 220),
+    ('\u{357}', 230),
+    ('\u{358}', 232),
+    ('\u{359}', 220),
+    ('\u{35a}', 220),
+    ('\u{35b}', 230),
+    ('\u{35c}', 233),
+    ('\u{35d}', 234),
+    ('\u{35e}', 234),
+    ('\u{35f}', 233),
+    ('\u{360}', 234),
+    ('\u{361}', 234),
+    ('\u{362}', 233),
+    ('\u{363}', 230),
+    ('\u{364}', 230),
+    ('\u{365}', 230),
+    ('\u{366}', 230),
+    ('\u{367}', 230),
+    ('\u{368}', 230),
+    ('\u{369}', 230),
+    ('\u{36a}', 230),
+    ('\u{36b}', 230),
+    ('\u{36c}', 230),
+    ('\u{36d}', 230),
+    ('\u{36e}', 230),
+    ('\u{36f}', 230),
+    ('\u{483}', 230),
+    ('\u{484}', 230),
+    ('\u{485}', 230),
+    ('\u{486}', 230),
+    ('\u{487}', 230),
+    ('\u{1dc0}', 230),
+    ('\u{1dc1}', 230),
+    ('\u{1dc2}', 220),
+    ('\u{1dc3}', 230),
+    ('\u{1dc4}', 230),
+    ('\u{1dc5}', 230),
+    ('\u{1dc6}', 230),
+    ('\u{1dc7}', 230),
+    ('\u{1dc8}', 230),
+    ('\u{1dc9}', 230),
+    ('\u{1dca}', 220),
+    ('\u{1dcb}', 230),
+    ('\u{1dcc}', 230),
+    ('\u{1dcd}', 234),
+    ('\u{1dce}', 214),
+    ('\u{1dcf}', 220),
+    ('\u{1dd0}', 202),
+    ('\u{1dd1}', 230),
+    ('\u{1dd2}', 230),
+    ('\u{1dd3}', 230),
+    ('\u{1dd4}', 230),
+    ('\u{1dd5}', 230),
+    ('\u{1dd6}', 230),
+    ('\u{1dd7}', 230),
+    ('\u{1dd8}', 230),
+    ('\u{1dd9}', 230),
+    ('\u{1dda}', 230),
+    ('\u{1ddb}', 230),
+    ('\u{1ddc}', 230),
+    ('\u{1ddd}', 230),
+    ('\u{1dde}', 230),
+    ('\u{1ddf}', 230),
+    ('\u{1de0}', 230),
+    ('\u{1de1}', 230),
+    ('\u{1de2}', 230),
+    ('\u{1de3}', 230),
+    ('\u{1de4}', 230),
+    ('\u{1de5}', 230),
+    ('\u{1de6}', 230),
+    ('\u{1de7}', 230),
+    ('\u{1de8}', 230),
+    ('\u{1de9}', 230),
+    ('\u{1dea}', 230),
+    ('\u{1deb}', 230),
+    ('\u{1dec}', 230),
+    ('\u{1ded}', 230),
+    ('\u{1dee}', 230),
+    ('\u{1def}', 230),
+    ('\u{1df0}', 230),
+    ('\u{1df1}', 230),
+    ('\u{1df2}', 230),
+    ('\u{1df3}', 230),
+    ('\u{1df4}', 230),
+    ('\u{1df5}', 230),
+    ('\u{1df6}', 232),
+    ('\u{1df7}', 228),
+    ('\u{1df8}', 228),
+    ('\u{1df9}', 220),
+    ('\u{1dfa}', 218),
+    ('\u{1dfb}', 230),
+    ('\u{1dfc}', 233),
+    ('\u{1dfd}', 220),
+    ('\u{1dfe}', 230),
+    ('\u{1dff}', 220),
+    ('\u{20d0}', 230),
+    ('\u{20d1}', 230),
+    ('\u{20d2}', 1),
+    ('\u{20d3}', 1),
+    ('\u{20d4}', 230),
+    ('\u{20d5}', 230),
+    ('\u{20d6}', 230),
+    ('\u{20d7}', 230),
+    ('\u{20d8}', 1),
+    ('\u{20d9}', 1),
+    ('\u{20da}', 1),
+    ('\u{20db}', 230),
+    ('\u{20dc}', 230),
+    ('\u{20e1}', 230),
+    ('\u{20e5}', 1),
+    ('\u{20e6}', 1),
+    ('\u{20e7}', 230),
+    ('\u{20e8}', 220),
+    ('\u{20e9}', 230),
+    ('\u{20ea}', 1),
+    ('\u{20eb}', 1),
+    ('\u{20ec}', 220),
+    ('\u{20ed}', 220),
+    ('\u{20ee}', 220),
+    ('\u{20ef}', 220),
+    ('\u{20f0}', 230),
+    ('\u{fe20}', 230),
+    ('\u{fe21}', 230),
+    ('\u{fe22}', 230),
+    ('\u{fe23}', 230),
+    ('\u{fe24}', 230),
+    ('\u{fe25}', 230),
+    ('\u{fe26}', 230),
+    ('\u{fe27}', 220),
+    ('\u{fe28}', 220),
+    ('\u{fe29}', 220),
+    ('\u{fe2a}', 220),
+    ('\u{fe2b}', 220),
+    ('\u{fe2c}', 220),
+    ('\u{fe2d}', 220),
+    ('\u{fe2e}', 230),
+    ('\u{fe2f}', 230),
+];
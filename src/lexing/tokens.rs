@@ -1,15 +1,19 @@
 use crate::common::multiphase::{
-    Accessibility, Identifier, InterpolatedString, Number, OverloadableInfixOperator,
-    OverloadableSliceOperator, PostfixOperator, PseudoIdentifier, Shebang, SyDoc, SylanString,
+    Accessibility, HexFloat, Identifier, InterpolatedString, Number, NumericType,
+    OverloadableInfixOperator, OverloadableSliceOperator, PostfixOperator, PseudoIdentifier,
+    Scientific, Shebang, SyDoc, SylanString,
 };
 use crate::common::version::Version;
+use crate::lexing::lexer;
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Literal {
     Char(char),
     InterpolatedString(InterpolatedString),
     String(SylanString),
-    Number(Number),
+    Number(Number, Option<NumericType>),
+    HexFloat(HexFloat),
+    Scientific(Scientific),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -20,11 +24,15 @@ pub enum BranchingAndJumping {
     For,
     Switch,
     Select,
+    Yield,
+    Break,
+    Fallthrough,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum DeclarationHead {
     Class,
+    Embeds,
     Extend,
 
     /// Extern means quite different things depending on whether it refers to a
@@ -49,6 +57,10 @@ pub enum Grouping {
     CloseSquareBracket,
     OpenBrace,
     OpenParentheses,
+
+    // `#{`, opening a record literal. Records close with a plain brace.
+    OpenRecord,
+
     OpenSquareBracket,
 }
 
@@ -72,6 +84,7 @@ pub enum ModuleDefinitions {
 pub enum Binding {
     As,
     Assign,
+    Const,
     Final,
     Var,
 }
@@ -109,6 +122,15 @@ pub enum Token {
     Colon,
     Dot,
     Eof,
+
+    // Only emitted by lexers with significant newlines; otherwise newlines are
+    // just whitespace trivia like any other.
+    NewLine,
+
+    // Stands in for something that couldn't be lexed, so that lexing can carry
+    // on and report any further errors in the same pass.
+    Error(lexer::Error),
+
     LambdaArrow,
 
     // Sylan resolves symbols relatively. To resolve globally, use the `global`
@@ -123,6 +145,16 @@ pub enum Token {
     Rest,
     SubItemSeparator,
     Throw,
+
+    // Introduces the cause of a thrown exception, as in `throw e caused by c`.
+    Caused,
+
+    // Runs a block when the block around it exits, as in `defer { close() }`.
+    Defer,
+
+    // Stands in for code yet to be written, as in `fun area() Int { ??? }`.
+    Hole,
+
     Timeout,
     Use,
 
@@ -84,21 +84,26 @@
 
 #![forbid(unsafe_code)]
 
-use std::env::{args, Args};
+use std::env::args;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, BufRead, Read, Write};
 
+use common::diagnostics::Severity;
+use intepreter::values::{Radix, Value};
+use intepreter::Interpreter;
 use lexing::lexer::Lexer;
 use lexing::Tokens;
 use parsing::Parser;
 use source::in_memory::Source;
 
 mod common;
+mod intepreter;
 mod lexing;
 mod parsing;
+mod simplification;
 mod source;
 
-fn load_source(args: Args) -> Result<String, String> {
+fn load_source(args: impl Iterator<Item = String>) -> Result<String, String> {
     let args_vector = args.collect::<Vec<String>>();
     if args_vector.len() <= 1 {
         Err("source path arg missing".to_string())
@@ -122,7 +127,86 @@ fn demo(parser: Parser) -> Result<(), String> {
         .map_err(|err| format!("failed to parse: {:?}", err))
 }
 
+/// Print every problem found in a source without running it, failing if any of
+/// them are errors rather than warnings.
+fn check(source: &str) -> Result<(), String> {
+    let diagnostics = parsing::check_str(source, &intepreter::builtin_names());
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    if errors == 0 {
+        Ok(())
+    } else {
+        Err(format!("found {} error(s)", errors))
+    }
+}
+
+/// Print a source reformatted in the default style, or nothing if it can't be.
+fn format(source: &str) -> Result<(), String> {
+    print!("{}", parsing::format_str(source)?);
+    Ok(())
+}
+
+/// Read lines from stdin and evaluate each in turn, keeping bindings around for
+/// later lines. Errors are reported without leaving the REPL.
+///
+/// `:radix hex`, `:radix bin`, and `:radix dec` change the base in which
+/// integer results are printed.
+fn repl() -> Result<(), String> {
+    let interpreter = Interpreter::default();
+    let mut radix = Radix::Decimal;
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        stdout
+            .flush()
+            .map_err(|err| format!("failed to write the prompt: {}", err))?;
+
+        let line = match lines.next() {
+            Some(line) => line.map_err(|err| format!("failed to read a line: {}", err))?,
+            None => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(base) = line.trim().strip_prefix(":radix") {
+            match base.trim() {
+                "dec" => radix = Radix::Decimal,
+                "hex" => radix = Radix::Hexadecimal,
+                "bin" => radix = Radix::Binary,
+                other => println!("error: unknown radix: {}", other),
+            }
+            continue;
+        }
+
+        match parsing::parse_interactive(&line) {
+            Ok(node) => match interpreter.interpret(&node) {
+                Ok(Value::Void) => {}
+                Ok(value) => println!("{}", value.to_string_in(radix)),
+                Err(err) => println!("error: {}", err),
+            },
+            Err(err) => println!("error: failed to parse: {:?}", err),
+        }
+    }
+    println!();
+    Ok(())
+}
+
 fn main() -> Result<(), String> {
+    match args().nth(1).as_deref() {
+        Some("--repl") => return repl(),
+        Some("--check") => return check(&load_source(args().skip(1))?),
+        Some("--format") => return format(&load_source(args().skip(1))?),
+        _ => {}
+    }
+
     let source_string = load_source(args())?;
     let source = Source::from(source_string.chars().collect::<Vec<char>>());
     let lexer = Lexer::from(source);
@@ -32,8 +32,8 @@
 //! `...`, `it`, `continue`, `this`, `This`, `this.module`, `this.package`,
 //! `super`, and `_`. They are also the only keyphrases that are allowed to be
 //! shadowed in the same block; user-defined symbols will fail to bind if a binding
-//! of the same name already exists in the same or outer scope. They are called
-//! _pseudoidentifiers_.
+//! of the same name already exists in the same block, although they can shadow
+//! bindings in outer ones. They are called _pseudoidentifiers_.
 //!
 //! A simplification step is performed before giving the AST to the backend as
 //! a jump is needed from Sylan's pragmatic, large syntax to the much smaller,
@@ -46,33 +46,46 @@
 
 use std::collections::HashSet;
 use std::default::Default;
-use std::rc::Rc;
+use std::mem;
 use std::result;
+use std::sync::Arc;
 
+use crate::common::diagnostics::Diagnostic;
 use crate::common::multiphase::{
     self, Accessibility, Identifier, OverloadableInfixOperator, PseudoIdentifier,
 };
 use crate::common::peekable_buffer::PeekableBuffer;
 use crate::common::version::Version;
-use crate::lexing::lexer;
+use crate::lexing::lexer::{self, Lexer, LexerTaskError};
+use crate::lexing::lints;
 use crate::lexing::tokens::{
-    self, Binding, BranchingAndJumping, DeclarationHead, Grouping, Literal, Macros, Modifier, Token,
+    self, Binding, BranchingAndJumping, DeclarationHead, Grouping, Literal, Macros, Modifier,
+    ModuleDefinitions, Token,
 };
 use crate::lexing::Tokens;
 use crate::parsing::{
     modifier_sets::{AccessibilityModifierExtractor, ModifierSets},
     nodes::{
-        Block, Case, CaseMatch, Class, ClassValueParameterFieldUpgrade, CompositePattern, Cond,
-        CondCase, Expression, For, FunModifiers, FunSignature, If, Item, Lambda, LambdaSignature,
-        LambdaValueParameter, MainPackage, Method, Operator, Package, Pattern, PatternGetter,
-        PatternItem, Select, Switch, Symbol, SymbolLookup, Throw, Timeout, TypeArgument,
-        TypeParameter, TypeReference, ValueArgument, ValueParameter,
+        checks, content_hashing::hash_main_file, Block, CallingConvention, Case, CaseMatch, Class,
+        ClassValueParameterFieldUpgrade, CompositePattern, Cond, CondCase, Expression, For,
+        FunModifiers, FunSignature, If, Item, Lambda, LambdaSignature, LambdaValueParameter,
+        MainPackage, Method, Operator, Package, Pattern, PatternGetter, PatternItem, Select,
+        SelectCase, SlicePattern, Switch, Symbol, SymbolLookup, Throw, Timeout, TypeArgument,
+        TypeExpression, TypeParameter, TypeReference, ValueArgument, ValueParameter, While,
+        WhileVar,
     },
+    token_stream::TokenStream,
 };
+use crate::source::{in_memory::Source, Position, Span};
 use nodes::CallArguments;
 
+mod concurrent;
+#[cfg(test)]
+mod conformance;
+mod formatting;
 mod modifier_sets;
-mod nodes;
+pub mod nodes;
+mod token_stream;
 
 // TODO: break cycles in scopes to cleanup memory properly.
 
@@ -88,16 +101,109 @@ pub enum ParserErrorDescription {
 #[derive(Debug)]
 pub struct ParserError {
     description: ParserErrorDescription,
+
+    /// Where the parser was in the source when it failed, if it had got
+    /// anywhere at all.
+    position: Option<Position>,
 }
 
 #[derive(Debug)]
 pub enum Error {
-    Lexer(lexer::Error),
+    /// Every token that couldn't be lexed. These take precedence over parser
+    /// errors, which are likely to be knock-on effects of them.
+    Lexer(Vec<lexer::Error>),
     Parser(ParserError),
 }
 
+impl Error {
+    /// Where the error is, if known. Of several lexer errors, it's the
+    /// earliest in the source.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Error::Lexer(errors) => errors
+                .iter()
+                .map(lexer::Error::position)
+                .min_by_key(|position| position.absolute_character_index()),
+            Error::Parser(error) => error.position,
+        }
+    }
+
+    /// The error as diagnostics, one for each lexer error.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Error::Lexer(errors) => errors.iter().map(Diagnostic::from).collect(),
+            Error::Parser(error) => vec![Diagnostic::from(error)],
+        }
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(error: &ParserError) -> Self {
+        let (code, message) = match &error.description {
+            ParserErrorDescription::Described(description) => ("parser", description.clone()),
+            ParserErrorDescription::Expected(token) => {
+                ("expected-token", format!("expected {:?}", token))
+            }
+            ParserErrorDescription::Unexpected(token) => {
+                ("unexpected-token", format!("unexpected {:?}", token))
+            }
+            ParserErrorDescription::LexerThreadFailed(description) => {
+                ("lexer-thread", description.clone())
+            }
+            ParserErrorDescription::PrematureEof => {
+                ("premature-eof", "the source ended early".to_owned())
+            }
+        };
+        Diagnostic::error(code, message, error.position.map(Span::at))
+    }
+}
+
 type Result<T> = result::Result<T, Error>;
 
+/// By convention types start with capital letters and values don't, so a
+/// lowercase name looked up on a capitalised one, as in `Int.parse`, is taken to
+/// be a type's method rather than a package member or an instance lookup.
+fn is_type_member(lookup: &[Identifier]) -> bool {
+    let starts_uppercase = |identifier: &Identifier| matches!(identifier.0.chars().next(), Some(c) if c.is_uppercase());
+    match lookup {
+        [.., r#type, member] => starts_uppercase(r#type) && !starts_uppercase(member),
+        _ => false,
+    }
+}
+
+/// How tightly an infix operator binds its operands; higher binds tighter.
+/// Pipes bind loosest, so that `xs |> f + 1` pipes into the sum, then the
+/// logical operators, comparisons, and bitwise operators, and finally the
+/// arithmetic ones in their usual order.
+fn infix_precedence(operator: &OverloadableInfixOperator) -> u8 {
+    use OverloadableInfixOperator::*;
+    match operator {
+        Pipe | Cascade => 1,
+        Or => 2,
+        Xor => 3,
+        And => 4,
+        Equals | NotEqual => 5,
+        LessThan | LessThanOrEqual | GreaterThan | GreaterThanOrEqual => 6,
+        BitwiseOr => 7,
+        BitwiseXor => 8,
+        Ampersand => 9,
+        LeftShift | RightShift | UnsignedRightShift => 10,
+        Add | Subtract | MatrixAdd | MatrixSubtract => 11,
+        Multiply | Divide | Modulo | MatrixMultiply | MatrixDivide | MatrixTranspose => 12,
+        Power | MatrixPower => 13,
+        Compose => 14,
+    }
+}
+
+/// `2 ** 3 ** 2` is `2 ** (3 ** 2)`, as in mathematics; everything else
+/// groups leftwards.
+fn is_right_associative(operator: &OverloadableInfixOperator) -> bool {
+    matches!(
+        operator,
+        OverloadableInfixOperator::Power | OverloadableInfixOperator::MatrixPower
+    )
+}
+
 fn new_void() -> TypeReference {
     TypeReference::new(Symbol::Absolute(SymbolLookup(vec![
         Identifier::from("sylan"),
@@ -107,8 +213,38 @@ fn new_void() -> TypeReference {
 }
 
 pub struct Parser {
-    tokens: Tokens,
-    current_scope: Rc<Block>,
+    tokens: TokenStream,
+    retain_comments: bool,
+    current_scope: Arc<Block>,
+
+    /// Names bound by local bindings, parameters, and patterns that are in
+    /// scope at the current point of the parse, innermost last.
+    local_names: Vec<Identifier>,
+
+    /// Labels of the `for` loops enclosing the current point of the parse,
+    /// innermost last.
+    loop_labels: Vec<Identifier>,
+
+    /// Labels of the blocks enclosing the current point of the parse that
+    /// `break` can exit, innermost last.
+    block_labels: Vec<Identifier>,
+
+    /// How many times `fallthrough` has been used in the `switch` arm being
+    /// parsed, or `None` outside of one.
+    arm_fallthroughs: Option<usize>,
+
+    /// Whether a case guard is being parsed, in which case every bare name it
+    /// references must be in `local_names`.
+    resolving_guard: bool,
+
+    /// Where the first top-level executable code in the file is, if it has
+    /// any.
+    top_level_code_position: Option<Position>,
+
+    /// Names bound by `const` so far, which later constant expressions can
+    /// refer to.
+    constants: Vec<Identifier>,
+
     modifier_sets: ModifierSets,
     accessibility_modifier_extractor: AccessibilityModifierExtractor,
 }
@@ -116,8 +252,16 @@ pub struct Parser {
 impl From<Tokens> for Parser {
     fn from(tokens: Tokens) -> Self {
         Self {
-            tokens,
-            current_scope: Rc::new(Block::new_root()),
+            tokens: TokenStream::from(tokens),
+            retain_comments: false,
+            current_scope: Arc::new(Block::new_root()),
+            local_names: vec![],
+            loop_labels: vec![],
+            block_labels: vec![],
+            arm_fallthroughs: None,
+            resolving_guard: false,
+            top_level_code_position: None,
+            constants: vec![],
             modifier_sets: Default::default(),
             accessibility_modifier_extractor: AccessibilityModifierExtractor::new(),
         }
@@ -125,23 +269,39 @@ impl From<Tokens> for Parser {
 }
 
 impl Parser {
+    /// Keep ordinary comments in the AST, attached to the nodes they lead or
+    /// trail, rather than discarding them with the rest of the trivia.
+    pub fn retaining_comments(mut self) -> Self {
+        self.retain_comments = true;
+        self
+    }
+
     //
     // Utilities
     //
 
-    /// Fail at parsing, describing the reason why.
-    fn fail<T>(&self, message: impl Into<String>) -> Result<T> {
+    /// Fail at parsing with the given description, stamping the error with
+    /// the position of the token that caused it.
+    fn fail_with<T>(&mut self, description: ParserErrorDescription) -> Result<T> {
+        let position = self
+            .tokens
+            .current_position()
+            .or_else(|| self.tokens.last_position());
         Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::Described(message.into()),
+            description,
+            position,
         }))
     }
 
+    /// Fail at parsing, describing the reason why.
+    fn fail<T>(&mut self, message: impl Into<String>) -> Result<T> {
+        self.fail_with(ParserErrorDescription::Described(message.into()))
+    }
+
     /// Fail at parsing, stating that the `expected` token was expected but
     /// did not appear.
-    fn expected<T>(&self, expected: Token) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::Expected(expected),
-        }))
+    fn expected<T>(&mut self, expected: Token) -> Result<T> {
+        self.fail_with(ParserErrorDescription::Expected(expected))
     }
 
     /// Return a successful empty result if it is indeed the next token in the
@@ -162,41 +322,75 @@ impl Parser {
     /// token. Otherwise fail at parsing, stating that the `expected` token was
     /// expected but did not appear.
     fn expect_and_read(&mut self, expected: Token) -> Result<Token> {
-        let next = self.tokens.read();
-        next.map(|lexed| lexed.token)
-            .filter(|token| *token == expected)
-            .map(Ok)
-            .unwrap_or_else(|| self.expected(expected))
+        match self.peek() {
+            Some(token) if token == expected => {
+                self.tokens.discard();
+                Ok(token)
+            }
+            Some(_) => self.expected(expected),
+            None => self.premature_eof(),
+        }
     }
 
     /// Discard the next read token in the stream if it matches the expected
     /// token. Otherwise fail at parsing, stating that the `expected` token was
     /// expected but did not appear.
     fn expect_and_discard(&mut self, expected: Token) -> Result<()> {
-        if let Some(lexed) = self.tokens.read() {
-            if lexed.token == expected {
-                Ok(())
-            } else {
-                self.expected(expected)
-            }
-        } else {
-            self.premature_eof()
-        }
+        self.expect_and_read(expected).map(|_| ())
     }
 
     /// Fail at parsing, stating that the `unexpected` token was unexpected
     /// and therefore cannot be handled.
-    fn unexpected<T>(&self, unexpected: Token) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::Unexpected(unexpected),
-        }))
+    fn unexpected<T>(&mut self, unexpected: Token) -> Result<T> {
+        self.fail_with(ParserErrorDescription::Unexpected(unexpected))
     }
 
     /// Fail at parsing because an EOF was encountered unexpectedly.
-    fn premature_eof<T>(&self) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::PrematureEof,
-        }))
+    fn premature_eof<T>(&mut self) -> Result<T> {
+        self.fail_with(ParserErrorDescription::PrematureEof)
+    }
+
+    /// Run a sub-parser, collecting the comments leading and trailing
+    /// whatever it parses if comments are being retained.
+    fn with_comments<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<(T, nodes::Comments)> {
+        let leading = self.next_comments(true);
+        let parsed = parse(self)?;
+        let trailing = self.next_comments(false);
+        Ok((parsed, nodes::Comments { leading, trailing }))
+    }
+
+    /// Run a parser, also yielding the span of the tokens it consumed.
+    fn spanned<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<(T, Option<Span>)> {
+        let start = self.tokens.current_position();
+        let parsed = parse(self)?;
+        let end = self.tokens.last_position();
+        let span = start.zip(end).map(|(start, end)| Span { start, end });
+        Ok((parsed, span))
+    }
+
+    /// Comments in the next token's trivia that are either on their own lines
+    /// or trail the previous token on its line.
+    fn next_comments(&mut self, on_own_lines: bool) -> Vec<String> {
+        if !self.retain_comments {
+            return vec![];
+        }
+        self.tokens
+            .peek()
+            .map(|lexed| {
+                lexed
+                    .comments()
+                    .into_iter()
+                    .filter(|comment| comment.follows_newline == on_own_lines)
+                    .map(|comment| comment.text)
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     //
@@ -257,7 +451,6 @@ impl Parser {
                     false
                 }
             });
-            self.tokens.discard();
 
             if is_modifier {
                 if let Token::Modifier(modifier) = self.read().unwrap() {
@@ -278,14 +471,23 @@ impl Parser {
     fn parse_symbol(&mut self) -> Result<nodes::Symbol> {
         let mut lookup = vec![];
 
-        Ok(
-            if let Some(Token::PseudoIdentifier(pseudo_identifier)) = self.peek() {
+        let this_member = self.next_is(&Token::PseudoIdentifier(PseudoIdentifier::This))
+            && self.nth_is(1, &Token::Dot);
+        Ok(match self.peek() {
+            Some(Token::PseudoIdentifier(pseudo_identifier)) if !this_member => {
+                self.tokens.discard();
                 nodes::Symbol::Pseudo(pseudo_identifier)
-            } else {
+            }
+            _ => {
+                let is_relative = !(this_member || self.next_is(&Token::Global));
                 let new = if self.next_is(&Token::Global) {
                     self.tokens.discard();
                     self.expect_and_discard(Token::Dot)?;
                     nodes::Symbol::Absolute
+                } else if this_member {
+                    self.tokens.discard();
+                    self.tokens.discard();
+                    nodes::Symbol::ThisMember
                 } else {
                     nodes::Symbol::Relative
                 };
@@ -294,12 +496,15 @@ impl Parser {
                     lookup.push(self.parse_identifier()?);
                     if self.next_is(&Token::Dot) {
                         self.tokens.discard();
-                    } else {
+                    } else if !(is_relative && is_type_member(&lookup)) {
                         break new(SymbolLookup(lookup));
+                    } else {
+                        let member = lookup.pop().unwrap();
+                        break nodes::Symbol::TypeMember(SymbolLookup(lookup), member);
                     }
                 }
-            },
-        )
+            }
+        })
     }
 
     fn parse_class_definition(&mut self) -> Result<nodes::Type> {
@@ -329,7 +534,14 @@ impl Parser {
 
         let does_implement = self.next_is(&Token::DeclarationHead(DeclarationHead::Implements));
         let implements = if does_implement {
-            self.parse_implements_clause()?
+            self.parse_type_list_clause()?
+        } else {
+            vec![]
+        };
+
+        let does_embed = self.next_is(&Token::DeclarationHead(DeclarationHead::Embeds));
+        let embeds = if does_embed {
+            self.parse_type_list_clause()?
         } else {
             vec![]
         };
@@ -343,6 +555,7 @@ impl Parser {
 
         let class = Class {
             implements,
+            embeds,
             methods,
             fields,
             value_parameters,
@@ -358,6 +571,7 @@ impl Parser {
     }
 
     fn parse_class_value_parameters(&mut self) -> Result<Vec<nodes::ClassValueParameter>> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
         let mut parameters = vec![];
 
         loop {
@@ -438,6 +652,7 @@ impl Parser {
                         default_value: None,
                         sydoc: None,
                         is_syntax,
+                        is_mutable: false,
                     }
                 }
                 Some(Token::SubItemSeparator) => {
@@ -445,7 +660,6 @@ impl Parser {
 
                     let pattern = self.parse_pattern()?;
                     let type_annotation = self.parse_type_reference()?;
-                    self.tokens.discard();
                     ValueParameter {
                         label: None,
                         pattern,
@@ -453,6 +667,7 @@ impl Parser {
                         default_value: None,
                         sydoc: None,
                         is_syntax,
+                        is_mutable: false,
                     }
                 }
                 Some(Token::Colon) => {
@@ -475,6 +690,34 @@ impl Parser {
                         default_value,
                         sydoc,
                         is_syntax,
+                        is_mutable: false,
+                    }
+                }
+                Some(Token::Grouping(Grouping::OpenSquareBracket)) => {
+                    // No pattern continues with type arguments, so it must be
+                    // a basic parameter name and a generic type.
+
+                    let pattern = self.parse_pattern()?;
+                    let type_annotation = self.parse_type_reference()?;
+                    let default_value = if self.next_is(&Token::Colon) {
+                        Some(self.parse_default_value()?)
+                    } else {
+                        None
+                    };
+                    let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
+                        self.tokens.discard();
+                        Some(doc)
+                    } else {
+                        None
+                    };
+                    ValueParameter {
+                        label: None,
+                        pattern,
+                        type_annotation,
+                        default_value,
+                        sydoc,
+                        is_syntax,
+                        is_mutable: false,
                     }
                 }
                 Some(_) => {
@@ -502,6 +745,7 @@ impl Parser {
                         default_value,
                         sydoc,
                         is_syntax,
+                        is_mutable: false,
                     }
                 }
                 None => self.premature_eof()?,
@@ -529,13 +773,106 @@ impl Parser {
                 default_value,
                 sydoc,
                 is_syntax,
+                is_mutable: false,
             }
         })
     }
 
+    /// Parse an expression whose value is used, such as a binding's value or
+    /// an argument. Unlike an expression in statement position, an `if` here
+    /// must yield something on every branch and so needs an `else`.
+    fn parse_value_expression(&mut self) -> Result<nodes::Expression> {
+        let expression = self.parse_operand_expression()?;
+        if self.next_starts_conditional() {
+            self.parse_conditional(expression)
+        } else {
+            Ok(expression)
+        }
+    }
+
+    /// A value expression that can't be a `cond ? a : b` conditional without
+    /// grouping it, so that conditionals bind more loosely than any operator.
+    ///
+    /// An `if` at the end of a block used for its value, including the
+    /// branches of another `if` or a `switch`, is used for its value too.
+    fn parse_operand_expression(&mut self) -> Result<nodes::Expression> {
+        let expression = self.parse_expression()?;
+        self.check_operand(expression)
+    }
+
+    fn check_operand(&mut self, expression: nodes::Expression) -> Result<nodes::Expression> {
+        match &expression {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(_))
+            | Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(_))
+            | Expression::Context(_)
+                if !expression.yields_value_on_every_branch() =>
+            {
+                self.fail("an `if` used for its value must have an `else` branch")
+            }
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::While(_))
+                if !expression.is_bottom_typed(&self.loop_labels) =>
+            {
+                self.fail("a `while` loop used for its value must never finish")
+            }
+            _ => Ok(expression),
+        }
+    }
+
+    /// A `?` is a conditional rather than the postfix bind operator if it's
+    /// followed, on the same line, by something that can start an expression.
+    fn next_starts_conditional(&mut self) -> bool {
+        let (question_mark, next) = match self.tokens.peek_many(2) {
+            Some([question_mark, next]) => (question_mark, next),
+            _ => return false,
+        };
+        let starts_expression = matches!(
+            &next.token,
+            Token::Literal(_)
+                | Token::Identifier(_)
+                | Token::PseudoIdentifier(_)
+                | Token::LambdaArrow
+                | Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade)
+                | Token::Throw
+                | Token::With
+                | Token::Grouping(Grouping::OpenParentheses)
+                | Token::Grouping(Grouping::OpenRecord)
+                | Token::BranchingAndJumping(BranchingAndJumping::For)
+                | Token::BranchingAndJumping(BranchingAndJumping::If)
+                | Token::BranchingAndJumping(BranchingAndJumping::Select)
+                | Token::BranchingAndJumping(BranchingAndJumping::Switch)
+        );
+        (question_mark.token == Token::PostfixOperator(multiphase::PostfixOperator::Bind))
+            && starts_expression
+            && (question_mark.position.line() == next.position.line())
+    }
+
+    /// `cond ? a : b` is sugar for `if cond { a } else { b }`. It nests to the
+    /// right, so `a ? b : c ? d : e` chooses between `b` and `c ? d : e`.
+    fn parse_conditional(&mut self, condition: nodes::Expression) -> Result<nodes::Expression> {
+        self.tokens.discard();
+        let then = self.parse_value_expression()?;
+        self.expect_and_discard(Token::Colon)?;
+        let otherwise = self.parse_value_expression()?;
+
+        let branch = |expression| Block {
+            expressions: vec![expression],
+            bindings: vec![],
+            parent: Some(self.current_scope.clone()),
+            span: None,
+            interleaved: false,
+        };
+        Ok(Expression::BranchingAndJumping(
+            nodes::BranchingAndJumping::If(If {
+                condition: Box::new(condition),
+                then: branch(then),
+                else_clause: Some(branch(otherwise)),
+            }),
+        ))
+    }
+
     fn parse_default_value(&mut self) -> Result<nodes::Expression> {
         self.expect_and_discard(Token::Colon)?;
-        self.parse_expression()
+        self.parse_value_expression()
     }
 
     fn parse_class_parameter_field_upgrade(&mut self) -> Result<nodes::ClassValueParameter> {
@@ -544,11 +881,7 @@ impl Parser {
         let accessibility = self
             .accessibility_modifier_extractor
             .extract_accessibility_modifier(&modifiers)
-            .map_err(|err| {
-                Error::Parser(ParserError {
-                    description: ParserErrorDescription::Described(err),
-                })
-            })?;
+            .or_else(|err| self.fail(err))?;
 
         let field_upgrade = Some(ClassValueParameterFieldUpgrade { accessibility });
 
@@ -560,7 +893,9 @@ impl Parser {
         Ok(parameter)
     }
 
-    fn parse_implements_clause(&mut self) -> Result<Vec<TypeReference>> {
+    /// Parse a keyword followed by comma-separated types, such as an
+    /// `implements` or `embeds` clause.
+    fn parse_type_list_clause(&mut self) -> Result<Vec<TypeReference>> {
         self.tokens.discard();
 
         let mut type_symbols = vec![];
@@ -574,10 +909,42 @@ impl Parser {
         }
     }
 
+    /// Parse the fields and methods of a class. Fields are bindings, so
+    /// associated constants are just `final` fields whose values aren't
+    /// lambdas.
     fn parse_class_body(
         &mut self,
     ) -> Result<(Vec<nodes::Field>, Vec<nodes::ConcreteMethod>, Block)> {
-        todo!()
+        let mut fields = vec![];
+        let mut methods = vec![];
+        let ((), span) = self.spanned(|parser| {
+            parser.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+            loop {
+                match parser.peek() {
+                    Some(Token::Grouping(Grouping::CloseBrace)) => {
+                        parser.tokens.discard();
+                        break Ok(());
+                    }
+                    Some(Token::DeclarationHead(DeclarationHead::Fun)) => {
+                        methods.push(parser.parse_method()?);
+                    }
+                    Some(Token::DeclarationHead(DeclarationHead::Extern)) => {
+                        parser.tokens.discard();
+                        fields.push(parser.parse_field(true)?);
+                    }
+                    Some(Token::Binding(Binding::Final)) | Some(Token::Binding(Binding::Var)) => {
+                        fields.push(parser.parse_field(false)?);
+                    }
+                    _ => break parser.fail("expected a field or a method in the class body"),
+                }
+            }
+        })?;
+
+        let initialiser = Block {
+            span,
+            ..Block::new_root()
+        };
+        Ok((fields, methods, initialiser))
     }
 
     fn parse_with(&mut self) -> Result<nodes::Expression> {
@@ -586,27 +953,245 @@ impl Parser {
         Ok(Expression::Context(scope))
     }
 
-    fn parse_extension(&mut self) -> Result<nodes::Extension> {
+    /// `spawn` is only a keyword directly before a lambda without parameters,
+    /// so it can still name things elsewhere, such as the `spawn` function in
+    /// `sylan.core.lang`. Cascading into a block would otherwise be invalid, so
+    /// there's no ambiguity.
+    fn next_starts_spawn(&mut self) -> bool {
+        let is_arrow = self.nth_is(1, &Token::LambdaArrow)
+            || self.nth_is(
+                1,
+                &Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade),
+            );
+        is_arrow && self.nth_is(2, &Token::Grouping(Grouping::OpenBrace))
+    }
+
+    fn parse_spawn(&mut self) -> Result<nodes::Spawn> {
         self.tokens.discard();
-        unimplemented!();
+        let lambda = self.parse_lambda()?;
+        Ok(nodes::Spawn { task: lambda.block })
     }
 
-    fn parse_for(&mut self) -> Result<nodes::For> {
+    fn parse_labelled_block(&mut self) -> Result<nodes::LabelledBlock> {
+        let label = self.parse_identifier()?;
+        self.expect_and_discard(Token::Colon)?;
+        self.block_labels.push(label.clone());
+        let scope = self.parse_block();
+        self.block_labels.pop();
+        Ok(nodes::LabelledBlock {
+            label,
+            scope: scope?,
+        })
+    }
+
+    fn parse_break(&mut self) -> Result<nodes::Break> {
         self.tokens.discard();
+        let label = self.parse_identifier()?;
+        if !self.block_labels.contains(&label) {
+            return self.fail(format!(
+                "`break {}` isn't within a block labelled `{}`",
+                label.0, label.0
+            ));
+        }
+        let value = match self.peek() {
+            None | Some(Token::Grouping(Grouping::CloseBrace)) => None,
+            Some(_) => Some(Box::new(self.parse_value_expression()?)),
+        };
+        Ok(nodes::Break { label, value })
+    }
+
+    fn parse_extension(&mut self) -> Result<nodes::Extension> {
+        self.tokens.discard();
+
+        let extension_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+            self.parse_type_parameter_list()?
+        } else {
+            vec![]
+        };
+        let symbol = self.parse_symbol()?;
+        let type_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+            self.parse_type_parameter_list()?
+        } else {
+            vec![]
+        };
+        let implements = if self.next_is(&Token::Colon) {
+            self.parse_type_list_clause()?
+        } else {
+            vec![]
+        };
 
-        let reiteration_symbol = if self.next_is(&Token::Binding(tokens::Binding::Var)) {
+        let mut methods = vec![];
+        if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
             self.tokens.discard();
-            None
-        } else if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
-            None
+            while !self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
+                methods.push(self.parse_method()?);
+            }
+            self.tokens.discard();
+        }
+
+        Ok(nodes::Extension {
+            symbol,
+            extension_parameters,
+            type_parameters,
+            item: Class {
+                implements,
+                embeds: vec![],
+                methods,
+                fields: vec![],
+                value_parameters: vec![],
+                instance_initialiser: Block::new_root(),
+            },
+            sydoc: None,
+        })
+    }
+
+    /// The lexer can't tell an arrow from a cascade, so, as with lambdas,
+    /// either token introduces an arrow return type.
+    fn next_is_arrow(&mut self) -> bool {
+        self.next_is(&Token::LambdaArrow)
+            || self.next_is(&Token::OverloadableInfixOperator(
+                OverloadableInfixOperator::Cascade,
+            ))
+    }
+
+    fn next_ends_method_signature(&mut self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(Token::Eof)
+                | Some(Token::Grouping(Grouping::OpenBrace))
+                | Some(Token::Grouping(Grouping::CloseBrace))
+                | Some(Token::DeclarationHead(DeclarationHead::Fun))
+        )
+    }
+
+    /// Parse the return type written after a fun's parameters, either
+    /// juxtaposed as in `fun f() Int` or after an arrow as in `fun f() -> Int`,
+    /// matching lambdas. A fun can use either form but not both.
+    fn parse_fun_return_type(&mut self, ignorable: bool) -> Result<Option<nodes::ReturnType>> {
+        let r#type = if self.next_is_arrow() {
+            self.tokens.discard();
+            self.parse_type_reference()?
+        } else if self.next_ends_method_signature() {
+            return Ok(None);
+        } else {
+            let r#type = self.parse_type_reference()?;
+            if self.next_is_arrow() {
+                self.fail(
+                    "a return type goes either straight after the parameters or after an arrow, \
+                     not both",
+                )?;
+            }
+            r#type
+        };
+        Ok(Some(nodes::ReturnType { r#type, ignorable }))
+    }
+
+    /// Parse a method's signature, leaving any body to the caller. Unlike a
+    /// top-level `fun`, a method is followed by either its body, the next
+    /// method, or the end of its type, so anything else after its parameters
+    /// must be its return type.
+    fn parse_method_signature(&mut self) -> Result<nodes::AbstractMethod> {
+        self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Fun))?;
+        let modifiers = self.parse_modifiers(&self.modifier_sets.method.clone())?;
+        let name = self.parse_identifier()?;
+
+        let type_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+            self.parse_type_parameter_list()?
         } else {
+            vec![]
+        };
+
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
+        let value_parameters = self.parse_fun_value_parameter_list()?;
+
+        let return_type = self.parse_fun_return_type(modifiers.contains(&Modifier::Ignorable))?;
+
+        let accessibility = self
+            .accessibility_modifier_extractor
+            .extract_accessibility_modifier(&modifiers)
+            .or_else(|msg| self.fail(msg))?;
+
+        Ok(nodes::AbstractMethod {
+            modifiers: nodes::MethodModifiers {
+                fun_modifiers: FunModifiers {
+                    accessibility,
+                    r#extern: None,
+                    is_operator: modifiers.contains(&Modifier::Operator),
+                },
+                overrides: modifiers.contains(&Modifier::Override),
+            },
+            signature: FunSignature {
+                name,
+                sydoc: None,
+                type_parameters,
+                value_parameters,
+                return_type,
+            },
+        })
+    }
+
+    /// Parse the body following a method's signature, in which its parameters
+    /// are in scope.
+    fn parse_method_body(
+        &mut self,
+        r#abstract: nodes::AbstractMethod,
+    ) -> Result<nodes::ConcreteMethod> {
+        let outer_names = self.local_names.len();
+        for parameter in &r#abstract.signature.value_parameters {
+            self.local_names.extend(parameter.pattern.bound_names());
+        }
+        let scope = self.parse_block();
+        self.local_names.truncate(outer_names);
+        Ok(nodes::ConcreteMethod {
+            r#abstract,
+            scope: scope?,
+        })
+    }
+
+    /// Parse a method with a body. Only interfaces can leave a method's body
+    /// out, requiring their implementations to provide it.
+    fn parse_method(&mut self) -> Result<nodes::ConcreteMethod> {
+        let signature = self.parse_method_signature()?;
+        if !self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+            self.fail(format!(
+                "`{}` has no body, but only interfaces can declare methods without one",
+                signature.signature.name.0
+            ))?;
+        }
+        self.parse_method_body(signature)
+    }
+
+    fn parse_for(&mut self) -> Result<nodes::For> {
+        self.tokens.discard();
+
+        // A label comes straight before the `var` or the body; anything else
+        // starts the bindings, which can leave out their `var`.
+        let is_labelled = matches!(self.peek(), Some(Token::Identifier(_)))
+            && (self.nth_is(1, &Token::Binding(tokens::Binding::Var))
+                || self.nth_is(1, &Token::Grouping(Grouping::OpenBrace)));
+        let reiteration_symbol = if is_labelled {
             Some(self.parse_identifier()?)
+        } else {
+            None
         };
+        if self.next_is(&Token::Binding(tokens::Binding::Var)) {
+            self.tokens.discard();
+        }
 
+        let outer_names = self.local_names.len();
+        let outer_labels = self.loop_labels.len();
+        let outer_block_labels = self.block_labels.len();
+        self.loop_labels.extend(reiteration_symbol.clone());
+        self.block_labels.extend(reiteration_symbol.clone());
         let mut bindings = vec![];
+        let mut yield_expression = None;
         let scope = loop {
             if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
                 break self.parse_block()?;
+            } else if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::Yield)) {
+                self.tokens.discard();
+                yield_expression = Some(Box::new(self.parse_value_expression()?));
+                break self.parse_block()?;
             } else {
                 bindings.push(self.parse_local_binding()?);
                 if self.next_is(&Token::SubItemSeparator) {
@@ -615,17 +1200,34 @@ impl Parser {
             }
         };
 
+        self.local_names.truncate(outer_names);
+        self.loop_labels.truncate(outer_labels);
+        self.block_labels.truncate(outer_block_labels);
+
+        let else_clause = if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::Else)) {
+            if yield_expression.is_some() {
+                return self
+                    .fail("a comprehension always yields its list, so can't have an `else`");
+            }
+            self.tokens.discard();
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+
         Ok(For {
             bindings,
             scope,
             reiteration_symbol,
+            yield_expression,
+            else_clause,
         })
     }
 
     fn parse_if(&mut self) -> Result<nodes::If> {
         self.tokens.discard();
 
-        let condition = self.parse_expression()?;
+        let condition = self.parse_value_expression()?;
         let then = self.parse_block()?;
 
         let else_clause = if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::Else)) {
@@ -641,6 +1243,8 @@ impl Parser {
                         )],
                         bindings: vec![],
                         parent: Some(self.current_scope.clone()),
+                        span: None,
+                        interleaved: false,
                     }
                 } else {
                     self.parse_block()?
@@ -657,6 +1261,32 @@ impl Parser {
         })
     }
 
+    /// Parse either a `while` or a `while var`, which rebinds its pattern
+    /// before each iteration and stops once the pattern no longer matches.
+    /// Its bindings are only in scope within its body.
+    fn parse_while(&mut self) -> Result<nodes::BranchingAndJumping> {
+        self.tokens.discard();
+        if self.next_is(&Token::Binding(Binding::Var)) {
+            self.tokens.discard();
+            let outer_names = self.local_names.len();
+            let binding = self.parse_local_binding();
+            let scope = binding.and_then(|binding| Ok((binding, self.parse_block()?)));
+            self.local_names.truncate(outer_names);
+            let (binding, scope) = scope?;
+            Ok(nodes::BranchingAndJumping::WhileVar(WhileVar {
+                binding: Box::new(binding),
+                scope,
+            }))
+        } else {
+            let condition = self.parse_value_expression()?;
+            let scope = self.parse_block()?;
+            Ok(nodes::BranchingAndJumping::While(While {
+                condition: Box::new(condition),
+                scope,
+            }))
+        }
+    }
+
     fn parse_type_reference(&mut self) -> Result<nodes::TypeReference> {
         let symbol = self.parse_symbol()?;
         let type_arguments = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
@@ -670,34 +1300,44 @@ impl Parser {
         })
     }
 
-    fn parse_composite_pattern_getter(&mut self, next: &Token) -> Result<Option<PatternGetter>> {
+    fn parse_composite_pattern_getter(&mut self, next: &Token) -> Result<PatternGetter> {
         let second_token_is_colon = self.nth_is(1, &Token::Colon);
 
         match &next {
-            Token::Rest => {
-                self.tokens.discard();
-                self.expect(Token::Grouping(Grouping::CloseParentheses))?;
-                Ok(None)
-            }
-
-            Token::Identifier(ref identifier) if !second_token_is_colon => {
+            Token::Identifier(ref identifier) if !second_token_is_colon => {
                 self.tokens.discard();
                 let pattern = Pattern {
                     item: PatternItem::Identifier(identifier.clone()),
                     bound_match: None,
                 };
-                Ok(Some(PatternGetter {
+                Ok(PatternGetter {
                     name: identifier.clone(),
                     pattern,
-                }))
+                })
             }
 
             _ => {
                 let name = self.parse_identifier()?;
                 self.expect_and_discard(Token::Colon)?;
                 let pattern = self.parse_pattern()?;
-                Ok(Some(PatternGetter { name, pattern }))
+                Ok(PatternGetter { name, pattern })
+            }
+        }
+    }
+
+    /// Parse the rest of a pattern after its `..`, yielding the name it's
+    /// bound to. A bare `..` or `.._` ignores the rest instead.
+    fn parse_rest_binding(&mut self) -> Option<Identifier> {
+        match self.peek() {
+            Some(Token::Identifier(identifier)) => {
+                self.tokens.discard();
+                Some(identifier)
             }
+            Some(Token::PseudoIdentifier(PseudoIdentifier::PlaceholderIdentifier)) => {
+                self.tokens.discard();
+                None
+            }
+            _ => None,
         }
     }
 
@@ -719,6 +1359,7 @@ impl Parser {
             self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
 
             let mut getters = vec![];
+            let mut rest_binding = None;
             let ignore_rest = loop {
                 let next = self
                     .tokens
@@ -728,13 +1369,20 @@ impl Parser {
 
                 if next == Token::Grouping(Grouping::CloseParentheses) {
                     break false;
-                } else if let Some(getter) = self.parse_composite_pattern_getter(&next)? {
-                    getters.push(getter);
-                } else {
+                } else if next == Token::Rest {
+                    self.tokens.discard();
+                    rest_binding = self.parse_rest_binding();
+                    self.expect(Token::Grouping(Grouping::CloseParentheses))?;
                     break true;
+                } else {
+                    getters.push(self.parse_composite_pattern_getter(&next)?);
                 }
 
-                self.expect_and_discard(Token::SubItemSeparator)?;
+                if self.next_is(&Token::SubItemSeparator) {
+                    self.tokens.discard();
+                } else {
+                    self.expect(Token::Grouping(Grouping::CloseParentheses))?;
+                }
             };
 
             self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
@@ -743,6 +1391,7 @@ impl Parser {
                 r#type,
                 getters,
                 ignore_rest,
+                rest_binding,
                 infer_enum_type,
             };
             Ok(composite)
@@ -760,10 +1409,25 @@ impl Parser {
 
         let item = self
             .parse_literal(token.clone())
-            .map(|lexed_token| Ok(PatternItem::Literal(lexed_token)))
+            .map(|lexed_token| {
+                self.tokens.discard();
+                let literal = self.concatenate_adjacent_strings(lexed_token);
+                if self.next_is(&Token::Rest) {
+                    self.parse_range_pattern(literal)
+                } else {
+                    Ok(PatternItem::Literal(literal))
+                }
+            })
             .unwrap_or_else(|| match token {
-                Token::Identifier(identifier) => Ok(PatternItem::Identifier(identifier)),
+                Token::Identifier(identifier)
+                    if !self.nth_is(1, &Token::Grouping(Grouping::OpenParentheses))
+                        && !self.nth_is(1, &Token::Dot) =>
+                {
+                    self.tokens.discard();
+                    Ok(PatternItem::Identifier(identifier))
+                }
                 Token::PseudoIdentifier(PseudoIdentifier::PlaceholderIdentifier) => {
+                    self.tokens.discard();
                     Ok(PatternItem::Ignored)
                 }
                 Token::Rest => {
@@ -771,16 +1435,104 @@ impl Parser {
                     let symbol = self.parse_symbol()?;
                     Ok(PatternItem::BoundSymbol(symbol))
                 }
+                Token::Grouping(Grouping::OpenParentheses) => self.parse_tuple_pattern(),
+                Token::Grouping(Grouping::OpenSquareBracket) => self.parse_slice_pattern(),
                 _ => {
                     let composite = self.parse_composite_pattern()?;
                     Ok(PatternItem::Composite(composite))
                 }
             });
+        let item = item?;
 
-        Ok(Pattern {
-            item: item?,
-            bound_match: None,
-        })
+        // `as` also matches the whole value, as in `Circle(r) as c`, binding
+        // both `r` and `c`.
+        let bound_match = if self.next_is(&Token::Binding(Binding::As)) {
+            self.tokens.discard();
+            let bound_match = self.parse_pattern()?;
+            if !bound_match.is_irrefutable() {
+                return self.fail("the pattern after `as` must match any value");
+            }
+            Some(Box::new(bound_match))
+        } else {
+            None
+        };
+
+        Ok(Pattern { item, bound_match })
+    }
+
+    fn parse_range_pattern(&mut self, start: nodes::Literal) -> Result<PatternItem> {
+        self.tokens.discard();
+        let end = match self.read() {
+            Some(token) => self.parse_literal(token),
+            None => self.premature_eof()?,
+        };
+        match (start, end) {
+            (start @ nodes::Literal::Number(..), Some(end @ nodes::Literal::Number(..)))
+            | (start @ nodes::Literal::Char(_), Some(end @ nodes::Literal::Char(_))) => {
+                Ok(PatternItem::Range(nodes::RangePattern { start, end }))
+            }
+            _ => self.fail("a range must be between two numbers or two chars"),
+        }
+    }
+
+    fn parse_tuple_pattern(&mut self) -> Result<PatternItem> {
+        self.tokens.discard();
+        let mut elements = vec![];
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                self.tokens.discard();
+                break Ok(PatternItem::Tuple(elements));
+            }
+            elements.push(self.parse_pattern()?);
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            } else {
+                self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
+                break Ok(PatternItem::Tuple(elements));
+            }
+        }
+    }
+
+    fn parse_slice_pattern(&mut self) -> Result<PatternItem> {
+        self.tokens.discard();
+        let mut leading = vec![];
+        let mut rest = None;
+        let mut trailing = vec![];
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseSquareBracket)) {
+                self.tokens.discard();
+                break;
+            }
+            if self.next_is(&Token::Rest) {
+                self.tokens.discard();
+                if rest.is_some() {
+                    self.fail("a slice pattern can only have one rest")?;
+                }
+                let item = match self.parse_rest_binding() {
+                    Some(name) => PatternItem::Identifier(name),
+                    None => PatternItem::Ignored,
+                };
+                rest = Some(Box::new(Pattern {
+                    item,
+                    bound_match: None,
+                }));
+            } else if rest.is_some() {
+                trailing.push(self.parse_pattern()?);
+            } else {
+                leading.push(self.parse_pattern()?);
+            }
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            } else {
+                self.expect_and_discard(Token::Grouping(Grouping::CloseSquareBracket))?;
+                break;
+            }
+        }
+        Ok(PatternItem::Slice(SlicePattern {
+            leading,
+            rest,
+            trailing,
+        }))
     }
 
     fn parse_value_argument_list(&mut self) -> Result<Vec<ValueArgument>> {
@@ -806,12 +1558,33 @@ impl Parser {
                 None
             };
 
-            let expression = self.parse_expression()?;
+            let is_spread = self.next_is(&Token::Rest);
+            if is_spread {
+                if label.is_some() {
+                    self.fail("spread arguments cannot be labelled")?;
+                }
+                self.tokens.discard();
+            }
+
+            let expression = self.parse_value_expression()?;
             let argument = ValueArgument {
                 label,
                 value: expression,
+                is_spread,
             };
-            arguments.push(argument)
+            arguments.push(argument);
+
+            // Spreads expand into every remaining positional parameter, so
+            // nothing can follow them.
+            if is_spread && !self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                self.fail("a spread argument must be the last argument")?;
+            }
+
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            } else {
+                self.expect(Token::Grouping(Grouping::CloseParentheses))?;
+            }
         }
     }
 
@@ -838,12 +1611,19 @@ impl Parser {
                 None
             };
 
-            let type_reference = self.parse_type_reference()?;
+            let value = self.parse_type_expression()?;
             let argument = TypeArgument {
                 label,
-                value: type_reference,
+                value,
+                is_spread: false,
             };
-            arguments.push(argument)
+            arguments.push(argument);
+
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            } else {
+                self.expect(Token::Grouping(Grouping::CloseSquareBracket))?;
+            }
         }
     }
 
@@ -857,8 +1637,13 @@ impl Parser {
     fn parse_inside_import_stems(&mut self) -> Result<Vec<nodes::Import>> {
         let mut imports = vec![];
         loop {
+            let is_reexported = self.next_is(&Token::ModuleDefinitions(ModuleDefinitions::Exports));
+            if is_reexported {
+                self.tokens.discard();
+            }
+
             let mut whole: Vec<Identifier> = vec![];
-            let readers = loop {
+            loop {
                 match self.peek() {
                     Some(Token::Identifier(identifier)) => {
                         self.tokens.discard();
@@ -867,27 +1652,79 @@ impl Parser {
                     Some(Token::Dot) => {
                         self.tokens.discard();
                     }
-                    Some(Token::With) => break self.parse_import_readers_list()?,
-                    _ => break vec![],
+                    _ => break,
                 }
-            };
+            }
 
-            let (root, stem) = if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+            let glob = OverloadableInfixOperator::Multiply;
+            let (root, stem) = if self.next_is(&Token::OverloadableInfixOperator(glob)) {
                 self.tokens.discard();
-                let stems = self.parse_inside_import_stems()?;
-                self.expect_and_discard(Token::Grouping(Grouping::CloseBrace))?;
-                let stem = nodes::ImportStem::Multiple(stems);
-                (None, stem)
-            } else {
-                let stem = nodes::ImportStem::Single(nodes::ImportSingleStem {
-                    name: whole.pop().unwrap(),
-                    readers,
-                });
+                if whole.is_empty() {
+                    self.fail("a glob import must name the package it imports from")?;
+                }
+                if self.next_is(&Token::Binding(Binding::As)) {
+                    self.fail("glob imports can't be renamed; rename the clashing items instead")?;
+                }
+                if is_reexported {
+                    self.fail(
+                        "glob imports can't be re-exported; list the items to re-export instead",
+                    )?;
+                }
                 let root = Symbol::Relative(SymbolLookup(whole));
-                (Some(root), stem)
+                (Some(root), nodes::ImportStem::Glob)
+            } else {
+                let alias = if self.next_is(&Token::Binding(Binding::As)) {
+                    self.tokens.discard();
+                    let overrides_builtin = self.next_is(&Token::Modifier(Modifier::Override));
+                    if overrides_builtin {
+                        self.tokens.discard();
+                    }
+                    Some((self.parse_identifier()?, overrides_builtin))
+                } else {
+                    None
+                };
+
+                let readers = if self.next_is(&Token::With) {
+                    self.parse_import_readers_list()?
+                } else {
+                    vec![]
+                };
+
+                if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+                    if alias.is_some() {
+                        self.fail("only single imports can be renamed; rename each item instead")?;
+                    }
+                    self.tokens.discard();
+                    let stems = self.parse_inside_import_stems()?;
+                    self.expect_and_discard(Token::Grouping(Grouping::CloseBrace))?;
+                    let stem = nodes::ImportStem::Multiple(stems);
+                    let root = if whole.is_empty() {
+                        None
+                    } else {
+                        Some(Symbol::Relative(SymbolLookup(whole)))
+                    };
+                    (root, stem)
+                } else {
+                    let name = match whole.pop() {
+                        Some(name) => name,
+                        None => self.fail("identifier expected")?,
+                    };
+                    let stem = nodes::ImportStem::Single(nodes::ImportSingleStem {
+                        name,
+                        overrides_builtin: matches!(alias, Some((_, true))),
+                        alias: alias.map(|(alias, _)| alias),
+                        readers,
+                    });
+                    let root = Symbol::Relative(SymbolLookup(whole));
+                    (Some(root), stem)
+                }
             };
 
-            let import = nodes::Import { root, stem };
+            let import = nodes::Import {
+                root,
+                stem,
+                is_reexported,
+            };
             imports.push(import);
 
             if self.next_is(&Token::SubItemSeparator) {
@@ -915,30 +1752,147 @@ impl Parser {
         }
     }
 
+    /// Parse an interface's methods. Those written as only a signature are
+    /// abstract, to be implemented by the interface's implementations, whereas
+    /// those with bodies are defaults.
     fn parse_interface_body(&mut self) -> Result<Vec<Method>> {
-        unimplemented!()
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+
+        let mut methods = vec![];
+        loop {
+            match self.peek() {
+                Some(Token::Grouping(Grouping::CloseBrace)) => {
+                    self.tokens.discard();
+                    break Ok(methods);
+                }
+                Some(Token::DeclarationHead(DeclarationHead::Fun)) => {
+                    let signature = self.parse_method_signature()?;
+                    methods.push(if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+                        Method::Concrete(self.parse_method_body(signature)?)
+                    } else {
+                        Method::Abstract(signature)
+                    });
+                }
+                _ => break self.fail("expected a method in the interface body"),
+            }
+        }
     }
 
     fn parse_interface_definition(&mut self) -> Result<nodes::Type> {
-        unimplemented!()
+        self.tokens.discard();
+
+        let name = self.parse_identifier()?;
+        let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
+            self.tokens.discard();
+            Some(doc)
+        } else {
+            None
+        };
+
+        let type_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+            self.parse_type_parameter_list()?
+        } else {
+            vec![]
+        };
+
+        let extends = if self.next_is(&Token::Extends) {
+            self.parse_type_list_clause()?
+        } else {
+            vec![]
+        };
+
+        let methods = if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+            self.parse_interface_body()?
+        } else {
+            vec![]
+        };
+
+        Ok(nodes::Type {
+            name,
+            type_parameters,
+            item: nodes::TypeItem::Interface(nodes::Interface { extends, methods }),
+            sydoc,
+        })
     }
 
-    fn parse_type_constraints(&mut self) -> Result<Vec<TypeReference>> {
+    fn parse_type_constraints(&mut self) -> Result<TypeExpression> {
         self.tokens.discard();
+        self.parse_type_expression()
+    }
 
-        let mut constraints = vec![];
+    /// Parse a type, including unions and intersections of types. Each
+    /// operator's operands are collected into one node, with intersections
+    /// binding tighter than unions.
+    fn parse_type_expression(&mut self) -> Result<TypeExpression> {
+        self.parse_type_operands(OverloadableInfixOperator::BitwiseOr)
+    }
+
+    fn parse_type_operands(
+        &mut self,
+        operator: OverloadableInfixOperator,
+    ) -> Result<TypeExpression> {
+        let mut operands = vec![];
         loop {
-            constraints.push(self.parse_type_reference()?);
-            if self.next_is(&Token::OverloadableInfixOperator(
-                OverloadableInfixOperator::Ampersand,
-            )) {
-                self.expect_and_discard(Token::OverloadableInfixOperator(
-                    OverloadableInfixOperator::Ampersand,
-                ))?;
+            operands.push(match operator {
+                OverloadableInfixOperator::BitwiseOr => {
+                    self.parse_type_operands(OverloadableInfixOperator::Ampersand)?
+                }
+                _ => self.parse_type_operand()?,
+            });
+            if self.next_is(&Token::OverloadableInfixOperator(operator.clone())) {
+                self.tokens.discard();
             } else {
-                break Ok(constraints);
+                break;
             }
         }
+
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else if operator == OverloadableInfixOperator::BitwiseOr {
+            TypeExpression::Union(operands)
+        } else {
+            TypeExpression::Intersection(operands)
+        })
+    }
+
+    fn parse_type_operand(&mut self) -> Result<TypeExpression> {
+        let operand = if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
+            self.tokens.discard();
+            let mut types = vec![];
+            while !self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                types.push(self.parse_type_expression()?);
+                if self.next_is(&Token::SubItemSeparator) {
+                    self.tokens.discard();
+                } else {
+                    break;
+                }
+            }
+            self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
+
+            if self.next_is_arrow() {
+                self.tokens.discard();
+                let result = self.parse_type_expression()?;
+                return Ok(TypeExpression::Function(types, Box::new(result)));
+            } else if types.len() == 1 {
+                types.pop().unwrap()
+            } else {
+                self.fail(
+                    "parentheses around types either group a single type or list a function \
+                     type's parameters before its `->`",
+                )?
+            }
+        } else {
+            TypeExpression::Reference(self.parse_type_reference()?)
+        };
+
+        Ok(
+            if self.next_is(&Token::PostfixOperator(multiphase::PostfixOperator::Bind)) {
+                self.tokens.discard();
+                TypeExpression::Optional(Box::new(operand))
+            } else {
+                operand
+            },
+        )
     }
 
     fn parse_type_parameter_list(&mut self) -> Result<Vec<TypeParameter>> {
@@ -953,15 +1907,15 @@ impl Parser {
                     (None, identifier)
                 };
 
-                let upper_bounds = if self.next_is(&Token::Extends) {
-                    self.parse_type_constraints()?
+                let upper_bound = if self.next_is(&Token::Extends) {
+                    Some(self.parse_type_constraints()?)
                 } else {
-                    vec![]
+                    None
                 };
 
                 let default_value = if self.next_is(&Token::Colon) {
-                    self.expect_and_discard(Token::Binding(Binding::Assign))?;
-                    Some(self.parse_type_reference()?)
+                    self.tokens.discard();
+                    Some(self.parse_type_expression()?)
                 } else {
                     None
                 };
@@ -976,7 +1930,7 @@ impl Parser {
                 list.push(TypeParameter {
                     label,
                     name,
-                    upper_bounds,
+                    upper_bound,
                     default_value,
                     sydoc,
                 });
@@ -1002,12 +1956,13 @@ impl Parser {
                 break Ok(parameters);
             }
 
-            let parameter = self.parse_value_parameter()?;
-            parameters.push(parameter);
-
-            if self.next_is(&Token::SubItemSeparator) {
+            let is_mutable = self.next_is(&Token::Binding(Binding::Var));
+            if is_mutable {
                 self.tokens.discard();
             }
+            let mut parameter = self.parse_value_parameter()?;
+            parameter.is_mutable = is_mutable;
+            parameters.push(parameter);
 
             match self.peek() {
                 Some(Token::SubItemSeparator) => {
@@ -1031,6 +1986,11 @@ impl Parser {
                 break Ok(parameters);
             }
 
+            let is_mutable = self.next_is(&Token::Binding(Binding::Var));
+            if is_mutable {
+                self.tokens.discard();
+            }
+
             let label = if self.match_nth(1, |t| matches!(t, Token::Identifier(..))) {
                 let label = match self.read() {
                     Some(Token::Identifier(identifier)) => identifier,
@@ -1054,6 +2014,7 @@ impl Parser {
                 label,
                 pattern,
                 default_value,
+                is_mutable,
             };
 
             parameters.push(parameter);
@@ -1082,14 +2043,55 @@ impl Parser {
     /// subexpresion. This is avoid the ambiguity between a lambda literal and the shorthand for
     /// passing a lambda as a final argument, specifically when that shorthand is on a new line.
     fn parse_lambda(&mut self) -> Result<nodes::Lambda> {
-        self.expect_and_discard(Token::LambdaArrow)?;
+        match self.read() {
+            Some(Token::LambdaArrow)
+            | Some(Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade)) => {}
+            Some(unexpected) => self.unexpected(unexpected)?,
+            None => self.premature_eof()?,
+        }
+        let outer_names = self.local_names.len();
+        let outer_block_labels = std::mem::take(&mut self.block_labels);
         let signature = self.parse_lambda_signature()?;
-        let block = self.parse_block()?;
+        for parameter in &signature.value_parameters {
+            self.local_names.extend(parameter.pattern.bound_names());
+        }
+        let block = self.parse_block();
+        self.local_names.truncate(outer_names);
+        self.block_labels = outer_block_labels;
+        let block = block?;
 
         Ok(Lambda { signature, block })
     }
 
-    fn parse_fun(&mut self) -> Result<nodes::Fun> {
+    /// Parse an `extern` fun, which is defined elsewhere and so has no body. A
+    /// string naming its calling convention can follow the `extern`, as in
+    /// `extern "C" fun`.
+    fn parse_extern_fun(&mut self) -> Result<nodes::Fun> {
+        self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Extern))?;
+        let calling_convention = match self.peek() {
+            Some(Token::Literal(Literal::String(name))) => {
+                self.tokens.discard();
+                match CallingConvention::from_name(&name.0) {
+                    Some(calling_convention) => calling_convention,
+                    None => {
+                        let known = CallingConvention::ALL
+                            .iter()
+                            .map(|convention| format!("\"{}\"", convention.name()))
+                            .collect::<Vec<_>>()
+                            .join(" or ");
+                        return self.fail(format!(
+                            "unknown calling convention \"{}\"; expected {}",
+                            name.0, known
+                        ));
+                    }
+                }
+            }
+            _ => CallingConvention::default(),
+        };
+        self.parse_fun(Some(calling_convention))
+    }
+
+    fn parse_fun(&mut self, r#extern: Option<CallingConvention>) -> Result<nodes::Fun> {
         self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Fun))?;
         let modifiers = self.parse_modifiers(&self.modifier_sets.function.clone())?;
         let name = self.parse_identifier()?;
@@ -1100,30 +2102,38 @@ impl Parser {
             vec![]
         };
 
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
         let value_parameters = self.parse_fun_value_parameter_list()?;
 
-        // TODO: resolve the parsing ambiguity between:
-        //
-        // * Extern void functions that drop return types, with a symbol on the
-        //   next line in the main package.
-        // * Extern non-void functions that state a return type in the main
-        //   package.
-        let return_type = todo!();
+        // An extern fun has no body to end its signature, so in the main
+        // package a `Void` one could be followed by an expression that looks
+        // like a juxtaposed return type. Its return type must therefore start
+        // on the same line as its parameters end, unless an arrow introduces
+        // it.
+        let ends_line = match (self.tokens.last_position(), self.tokens.current_position()) {
+            (Some(last), Some(next)) => last.line() != next.line(),
+            _ => true,
+        };
+        let return_type = if r#extern.is_some() && !self.next_is_arrow() && ends_line {
+            None
+        } else {
+            self.parse_fun_return_type(modifiers.contains(&Modifier::Ignorable))?
+        };
 
-        let block = self.parse_block()?;
+        let block = if r#extern.is_some() {
+            Block::new_root()
+        } else {
+            self.parse_block()?
+        };
 
         let accessibility = self
             .accessibility_modifier_extractor
             .extract_accessibility_modifier(&modifiers)
-            .map_err(|msg| {
-                Error::Parser(ParserError {
-                    description: ParserErrorDescription::Described(msg),
-                })
-            })?;
+            .or_else(|msg| self.fail(msg))?;
 
         let modifiers = FunModifiers {
             accessibility,
-            is_extern: todo!(),
+            r#extern,
             is_operator: modifiers.contains(&Modifier::Operator),
         };
 
@@ -1166,8 +2176,28 @@ impl Parser {
     }
 
     fn parse_local_var_binding(&mut self) -> Result<nodes::Binding> {
-        self.tokens.discard();
-        self.parse_local_binding()
+        let ((binding, span), comments) = self.with_comments(|parser| {
+            parser.spanned(|parser| {
+                parser.tokens.discard();
+                parser.parse_local_binding()
+            })
+        })?;
+        Ok(nodes::Binding {
+            comments,
+            sydoc: self.parse_trailing_sydoc(),
+            span,
+            ..binding
+        })
+    }
+
+    fn parse_trailing_sydoc(&mut self) -> Option<multiphase::SyDoc> {
+        match self.peek() {
+            Some(Token::SyDoc(sydoc)) => {
+                self.tokens.discard();
+                Some(sydoc)
+            }
+            _ => None,
+        }
     }
 
     fn parse_local_binding(&mut self) -> Result<nodes::Binding> {
@@ -1175,16 +2205,43 @@ impl Parser {
 
         self.expect_and_discard(Token::Binding(Binding::Assign))?;
 
-        let value = self.parse_expression()?;
+        let value = self.parse_value_expression()?;
+        self.local_names.extend(pattern.bound_names());
 
         Ok(nodes::Binding {
             pattern,
             value: Box::new(value),
             explicit_type_annotation: None,
+            comments: Default::default(),
+            sydoc: None,
+            span: None,
         })
     }
 
     fn parse_binding(&mut self) -> Result<nodes::Binding> {
+        let ((binding, span), comments) =
+            self.with_comments(|parser| parser.spanned(Self::parse_uncommented_binding))?;
+        Ok(nodes::Binding {
+            comments,
+            sydoc: self.parse_trailing_sydoc(),
+            span,
+            ..binding
+        })
+    }
+
+    fn parse_const_binding(&mut self) -> Result<nodes::Binding> {
+        let binding = self.parse_binding()?;
+        if !binding.value.is_constant(&self.constants) {
+            self.fail(
+                "a const must be bound to a constant expression, such as a literal or an \
+                 operation on other constants",
+            )?;
+        }
+        self.constants.extend(binding.pattern.bound_names());
+        Ok(binding)
+    }
+
+    fn parse_uncommented_binding(&mut self) -> Result<nodes::Binding> {
         self.tokens.discard();
         let declaration_modifiers = self.parse_modifiers(&self.modifier_sets.binding.clone())?;
 
@@ -1193,51 +2250,57 @@ impl Parser {
         let explicit_type_annotation = if self.next_is(&Token::Binding(Binding::Assign)) {
             None
         } else {
-            Some(self.parse_type_reference()?)
+            Some(self.parse_type_expression()?)
         };
         self.expect_and_discard(Token::Binding(Binding::Assign))?;
 
-        let value = self.parse_expression()?;
+        let value = self.parse_value_expression()?;
+        self.local_names.extend(pattern.bound_names());
 
         Ok(nodes::Binding {
             pattern,
             value: Box::new(value),
             explicit_type_annotation,
+            comments: Default::default(),
+            sydoc: None,
+            span: None,
         })
     }
 
-    fn parse_field(&mut self) -> Result<nodes::Field> {
-        self.tokens.discard();
+    fn parse_field(&mut self, is_extern: bool) -> Result<nodes::Field> {
+        let is_final = match self.tokens.read().map(|lexed| lexed.token) {
+            Some(Token::Binding(Binding::Final)) => true,
+            Some(Token::Binding(Binding::Var)) => false,
+            _ => return self.fail("expected `final` or `var` to start a field"),
+        };
         let declaration_modifiers = self.parse_modifiers(&self.modifier_sets.field.clone())?;
         let accessibility = self
             .accessibility_modifier_extractor
             .extract_accessibility_modifier(&declaration_modifiers)
-            .map_err(|msg| {
-                Error::Parser(ParserError {
-                    description: ParserErrorDescription::Described(msg),
-                })
-            })?;
+            .or_else(|msg| self.fail(msg))?;
 
         let pattern = self.parse_pattern()?;
 
         let explicit_type_annotation = if self.next_is(&Token::Binding(Binding::Assign)) {
             None
         } else {
-            Some(self.parse_type_reference()?)
+            Some(self.parse_type_expression()?)
         };
         self.expect_and_discard(Token::Binding(Binding::Assign))?;
 
-        let is_extern = todo!();
-
-        let value = self.parse_expression()?;
+        let value = self.parse_value_expression()?;
 
         Ok(nodes::Field {
             accessibility,
             is_extern,
+            is_final,
             binding: nodes::Binding {
                 pattern,
                 value: Box::new(value),
                 explicit_type_annotation,
+                comments: Default::default(),
+                sydoc: None,
+                span: None,
             },
         })
     }
@@ -1245,7 +2308,7 @@ impl Parser {
     fn parse_identifier(&mut self) -> Result<Identifier> {
         if let Some(lexed) = self.tokens.read() {
             if let Token::Identifier(identifier) = lexed.token {
-                Ok(identifier)
+                Ok(identifier.at(lexed.position))
             } else {
                 self.fail("identifier expected")
             }
@@ -1254,55 +2317,76 @@ impl Parser {
         }
     }
 
-    fn parse_select(&mut self) -> Result<nodes::Select> {
-        self.tokens.discard();
-        let message_type = self.parse_type_reference()?;
-        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+    /// A pattern optionally followed by an `if` guard. The guard can see the
+    /// names the pattern binds as well as those already in scope. Package-level
+    /// names aren't tracked by the parser yet, so a guard must reach those via
+    /// a qualified symbol or a call.
+    fn parse_case_match(&mut self) -> Result<CaseMatch> {
+        let pattern = self.parse_pattern()?;
+
+        let guard = if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If)) {
+            self.tokens.discard();
+
+            let outer_names = self.local_names.len();
+            self.local_names.extend(pattern.bound_names());
+            let was_resolving_guard = mem::replace(&mut self.resolving_guard, true);
+            let guard = self.parse_value_expression();
+            self.resolving_guard = was_resolving_guard;
+            self.local_names.truncate(outer_names);
+            Some(guard?)
+        } else {
+            None
+        };
+
+        Ok(CaseMatch { pattern, guard })
+    }
+
+    fn parse_case_body(&mut self, matches: &[CaseMatch]) -> Result<Block> {
+        let outer_names = self.local_names.len();
+        for case_match in matches {
+            self.local_names.extend(case_match.pattern.bound_names());
+        }
+        let body = self.parse_block();
+        self.local_names.truncate(outer_names);
+        body
+    }
+
+    fn resolve_guard_symbol(&mut self, symbol: &Symbol) -> Result<()> {
+        if let Symbol::Relative(SymbolLookup(lookup)) = symbol {
+            if let [name] = &lookup[..] {
+                if !self.local_names.contains(name) {
+                    self.fail(format!("unknown name in guard: {}", name.0))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_select(&mut self) -> Result<nodes::Select> {
+        self.tokens.discard();
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
         let mut cases = vec![];
         let mut timeout = None;
 
         loop {
-            let mut matches = vec![];
             if self.next_is(&Token::Timeout) {
                 if timeout.is_none() {
-                    let nanoseconds = Box::new(self.parse_expression()?);
+                    self.tokens.discard();
+                    let nanoseconds = Box::new(self.parse_value_expression()?);
                     let body = self.parse_block()?;
                     timeout = Some(Timeout { nanoseconds, body });
                 } else {
                     self.unexpected(Token::Timeout)?;
                 }
             } else {
-                let body = loop {
-                    let pattern = self.parse_pattern()?;
-
-                    let guard =
-                        if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If)) {
-                            self.expect_and_discard(Token::BranchingAndJumping(
-                                BranchingAndJumping::If,
-                            ))?;
-                            Some(self.parse_expression()?)
-                        } else {
-                            None
-                        };
-
-                    matches.push(CaseMatch { pattern, guard });
-
-                    if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
-                        break self.parse_block()?;
-                    } else {
-                        self.expect_and_discard(Token::SubItemSeparator)?;
-                    }
-                };
-                cases.push(Case { matches, body });
+                let message_type = self.parse_type_reference()?;
+                let case = self.parse_case()?;
+                cases.push(SelectCase { message_type, case });
             }
 
             if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
                 self.tokens.discard();
-                break Ok(Select {
-                    message_type,
-                    cases,
-                    timeout,
-                });
+                break Ok(Select { cases, timeout });
             }
         }
     }
@@ -1312,60 +2396,137 @@ impl Parser {
 
         let mut cases = vec![];
         loop {
-            let mut conditions = vec![];
-            let then = loop {
-                let expression = self.parse_expression()?;
-                conditions.push(expression);
-
-                if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
-                    break self.parse_block()?;
-                } else {
-                    self.expect_and_discard(Token::SubItemSeparator)?;
+            if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::Else)) {
+                self.tokens.discard();
+                let else_clause = Some(self.parse_block()?);
+                if !self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
+                    break self.fail("an else arm must be the last arm of a cond");
                 }
-            };
-            cases.push(CondCase { conditions, then });
+                self.tokens.discard();
+                break Ok(Cond { cases, else_clause });
+            }
+
+            cases.push(self.parse_cond_case()?);
 
             if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
                 self.tokens.discard();
-                break Ok(Cond(cases));
+                break Ok(Cond {
+                    cases,
+                    else_clause: None,
+                });
             }
         }
     }
 
-    fn parse_direct_switch(&mut self) -> Result<Switch> {
-        let expression = self.parse_expression()?;
-        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
-        let mut cases = vec![];
-
-        loop {
-            let mut matches = vec![];
-            let body = loop {
-                let pattern = self.parse_pattern()?;
+    fn parse_cond_case(&mut self) -> Result<CondCase> {
+        let ((conditions, then), span) = self.spanned(|parser| {
+            let mut conditions = vec![];
+            loop {
+                conditions.push(parser.parse_value_expression()?);
 
-                let guard = if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If)) {
-                    self.expect_and_discard(Token::BranchingAndJumping(BranchingAndJumping::If))?;
-                    Some(self.parse_expression()?)
+                if parser.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+                    break Ok((conditions, Some(parser.parse_block()?)));
+                } else if parser.next_is(&Token::Colon) {
+                    parser.tokens.discard();
+                    break Ok((conditions, None));
                 } else {
-                    None
-                };
+                    parser.expect_and_discard(Token::SubItemSeparator)?;
+                }
+            }
+        })?;
+        Ok(CondCase {
+            conditions,
+            then,
+            span,
+        })
+    }
 
-                matches.push(CaseMatch { pattern, guard });
+    /// Parse a `switch` or `select` case: one or more matches sharing a body.
+    fn parse_case(&mut self) -> Result<Case> {
+        let ((matches, body), span) = self.spanned(|parser| {
+            let mut matches = vec![];
+            loop {
+                matches.push(parser.parse_case_match()?);
 
-                if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
-                    break self.parse_block()?;
+                if parser.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+                    break;
                 } else {
-                    self.expect_and_discard(Token::SubItemSeparator)?;
+                    parser.expect_and_discard(Token::SubItemSeparator)?;
                 }
-            };
-            cases.push(Case { matches, body });
+            }
+            let body = parser.parse_case_body(&matches)?;
+            Ok((matches, body))
+        })?;
+        Ok(Case {
+            matches,
+            body,
+            span,
+        })
+    }
+
+    fn parse_direct_switch(&mut self) -> Result<Switch> {
+        let expression = self.parse_value_expression()?;
+        let binding = if self.next_is(&Token::Binding(Binding::As)) {
+            self.tokens.discard();
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+
+        let outer_names = self.local_names.len();
+        self.local_names.extend(binding.clone());
+        let outer_fallthroughs = self.arm_fallthroughs.take();
+        let cases = self.parse_switch_cases();
+        self.arm_fallthroughs = outer_fallthroughs;
+        self.local_names.truncate(outer_names);
+        let cases = cases?;
+
+        for (case, next) in cases.iter().zip(cases.iter().skip(1)) {
+            if case.falls_through() && next.binds_names() {
+                return self.fail(
+                    "can't fall through into an arm that binds names, as nothing would bind them",
+                );
+            }
+        }
+        if cases.last().map(Case::falls_through) == Some(true) {
+            return self.fail("the last switch arm has no arm to fall through into");
+        }
+
+        Ok(Switch {
+            expression: Box::new(expression),
+            binding,
+            cases,
+        })
+    }
+
+    fn parse_switch_cases(&mut self) -> Result<Vec<Case>> {
+        let mut cases = vec![];
+        loop {
+            self.arm_fallthroughs = Some(0);
+            let case = self.parse_case()?;
+            if self.arm_fallthroughs != Some(case.falls_through() as usize) {
+                return self.fail("`fallthrough` can only come at the end of a switch arm");
+            }
+            cases.push(case);
 
             if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
                 self.tokens.discard();
-                break Ok(Switch {
-                    expression: Box::new(expression),
-                    cases,
-                });
+                break Ok(cases);
+            }
+        }
+    }
+
+    fn parse_fallthrough(&mut self) -> Result<nodes::Expression> {
+        self.tokens.discard();
+        match &mut self.arm_fallthroughs {
+            Some(count) => {
+                *count += 1;
+                Ok(Expression::BranchingAndJumping(
+                    nodes::BranchingAndJumping::Fallthrough,
+                ))
             }
+            None => self.fail("`fallthrough` can only be used in a switch arm"),
         }
     }
 
@@ -1388,10 +2549,40 @@ impl Parser {
         }
     }
 
+    fn parse_use(&mut self) -> Result<nodes::Use> {
+        self.tokens.discard();
+        let resource = self.parse_operand_expression()?;
+        Ok(nodes::Use(Box::new(resource)))
+    }
+
+    fn parse_defer(&mut self) -> Result<nodes::Defer> {
+        self.tokens.discard();
+        Ok(nodes::Defer(self.parse_block()?))
+    }
+
+    fn parse_hole(&mut self) -> nodes::Expression {
+        let position = self.tokens.current_position();
+        self.tokens.discard();
+        Expression::Hole(position)
+    }
+
     fn parse_throw(&mut self) -> Result<nodes::Throw> {
         self.tokens.discard();
-        let expression = self.parse_expression()?;
-        Ok(Throw(Box::new(expression)))
+        let exception = self.parse_value_expression()?;
+        let cause = if self.next_is(&Token::Caused) {
+            self.tokens.discard();
+            match self.read() {
+                Some(Token::Identifier(by)) if *by.0 == "by" => {}
+                _ => self.fail("expected `by` after `caused`")?,
+            }
+            Some(Box::new(self.parse_value_expression()?))
+        } else {
+            None
+        };
+        Ok(Throw {
+            exception: Box::new(exception),
+            cause,
+        })
     }
 
     fn parse_literal(&mut self, token: Token) -> Option<nodes::Literal> {
@@ -1402,12 +2593,36 @@ impl Parser {
             Token::Literal(Literal::InterpolatedString(string)) => {
                 Some(nodes::Literal::InterpolatedString(string))
             }
-            Token::Literal(Literal::Number(number)) => Some(nodes::Literal::Number(number)),
+            Token::Literal(Literal::Number(number, numeric_type)) => {
+                Some(nodes::Literal::Number(number, numeric_type))
+            }
+            Token::Literal(Literal::HexFloat(float)) => Some(nodes::Literal::HexFloat(float)),
+            Token::Literal(Literal::Scientific(scientific)) => {
+                Some(nodes::Literal::Scientific(scientific))
+            }
             Token::Literal(Literal::String(string)) => Some(nodes::Literal::String(string)),
             _ => None,
         }
     }
 
+    /// Plain strings directly after a plain string literal are concatenated onto
+    /// it, as in C, so that long strings can be split across lines. Interpolated
+    /// strings are never concatenated, so a plain string next to one is left as
+    /// a literal of its own.
+    fn concatenate_adjacent_strings(&mut self, literal: nodes::Literal) -> nodes::Literal {
+        match literal {
+            nodes::Literal::String(string) => {
+                let mut concatenated = string.0.to_string();
+                while let Some(Token::Literal(Literal::String(next))) = self.peek() {
+                    self.tokens.discard();
+                    concatenated.push_str(&next.0);
+                }
+                nodes::Literal::String(multiphase::SylanString::from(concatenated))
+            }
+            other => other,
+        }
+    }
+
     fn parse_leading_identifier(&mut self) -> Result<nodes::Expression> {
         let symbol = self.parse_symbol()?;
         if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
@@ -1436,7 +2651,10 @@ impl Parser {
                 nodes::BranchingAndJumping::Call(call),
             ))
         } else {
-            Ok(nodes::Expression::Symbol(self.parse_symbol()?))
+            if self.resolving_guard {
+                self.resolve_guard_symbol(&symbol)?;
+            }
+            Ok(nodes::Expression::Symbol(symbol))
         }
     }
 
@@ -1504,7 +2722,7 @@ impl Parser {
                         break;
                     } else {
                         let n = match self.read() {
-                            Some(Token::Literal(Literal::Number(number))) => number,
+                            Some(Token::Literal(Literal::Number(number, _))) => number,
                             Some(unexpected) => self.unexpected(unexpected)?,
                             None => self.premature_eof()?,
                         };
@@ -1540,12 +2758,24 @@ impl Parser {
     }
 
     fn parse_expression(&mut self) -> Result<nodes::Expression> {
+        let term = self.parse_term()?;
+        self.parse_infix_operations(term, 0)
+    }
+
+    /// An expression up to, but not including, any infix operator following
+    /// it.
+    fn parse_term(&mut self) -> Result<nodes::Expression> {
         let token = self.tokens.peek().cloned();
         let expression = match token {
             Some(lexed) => {
                 let token = lexed.token;
                 self.parse_literal(token.clone())
-                    .map(|literal| Ok(nodes::Expression::Literal(literal)))
+                    .map(|literal| {
+                        self.tokens.discard();
+                        Ok(nodes::Expression::Literal(
+                            self.concatenate_adjacent_strings(literal),
+                        ))
+                    })
                     .unwrap_or_else(|| match token {
                         // Non-atomic tokens each delegate to a dedicated method.
                         Token::With => self.parse_with(),
@@ -1555,6 +2785,9 @@ impl Parser {
                                 nodes::BranchingAndJumping::For(self.parse_for()?),
                             ))
                         }
+                        Token::BranchingAndJumping(BranchingAndJumping::While) => self
+                            .parse_while()
+                            .map(nodes::Expression::BranchingAndJumping),
                         Token::BranchingAndJumping(BranchingAndJumping::If) => {
                             self.parse_if().map(|if_token| {
                                 nodes::Expression::BranchingAndJumping(
@@ -1562,12 +2795,31 @@ impl Parser {
                                 )
                             })
                         }
-                        Token::LambdaArrow => self
-                            .parse_lambda()
-                            .map(|f| nodes::Expression::Literal(nodes::Literal::Lambda(f))),
+                        // The lexer can't tell a lambda's arrow from the cascade operator, but
+                        // only the former can start an expression.
+                        Token::LambdaArrow
+                        | Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade) => {
+                            self.parse_lambda()
+                                .map(|f| nodes::Expression::Literal(nodes::Literal::Lambda(f)))
+                        }
                         Token::Grouping(Grouping::OpenParentheses) => {
                             self.parse_grouped_expression()
                         }
+                        Token::Grouping(Grouping::OpenSquareBracket) => self
+                            .parse_list(false)
+                            .map(|list| nodes::Expression::Literal(nodes::Literal::List(list))),
+                        Token::Binding(Binding::Var)
+                            if self.nth_is(1, &Token::Grouping(Grouping::OpenSquareBracket)) =>
+                        {
+                            self.tokens.discard();
+                            self.parse_list(true)
+                                .map(|list| nodes::Expression::Literal(nodes::Literal::List(list)))
+                        }
+                        Token::Grouping(Grouping::OpenRecord) => {
+                            self.parse_record().map(|record| {
+                                nodes::Expression::Literal(nodes::Literal::Record(record))
+                            })
+                        }
                         Token::BranchingAndJumping(BranchingAndJumping::Select) => {
                             self.parse_select().map(|select| {
                                 nodes::Expression::BranchingAndJumping(
@@ -1575,13 +2827,41 @@ impl Parser {
                                 )
                             })
                         }
+                        Token::Identifier(..)
+                            if self.nth_is(1, &Token::Colon)
+                                && self.nth_is(2, &Token::Grouping(Grouping::OpenBrace)) =>
+                        {
+                            self.parse_labelled_block().map(|labelled| {
+                                nodes::Expression::BranchingAndJumping(
+                                    nodes::BranchingAndJumping::LabelledBlock(labelled),
+                                )
+                            })
+                        }
+                        Token::Identifier(ref identifier)
+                            if identifier.0.as_str() == "spawn" && self.next_starts_spawn() =>
+                        {
+                            self.parse_spawn().map(nodes::Expression::Spawn)
+                        }
                         Token::Identifier(..) | Token::PseudoIdentifier(..) => {
                             self.parse_leading_identifier()
                         }
+                        Token::BranchingAndJumping(BranchingAndJumping::Fallthrough) => {
+                            self.parse_fallthrough()
+                        }
+                        Token::BranchingAndJumping(BranchingAndJumping::Break) => {
+                            self.parse_break().map(|r#break| {
+                                nodes::Expression::BranchingAndJumping(
+                                    nodes::BranchingAndJumping::Break(r#break),
+                                )
+                            })
+                        }
                         Token::BranchingAndJumping(BranchingAndJumping::Switch) => {
                             self.parse_switch()
                         }
                         Token::Throw => self.parse_throw().map(nodes::Expression::Throw),
+                        Token::Use => self.parse_use().map(nodes::Expression::Use),
+                        Token::Defer => self.parse_defer().map(nodes::Expression::Defer),
+                        Token::Hole => Ok(self.parse_hole()),
 
                         non_expression => self.unexpected(non_expression),
                     })
@@ -1593,31 +2873,14 @@ impl Parser {
                  ",
             ),
         }?;
+        let expression = self.parse_postfix_chain(expression)?;
 
         match self.peek() {
-            Some(Token::Grouping(Grouping::OpenParentheses)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::Grouping(Grouping::OpenSquareBracket)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_typed_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::OverloadableSliceOperator(multiphase::OverloadableSliceOperator::Open)) => {
-                Ok(Expression::Operator(Operator::MultiSlice(
-                    self.parse_slice()?,
-                )))
-            }
-            Some(Token::PostfixOperator(operator)) => Ok(Expression::Operator(
-                nodes::Operator::Postfix(Box::new(expression), operator),
-            )),
-            Some(Token::OverloadableInfixOperator(operator)) => {
-                Ok(Expression::Operator(Operator::OverloadableInfix(
+            Some(Token::PostfixOperator(operator)) if !self.next_starts_conditional() => {
+                self.tokens.discard();
+                Ok(Expression::Operator(nodes::Operator::Postfix(
                     Box::new(expression),
                     operator,
-                    Box::new(self.parse_expression()?),
                 )))
             }
             _ => Ok(expression),
@@ -1628,12 +2891,22 @@ impl Parser {
     /// subexpressions with parentheses and lambda literals. Both of those exclusions are to make
     /// parsing unambiguous without requiring explicit line continuations.
     fn parse_outermost_expression(&mut self) -> Result<nodes::Expression> {
+        let term = self.parse_outermost_term()?;
+        self.parse_infix_operations(term, 0)
+    }
+
+    fn parse_outermost_term(&mut self) -> Result<nodes::Expression> {
         let token = self.tokens.peek().cloned();
         let expression = match token {
             Some(lexed) => {
                 let token = lexed.token;
                 self.parse_literal(token.clone())
-                    .map(|literal| Ok(nodes::Expression::Literal(literal)))
+                    .map(|literal| {
+                        self.tokens.discard();
+                        Ok(nodes::Expression::Literal(
+                            self.concatenate_adjacent_strings(literal),
+                        ))
+                    })
                     .unwrap_or_else(|| match token {
                         // Non-atomic tokens each delegate to a dedicated method.
                         Token::With => self.parse_with(),
@@ -1643,6 +2916,9 @@ impl Parser {
                                 nodes::BranchingAndJumping::For(self.parse_for()?),
                             ))
                         }
+                        Token::BranchingAndJumping(BranchingAndJumping::While) => self
+                            .parse_while()
+                            .map(nodes::Expression::BranchingAndJumping),
                         Token::BranchingAndJumping(BranchingAndJumping::If) => {
                             self.parse_if().map(|if_token| {
                                 nodes::Expression::BranchingAndJumping(
@@ -1650,9 +2926,39 @@ impl Parser {
                                 )
                             })
                         }
+                        Token::Grouping(Grouping::OpenRecord) => {
+                            self.parse_record().map(|record| {
+                                nodes::Expression::Literal(nodes::Literal::Record(record))
+                            })
+                        }
+                        Token::Identifier(..)
+                            if self.nth_is(1, &Token::Colon)
+                                && self.nth_is(2, &Token::Grouping(Grouping::OpenBrace)) =>
+                        {
+                            self.parse_labelled_block().map(|labelled| {
+                                nodes::Expression::BranchingAndJumping(
+                                    nodes::BranchingAndJumping::LabelledBlock(labelled),
+                                )
+                            })
+                        }
+                        Token::Identifier(ref identifier)
+                            if identifier.0.as_str() == "spawn" && self.next_starts_spawn() =>
+                        {
+                            self.parse_spawn().map(nodes::Expression::Spawn)
+                        }
                         Token::Identifier(..) | Token::PseudoIdentifier(..) => {
                             self.parse_leading_identifier()
                         }
+                        Token::BranchingAndJumping(BranchingAndJumping::Fallthrough) => {
+                            self.parse_fallthrough()
+                        }
+                        Token::BranchingAndJumping(BranchingAndJumping::Break) => {
+                            self.parse_break().map(|r#break| {
+                                nodes::Expression::BranchingAndJumping(
+                                    nodes::BranchingAndJumping::Break(r#break),
+                                )
+                            })
+                        }
                         Token::BranchingAndJumping(BranchingAndJumping::Select) => {
                             self.parse_select().map(|select| {
                                 nodes::Expression::BranchingAndJumping(
@@ -1664,6 +2970,9 @@ impl Parser {
                             self.parse_switch()
                         }
                         Token::Throw => self.parse_throw().map(nodes::Expression::Throw),
+                        Token::Use => self.parse_use().map(nodes::Expression::Use),
+                        Token::Defer => self.parse_defer().map(nodes::Expression::Defer),
+                        Token::Hole => Ok(self.parse_hole()),
 
                         non_expression => self.unexpected(non_expression),
                     })
@@ -1675,114 +2984,302 @@ impl Parser {
                  ",
             ),
         }?;
+        let expression = self.parse_postfix_chain(expression)?;
 
         match self.peek() {
-            Some(Token::Grouping(Grouping::OpenParentheses)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::Grouping(Grouping::OpenSquareBracket)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_typed_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::OverloadableSliceOperator(multiphase::OverloadableSliceOperator::Open)) => {
-                Ok(Expression::Operator(Operator::MultiSlice(
-                    self.parse_slice()?,
-                )))
-            }
-            Some(Token::PostfixOperator(operator)) => Ok(Expression::Operator(
-                nodes::Operator::Postfix(Box::new(expression), operator),
-            )),
-            Some(Token::OverloadableInfixOperator(operator)) => {
-                Ok(Expression::Operator(Operator::OverloadableInfix(
+            Some(Token::PostfixOperator(operator)) if !self.next_starts_conditional() => {
+                self.tokens.discard();
+                Ok(Expression::Operator(nodes::Operator::Postfix(
                     Box::new(expression),
                     operator,
-                    Box::new(self.parse_expression()?),
                 )))
             }
             _ => Ok(expression),
         }
     }
 
+    /// Parse the infix operators following `left` that bind at least as
+    /// tightly as `minimum_precedence`, by precedence climbing. Operators of
+    /// the same precedence group leftwards, so `a - b - c` is `(a - b) - c`,
+    /// except for the right-associative ones such as `**`.
+    fn parse_infix_operations(
+        &mut self,
+        mut left: Expression,
+        minimum_precedence: u8,
+    ) -> Result<Expression> {
+        while let Some(Token::OverloadableInfixOperator(operator)) = self.peek() {
+            let precedence = infix_precedence(&operator);
+            if precedence < minimum_precedence {
+                break;
+            }
+            self.tokens.discard();
+
+            let term = self.parse_term()?;
+            let mut right = self.check_operand(term)?;
+            while let Some(Token::OverloadableInfixOperator(next)) = self.peek() {
+                let next_precedence = infix_precedence(&next);
+                let binds_rightwards = next_precedence > precedence
+                    || (next_precedence == precedence && is_right_associative(&next));
+                if !binds_rightwards {
+                    break;
+                }
+                right = self.parse_infix_operations(right, next_precedence)?;
+            }
+
+            left = Expression::Operator(Operator::OverloadableInfix(
+                Box::new(left),
+                operator,
+                Box::new(right),
+            ));
+        }
+        Ok(left)
+    }
+
+    /// Parse the calls, slices, and lookups following an expression, each
+    /// applying to the result of everything before it. `a.b().c[|0|].d()`
+    /// therefore calls `d` on a slice of `c`, which is looked up on the result
+    /// of calling `a.b`.
+    ///
+    /// A `?` is otherwise a postfix bind, so it's only an optional chain when
+    /// directly followed by a dot.
+    fn parse_postfix_chain(&mut self, mut expression: Expression) -> Result<Expression> {
+        loop {
+            expression = match self.peek() {
+                Some(Token::Grouping(Grouping::OpenParentheses)) => {
+                    Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
+                        self.parse_expression_call(expression)?,
+                    ))
+                }
+                Some(Token::Grouping(Grouping::OpenSquareBracket)) => {
+                    Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
+                        self.parse_typed_expression_call(expression)?,
+                    ))
+                }
+                Some(Token::OverloadableSliceOperator(
+                    multiphase::OverloadableSliceOperator::Open,
+                )) => {
+                    let slice = self.parse_slice()?;
+                    Expression::Operator(Operator::MultiSlice(Box::new(expression), slice))
+                }
+                Some(Token::Dot) => {
+                    self.tokens.discard();
+                    let member = self.parse_identifier()?;
+                    Expression::Operator(Operator::Lookup(Box::new(expression), member))
+                }
+                Some(Token::PostfixOperator(multiphase::PostfixOperator::Bind))
+                    if self.nth_is(1, &Token::Dot) =>
+                {
+                    self.tokens.discard();
+                    self.tokens.discard();
+                    let member = self.parse_identifier()?;
+                    Expression::Operator(Operator::OptionalChain(Box::new(expression), member))
+                }
+                _ => break Ok(expression),
+            }
+        }
+    }
+
     fn parse_block(&mut self) -> Result<nodes::Block> {
         let mut bindings = vec![];
         let mut expressions = vec![];
+        let mut interleaved = false;
+        let outer_names = self.local_names.len();
 
-        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
-        loop {
-            if self.next_is(&Token::Binding(Binding::Var)) {
-                bindings.push(self.parse_local_var_binding()?);
-            } else if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
-                self.tokens.discard();
-                break;
-            } else {
-                expressions.push(self.parse_outermost_expression()?);
+        let ((), span) = self.spanned(|parser| {
+            parser.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+            loop {
+                if parser.next_is(&Token::Binding(Binding::Var)) {
+                    interleaved |= !expressions.is_empty();
+                    bindings.push(parser.parse_local_var_binding()?);
+                } else if parser.next_is(&Token::Grouping(Grouping::CloseBrace)) {
+                    parser.tokens.discard();
+                    break Ok(());
+                } else {
+                    expressions.push(parser.parse_outermost_expression()?);
+                }
             }
-        }
+        })?;
+        self.local_names.truncate(outer_names);
 
         Ok(Block {
             expressions,
             bindings,
-            parent: Some(Rc::new(Block::within(&self.current_scope))),
+            parent: Some(Arc::new(Block::within(&self.current_scope))),
+            span,
+            interleaved,
         })
     }
 
-    fn parse_grouped_expression(&mut self) -> Result<nodes::Expression> {
+    fn parse_record(&mut self) -> Result<nodes::Record> {
         self.tokens.discard();
-        let expression = self.parse_expression()?;
-        self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
-        Ok(expression)
-    }
-
-    fn parse_inside_package(&mut self) -> Result<Vec<nodes::Item>> {
-        let mut items: Vec<Item> = vec![];
 
+        let mut fields: Vec<nodes::RecordField> = vec![];
         loop {
-            let maybe_token = self.tokens.peek().map(|lexed| lexed.token.clone());
-
-            match maybe_token {
-                None => break,
+            if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
+                self.tokens.discard();
+                break Ok(nodes::Record(fields));
+            }
 
-                Some(token) => match token {
-                    Token::DeclarationHead(DeclarationHead::Class) => {
-                        let class_definition = self.parse_class_definition()?;
-                        items.push(Item::Type(class_definition));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Extend) => {
-                        let extension = self.parse_extension()?;
-                        items.push(Item::Extension(extension));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Interface) => {
-                        let interface = self.parse_interface_definition()?;
-                        items.push(Item::Type(interface));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Package) => {
-                        let package = self.parse_package_definition()?;
-                        items.push(Item::Package(package));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Fun) => {
-                        let fun = self.parse_fun()?;
-                        items.push(Item::Fun(fun));
-                    }
-                    Token::Binding(Binding::Final) => {
-                        let binding = self.parse_binding()?;
-                        items.push(Item::Final(binding));
-                    }
+            let name = self.parse_identifier()?;
+            if fields.iter().any(|field| field.name == name) {
+                self.fail(format!("duplicate record field: {}", name.0))?;
+            }
+            self.expect_and_discard(Token::Colon)?;
+            let value = self.parse_value_expression()?;
+            fields.push(nodes::RecordField { name, value });
 
-                    unexpected => self.unexpected(unexpected)?,
-                },
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            } else {
+                self.expect(Token::Grouping(Grouping::CloseBrace))?;
             }
         }
-
-        Ok(items)
     }
 
-    fn parse_main_package(&mut self) -> Result<nodes::MainPackage> {
-        let mut items: Vec<Item> = vec![];
-
-        let mut implicit_main = Block::new_root();
+    /// Parse a parenthesised expression, which is a tuple if there's a comma
+    /// inside and a reference to an operator if it's only an operator.
+    fn parse_grouped_expression(&mut self) -> Result<nodes::Expression> {
+        self.tokens.discard();
+        if let Some(Token::OverloadableInfixOperator(operator)) = self.peek() {
+            if self.nth_is(1, &Token::Grouping(Grouping::CloseParentheses)) {
+                self.tokens.discard_many(2);
+                return Ok(Expression::Operator(Operator::Reference(operator)));
+            }
+        }
+        let expression = self.parse_value_expression()?;
+        if !self.next_is(&Token::SubItemSeparator) {
+            self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
+            return Ok(expression);
+        }
+
+        let mut elements = vec![expression];
+        loop {
+            self.tokens.discard();
+            if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                break;
+            }
+            elements.push(self.parse_value_expression()?);
+            if !self.next_is(&Token::SubItemSeparator) {
+                break;
+            }
+        }
+        self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
+        Ok(nodes::Expression::Literal(nodes::Literal::Tuple(
+            nodes::Tuple(elements),
+        )))
+    }
+
+    fn parse_list(&mut self, mutable: bool) -> Result<nodes::List> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenSquareBracket))?;
+        let mut elements = vec![];
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseSquareBracket)) {
+                self.tokens.discard();
+                break Ok(nodes::List { elements, mutable });
+            }
+
+            elements.push(self.parse_value_expression()?);
+
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            } else {
+                self.expect(Token::Grouping(Grouping::CloseSquareBracket))?;
+            }
+        }
+    }
+
+    fn parse_annotation(&mut self) -> Result<nodes::MacroItem> {
+        self.tokens.discard();
+        let target = self.parse_symbol()?;
+        if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
+            let arguments = self.parse_value_argument_list()?;
+            Ok(nodes::MacroItem::Call(nodes::Call {
+                target,
+                arguments: CallArguments {
+                    type_arguments: vec![],
+                    arguments,
+                },
+            }))
+        } else {
+            Ok(nodes::MacroItem::Bare(target))
+        }
+    }
+
+    fn parse_inside_package(&mut self) -> Result<Vec<nodes::Item>> {
+        let mut items: Vec<Item> = vec![];
+
+        loop {
+            let maybe_token = self.tokens.peek().map(|lexed| lexed.token.clone());
+
+            match maybe_token {
+                // The brace closing a nested package is left for the caller.
+                None | Some(Token::Eof) | Some(Token::Grouping(Grouping::CloseBrace)) => break,
+
+                Some(token) => match token {
+                    Token::DeclarationHead(DeclarationHead::Class) => {
+                        let class_definition = self.parse_class_definition()?;
+                        items.push(Item::Type(class_definition));
+                    }
+                    Token::DeclarationHead(DeclarationHead::Extend) => {
+                        let extension = self.parse_extension()?;
+                        items.push(Item::Extension(extension));
+                    }
+                    Token::DeclarationHead(DeclarationHead::Interface) => {
+                        let interface = self.parse_interface_definition()?;
+                        items.push(Item::Type(interface));
+                    }
+                    Token::DeclarationHead(DeclarationHead::Package) => {
+                        let package = self.parse_package_definition()?;
+                        items.push(Item::Package(package));
+                    }
+                    Token::DeclarationHead(DeclarationHead::Fun) => {
+                        let fun = self.parse_fun(None)?;
+                        items.push(Item::Fun(fun));
+                    }
+                    Token::DeclarationHead(DeclarationHead::Extern) => {
+                        let fun = self.parse_extern_fun()?;
+                        items.push(Item::Fun(fun));
+                    }
+                    Token::Binding(Binding::Final) => {
+                        let binding = self.parse_binding()?;
+                        items.push(Item::Final(binding));
+                    }
+                    Token::Binding(Binding::Const) => {
+                        let binding = self.parse_const_binding()?;
+                        items.push(Item::Const(binding));
+                    }
+                    Token::Macros(Macros::At) => {
+                        let annotation = self.parse_annotation()?;
+                        items.push(Item::Macro(nodes::Macro::Item(annotation)));
+                    }
+
+                    unexpected => self.unexpected(unexpected)?,
+                },
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn parse_main_package(&mut self) -> Result<nodes::MainPackage> {
+        let mut items: Vec<Item> = vec![];
+
+        let mut implicit_main = Block::new_root();
+
+        // A source with nothing but trivia in it is a valid, if useless, main
+        // package.
+        if self.tokens.match_next(|lexed| lexed.token == Token::Eof) {
+            return Ok(MainPackage {
+                package: Package {
+                    items,
+                    accessibility: Accessibility::Public,
+                    name: Identifier::from("main"),
+                    sydoc: None,
+                    imports: vec![],
+                },
+                block: implicit_main,
+            });
+        }
 
         self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Package))?;
 
@@ -1798,7 +3295,7 @@ impl Parser {
             let maybe_token = self.tokens.peek().map(|lexed| lexed.token.clone());
 
             match maybe_token {
-                None => break,
+                None | Some(Token::Eof) => break,
 
                 Some(token) => {
                     match token {
@@ -1819,22 +3316,37 @@ impl Parser {
                             items.push(Item::Package(package));
                         }
                         Token::DeclarationHead(DeclarationHead::Fun) => {
-                            let fun = self.parse_fun()?;
+                            let fun = self.parse_fun(None)?;
+                            items.push(Item::Fun(fun));
+                        }
+                        Token::DeclarationHead(DeclarationHead::Extern) => {
+                            let fun = self.parse_extern_fun()?;
                             items.push(Item::Fun(fun));
                         }
                         Token::Binding(Binding::Final) => {
                             let binding = self.parse_binding()?;
                             items.push(Item::Final(binding));
                         }
+                        Token::Binding(Binding::Const) => {
+                            let binding = self.parse_const_binding()?;
+                            items.push(Item::Const(binding));
+                        }
+                        Token::Macros(Macros::At) => {
+                            let annotation = self.parse_annotation()?;
+                            items.push(Item::Macro(nodes::Macro::Item(annotation)));
+                        }
 
                         // Unlike all other packages, the main package allows both variables
                         // without type annotations, falling back to type inference, and also
                         // arbritary expressions.
                         Token::Binding(Binding::Var) => {
+                            self.mark_top_level_code();
                             let binding = self.parse_local_var_binding()?;
+                            implicit_main.interleaved |= !implicit_main.expressions.is_empty();
                             implicit_main.bindings.push(binding);
                         }
                         _ => {
+                            self.mark_top_level_code();
                             let expression = self.parse_expression()?;
                             implicit_main.expressions.push(expression);
                         }
@@ -1857,6 +3369,27 @@ impl Parser {
         })
     }
 
+    fn mark_top_level_code(&mut self) {
+        if self.top_level_code_position.is_none() {
+            self.top_level_code_position = self.tokens.current_position();
+        }
+    }
+
+    fn parse_file_package(&mut self) -> Result<nodes::FilePackage> {
+        let main = self.parse_main_file()?.package;
+        match self.top_level_code_position {
+            None => Ok(nodes::FilePackage::Imported(main.package)),
+            Some(_) if *main.package.name.0 == "main" => Ok(nodes::FilePackage::EntryPoint(main)),
+            position => Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(format!(
+                    "only the main package can have top-level code, but {} does",
+                    main.package.name.0
+                )),
+                position,
+            })),
+        }
+    }
+
     fn maybe_parse_shebang(&mut self) -> Option<multiphase::Shebang> {
         let maybe_line = {
             let token = &self.tokens.peek()?.token;
@@ -1887,6 +3420,74 @@ impl Parser {
         maybe_version
     }
 
+    fn parse_module_definition(&mut self) -> Result<nodes::ModuleDefinition> {
+        self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Module))?;
+        let name = self.parse_module_name()?;
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+
+        let mut requirements = vec![];
+        loop {
+            match self.peek() {
+                Some(Token::Grouping(Grouping::CloseBrace)) => {
+                    self.tokens.discard();
+                    break Ok(nodes::ModuleDefinition { name, requirements });
+                }
+                Some(Token::ModuleDefinitions(ModuleDefinitions::Requires)) => {
+                    self.tokens.discard();
+                    let module = self.parse_module_name()?;
+                    let constraint = self.parse_version_constraint()?;
+                    requirements.push(nodes::Requirement { module, constraint });
+                }
+                Some(unexpected) => self.unexpected(unexpected)?,
+                None => self.fail("a module definition at the end of the file is not finished")?,
+            }
+        }
+    }
+
+    fn parse_module_name(&mut self) -> Result<SymbolLookup> {
+        let mut name = vec![self.parse_identifier()?];
+        while self.next_is(&Token::Dot) {
+            self.tokens.discard();
+            name.push(self.parse_identifier()?);
+        }
+        Ok(SymbolLookup(name))
+    }
+
+    fn parse_version_constraint(&mut self) -> Result<Option<nodes::VersionConstraint>> {
+        let comparison = match self.peek() {
+            Some(Token::OverloadableInfixOperator(operator)) => match operator {
+                OverloadableInfixOperator::Equals => nodes::VersionComparison::Equal,
+                OverloadableInfixOperator::NotEqual => nodes::VersionComparison::NotEqual,
+                OverloadableInfixOperator::LessThan => nodes::VersionComparison::LessThan,
+                OverloadableInfixOperator::LessThanOrEqual => {
+                    nodes::VersionComparison::LessThanOrEqual
+                }
+                OverloadableInfixOperator::GreaterThan => nodes::VersionComparison::GreaterThan,
+                OverloadableInfixOperator::GreaterThanOrEqual => {
+                    nodes::VersionComparison::GreaterThanOrEqual
+                }
+                _ => {
+                    return self.fail(
+                        "version constraints compare with `==`, `!=`, `<`, `<=`, `>`, or `>=`",
+                    )
+                }
+            },
+            _ => return Ok(None),
+        };
+        self.tokens.discard();
+
+        match self.peek() {
+            Some(Token::Version(version)) => {
+                self.tokens.discard();
+                Ok(Some(nodes::VersionConstraint {
+                    comparison,
+                    version,
+                }))
+            }
+            _ => self.fail("version constraints need a full version, such as `1.2.0`"),
+        }
+    }
+
     fn parse_main_file(&mut self) -> Result<nodes::MainFile> {
         let shebang = self.maybe_parse_shebang();
         let version = self.maybe_parse_version();
@@ -1903,14 +3504,2467 @@ impl Parser {
     /// finished before continuing.
     pub fn parse(mut self) -> Result<nodes::MainFile> {
         let file = self.parse_main_file();
+        self.finish(file)
+    }
+
+    /// Parse a file that might be either an entry point or imported by others,
+    /// deciding which by whether it has top-level executable code.
+    pub fn parse_any(mut self) -> Result<nodes::FilePackage> {
+        let package = self.parse_file_package();
+        self.finish(package)
+    }
+
+    /// Parse a lone expression rather than a whole file, such as a line typed
+    /// into a REPL. Anything after the expression is an error.
+    pub fn parse_single_expression(mut self) -> Result<nodes::Expression> {
+        let expression = self
+            .parse_value_expression()
+            .and_then(|expression| match self.peek() {
+                None | Some(Token::Eof) => Ok(expression),
+                Some(trailing) => self.unexpected(trailing),
+            });
+        self.finish(expression)
+    }
+
+    pub fn parse_single_module_definition(mut self) -> Result<nodes::ModuleDefinition> {
+        let definition = self
+            .parse_module_definition()
+            .and_then(|definition| match self.peek() {
+                None | Some(Token::Eof) => Ok(definition),
+                Some(trailing) => self.unexpected(trailing),
+            });
+        self.finish(definition)
+    }
+
+    pub fn parse_single_type(mut self) -> Result<TypeExpression> {
+        let r#type = self
+            .parse_type_expression()
+            .and_then(|r#type| match self.peek() {
+                None | Some(Token::Eof) => Ok(r#type),
+                Some(trailing) => self.unexpected(trailing),
+            });
+        self.finish(r#type)
+    }
+
+    /// Parse either a binding or a lone expression, such as a line typed into
+    /// a REPL. Like the main package, `var` bindings needn't be annotated.
+    pub fn parse_single_node(mut self) -> Result<nodes::Node> {
+        let node = match self.peek() {
+            Some(Token::Binding(Binding::Var)) => self
+                .parse_local_var_binding()
+                .map(|binding| nodes::Node::Item(Box::new(Item::Var(binding)))),
+            Some(Token::Binding(Binding::Final)) => self
+                .parse_binding()
+                .map(|binding| nodes::Node::Item(Box::new(Item::Final(binding)))),
+            Some(Token::Binding(Binding::Const)) => self
+                .parse_const_binding()
+                .map(|binding| nodes::Node::Item(Box::new(Item::Const(binding)))),
+            _ => self
+                .parse_value_expression()
+                .map(|expression| nodes::Node::Expression(Box::new(expression))),
+        }
+        .and_then(|node| match self.peek() {
+            None | Some(Token::Eof) => Ok(node),
+            Some(trailing) => self.unexpected(trailing),
+        });
+        self.finish(node)
+    }
+
+    /// Wait for the lexer to finish, favouring its errors over the parser's as
+    /// the latter are likely knock-on effects of the former.
+    fn finish<T>(self, parsed: Result<T>) -> Result<T> {
+        let position = self.tokens.last_position();
         let join_handle = self.tokens.join_lexer_thread();
-        join_handle.map_err(|err| {
+        join_handle.map_err(|err| lexer_task_error(err, position))?;
+        parsed
+    }
+}
+
+fn lexer_task_error(err: LexerTaskError, position: Option<Position>) -> Error {
+    match err {
+        LexerTaskError::Lexer(err) => Error::Lexer(vec![err]),
+        LexerTaskError::Invalid(errors) => Error::Lexer(errors),
+        LexerTaskError::Task(err) => {
             let description = ParserErrorDescription::LexerThreadFailed(format!(
                 "parsing failed due to not being able to join on the lexer thread: {:?}",
                 err,
             ));
-            Error::Parser(ParserError { description })
-        })?;
-        file
+            Error::Parser(ParserError {
+                description,
+                position,
+            })
+        }
+    }
+}
+
+/// Parse an AST straight from source text already in memory, lexing it along
+/// the way.
+pub fn parse_str(source: &str) -> Result<nodes::MainFile> {
+    parser_for(source)?.parse()
+}
+
+/// Parse a file straight from source text without knowing in advance whether
+/// it's an entry point or imported by others.
+pub fn parse_file_str(source: &str) -> Result<nodes::FilePackage> {
+    parser_for(source)?.parse_any()
+}
+
+/// Parse a single expression straight from source text, such as a line typed
+/// into a REPL.
+pub fn parse_expression(source: &str) -> Result<nodes::Expression> {
+    parser_for(source)?.parse_single_expression()
+}
+
+/// Parse a single type straight from source text, such as one written in an
+/// annotation or asked about by tooling.
+pub fn parse_type(source: &str) -> Result<TypeExpression> {
+    parser_for(source)?.parse_single_type()
+}
+
+/// Parse a module definition straight from source text, which declares the
+/// modules a module requires.
+pub fn parse_module_definition(source: &str) -> Result<nodes::ModuleDefinition> {
+    parser_for(source)?.parse_single_module_definition()
+}
+
+/// Parse a binding or a single expression straight from source text, as a REPL
+/// does with each line.
+pub fn parse_interactive(source: &str) -> Result<nodes::Node> {
+    parser_for(source)?.parse_single_node()
+}
+
+/// Lex and parse a main file, running the token lints and the AST's checks over
+/// it too, and gather every problem found as diagnostics in the order they
+/// appear in the source. Those without a span come last. The lints need the
+/// whole token stream, so a source that doesn't lex only has its lexer errors
+/// reported, and the checks need an AST, so they're skipped if it doesn't
+/// parse.
+pub fn check_str(source: &str, builtins: &[Identifier]) -> Vec<Diagnostic> {
+    let lexed = match tokens_for(source)
+        .and_then(|tokens| tokens.into_vec().map_err(|err| lexer_task_error(err, None)))
+    {
+        Ok(lexed) => lexed,
+        Err(err) => return err.diagnostics(),
+    };
+
+    let mut diagnostics = lints::mixed_indentation(&lexed)
+        .iter()
+        .map(Diagnostic::from)
+        .chain(
+            lints::confusable_identifiers(&lexed)
+                .iter()
+                .map(Diagnostic::from),
+        )
+        .collect::<Vec<_>>();
+    match Parser::from(Tokens::from_lexed(lexed)).parse() {
+        Ok(file) => diagnostics.extend(checks::check_main_file(&file, builtins)),
+        Err(err) => diagnostics.extend(err.diagnostics()),
+    }
+    diagnostics.sort_by_key(|diagnostic| {
+        (
+            diagnostic.span.is_none(),
+            diagnostic.span.map(|span| span.start),
+        )
+    });
+    diagnostics
+}
+
+/// Reformat a main file's source in the default style. Problems are described
+/// for people rather than returned structured, as all that can be done with a
+/// source that doesn't parse, or that has nodes without a layout yet, is to
+/// leave it as it is.
+///
+/// The reformatted source is parsed again and compared with the original by
+/// content hash. A formatter bug that changes what a program means is reported
+/// rather than printed, so that nobody overwrites their source with it.
+pub fn format_str(source: &str) -> result::Result<String, String> {
+    let file = parse_str(source).map_err(|err| {
+        err.diagnostics()
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    })?;
+    let formatted = formatting::format(&file, Default::default())
+        .map_err(|formatting::Unsupported(node)| format!("{} can't be formatted yet", node))?;
+    match parse_str(&formatted) {
+        Ok(reparsed) if hash_main_file(&reparsed) == hash_main_file(&file) => Ok(formatted),
+        _ => Err("formatting would change what the source means, so it was left alone".to_owned()),
+    }
+}
+
+fn parser_for(source: &str) -> Result<Parser> {
+    tokens_for(source).map(Parser::from)
+}
+
+fn tokens_for(source: &str) -> Result<Tokens> {
+    let chars = source.chars().collect::<Vec<char>>();
+    Tokens::from(Lexer::from(Source::from(chars))).map_err(|err| {
+        let description = ParserErrorDescription::LexerThreadFailed(format!(
+            "parsing failed due to not being able to start the lexer thread: {:?}",
+            err,
+        ));
+        Error::Parser(ParserError {
+            description,
+            position: None,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_main_block(source: &str) -> Block {
+        parse_str(source).unwrap().package.block
+    }
+
+    fn relative_symbol(name: &'static str) -> Symbol {
+        Symbol::Relative(SymbolLookup(vec![Identifier::from(name)]))
+    }
+
+    fn single_for(expression: &Expression) -> &For {
+        if let Expression::BranchingAndJumping(nodes::BranchingAndJumping::For(for_node)) =
+            expression
+        {
+            for_node
+        } else {
+            panic!("expected a for loop but got {:?}", expression)
+        }
+    }
+
+    fn single_call(expression: &Expression) -> &nodes::Call {
+        if let Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) = expression
+        {
+            call
+        } else {
+            panic!("expected a call but got {:?}", expression)
+        }
+    }
+
+    #[test]
+    fn labelled_for_reiterated_from_nested_loop() {
+        let block = parse_main_block(
+            "
+            package main
+
+            for outer var n = 10 {
+                for var m = 0 {
+                    outer(n - 1)
+                }
+            }
+            ",
+        );
+
+        let outer = single_for(&block.expressions[0]);
+        assert_eq!(outer.reiteration_symbol, Some(Identifier::from("outer")));
+        assert_eq!(outer.bindings.len(), 1);
+
+        let inner = single_for(&outer.scope.expressions[0]);
+        assert_eq!(inner.reiteration_symbol, None);
+
+        let call = single_call(&inner.scope.expressions[0]);
+        assert_eq!(call.target, relative_symbol("outer"));
+        assert_eq!(call.arguments.arguments.len(), 1);
+    }
+
+    #[test]
+    fn for_else_runs_unless_the_loop_breaks() {
+        let block = parse_main_block(
+            "package main
+            for search var i = 0 {
+                if i == 3 { break search i }
+            } else {
+                -1
+            }",
+        );
+        let search = single_for(&block.expressions[0]);
+        assert!(matches!(
+            search.scope.expressions[0],
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(_))
+        ));
+        assert_eq!(
+            search
+                .else_clause
+                .as_ref()
+                .map(|block| block.expressions.len()),
+            Some(1)
+        );
+
+        for (source, error) in &[
+            (
+                "package main\n for var i = 0 yield i { } else { 0 }",
+                "a comprehension always yields its list, so can't have an `else`",
+            ),
+            (
+                "package main\n for search var i = 0 { } else { break search 1 }",
+                "`break search` isn't within a block labelled `search`",
+            ),
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert_eq!(message, *error),
+                other => panic!("expected {:?} to fail: {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn destructuring_for_bindings() {
+        let block = parse_main_block(
+            "
+            package main
+
+            for (key, amount) = pair, [first, ..] = items {
+                for outer var (a, _) = pair {}
+            }
+            ",
+        );
+
+        let outer = single_for(&block.expressions[0]);
+        assert_eq!(outer.reiteration_symbol, None);
+        let names = outer
+            .bindings
+            .iter()
+            .map(|binding| binding.pattern.bound_names())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                vec![Identifier::from("key"), Identifier::from("amount")],
+                vec![Identifier::from("first")],
+            ]
+        );
+
+        let inner = single_for(&outer.scope.expressions[0]);
+        assert_eq!(inner.reiteration_symbol, Some(Identifier::from("outer")));
+        assert!(matches!(
+            inner.bindings[0].pattern.item,
+            PatternItem::Tuple(ref elements) if elements.len() == 2
+        ));
+    }
+
+    #[test]
+    fn unlabelled_continue_in_nested_loop() {
+        let block = parse_main_block(
+            "
+            package main
+
+            for outer var n = 10 {
+                for var m = 0 {
+                    continue(m + 1)
+                }
+            }
+            ",
+        );
+
+        let outer = single_for(&block.expressions[0]);
+        let inner = single_for(&outer.scope.expressions[0]);
+        let call = single_call(&inner.scope.expressions[0]);
+        assert_eq!(call.target, Symbol::Pseudo(PseudoIdentifier::Continue));
+    }
+
+    #[test]
+    fn for_comprehensions() {
+        let block = parse_main_block(
+            "
+            package main
+
+            for var n = 0 yield n * n {
+                if n < 4 {
+                    continue(n + 1)
+                }
+            }
+
+            for var n = 0 {
+                n
+            }
+            ",
+        );
+
+        let comprehension = single_for(&block.expressions[0]);
+        assert!(comprehension.is_comprehension());
+        assert_eq!(comprehension.bindings.len(), 1);
+        assert!(matches!(
+            comprehension.yield_expression.as_deref(),
+            Some(Expression::Operator(_))
+        ));
+
+        let statement = single_for(&block.expressions[1]);
+        assert!(!statement.is_comprehension());
+    }
+
+    #[test]
+    fn infinite_while_loops_are_bottom_typed() {
+        let block = parse_main_block("package main\n var forever = while True {}");
+        assert!(block.bindings[0].value.is_bottom_typed(&[]));
+
+        // A nested loop reiterating itself doesn't escape the outer one.
+        let block = parse_main_block(
+            "package main
+            var spinning = while True {
+                for var n = 0 {
+                    continue(n + 1)
+                }
+            }",
+        );
+        assert!(block.bindings[0].value.is_bottom_typed(&[]));
+
+        let block = parse_main_block("package main\n while running {}");
+        assert!(!block.expressions[0].is_bottom_typed(&[]));
+        assert!(parse_str("package main\n var x = while running {}").is_err());
+    }
+
+    #[test]
+    fn while_loops_escaped_by_reiterating_are_not_bottom_typed() {
+        let block = parse_main_block(
+            "package main
+            for outer var n = 0 {
+                while True {
+                    outer(n + 1)
+                }
+            }",
+        );
+        let outer = single_for(&block.expressions[0]);
+        let r#while = &outer.scope.expressions[0];
+        assert!(!r#while.is_bottom_typed(&[Identifier::from("outer")]));
+        assert!(r#while.is_bottom_typed(&[]));
+
+        assert!(parse_str(
+            "package main
+            for var n = 0 {
+                var x = while True {
+                    continue(n + 1)
+                }
+            }"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn annotations_with_keyword_arguments() {
+        let items = parse_str(
+            "package main
+            @config(limit: 5, retries: 3)
+            @deprecated
+            @route(\"/accounts\", verb: \"GET\")",
+        )
+        .unwrap()
+        .package
+        .package
+        .items;
+
+        let annotations = items
+            .iter()
+            .map(|item| match item {
+                Item::Macro(nodes::Macro::Item(annotation)) => annotation,
+                other => panic!("expected an annotation: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(annotations.len(), 3);
+
+        match annotations[0] {
+            nodes::MacroItem::Call(call) => {
+                assert_eq!(call.target, relative_symbol("config"));
+                let arguments = &call.arguments.arguments;
+                assert_eq!(
+                    arguments
+                        .iter()
+                        .map(|argument| argument.label.clone())
+                        .collect::<Vec<_>>(),
+                    vec![
+                        Some(Identifier::from("limit")),
+                        Some(Identifier::from("retries"))
+                    ]
+                );
+                assert_eq!(
+                    arguments[1].value,
+                    Expression::Literal(nodes::Literal::Number(multiphase::Number(3, 0), None))
+                );
+            }
+            other => panic!("expected an annotation with arguments: {:?}", other),
+        }
+
+        assert_eq!(
+            *annotations[1],
+            nodes::MacroItem::Bare(relative_symbol("deprecated"))
+        );
+
+        match annotations[2] {
+            nodes::MacroItem::Call(call) => {
+                let arguments = &call.arguments.arguments;
+                assert_eq!(arguments[0].label, None);
+                assert_eq!(arguments[1].label, Some(Identifier::from("verb")));
+            }
+            other => panic!("expected an annotation with arguments: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn intersection_bounds() {
+        let file =
+            parse_str("package main\nclass Sorted[T extends Comparable[T] & Printable]").unwrap();
+        let parameter = match &file.package.package.items[..] {
+            [Item::Type(r#type)] => &r#type.type_parameters[0],
+            other => panic!("expected a class: {:?}", other),
+        };
+        let reference = |name| TypeExpression::Reference(TypeReference::new(relative_symbol(name)));
+
+        let mut comparable = TypeReference::new(relative_symbol("Comparable"));
+        comparable.type_arguments.push(TypeArgument {
+            label: None,
+            value: reference("T"),
+            is_spread: false,
+        });
+        assert_eq!(
+            parameter.upper_bound,
+            Some(TypeExpression::Intersection(vec![
+                TypeExpression::Reference(comparable),
+                reference("Printable"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn union_types() {
+        let file = parse_str(
+            "package main
+            final name String | Null = x
+            final flattened A | B & C | D = x
+            final grouped Box[(A | B) & C] = x",
+        )
+        .unwrap();
+        let reference = |name| TypeExpression::Reference(TypeReference::new(relative_symbol(name)));
+        let annotations = file
+            .package
+            .package
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Final(binding) => binding.explicit_type_annotation.clone().unwrap(),
+                other => panic!("expected a final binding: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+
+        // Patterns can be followed by parentheses, so grouping is only
+        // unambiguous after the start of a type.
+        let mut boxed = TypeReference::new(relative_symbol("Box"));
+        boxed.type_arguments.push(TypeArgument {
+            label: None,
+            value: TypeExpression::Intersection(vec![
+                TypeExpression::Union(vec![reference("A"), reference("B")]),
+                reference("C"),
+            ]),
+            is_spread: false,
+        });
+
+        assert_eq!(
+            annotations,
+            vec![
+                TypeExpression::Union(vec![reference("String"), reference("Null")]),
+                TypeExpression::Union(vec![
+                    reference("A"),
+                    TypeExpression::Intersection(vec![reference("B"), reference("C")]),
+                    reference("D"),
+                ]),
+                TypeExpression::Reference(boxed),
+            ]
+        );
+    }
+
+    #[test]
+    fn optional_types() {
+        let file = parse_str(
+            "package main
+            final count Int? = x
+            final either Int | String? = x
+            final whole List[(Int | String)?] = x
+            final chained = a?.b?.c",
+        )
+        .unwrap();
+        let bindings = file
+            .package
+            .package
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Final(binding) => binding,
+                other => panic!("expected a final binding: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        let reference = |name| TypeExpression::Reference(TypeReference::new(relative_symbol(name)));
+        let optional = |r#type| TypeExpression::Optional(Box::new(r#type));
+
+        assert_eq!(
+            bindings[0].explicit_type_annotation,
+            Some(optional(reference("Int")))
+        );
+        assert_ne!(bindings[0].explicit_type_annotation, Some(reference("Int")));
+        assert_eq!(
+            bindings[1].explicit_type_annotation,
+            Some(TypeExpression::Union(vec![
+                reference("Int"),
+                optional(reference("String")),
+            ]))
+        );
+        let mut list = TypeReference::new(relative_symbol("List"));
+        list.type_arguments.push(TypeArgument {
+            label: None,
+            value: optional(TypeExpression::Union(vec![
+                reference("Int"),
+                reference("String"),
+            ])),
+            is_spread: false,
+        });
+        assert_eq!(
+            bindings[2].explicit_type_annotation,
+            Some(TypeExpression::Reference(list))
+        );
+
+        let chain = |receiver, member| {
+            Expression::Operator(Operator::OptionalChain(
+                Box::new(receiver),
+                Identifier::from(member),
+            ))
+        };
+        assert_eq!(
+            *bindings[3].value,
+            chain(chain(Expression::Symbol(relative_symbol("a")), "b"), "c")
+        );
+    }
+
+    #[test]
+    fn standalone_types() {
+        let reference = |name| TypeExpression::Reference(TypeReference::new(relative_symbol(name)));
+        let function = |parameters, result| TypeExpression::Function(parameters, Box::new(result));
+
+        let mut list = TypeReference::new(relative_symbol("List"));
+        list.type_arguments.push(TypeArgument {
+            label: None,
+            value: reference("Int"),
+            is_spread: false,
+        });
+        assert_eq!(
+            super::parse_type("List[Int]").unwrap(),
+            TypeExpression::Reference(list)
+        );
+        assert_eq!(
+            super::parse_type("(Int) -> Bool").unwrap(),
+            function(vec![reference("Int")], reference("Bool"))
+        );
+        assert_eq!(
+            super::parse_type("A | B").unwrap(),
+            TypeExpression::Union(vec![reference("A"), reference("B")])
+        );
+
+        // Results extend as far right as they can unless grouped.
+        assert_eq!(
+            super::parse_type("(Int, String) -> A | B").unwrap(),
+            function(
+                vec![reference("Int"), reference("String")],
+                TypeExpression::Union(vec![reference("A"), reference("B")])
+            )
+        );
+        assert_eq!(
+            super::parse_type("(() -> A) | B").unwrap(),
+            TypeExpression::Union(vec![function(vec![], reference("A")), reference("B")])
+        );
+
+        assert!(super::parse_type("Int Bool").is_err());
+        assert!(super::parse_type("(Int, Bool)").is_err());
+    }
+
+    #[test]
+    fn module_requirements() {
+        let lookup = |names: &[&'static str]| {
+            SymbolLookup(names.iter().map(|&name| Identifier::from(name)).collect())
+        };
+        let definition = super::parse_module_definition(
+            "module shop {
+                requires sylan.http >= 1.2.0
+                requires payments
+                requires legacy.billing < 10.0.12
+            }",
+        )
+        .unwrap();
+        assert_eq!(definition.name, lookup(&["shop"]));
+
+        let version = |major, minor, patch| Version {
+            major,
+            minor,
+            patch,
+        };
+        assert_eq!(
+            definition.requirements,
+            vec![
+                nodes::Requirement {
+                    module: lookup(&["sylan", "http"]),
+                    constraint: Some(nodes::VersionConstraint {
+                        comparison: nodes::VersionComparison::GreaterThanOrEqual,
+                        version: version(1, 2, 0),
+                    }),
+                },
+                nodes::Requirement {
+                    module: lookup(&["payments"]),
+                    constraint: None,
+                },
+                nodes::Requirement {
+                    module: lookup(&["legacy", "billing"]),
+                    constraint: Some(nodes::VersionConstraint {
+                        comparison: nodes::VersionComparison::LessThan,
+                        version: version(10, 0, 12),
+                    }),
+                },
+            ]
+        );
+
+        let constraint = definition.requirements[0].constraint.unwrap();
+        assert!(constraint.admits(version(1, 2, 0)));
+        assert!(constraint.admits(version(1, 10, 0)));
+        assert!(!constraint.admits(version(1, 1, 9)));
+    }
+
+    #[test]
+    fn malformed_version_constraints() {
+        for source in &[
+            "module shop { requires sylan.http >= 1.2 }",
+            "module shop { requires sylan.http >= latest }",
+            "module shop { requires sylan.http + 1.2.0 }",
+            "module shop { requires sylan.http >= }",
+        ] {
+            assert!(
+                super::parse_module_definition(source).is_err(),
+                "{} should be rejected",
+                source
+            );
+        }
+    }
+
+    /// Describe how a postfix chain nests, such as `lookup(call(a.b), c)`.
+    fn chain_shape(expression: &Expression) -> String {
+        let names = |SymbolLookup(names): &SymbolLookup| {
+            names
+                .iter()
+                .map(|name| name.0.as_str())
+                .collect::<Vec<_>>()
+                .join(".")
+        };
+        match expression {
+            Expression::Symbol(Symbol::Relative(lookup)) => names(lookup),
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(nodes::Call {
+                target: Symbol::Relative(lookup),
+                ..
+            })) => format!("call({})", names(lookup)),
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(call)) => {
+                format!("call({})", chain_shape(&call.target))
+            }
+            Expression::Operator(Operator::Lookup(receiver, member)) => {
+                format!("lookup({}, {})", chain_shape(receiver), member.0)
+            }
+            Expression::Operator(Operator::OptionalChain(receiver, member)) => {
+                format!("chain({}, {})", chain_shape(receiver), member.0)
+            }
+            Expression::Operator(Operator::MultiSlice(receiver, _)) => {
+                format!("slice({})", chain_shape(receiver))
+            }
+            other => panic!("unexpected in a chain: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn postfix_chains() {
+        let block = parse_main_block(
+            "package main
+            a.b().c
+            a.b().c[|0|].d()
+            f()()[|1 : 3|]
+            a.b(1).c?.d.e[Int]()
+            a.b.c",
+        );
+        let shapes = block
+            .expressions
+            .iter()
+            .map(chain_shape)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shapes,
+            vec![
+                "lookup(call(a.b), c)",
+                "call(lookup(slice(lookup(call(a.b), c)), d))",
+                "slice(call(call(f)))",
+                "call(lookup(chain(lookup(call(a.b), c), d), e))",
+                "a.b.c",
+            ]
+        );
+    }
+
+    #[test]
+    fn throws_with_causes() {
+        let block = parse_main_block(
+            "package main
+            throw failure
+            throw wrapped(failure) caused by original(1)",
+        );
+        let throws = block
+            .expressions
+            .iter()
+            .map(|expression| match expression {
+                Expression::Throw(throw) => throw,
+                other => panic!("expected a throw: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            *throws[0].exception,
+            Expression::Symbol(relative_symbol("failure"))
+        );
+        assert_eq!(throws[0].cause, None);
+
+        assert_eq!(
+            single_call(&throws[1].exception).target,
+            relative_symbol("wrapped")
+        );
+        let cause = throws[1].cause.as_ref().unwrap();
+        assert_eq!(single_call(cause).target, relative_symbol("original"));
+
+        assert!(parse_str("package main\nthrow failure caused original").is_err());
+    }
+
+    #[test]
+    fn select_cases_have_their_own_message_types() {
+        let block = parse_main_block(
+            "package main
+            select {
+                Deposit amount {
+                    amount
+                }
+                Withdrawal amount if amount > 0, _ {
+                    0
+                }
+                Reply[Int] _ {
+                    0
+                }
+            }",
+        );
+        let select = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Select(select)) => select,
+            other => panic!("expected a select: {:?}", other),
+        };
+
+        let types = select
+            .cases
+            .iter()
+            .map(|select_case| &select_case.message_type.symbol)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                &relative_symbol("Deposit"),
+                &relative_symbol("Withdrawal"),
+                &relative_symbol("Reply"),
+            ]
+        );
+        assert_eq!(select.cases[2].message_type.type_arguments.len(), 1);
+        assert_eq!(select.cases[0].case.matches.len(), 1);
+        assert_eq!(
+            select.cases[0].case.matches[0].pattern.item,
+            PatternItem::Identifier(Identifier::from("amount"))
+        );
+
+        // Later matches in the same case are patterns, not message types.
+        let second = &select.cases[1].case;
+        assert_eq!(second.matches.len(), 2);
+        assert!(second.matches[0].guard.is_some());
+        assert!(parse_str("package main\nselect {\n amount { amount }\n}").is_err());
+    }
+
+    #[test]
+    fn files_are_entry_points_only_with_top_level_code() {
+        match super::parse_file_str("package main\n final limit = 3\n var x = limit\n x + 1") {
+            Ok(nodes::FilePackage::EntryPoint(main)) => {
+                assert_eq!(main.package.items.len(), 1);
+                assert_eq!(main.block.bindings.len(), 1);
+                assert_eq!(main.block.expressions.len(), 1);
+            }
+            other => panic!("expected an entry point: {:?}", other),
+        }
+
+        match super::parse_file_str("package accounts\n final limit = 3\n class Ledger") {
+            Ok(nodes::FilePackage::Imported(package)) => {
+                assert_eq!(package.name, Identifier::from("accounts"));
+                assert_eq!(package.items.len(), 2);
+            }
+            other => panic!("expected an imported package: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn only_the_main_package_can_have_top_level_code() {
+        match super::parse_file_str("package accounts\n final limit = 3\n  limit + 1") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(description),
+                position: Some(position),
+            })) => {
+                assert_eq!(
+                    description,
+                    "only the main package can have top-level code, but accounts does"
+                );
+                assert_eq!(position.line(), 3);
+                assert_eq!(position.character_position_in_line(), 3);
+            }
+            other => panic!("expected top-level code to be rejected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spread_argument() {
+        let block = parse_main_block("package main\n f(1, ..xs)");
+
+        let arguments = &single_call(&block.expressions[0]).arguments.arguments;
+        assert_eq!(arguments.len(), 2);
+        assert!(!arguments[0].is_spread);
+        assert!(arguments[1].is_spread);
+        assert_eq!(
+            arguments[1].value,
+            Expression::Symbol(relative_symbol("xs"))
+        );
+    }
+
+    #[test]
+    fn spread_argument_must_be_last() {
+        assert!(parse_str("package main\n f(..xs, 1)").is_err());
+    }
+
+    #[test]
+    fn spread_argument_cannot_be_labelled() {
+        assert!(parse_str("package main\n f(of: ..xs)").is_err());
+    }
+
+    #[test]
+    fn value_position_if_requires_else() {
+        for source in &[
+            "package main\n var x = if a { 1 }",
+            "package main\n var x = if a { 1 } else if b { 2 }",
+            "package main\n f(if a { 1 })",
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert!(message.contains("`else`")),
+                other => panic!("expected a missing else error: {:?}", other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn guards_see_pattern_bindings() {
+        let block = parse_main_block(
+            "package main\n var limit = 10\n switch x { Some(y) if y < limit { f(y) }\n z { g(z) } }",
+        );
+        let switch = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch: {:?}", other),
+        };
+        assert!(switch.cases[0].matches[0].guard.is_some());
+        assert!(switch.cases[1].matches[0].guard.is_none());
+    }
+
+    #[test]
+    fn overlapping_switch_cases_keep_source_order() {
+        let block = parse_main_block(
+            "package main\n switch x { y if y > 1 { f() }\n z, w { g() }\n _ { h() } }",
+        );
+        let switch = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch: {:?}", other),
+        };
+        let patterns = switch
+            .cases
+            .iter()
+            .map(|case| {
+                case.matches
+                    .iter()
+                    .map(|case_match| case_match.pattern.item.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            patterns,
+            vec![
+                vec![nodes::PatternItem::Identifier(Identifier::from("y"))],
+                vec![
+                    nodes::PatternItem::Identifier(Identifier::from("z")),
+                    nodes::PatternItem::Identifier(Identifier::from("w")),
+                ],
+                vec![nodes::PatternItem::Ignored],
+            ]
+        );
+    }
+
+    #[test]
+    fn labelled_blocks() {
+        let block = parse_main_block(
+            "package main
+            var x = outer: {
+                var y = f()
+                if y > 0 {
+                    break outer y * 2
+                } else {
+                    y
+                }
+            }
+            done: {
+                break done
+            }",
+        );
+        let labelled = match &*block.bindings[0].value {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::LabelledBlock(
+                labelled,
+            )) => labelled,
+            other => panic!("expected a labelled block: {:?}", other),
+        };
+        assert_eq!(labelled.label, Identifier::from("outer"));
+        let then = &conditional(&labelled.scope.expressions[0]).then;
+        match &then.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Break(r#break)) => {
+                assert_eq!(r#break.label, Identifier::from("outer"));
+                assert!(matches!(
+                    r#break.value.as_deref(),
+                    Some(Expression::Operator(_))
+                ));
+            }
+            other => panic!("expected a break: {:?}", other),
+        }
+
+        match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::LabelledBlock(
+                labelled,
+            )) => {
+                assert!(matches!(
+                    &labelled.scope.expressions[0],
+                    Expression::BranchingAndJumping(nodes::BranchingAndJumping::Break(
+                        nodes::Break { value: None, .. }
+                    ))
+                ))
+            }
+            other => panic!("expected a labelled block: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn breaks_need_an_enclosing_labelled_block() {
+        for source in &[
+            "package main\n break nowhere 1",
+            "package main\n outer: { var f = -> { break outer 1 }\n f() }",
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert!(message.contains("isn't within a block labelled")),
+                other => panic!("expected an unknown label error: {:?}", other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn spawns() {
+        let block = parse_main_block(
+            "package main
+            var task = spawn -> {
+                var count = 1
+                send(count)
+            }
+            spawn(-> { 1 })
+            var spawn = 2
+            spawn -> 3",
+        );
+        match &*block.bindings[0].value {
+            Expression::Spawn(spawn) => {
+                assert_eq!(spawn.task.bindings.len(), 1);
+                assert_eq!(
+                    single_call(&spawn.task.expressions[0]).target,
+                    relative_symbol("send")
+                );
+            }
+            other => panic!("expected a spawn: {:?}", other),
+        }
+
+        // Elsewhere, `spawn` is just a name.
+        assert_eq!(
+            single_call(&block.expressions[0]).target,
+            relative_symbol("spawn")
+        );
+        assert_eq!(
+            block.bindings[1].pattern.bound_names(),
+            vec![Identifier::from("spawn")]
+        );
+        assert!(matches!(
+            &block.expressions[1],
+            Expression::Operator(Operator::OverloadableInfix(
+                _,
+                OverloadableInfixOperator::Cascade,
+                _
+            ))
+        ));
+
+        match parse_str("package main\n outer: { spawn -> { break outer 1 } }") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(message),
+                ..
+            })) => assert!(message.contains("isn't within a block labelled")),
+            other => panic!("expected an unknown label error: {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn switch_arms_bind_with_as() {
+        let block = parse_main_block(
+            "package main
+            switch shape {
+                Circle(r) as c { area(c, r) }
+                _ as other if describe(other) { 0 }
+            }",
+        );
+        let switch = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch: {:?}", other),
+        };
+        let pattern = &switch.cases[0].matches[0].pattern;
+        assert!(matches!(pattern.item, PatternItem::Composite(_)));
+        assert_eq!(
+            pattern.bound_match.as_deref(),
+            Some(&Pattern {
+                item: PatternItem::Identifier(Identifier::from("c")),
+                bound_match: None,
+            })
+        );
+        assert_eq!(
+            pattern.bound_names(),
+            vec![Identifier::from("r"), Identifier::from("c")]
+        );
+        assert!(switch.cases[1].matches[0].pattern.is_irrefutable());
+
+        match parse_str("package main\n switch shape { _ as 1 { 0 } }") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(message),
+                ..
+            })) => assert_eq!(message, "the pattern after `as` must match any value"),
+            other => panic!("expected a refutable `as` to be rejected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn switches_bind_their_scrutinee() {
+        let block = parse_main_block(
+            "package main
+            switch measure(x) as size {
+                0 { size }
+                n if size > n { n }
+            }",
+        );
+        let switch = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch: {:?}", other),
+        };
+        assert_eq!(switch.binding, Some(Identifier::from("size")));
+        assert_eq!(switch.cases.len(), 2);
+
+        let block = parse_main_block("package main\n switch x { _ { 0 } }");
+        match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => {
+                assert_eq!(switch.binding, None)
+            }
+            other => panic!("expected a switch: {:?}", other),
+        }
+        assert!(parse_str("package main\n switch x as (a, b) { _ { 0 } }").is_err());
+    }
+
+    #[test]
+    fn holes() {
+        use nodes::traversal::NodeRef;
+
+        let block = parse_main_block(
+            "package main
+            var area = -> { ??? }
+            f(???, 1 + ???)",
+        );
+        let holes = block
+            .bindings
+            .iter()
+            .map(NodeRef::Binding)
+            .chain(block.expressions.iter().map(NodeRef::Expression))
+            .flat_map(|root| root.descendants())
+            .filter_map(|node| match node {
+                NodeRef::Expression(Expression::Hole(position)) => position
+                    .map(|position| (position.line(), position.character_position_in_line())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(holes, vec![(2, 29), (3, 15), (3, 24)]);
+    }
+
+    #[test]
+    fn switch_arms_can_fall_through() {
+        let block = parse_main_block(
+            "package main
+            switch x {
+                1 {
+                    f()
+                    fallthrough
+                }
+                2 { g() }
+                _ { h() }
+            }",
+        );
+        let switch = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch: {:?}", other),
+        };
+        let falls_through = switch
+            .cases
+            .iter()
+            .map(nodes::Case::falls_through)
+            .collect::<Vec<_>>();
+        assert_eq!(falls_through, vec![true, false, false]);
+
+        for (source, error) in &[
+            (
+                "package main\n switch x { 1 { fallthrough\n f() }\n _ { g() } }",
+                "can only come at the end of a switch arm",
+            ),
+            (
+                "package main\n switch x { 1 { if y { fallthrough } else { f() } }\n _ { g() } }",
+                "can only come at the end of a switch arm",
+            ),
+            (
+                "package main\n switch x { 1 { f() }\n _ { fallthrough } }",
+                "no arm to fall through into",
+            ),
+            (
+                "package main\n switch x { 1 { fallthrough }\n y { g(y) } }",
+                "binds names",
+            ),
+            (
+                "package main\n for var n = 0 { fallthrough }",
+                "can only be used in a switch arm",
+            ),
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert!(message.contains(error), "{}", message),
+                other => panic!("expected a fallthrough error: {:?}", other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn retroactive_interface_implementations() {
+        let file = parse_str(
+            "package main
+            extend Account : Printable, Comparable[Account] {
+                fun describe() String {
+                    \"an account\"
+                }
+                fun override compare(other Account) Number { 0 }
+            }",
+        )
+        .unwrap();
+        let extension = match &file.package.package.items[0] {
+            Item::Extension(extension) => extension,
+            other => panic!("expected an extension: {:?}", other),
+        };
+        assert_eq!(extension.symbol, relative_symbol("Account"));
+
+        let implements = extension
+            .item
+            .implements
+            .iter()
+            .map(|reference| (reference.symbol.clone(), reference.type_arguments.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            implements,
+            vec![
+                (relative_symbol("Printable"), 0),
+                (relative_symbol("Comparable"), 1)
+            ]
+        );
+
+        let methods = extension
+            .item
+            .methods
+            .iter()
+            .map(|method| {
+                let r#abstract = &method.r#abstract;
+                (
+                    r#abstract.signature.name.0.as_str(),
+                    r#abstract.signature.value_parameters.len(),
+                    r#abstract.signature.return_type.is_some(),
+                    r#abstract.modifiers.overrides,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            vec![("describe", 0, true, false), ("compare", 1, true, true)]
+        );
+    }
+
+    #[test]
+    fn associated_constants() {
+        let file = parse_str(
+            "package main
+            class Limits {
+                final MAX Int = 100
+                var current = 0
+                final describe = -> { \"limits\" }
+                fun report() String {
+                    \"within limits\"
+                }
+            }",
+        )
+        .unwrap();
+        let class = match &file.package.package.items[0] {
+            Item::Type(nodes::Type {
+                item: nodes::TypeItem::Class(class),
+                ..
+            }) => class,
+            other => panic!("expected a class: {:?}", other),
+        };
+
+        let fields = class
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    field.binding.pattern.bound_names(),
+                    field.is_final,
+                    field.is_associated_constant(),
+                    field.accessibility.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    vec![Identifier::from("MAX")],
+                    true,
+                    true,
+                    Accessibility::Private
+                ),
+                (
+                    vec![Identifier::from("current")],
+                    false,
+                    false,
+                    Accessibility::Private
+                ),
+                (
+                    vec![Identifier::from("describe")],
+                    true,
+                    false,
+                    Accessibility::Private
+                ),
+            ]
+        );
+        assert!(class.fields[0].binding.explicit_type_annotation.is_some());
+
+        assert_eq!(class.methods.len(), 1);
+        assert_eq!(
+            class.methods[0].r#abstract.signature.name,
+            Identifier::from("report")
+        );
+    }
+
+    #[test]
+    fn class_value_parameters() {
+        let file = parse_str(
+            "package main
+            class Point(x Int, var public y Int, var z Int) {
+                final internal origin = 0
+            }",
+        )
+        .unwrap();
+        let class = match &file.package.package.items[0] {
+            Item::Type(nodes::Type {
+                item: nodes::TypeItem::Class(class),
+                ..
+            }) => class,
+            other => panic!("expected a class: {:?}", other),
+        };
+
+        let upgrades = class
+            .value_parameters
+            .iter()
+            .map(|parameter| {
+                parameter
+                    .field_upgrade
+                    .as_ref()
+                    .map(|upgrade| upgrade.accessibility.clone())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            upgrades,
+            vec![
+                None,
+                Some(Accessibility::Public),
+                Some(Accessibility::Private)
+            ]
+        );
+        assert_eq!(class.fields[0].accessibility, Accessibility::Internal);
+    }
+
+    #[test]
+    fn arrow_return_types() {
+        let methods = |source| match parse_str(source).unwrap().package.package.items.remove(0) {
+            Item::Type(nodes::Type {
+                item: nodes::TypeItem::Class(class),
+                ..
+            }) => class.methods,
+            other => panic!("expected a class: {:?}", other),
+        };
+
+        let arrow = methods("package main\n class Maths { fun double(x Int) -> Int { x + x } }");
+        let juxtaposed = methods("package main\n class Maths { fun double(x Int) Int { x + x } }");
+        assert_eq!(arrow[0].r#abstract, juxtaposed[0].r#abstract);
+        assert_eq!(arrow[0].scope.expressions, juxtaposed[0].scope.expressions);
+        assert!(arrow[0].r#abstract.signature.return_type.is_some());
+
+        assert!(
+            parse_str("package main\n class Maths { fun double(x Int) Int -> Int { x } }").is_err()
+        );
+        assert!(parse_str("package main\n class Maths { fun double(x Int) -> { x } }").is_err());
+    }
+
+    #[test]
+    fn interface_methods_without_bodies_are_abstract() {
+        let source = "package main
+            interface Shape extends Printable {
+                fun area() Float
+                fun reset()
+                fun describe() String { \"a shape\" }
+            }";
+        let interface = match parse_str(source).unwrap().package.package.items.remove(0) {
+            Item::Type(nodes::Type {
+                item: nodes::TypeItem::Interface(interface),
+                ..
+            }) => interface,
+            other => panic!("expected an interface: {:?}", other),
+        };
+        assert_eq!(
+            interface.extends,
+            vec![TypeReference::new(relative_symbol("Printable"))]
+        );
+
+        match &interface.methods[..] {
+            [Method::Abstract(area), Method::Abstract(reset), Method::Concrete(describe)] => {
+                assert_eq!(area.signature.name, Identifier::from("area"));
+                assert!(area.signature.return_type.is_some());
+                assert!(reset.signature.return_type.is_none());
+                assert_eq!(
+                    describe.r#abstract.signature.name,
+                    Identifier::from("describe")
+                );
+            }
+            other => panic!("expected two abstract methods and a default: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn extern_funs_take_calling_conventions() {
+        let source = "package main
+            extern \"C\" fun strlen(text String) Int
+            extern \"system\" fun beep(frequency Int, duration Int) -> Boolean
+            extern fun abort()
+            fun double(x Int) Int { x * 2 }
+            abort";
+        let file = parse_str(source).unwrap();
+        let funs = file
+            .package
+            .package
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Fun(fun) => (
+                    fun.signature.name.0.as_str(),
+                    fun.modifiers.r#extern,
+                    fun.signature.return_type.is_some(),
+                    fun.block.expressions.len(),
+                ),
+                other => panic!("expected a fun: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            funs,
+            vec![
+                ("strlen", Some(CallingConvention::C), true, 0),
+                ("beep", Some(CallingConvention::System), true, 0),
+                ("abort", Some(CallingConvention::C), false, 0),
+                ("double", None, true, 1),
+            ]
+        );
+
+        // The symbol after `abort` is on the next line, so it isn't taken as a
+        // return type.
+        assert_eq!(
+            file.package.block.expressions,
+            vec![Expression::Symbol(relative_symbol("abort"))]
+        );
+    }
+
+    #[test]
+    fn unknown_calling_conventions() {
+        match parse_str("package main\n extern \"fastcall\" fun f()") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(description),
+                ..
+            })) => assert_eq!(
+                description,
+                "unknown calling convention \"fastcall\"; expected \"C\" or \"system\""
+            ),
+            other => panic!("expected an unknown calling convention: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn concrete_classes_reject_methods_without_bodies() {
+        let source = "package main
+            class Square {
+                fun area() Float
+            }";
+        match parse_str(source) {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(description),
+                ..
+            })) => assert_eq!(
+                description,
+                "`area` has no body, but only interfaces can declare methods without one"
+            ),
+            other => panic!("expected a missing body to be rejected: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn range_patterns() {
+        let block = parse_main_block(
+            "package main\n switch x { 1..10 { f() }\n 'a'..'z' { g() }\n _ { h() } }",
+        );
+        let switch = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch: {:?}", other),
+        };
+        let number = |n| nodes::Literal::Number(multiphase::Number(n, 0), None);
+        assert_eq!(
+            switch.cases[0].matches[0].pattern.item,
+            nodes::PatternItem::Range(nodes::RangePattern {
+                start: number(1),
+                end: number(10),
+            })
+        );
+        assert_eq!(
+            switch.cases[1].matches[0].pattern.item,
+            nodes::PatternItem::Range(nodes::RangePattern {
+                start: nodes::Literal::Char('a'),
+                end: nodes::Literal::Char('z'),
+            })
+        );
+
+        match parse_str("package main\n switch x { 1..'z' { f() } }") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(message),
+                ..
+            })) => assert!(message.contains("two numbers or two chars")),
+            other => panic!("expected a mismatched range error: {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn overlapping_cond_arms_keep_source_order() {
+        let block = parse_main_block("package main\n switch { a, b { f() }\n a { g() } }");
+        let cond = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Cond(cond)) => cond,
+            other => panic!("expected a cond: {:?}", other),
+        };
+        let conditions = cond
+            .cases
+            .iter()
+            .map(|case| case.conditions.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conditions,
+            vec![
+                vec![
+                    Expression::Symbol(relative_symbol("a")),
+                    Expression::Symbol(relative_symbol("b")),
+                ],
+                vec![Expression::Symbol(relative_symbol("a"))],
+            ]
+        );
+    }
+
+    #[test]
+    fn cond_else_arms() {
+        let block = parse_main_block("package main\n switch { a { f() }\n else { g() } }");
+        let cond = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Cond(cond)) => cond,
+            other => panic!("expected a cond: {:?}", other),
+        };
+        assert_eq!(cond.cases.len(), 1);
+        assert_eq!(cond.else_clause.as_ref().unwrap().expressions.len(), 1);
+
+        match parse_str("package main\n switch { else { g() }\n a { f() } }") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(description),
+                ..
+            })) => assert_eq!(description, "an else arm must be the last arm of a cond"),
+            other => panic!("expected a misplaced else error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn identifiers_have_their_own_spans() {
+        let file = parse_str("package main\nclass Account\n  account.balance").unwrap();
+        let name = match &file.package.package.items[0] {
+            Item::Type(nodes::Type { name, .. }) => name,
+            other => panic!("expected a class: {:?}", other),
+        };
+        let name_span = name.span().unwrap();
+        assert_eq!(
+            (
+                name_span.start.line(),
+                name_span.start.character_position_in_line()
+            ),
+            (2, 7)
+        );
+
+        let lookup = match &file.package.block.expressions[0] {
+            Expression::Symbol(Symbol::Relative(SymbolLookup(lookup))) => lookup,
+            other => panic!("expected a lookup: {:?}", other),
+        };
+        let positions = lookup
+            .iter()
+            .map(|identifier| {
+                let span = identifier.span().unwrap();
+                assert_eq!(span.start, span.end);
+                (span.start.line(), span.start.character_position_in_line())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(3, 3), (3, 11)]);
+
+        // Spans don't affect equality.
+        assert_eq!(lookup[0], Identifier::from("account"));
+    }
+
+    #[test]
+    fn adjacent_strings_are_concatenated() {
+        let block = parse_main_block(
+            "package main
+            var greeting = \"Hello, \"
+                \"world\" \"!\"
+            greeting",
+        );
+        assert_eq!(
+            *block.bindings[0].value,
+            Expression::Literal(nodes::Literal::String(multiphase::SylanString::from(
+                "Hello, world!"
+            )))
+        );
+        assert_eq!(block.expressions.len(), 1);
+    }
+
+    #[test]
+    fn interpolated_strings_are_not_concatenated() {
+        let block = parse_main_block("package main\n \"Hello, \" $\"{name}\" \"!\"");
+        let literals = block
+            .expressions
+            .iter()
+            .map(|expression| match expression {
+                Expression::Literal(nodes::Literal::String(string)) => string.0.as_str(),
+                Expression::Literal(nodes::Literal::InterpolatedString(_)) => "<interpolated>",
+                other => panic!("expected a string: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(literals, vec!["Hello, ", "<interpolated>", "!"]);
+    }
+
+    #[test]
+    fn bodyless_cond_arms() {
+        let block = parse_main_block(
+            "package main
+            switch {
+                a, b:
+                c > d ? e : f:
+                g { h }
+            }",
+        );
+        let cond = match &block.expressions[0] {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Cond(cond)) => cond,
+            other => panic!("expected a cond: {:?}", other),
+        };
+        let arms = cond
+            .cases
+            .iter()
+            .map(|case| (case.conditions.len(), case.then.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(arms, vec![(2, false), (1, false), (1, true)]);
+        assert!(matches!(
+            cond.cases[1].conditions[0],
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(_))
+        ));
+    }
+
+    #[test]
+    fn guards_reject_unknown_names() {
+        for source in &[
+            "package main\n switch x { Some(y) if z < 0 { f(y) } }",
+            "package main\n switch x { Some(y) { f(y) }\n Other(o) if y < 0 { f(o) } }",
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert!(message.starts_with("unknown name in guard: ")),
+                other => panic!("expected an unknown name error: {:?}", other.err()),
+            }
+        }
+    }
+
+    fn single_expression(block: &Block) -> &Expression {
+        assert!(block.bindings.is_empty());
+        assert_eq!(block.expressions.len(), 1);
+        &block.expressions[0]
+    }
+
+    fn conditional(expression: &Expression) -> &If {
+        match expression {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(if_node)) => if_node,
+            other => panic!("expected a conditional: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn conditionals_desugar_to_ifs() {
+        let block = parse_main_block("package main\n var x = a == b ? 1 : f(c)");
+        let if_node = conditional(&block.bindings[0].value);
+
+        match &*if_node.condition {
+            Expression::Operator(Operator::OverloadableInfix(left, operator, right)) => {
+                assert_eq!(**left, Expression::Symbol(relative_symbol("a")));
+                assert_eq!(*operator, OverloadableInfixOperator::Equals);
+                assert_eq!(**right, Expression::Symbol(relative_symbol("b")));
+            }
+            other => panic!(
+                "expected the whole comparison as the condition: {:?}",
+                other
+            ),
+        }
+        assert_eq!(
+            *single_expression(&if_node.then),
+            Expression::Literal(nodes::Literal::Number(multiphase::Number(1, 0), None))
+        );
+        assert!(matches!(
+            single_expression(if_node.else_clause.as_ref().unwrap()),
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(..))
+        ));
+    }
+
+    #[test]
+    fn conditionals_nest_to_the_right() {
+        let block = parse_main_block("package main\n var x = a ? b : c ? d : e");
+        let outer = conditional(&block.bindings[0].value);
+        assert_eq!(*outer.condition, Expression::Symbol(relative_symbol("a")));
+
+        let inner = conditional(single_expression(outer.else_clause.as_ref().unwrap()));
+        assert_eq!(*inner.condition, Expression::Symbol(relative_symbol("c")));
+    }
+
+    #[test]
+    fn bind_operator_is_not_a_conditional() {
+        let block = parse_main_block("package main\n var x = f(y?)");
+        let call = match &*block.bindings[0].value {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => call,
+            other => panic!("expected a call: {:?}", other),
+        };
+        assert!(matches!(
+            call.arguments.arguments[0].value,
+            Expression::Operator(Operator::Postfix(_, multiphase::PostfixOperator::Bind))
+        ));
+    }
+
+    #[test]
+    fn force_unwraps() {
+        let block = parse_main_block("package main\n var x = f(y!)\n x!");
+        let call = match &*block.bindings[0].value {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => call,
+            other => panic!("expected a call: {:?}", other),
+        };
+        assert!(matches!(
+            call.arguments.arguments[0].value,
+            Expression::Operator(Operator::Postfix(
+                _,
+                multiphase::PostfixOperator::ForceUnwrap
+            ))
+        ));
+        assert!(matches!(
+            block.expressions[0],
+            Expression::Operator(Operator::Postfix(
+                _,
+                multiphase::PostfixOperator::ForceUnwrap
+            ))
+        ));
+    }
+
+    #[test]
+    fn tail_position_ifs_yield_their_blocks_value() {
+        for source in &[
+            "package main\n var x = with { f()\n if a { 1 } }",
+            "package main\n var x = if a { if b { 1 } } else { 2 }",
+            "package main\n var x = switch y { 1 { 2 }\n _ { if a { 3 } } }",
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert!(message.contains("`else`")),
+                other => panic!("expected a missing else error: {:?}", other.err()),
+            }
+        }
+
+        let block = parse_main_block(
+            "package main
+            var x = with { f()\n if a { 1 } else { 2 } }
+            with { f()\n if a { g() } }",
+        );
+        assert_eq!(block.bindings.len(), 1);
+        assert_eq!(block.expressions.len(), 1);
+    }
+
+    #[test]
+    fn value_position_if_with_else() {
+        let block =
+            parse_main_block("package main\n var x = if a { 1 } else if b { 2 } else { 3 }");
+        assert_eq!(block.bindings.len(), 1);
+    }
+
+    #[test]
+    fn statement_position_if_without_else() {
+        let block = parse_main_block("package main\n if a { f() }");
+        match &block.expressions[..] {
+            [Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(if_node))] => {
+                assert!(if_node.else_clause.is_none());
+            }
+            other => panic!("expected a single if: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn comments_are_associated_with_bindings() {
+        let source = "package main\n\n// The answer.\n/* Really. */\nvar x = 42 // Trailing.\n";
+        let tokens = Tokens::from(Lexer::from(Source::from(
+            source.chars().collect::<Vec<char>>(),
+        )));
+        let file = Parser::from(tokens.unwrap())
+            .retaining_comments()
+            .parse()
+            .unwrap();
+
+        let comments = &file.package.block.bindings[0].comments;
+        assert_eq!(comments.leading, vec!["// The answer.", "/* Really. */"]);
+        assert_eq!(comments.trailing, vec!["// Trailing."]);
+    }
+
+    #[test]
+    fn comments_are_dropped_by_default() {
+        let block = parse_main_block("package main\n// The answer.\nvar x = 42");
+        assert_eq!(block.bindings[0].comments, Default::default());
+    }
+
+    #[test]
+    fn trailing_sydocs_document_bindings() {
+        let file = parse_str(
+            "package main
+            var x = 42 /** The answer. */
+            var y = x
+            final z = 1
+            /** Documented on the next line. */
+            f(y)",
+        )
+        .unwrap();
+
+        let bindings = &file.package.block.bindings;
+        assert_eq!(
+            bindings[0].sydoc,
+            Some(multiphase::SyDoc::from(" The answer. "))
+        );
+        assert_eq!(bindings[1].sydoc, None);
+        match &file.package.package.items[0] {
+            Item::Final(binding) => assert_eq!(
+                binding.sydoc,
+                Some(multiphase::SyDoc::from(" Documented on the next line. "))
+            ),
+            other => panic!("expected a final binding: {:?}", other),
+        }
+        assert_eq!(file.package.block.expressions.len(), 1);
+
+        assert!(parse_str("package main\n f(1) /** Not a binding. */").is_err());
+    }
+
+    #[test]
+    fn accessibility_modifiers() {
+        let file = parse_str("package main\n fun public a() {}\n fun internal b() {}\n fun c() {}")
+            .unwrap();
+        let accessibilities = file
+            .package
+            .package
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Fun(fun) => fun.modifiers.accessibility.clone(),
+                other => panic!("expected a fun: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            accessibilities,
+            vec![
+                Accessibility::Public,
+                Accessibility::Internal,
+                Accessibility::Private
+            ]
+        );
+    }
+
+    #[test]
+    fn const_bindings() {
+        let file = parse_str(
+            "package main\n const size = 4 * 1024\n const limits = #{ max: size + 1 }\n final x = f()",
+        )
+        .unwrap();
+        let items = &file.package.package.items;
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[0], Item::Const(..)));
+        assert!(matches!(items[1], Item::Const(..)));
+        assert!(matches!(items[2], Item::Final(..)));
+    }
+
+    #[test]
+    fn const_bindings_need_constant_values() {
+        for source in &[
+            "package main\n const x = f()",
+            "package main\n const x = y + 1",
+            "package main\n final y = 1\n const x = y",
+        ] {
+            match parse_str(source) {
+                Err(Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(message),
+                    ..
+                })) => assert!(message.starts_with("a const must be bound to a constant")),
+                other => panic!("expected a non-constant error: {:?}", other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn grouped_import_renames() {
+        let file = parse_str("package main (a.{b as x, c as y})").unwrap();
+        let imports = &file.package.package.imports;
+        assert_eq!(imports.len(), 1);
+        assert_eq!(
+            imports[0].root,
+            Some(Symbol::Relative(SymbolLookup(vec![Identifier::from("a")])))
+        );
+
+        let stems = match &imports[0].stem {
+            nodes::ImportStem::Multiple(stems) => stems,
+            other => panic!("expected a grouped import: {:?}", other),
+        };
+        let renames = stems
+            .iter()
+            .map(|import| match &import.stem {
+                nodes::ImportStem::Single(nodes::ImportSingleStem { name, alias, .. }) => {
+                    (name.clone(), alias.clone())
+                }
+                other => panic!("expected a single import: {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            renames,
+            vec![
+                (Identifier::from("b"), Some(Identifier::from("x"))),
+                (Identifier::from("c"), Some(Identifier::from("y"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn glob_imports() {
+        let file = parse_str("package main (a.b.*, c.{d.*, e})").unwrap();
+        let imports = &file.package.package.imports;
+        assert_eq!(imports.len(), 2);
+        assert_eq!(
+            imports[0],
+            nodes::Import {
+                root: Some(Symbol::Relative(SymbolLookup(vec![
+                    Identifier::from("a"),
+                    Identifier::from("b"),
+                ]))),
+                stem: nodes::ImportStem::Glob,
+                is_reexported: false,
+            }
+        );
+
+        let stems = match &imports[1].stem {
+            nodes::ImportStem::Multiple(stems) => stems,
+            other => panic!("expected a grouped import: {:?}", other),
+        };
+        assert_eq!(stems[0].stem, nodes::ImportStem::Glob);
+        assert_eq!(stems[0].root, Some(relative_symbol("d")));
+        assert!(matches!(stems[1].stem, nodes::ImportStem::Single(_)));
+
+        assert!(parse_str("package main (a.* as b)").is_err());
+        assert!(parse_str("package main (*)").is_err());
+    }
+
+    #[test]
+    fn reexported_imports() {
+        let file = parse_str(
+            "package facade (exports geometry.Point, shapes.{exports Circle, Square}, io.write)",
+        )
+        .unwrap();
+        let imports = &file.package.package.imports;
+        assert!(imports[0].is_reexported);
+        assert!(!imports[2].is_reexported);
+        let stems = match &imports[1].stem {
+            nodes::ImportStem::Multiple(stems) => stems,
+            other => panic!("expected a grouped import: {:?}", other),
+        };
+        assert!(!imports[1].is_reexported);
+        assert!(stems[0].is_reexported);
+        assert!(!stems[1].is_reexported);
+
+        assert!(parse_str("package facade (exports geometry.*)").is_err());
+    }
+
+    #[test]
+    fn grouped_imports_cannot_be_renamed_as_a_whole() {
+        assert!(parse_str("package main (a.{b, c} as x)").is_err());
+        assert!(parse_str("package main (a as x.{b, c})").is_err());
+    }
+
+    #[test]
+    fn record_literal() {
+        let block = parse_main_block("package main\n var point = #{ x: 1, y: 2 }\n f(point.x)");
+
+        let fields = match &*block.bindings[0].value {
+            Expression::Literal(nodes::Literal::Record(nodes::Record(fields))) => fields,
+            other => panic!("expected a record: {:?}", other),
+        };
+        let names = fields
+            .iter()
+            .map(|field| field.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![Identifier::from("x"), Identifier::from("y")]);
+
+        let call = single_call(&block.expressions[0]);
+        assert_eq!(
+            call.arguments.arguments[0].value,
+            Expression::Symbol(Symbol::Relative(SymbolLookup(vec![
+                Identifier::from("point"),
+                Identifier::from("x"),
+            ])))
+        );
+    }
+
+    #[test]
+    fn record_literal_fields_are_unique() {
+        assert!(parse_str("package main\n var point = #{ x: 1, x: 2 }").is_err());
+    }
+
+    #[test]
+    fn every_lexing_error_is_reported() {
+        match parse_str("package main\n var x = '\\q'\n var y = \u{7}\n") {
+            Err(Error::Lexer(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("expected lexing errors: {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn quoted_keywords_bind_as_names() {
+        let block = parse_main_block("package main\n var `class` = 1\n f(`class`)");
+        assert_eq!(
+            block.bindings[0].pattern.item,
+            nodes::PatternItem::Identifier(Identifier::from("class"))
+        );
+    }
+
+    #[test]
+    fn parses_a_lone_expression() {
+        let expression = super::parse_expression("1 + f(x) * 2").unwrap();
+        match expression {
+            Expression::Operator(Operator::OverloadableInfix(left, operator, right)) => {
+                assert_eq!(
+                    *left,
+                    Expression::Literal(nodes::Literal::Number(multiphase::Number(1, 0), None))
+                );
+                assert_eq!(operator, OverloadableInfixOperator::Add);
+                assert!(matches!(
+                    *right,
+                    Expression::Operator(Operator::OverloadableInfix(
+                        _,
+                        OverloadableInfixOperator::Multiply,
+                        _
+                    ))
+                ));
+            }
+            other => panic!("expected an addition: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn infix_operators_climb_by_precedence() {
+        let hash = |expression: &str| {
+            hash_main_file(&parse_str(&format!("package main\nvar _ = {}", expression)).unwrap())
+        };
+        let same_shape = |source: &str, grouped: &str| hash(source) == hash(grouped);
+        assert!(same_shape("a - b - c", "(a - b) - c"));
+        assert!(!same_shape("a - b - c", "a - (b - c)"));
+        assert!(same_shape("a + b == c", "(a + b) == c"));
+        assert!(same_shape("a * b + c * d", "(a * b) + (c * d)"));
+        assert!(same_shape("a || b && c == d", "a || (b && (c == d))"));
+        assert!(same_shape("a ** b ** c", "a ** (b ** c)"));
+    }
+
+    #[test]
+    fn single_expression_rejects_trailing_tokens() {
+        match super::parse_expression("1 + 2 )") {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Unexpected(token),
+                ..
+            })) => assert_eq!(token, Token::Grouping(Grouping::CloseParentheses)),
+            other => panic!("expected trailing tokens to be rejected: {:?}", other),
+        }
+        assert!(super::parse_expression("").is_err());
+    }
+
+    #[test]
+    fn type_member_references() {
+        let lookup = |names: &[&'static str]| {
+            SymbolLookup(names.iter().map(|&name| Identifier::from(name)).collect())
+        };
+        let block = parse_main_block(
+            "package main
+            var parse = Int.parse
+            var handle = :Int.parse
+            var qualified = sylan.lang.Int.parse
+            var field = point.x
+            var variant = Option.Some",
+        );
+        let values = block
+            .bindings
+            .iter()
+            .map(|binding| (*binding.value).clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Expression::Symbol(Symbol::TypeMember(
+                    lookup(&["Int"]),
+                    Identifier::from("parse")
+                )),
+                Expression::MemberHandle(Symbol::TypeMember(
+                    lookup(&["Int"]),
+                    Identifier::from("parse")
+                )),
+                Expression::Symbol(Symbol::TypeMember(
+                    lookup(&["sylan", "lang", "Int"]),
+                    Identifier::from("parse")
+                )),
+                Expression::Symbol(Symbol::Relative(lookup(&["point", "x"]))),
+                Expression::Symbol(Symbol::Relative(lookup(&["Option", "Some"]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        assert_eq!(
+            super::parse_expression("(1)").unwrap(),
+            Expression::Literal(nodes::Literal::Number(multiphase::Number(1, 0), None))
+        );
+        match super::parse_expression("(1, x,)").unwrap() {
+            Expression::Literal(nodes::Literal::Tuple(nodes::Tuple(elements))) => {
+                assert_eq!(elements.len(), 2)
+            }
+            other => panic!("expected a tuple: {:?}", other),
+        }
+
+        let block = parse_main_block("package main\n var (a, _) = (1, 2)");
+        let pattern = &block.bindings[0].pattern;
+        assert!(matches!(&pattern.item, PatternItem::Tuple(elements) if elements.len() == 2));
+        assert_eq!(pattern.bound_names(), vec![Identifier::from("a")]);
+    }
+
+    #[test]
+    fn operator_references() {
+        let reference = |operator| Expression::Operator(Operator::Reference(operator));
+        assert_eq!(
+            super::parse_expression("(+)").unwrap(),
+            reference(OverloadableInfixOperator::Add)
+        );
+        assert_eq!(
+            super::parse_expression("(*)").unwrap(),
+            reference(OverloadableInfixOperator::Multiply)
+        );
+
+        match super::parse_expression("xs.reduce((+))").unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => {
+                assert_eq!(
+                    call.arguments.arguments[0].value,
+                    reference(OverloadableInfixOperator::Add)
+                );
+            }
+            other => panic!("expected a call: {:?}", other),
+        }
+
+        // Operators with operands are still grouped as usual.
+        assert!(matches!(
+            super::parse_expression("(a + b)").unwrap(),
+            Expression::Operator(Operator::OverloadableInfix(..))
+        ));
+        assert!(super::parse_expression("(+ b)").is_err());
+    }
+
+    #[test]
+    fn list_literals() {
+        let list = |source: &str| match super::parse_expression(source).unwrap() {
+            Expression::Literal(nodes::Literal::List(list)) => list,
+            other => panic!("expected a list: {:?}", other),
+        };
+        let type_named = |name: &'static str| {
+            TypeExpression::Reference(TypeReference::new(Symbol::Relative(SymbolLookup(vec![
+                Identifier::from(name),
+            ]))))
+        };
+
+        let immutable = list("[1, 2, 3]");
+        assert_eq!(immutable.elements.len(), 3);
+        assert!(!immutable.mutable);
+        assert_eq!(immutable.inferred_type(), type_named("List"));
+
+        let mutable = list("var [1, 2,]");
+        assert_eq!(mutable.elements.len(), 2);
+        assert_eq!(mutable.inferred_type(), type_named("MutableList"));
+
+        assert!(list("[]").elements.is_empty());
+        assert!(super::parse_expression("[1, 2").is_err());
+
+        let block = parse_main_block("package main\n var xs = [a, f(b), [c]]");
+        assert!(matches!(
+            &*block.bindings[0].value,
+            Expression::Literal(nodes::Literal::List(list)) if list.elements.len() == 3
+        ));
+    }
+
+    #[test]
+    fn slice_patterns() {
+        let slice = |source: &str| match parse_main_block(source).bindings.remove(0).pattern.item {
+            PatternItem::Slice(slice) => slice,
+            other => panic!("expected a slice pattern: {:?}", other),
+        };
+        let identifier = |name: &'static str| Pattern {
+            item: PatternItem::Identifier(Identifier::from(name)),
+            bound_match: None,
+        };
+
+        let head_and_rest = slice("package main\n var [first, ..rest] = xs");
+        assert_eq!(head_and_rest.leading, vec![identifier("first")]);
+        assert_eq!(head_and_rest.rest, Some(Box::new(identifier("rest"))));
+        assert!(head_and_rest.trailing.is_empty());
+
+        let middle_rest = slice("package main\n var [first, ..middle, last] = xs");
+        assert_eq!(middle_rest.leading, vec![identifier("first")]);
+        assert_eq!(middle_rest.rest, Some(Box::new(identifier("middle"))));
+        assert_eq!(middle_rest.trailing, vec![identifier("last")]);
+
+        let ignored_rest = slice("package main\n var [.., last] = xs");
+        assert!(ignored_rest.leading.is_empty());
+        assert!(matches!(
+            ignored_rest.rest.as_deref(),
+            Some(Pattern {
+                item: PatternItem::Ignored,
+                ..
+            })
+        ));
+
+        let exact = slice("package main\n var [a, b] = xs");
+        assert_eq!(exact.leading.len(), 2);
+        assert_eq!(exact.rest, None);
+
+        assert!(parse_str("package main\n var [a, ..b, ..c] = xs").is_err());
+    }
+
+    #[test]
+    fn composite_pattern_rest_bindings() {
+        let composite =
+            |source: &str| match parse_main_block(source).bindings.remove(0).pattern.item {
+                PatternItem::Composite(composite) => composite,
+                other => panic!("expected a composite pattern: {:?}", other),
+            };
+
+        let bound = composite("package main\n var Point(x, ..others) = p");
+        assert_eq!(bound.getters.len(), 1);
+        assert!(bound.ignore_rest);
+        assert_eq!(bound.rest_binding, Some(Identifier::from("others")));
+
+        let ignored = composite("package main\n var Point(x, ..) = p");
+        assert!(ignored.ignore_rest);
+        assert_eq!(ignored.rest_binding, None);
+        assert_ne!(bound, ignored);
+
+        let pattern = parse_main_block("package main\n var Point(x, ..others) = p")
+            .bindings
+            .remove(0)
+            .pattern;
+        assert_eq!(
+            pattern.bound_names(),
+            vec![Identifier::from("x"), Identifier::from("others")]
+        );
+    }
+
+    #[test]
+    fn interactive_lines_are_bindings_or_expressions() {
+        let item = match super::parse_interactive("var add = -> x, y { x + y }") {
+            Ok(nodes::Node::Item(item)) => *item,
+            other => panic!("expected an item: {:?}", other),
+        };
+        match item {
+            Item::Var(binding) => match *binding.value {
+                Expression::Literal(nodes::Literal::Lambda(lambda)) => {
+                    assert_eq!(lambda.signature.value_parameters.len(), 2)
+                }
+                other => panic!("expected a lambda: {:?}", other),
+            },
+            other => panic!("expected a var binding: {:?}", other),
+        }
+        assert!(matches!(
+            super::parse_interactive("add(1, 2)"),
+            Ok(nodes::Node::Expression(_))
+        ));
+    }
+
+    #[test]
+    fn empty_file() {
+        let file = parse_str("").unwrap();
+        assert!(file.shebang.is_none());
+        assert!(file.version.is_none());
+        assert!(file.package.package.items.is_empty());
+        assert!(file.package.block.bindings.is_empty());
+        assert!(file.package.block.expressions.is_empty());
+    }
+
+    #[test]
+    fn whitespace_and_comments_only_file() {
+        let file = parse_str("  \n\t// Nothing to see here.\n/* Nor /* here */. */\n\n").unwrap();
+        assert!(file.package.package.items.is_empty());
+        assert!(file.package.block.expressions.is_empty());
+    }
+
+    #[test]
+    fn errors_are_stamped_with_positions() {
+        match parse_str("package main\n f(..xs, 1)") {
+            Err(Error::Parser(ParserError {
+                position: Some(position),
+                ..
+            })) => {
+                assert_eq!(position.line(), 2);
+                assert_eq!(position.character_position_in_line(), 8);
+            }
+            other => panic!("expected a positioned parser error: {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn errors_become_diagnostics() {
+        use crate::common::diagnostics::Severity;
+
+        let summary = |diagnostics: &[Diagnostic]| {
+            diagnostics
+                .iter()
+                .map(|diagnostic| {
+                    let start = diagnostic.span.unwrap().start;
+                    (
+                        diagnostic.severity,
+                        diagnostic.code,
+                        start.line(),
+                        start.character_position_in_line(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut diagnostics = parse_str("package main\n var x = '\\q'\n var y = \u{7}\n")
+            .unwrap_err()
+            .diagnostics();
+        assert_eq!(
+            summary(&diagnostics),
+            vec![
+                (Severity::Error, "lexer", 2, 13),
+                (Severity::Error, "unexpected-char", 3, 10),
+            ]
+        );
+
+        let parsed = parse_str("package main\n f(..xs, 1)")
+            .unwrap_err()
+            .diagnostics();
+        assert_eq!(summary(&parsed), vec![(Severity::Error, "parser", 2, 8)]);
+
+        diagnostics.extend(parsed);
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error));
+    }
+
+    #[test]
+    fn checking_gathers_lints_and_errors_in_source_order() {
+        use crate::common::diagnostics::Severity;
+
+        let summary = |source| {
+            check_str(source, &[Identifier::from("f"), Identifier::from("xs")])
+                .iter()
+                .map(|diagnostic| {
+                    let span = diagnostic.span.unwrap();
+                    (
+                        diagnostic.severity,
+                        diagnostic.code,
+                        span.start.line(),
+                        span.start.character_position_in_line(),
+                        span.end.character_position_in_line(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            summary("package main\n var p\u{430}yment = 1\n \t\tf(..xs, 1)\n"),
+            vec![
+                (Severity::Warning, "confusable-identifier", 2, 6, 6),
+                (Severity::Warning, "mixed-indentation", 3, 1, 3),
+                (Severity::Error, "parser", 3, 10, 10),
+            ]
+        );
+        assert_eq!(
+            summary("package main\n \tvar x = '\\q'"),
+            vec![(Severity::Error, "lexer", 2, 14, 14)]
+        );
+        assert!(check_str("package main\n var x = 1\n", &[]).is_empty());
+        assert_eq!(
+            summary("package main\n var x = x\n"),
+            vec![(Severity::Warning, "self-binding", 2, 2, 10)]
+        );
     }
 }
@@ -0,0 +1,227 @@
+//! Parsing a file's top-level items concurrently.
+//!
+//! The whole file is lexed up front and split into chunks of top-level items by
+//! scanning for the keywords that start them outside of any brackets, which is
+//! far quicker than parsing. Each chunk is then parsed on its own thread and
+//! the items are reassembled in order into the file's package.
+//!
+//! Chunks are parsed in isolation, so anything that relies on an earlier chunk
+//! fails, such as a `const` referring to one declared in an earlier chunk. So
+//! does top-level code after the first item, since it isn't delimited by
+//! keywords. Rather than trying to patch those up, any failure falls back to
+//! parsing the whole file sequentially. The result is therefore always exactly
+//! what [parse_str] gives, errors included.
+
+use std::num::NonZeroUsize;
+use std::thread;
+
+use super::{nodes, parse_str, Parser, Result};
+use crate::common::multiphase::OverloadableSliceOperator;
+use crate::common::peekable_buffer::PeekableBuffer;
+use crate::lexing::lexer::{LexedToken, Lexer};
+use crate::lexing::tokens::{Binding, DeclarationHead, Grouping, Macros, Token};
+use crate::lexing::Tokens;
+use crate::source::in_memory::Source;
+
+/// Parse a file like [parse_str] does, but with its top-level items split
+/// across as many threads as the machine can run in parallel.
+pub fn parse_str_concurrently(source: &str) -> Result<nodes::MainFile> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    parse_in_chunks(source, threads)
+}
+
+fn parse_in_chunks(source: &str, chunks: usize) -> Result<nodes::MainFile> {
+    match lex_up_front(source).and_then(|lexed| parse_lexed_in_chunks(lexed, chunks)) {
+        Some(file) => Ok(file),
+        None => parse_str(source),
+    }
+}
+
+/// All of a source's tokens up to and including its EOF, unless the source
+/// couldn't be lexed.
+fn lex_up_front(source: &str) -> Option<Vec<LexedToken>> {
+    let lexer = Lexer::from(Source::from(source.chars().collect::<Vec<char>>()));
+    let mut tokens = Tokens::from(lexer).ok()?;
+
+    let mut lexed = vec![];
+    while let Some(token) = tokens.read() {
+        let is_eof = token.token == Token::Eof;
+        lexed.push(token);
+        if is_eof {
+            break;
+        }
+    }
+
+    let finished = lexed.last().map(|lexed| &lexed.token) == Some(&Token::Eof);
+    match tokens.join_lexer_thread() {
+        Ok(()) if finished => Some(lexed),
+        _ => None,
+    }
+}
+
+fn parse_lexed_in_chunks(lexed: Vec<LexedToken>, chunks: usize) -> Option<nodes::MainFile> {
+    // The first item head is the file's own package declaration, so the file's
+    // header runs up to the one after it.
+    let item_starts = match item_starts(&lexed).split_first() {
+        Some((_, item_starts)) if !item_starts.is_empty() => item_starts.to_vec(),
+        _ => return None,
+    };
+    let eof = lexed.last()?.clone();
+
+    let header = terminated(&lexed[..item_starts[0]], &eof);
+    let mut file = Parser::from(Tokens::from_lexed(header)).parse().ok()?;
+
+    let items_per_chunk = item_starts.len().div_ceil(chunks.max(1));
+    let chunk_starts = item_starts
+        .into_iter()
+        .step_by(items_per_chunk)
+        .collect::<Vec<_>>();
+
+    let ends = chunk_starts
+        .iter()
+        .skip(1)
+        .copied()
+        .chain(Some(lexed.len() - 1));
+    let chunks = chunk_starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| terminated(&lexed[start..end], &eof))
+        .collect::<Vec<_>>();
+
+    let parsed = thread::scope(|scope| {
+        let handles = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || parse_chunk(chunk)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().ok().flatten())
+            .collect::<Option<Vec<_>>>()
+    })?;
+
+    file.package
+        .package
+        .items
+        .extend(parsed.into_iter().flatten());
+    Some(file)
+}
+
+fn terminated(tokens: &[LexedToken], eof: &LexedToken) -> Vec<LexedToken> {
+    let mut terminated = tokens.to_vec();
+    terminated.push(eof.clone());
+    terminated
+}
+
+/// Parse a chunk of top-level items, which must be all that's in it.
+fn parse_chunk(chunk: Vec<LexedToken>) -> Option<Vec<nodes::Item>> {
+    let mut parser = Parser::from(Tokens::from_lexed(chunk));
+    let items = parser.parse_inside_package().ok()?;
+    match parser.peek() {
+        Some(Token::Eof) => Some(items),
+        _ => None,
+    }
+}
+
+/// The indices of tokens that start top-level items, i.e. those starting items
+/// that aren't nested within any brackets.
+fn item_starts(lexed: &[LexedToken]) -> Vec<usize> {
+    let mut depth = 0usize;
+    let mut starts = vec![];
+    for (index, token) in lexed.iter().enumerate() {
+        match &token.token {
+            Token::Grouping(Grouping::OpenBrace)
+            | Token::Grouping(Grouping::OpenParentheses)
+            | Token::Grouping(Grouping::OpenRecord)
+            | Token::Grouping(Grouping::OpenSquareBracket)
+            | Token::OverloadableSliceOperator(OverloadableSliceOperator::Open) => depth += 1,
+            Token::Grouping(_)
+            | Token::OverloadableSliceOperator(OverloadableSliceOperator::Close) => {
+                depth = depth.saturating_sub(1)
+            }
+            Token::DeclarationHead(DeclarationHead::Class)
+            | Token::DeclarationHead(DeclarationHead::Extend)
+            | Token::DeclarationHead(DeclarationHead::Interface)
+            | Token::DeclarationHead(DeclarationHead::Package)
+            | Token::DeclarationHead(DeclarationHead::Fun)
+            | Token::Binding(Binding::Final)
+            | Token::Binding(Binding::Const)
+            | Token::Macros(Macros::At)
+                if depth == 0 =>
+            {
+                starts.push(index)
+            }
+            _ => {}
+        }
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Error;
+
+    const ITEMS: &str = "package main
+        class Account
+        @deprecated
+        final limit Int | Null = 5
+        package inner {
+            class Ledger
+            final nested = #{ depth: 2 }
+        }
+        const rate = 3
+        const doubled = rate * 2
+        class Entry[T extends Comparable[T] & Printable]
+        final scaled = if limit > 2 { limit } else { 0 }
+        @route(\"/accounts\", verb: \"GET\")
+        final pairs = for var n = 0 yield n { continue(n + 1) }";
+
+    #[test]
+    fn concurrent_parsing_matches_sequential_parsing() {
+        let sequential = parse_str(ITEMS).unwrap();
+        assert_eq!(sequential.package.package.items.len(), 10);
+        for chunks in 1..=12 {
+            assert_eq!(parse_in_chunks(ITEMS, chunks).unwrap(), sequential);
+        }
+        assert_eq!(parse_str_concurrently(ITEMS).unwrap(), sequential);
+
+        let lexed = lex_up_front(ITEMS).unwrap();
+        assert_eq!(item_starts(&lexed).len(), 11);
+        assert!(parse_lexed_in_chunks(lexed, 4).is_some());
+    }
+
+    #[test]
+    fn unsplittable_files_are_parsed_sequentially() {
+        let sources = [
+            // Top-level code after the first item.
+            "package main\nclass Account\nvar balance = 0\nprint(balance)",
+            // Constants referring to those in other chunks.
+            "package main\nconst rate = 3\nclass Account\nconst doubled = rate * 2",
+            // Nothing but a package declaration.
+            "package main",
+            "",
+        ];
+        for source in &sources {
+            assert_eq!(
+                parse_in_chunks(source, 3).unwrap(),
+                parse_str(source).unwrap()
+            );
+        }
+        assert!(parse_lexed_in_chunks(lex_up_front(sources[0]).unwrap(), 3).is_none());
+    }
+
+    #[test]
+    fn errors_match_sequential_parsing() {
+        let sources = [
+            "package main\nclass Account\nconst doubled = rate * 2",
+            "package main\nclass Account\nfinal x = }",
+            "package main\nclass Account\nfinal x = 0x1.p",
+        ];
+        for source in &sources {
+            let concurrent = parse_in_chunks(source, 3).unwrap_err();
+            let sequential = parse_str(source).unwrap_err();
+            assert!(matches!(concurrent, Error::Parser(_) | Error::Lexer(_)));
+            assert_eq!(format!("{:?}", concurrent), format!("{:?}", sequential));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Runs the grammar fixtures in `tests/grammar` through the parser, so that
+//! coverage of the grammar grows by adding Sylan files rather than Rust code.
+//!
+//! Each fixture is a `.sy` file whose first line states what parsing it should
+//! do: either `// expect: parses`, or `// expect: fails at LINE:COLUMN` with
+//! the position the error is reported at, both counting from one. The line is
+//! an ordinary comment, so it's parsed along with the rest of the fixture.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::parse_str;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/grammar");
+
+#[derive(Debug, PartialEq)]
+enum Expectation {
+    Parses,
+    FailsAt { line: usize, column: usize },
+}
+
+fn parse_expectation(first_line: &str) -> Result<Expectation, String> {
+    let expectation = first_line
+        .trim()
+        .strip_prefix("// expect:")
+        .ok_or("the first line must be an `// expect:` comment")?
+        .trim();
+    if expectation == "parses" {
+        return Ok(Expectation::Parses);
+    }
+
+    let position = expectation
+        .strip_prefix("fails at ")
+        .ok_or_else(|| format!("unknown expectation \"{}\"", expectation))?;
+    let malformed = || format!("expected a position as LINE:COLUMN, not \"{}\"", position);
+    let (line, column) = match position.split_once(':') {
+        Some(position) => position,
+        None => return Err(malformed()),
+    };
+    Ok(Expectation::FailsAt {
+        line: line.parse().map_err(|_| malformed())?,
+        column: column.parse().map_err(|_| malformed())?,
+    })
+}
+
+/// Check a fixture, describing how it went wrong if it did.
+fn check(path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let expectation = parse_expectation(source.lines().next().unwrap_or(""))?;
+    match (expectation, parse_str(&source)) {
+        (Expectation::Parses, Ok(_)) => Ok(()),
+        (Expectation::Parses, Err(error)) => Err(format!("failed to parse: {:?}", error)),
+        (Expectation::FailsAt { .. }, Ok(_)) => Err("parsed, but was expected to fail".to_owned()),
+        (Expectation::FailsAt { line, column }, Err(error)) => match error.position() {
+            Some(position)
+                if position.line() == line && position.character_position_in_line() == column =>
+            {
+                Ok(())
+            }
+            Some(position) => Err(format!(
+                "failed at {}:{} rather than {}:{}: {:?}",
+                position.line(),
+                position.character_position_in_line(),
+                line,
+                column,
+                error
+            )),
+            None => Err(format!("failed without a position: {:?}", error)),
+        },
+    }
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(FIXTURES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("sy"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn grammar_fixtures() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty(), "no fixtures found in {}", FIXTURES);
+
+    let failures = fixtures
+        .iter()
+        .filter_map(|path| {
+            check(path)
+                .err()
+                .map(|failure| format!("{}: {}", path.display(), failure))
+        })
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn expectations() {
+    assert_eq!(
+        parse_expectation("// expect: parses"),
+        Ok(Expectation::Parses)
+    );
+    assert_eq!(
+        parse_expectation("// expect: fails at 3:12"),
+        Ok(Expectation::FailsAt {
+            line: 3,
+            column: 12
+        })
+    );
+    assert!(parse_expectation("package main").is_err());
+    assert!(parse_expectation("// expect: fails").is_err());
+    assert!(parse_expectation("// expect: fails at 3").is_err());
+}
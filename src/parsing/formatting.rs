@@ -0,0 +1,1395 @@
+//! # Sylan's Formatter
+//!
+//! The formatter turns an AST back into source text laid out in a consistent
+//! style. As the AST drops grammatically useful but semantically useless
+//! tokens, the layout is decided entirely by the formatter rather than being
+//! carried over from the original source. Comments survive if the parser was
+//! asked to retain them.
+//!
+//! The style is configurable where teams commonly disagree: indentation and
+//! brace placement. Everything else is fixed, so that any two formatted
+//! sources with the same configuration look alike.
+//!
+//! Formatting is idempotent: formatting the parse of formatted source yields
+//! the same source again.
+//!
+//! Some rarer nodes, such as enums, interfaces, and `select`s, have no layout
+//! yet. Formatting a file containing one fails with [Unsupported] rather than
+//! producing source that wouldn't parse back to the same AST.
+
+use std::result;
+
+use super::{infix_precedence, is_right_associative};
+use crate::common::multiphase::{
+    Accessibility, OverloadableInfixOperator, PostfixOperator, PseudoIdentifier,
+};
+use crate::parsing::nodes::{
+    Binding, Block, BranchingAndJumping, Break, Call, CallArguments, Case, Class,
+    ClassValueParameter, Comments, CompositePattern, ConcreteMethod, Cond, Expression,
+    ExpressionCall, Field, For, Fun, FunSignature, If, Import, ImportStem, Item, Lambda,
+    LambdaValueParameter, Literal, MainFile, Operator, Package, Pattern, PatternItem, Record,
+    SlicePattern, Switch, Symbol, SymbolLookup, Throw, Tuple, Type, TypeExpression, TypeItem,
+    TypeParameter, TypeReference, ValueParameter,
+};
+
+/// How far each level of nesting is indented.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Indentation {
+    Tabs,
+    Spaces(usize),
+}
+
+/// Where the opening brace of a block goes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BraceStyle {
+    /// At the end of the line that introduces the block, e.g. `if a {`.
+    SameLine,
+
+    /// On a line of its own, aligned with the line that introduces the block.
+    NextLine,
+}
+
+/// By default, the formatter indents with four spaces and puts opening braces
+/// on the same line as whatever introduces them, matching the examples and the
+/// standard library.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FormatterConfig {
+    pub indentation: Indentation,
+    pub brace_style: BraceStyle,
+}
+
+impl Default for FormatterConfig {
+    fn default() -> Self {
+        Self {
+            indentation: Indentation::Spaces(4),
+            brace_style: BraceStyle::SameLine,
+        }
+    }
+}
+
+/// A kind of node the formatter has no layout for yet, such as `enums`, or an
+/// arrangement of nodes it can't write back in their original order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Unsupported(pub &'static str);
+
+type Result = result::Result<(), Unsupported>;
+
+fn unsupported(node: &'static str) -> Result {
+    Err(Unsupported(node))
+}
+
+/// Format a whole main file under the given configuration.
+pub fn format(file: &MainFile, config: FormatterConfig) -> result::Result<String, Unsupported> {
+    let mut formatter = Formatter {
+        config,
+        output: String::new(),
+        depth: 0,
+    };
+    formatter.format_main_file(file)?;
+    Ok(formatter.output)
+}
+
+/// Render just the signature of a function or method, such as
+/// `first[T extends Printable](of list List[T]) T`, for documentation and
+/// hover text. Bodies are left out, as are modifiers, which belong to the
+/// declaration rather than the signature.
+pub fn format_signature(signature: &FunSignature) -> result::Result<String, Unsupported> {
+    let mut formatter = Formatter {
+        config: Default::default(),
+        output: String::new(),
+        depth: 0,
+    };
+    formatter.format_signature(signature)?;
+    Ok(formatter.output)
+}
+
+struct Formatter {
+    config: FormatterConfig,
+    output: String,
+    depth: usize,
+}
+
+impl Formatter {
+    fn write(&mut self, s: &str) {
+        self.output.push_str(s);
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            match self.config.indentation {
+                Indentation::Tabs => self.output.push('\t'),
+                Indentation::Spaces(n) => self.output.extend((0..n).map(|_| ' ')),
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.output.push('\n');
+        self.indent();
+    }
+
+    /// Format each of `items` with `format_one`, writing `separator` between
+    /// them.
+    fn format_separated<T>(
+        &mut self,
+        items: &[T],
+        separator: &str,
+        mut format_one: impl FnMut(&mut Self, &T) -> Result,
+    ) -> Result {
+        for (i, item) in items.iter().enumerate() {
+            if 0 < i {
+                self.write(separator);
+            }
+            format_one(self, item)?;
+        }
+        Ok(())
+    }
+
+    fn format_main_file(&mut self, file: &MainFile) -> Result {
+        if let Some(shebang) = &file.shebang {
+            self.write(&format!("#!{}\n", shebang.0));
+        }
+        if let Some(version) = &file.version {
+            self.write(&format!("v{}.{}\n", version.major, version.minor));
+        }
+        if file.shebang.is_some() || file.version.is_some() {
+            self.write("\n");
+        }
+
+        self.format_package_head(&file.package.package)?;
+
+        let block = &file.package.block;
+        if block.interleaved {
+            return unsupported("expressions before bindings");
+        }
+        let items = &file.package.package.items;
+        if !(items.is_empty() && block.bindings.is_empty() && block.expressions.is_empty()) {
+            self.write("\n");
+        }
+        for item in items {
+            self.write("\n");
+            self.format_item(item)?;
+        }
+        for binding in &block.bindings {
+            self.write("\n");
+            self.format_binding("var", binding)?;
+        }
+        for expression in &block.expressions {
+            self.write("\n");
+            self.format_expression(expression)?;
+        }
+        self.write("\n");
+        Ok(())
+    }
+
+    fn format_package_head(&mut self, package: &Package) -> Result {
+        self.write(&format!("package {}", package.name.0));
+        if !package.imports.is_empty() {
+            self.write(" (");
+            self.format_imports(&package.imports)?;
+            self.write(")");
+        }
+        Ok(())
+    }
+
+    fn format_imports(&mut self, imports: &[Import]) -> Result {
+        self.format_separated(imports, ", ", |formatter, import| {
+            if import.is_reexported {
+                formatter.write("exports ");
+            }
+            if let Some(Symbol::Relative(SymbolLookup(root))) = &import.root {
+                for identifier in root {
+                    formatter.write(&format!("{}.", identifier.0));
+                }
+            }
+            match &import.stem {
+                ImportStem::Single(stem) => {
+                    if !stem.readers.is_empty() {
+                        return unsupported("import readers");
+                    }
+                    formatter.write(&stem.name.0);
+                    if let Some(alias) = &stem.alias {
+                        let marker = if stem.overrides_builtin {
+                            "override "
+                        } else {
+                            ""
+                        };
+                        formatter.write(&format!(" as {}{}", marker, alias.0));
+                    }
+                }
+                ImportStem::Multiple(stems) => {
+                    formatter.write("{");
+                    formatter.format_imports(stems)?;
+                    formatter.write("}");
+                }
+                ImportStem::Glob => formatter.write("*"),
+            }
+            Ok(())
+        })
+    }
+
+    fn format_item(&mut self, item: &Item) -> Result {
+        match item {
+            Item::Const(binding) => self.format_binding("const", binding),
+            Item::Final(binding) => self.format_binding("final", binding),
+            Item::Var(binding) => self.format_binding("var", binding),
+            Item::Fun(fun) => self.format_fun(fun),
+            Item::Type(r#type) => self.format_type(r#type),
+            Item::Extension(_) => unsupported("extensions"),
+            Item::Package(_) => unsupported("nested packages"),
+            Item::Macro(_) => unsupported("macros"),
+        }
+    }
+
+    fn format_fun(&mut self, fun: &Fun) -> Result {
+        if let Some(calling_convention) = fun.modifiers.r#extern {
+            self.write(&format!("extern \"{}\" ", calling_convention.name()));
+        }
+        self.write("fun ");
+        self.format_accessibility(&fun.modifiers.accessibility);
+        if fun.modifiers.is_operator {
+            self.write("operator ");
+        }
+        self.format_ignorability(&fun.signature);
+        self.format_signature(&fun.signature)?;
+        if fun.modifiers.r#extern.is_none() {
+            self.format_block(&fun.block)?;
+        }
+        Ok(())
+    }
+
+    /// Private is the default, so it's never written out.
+    fn format_accessibility(&mut self, accessibility: &Accessibility) {
+        match accessibility {
+            Accessibility::Private => {}
+            Accessibility::Internal => self.write("internal "),
+            Accessibility::Public => self.write("public "),
+        }
+    }
+
+    fn format_ignorability(&mut self, signature: &FunSignature) {
+        if let Some(return_type) = &signature.return_type {
+            if return_type.ignorable {
+                self.write("ignorable ");
+            }
+        }
+    }
+
+    fn format_type(&mut self, r#type: &Type) -> Result {
+        let class = match &r#type.item {
+            TypeItem::Class(class) => class,
+            TypeItem::Enum(_) => return unsupported("enums"),
+            TypeItem::Interface(_) => return unsupported("interfaces"),
+        };
+        self.write(&format!("class {}", r#type.name.0));
+        if let Some(sydoc) = &r#type.sydoc {
+            self.write(&format!(" /**{}*/", sydoc.0));
+        }
+        self.format_type_parameters(&r#type.type_parameters)?;
+        if !class.value_parameters.is_empty() {
+            self.write("(");
+            self.format_separated(
+                &class.value_parameters,
+                ", ",
+                Self::format_class_value_parameter,
+            )?;
+            self.write(")");
+        }
+        self.format_type_list(" implements ", &class.implements)?;
+        self.format_type_list(" embeds ", &class.embeds)?;
+        self.format_class_body(class)
+    }
+
+    fn format_class_value_parameter(&mut self, parameter: &ClassValueParameter) -> Result {
+        if let Some(field_upgrade) = &parameter.field_upgrade {
+            self.write("var ");
+            self.format_accessibility(&field_upgrade.accessibility);
+        }
+        self.format_value_parameter(&parameter.parameter)
+    }
+
+    fn format_type_list(&mut self, keyword: &str, types: &[TypeReference]) -> Result {
+        if !types.is_empty() {
+            self.write(keyword);
+            self.format_separated(types, ", ", Self::format_type_reference)?;
+        }
+        Ok(())
+    }
+
+    /// Fields come first, then methods, each method set apart by a blank line.
+    fn format_class_body(&mut self, class: &Class) -> Result {
+        if class.fields.is_empty() && class.methods.is_empty() {
+            self.open_brace();
+            self.write("}");
+            return Ok(());
+        }
+
+        self.open_brace();
+        self.depth += 1;
+        for field in &class.fields {
+            self.new_line();
+            self.format_field(field)?;
+        }
+        for (i, method) in class.methods.iter().enumerate() {
+            if 0 < i || !class.fields.is_empty() {
+                self.write("\n");
+            }
+            self.new_line();
+            self.format_method(method)?;
+        }
+        self.close_brace();
+        Ok(())
+    }
+
+    fn format_field(&mut self, field: &Field) -> Result {
+        if field.is_extern {
+            self.write("extern ");
+        }
+        self.write(if field.is_final { "final " } else { "var " });
+        self.format_accessibility(&field.accessibility);
+        self.format_binding_body(&field.binding)
+    }
+
+    fn format_method(&mut self, method: &ConcreteMethod) -> Result {
+        let modifiers = &method.r#abstract.modifiers;
+        self.write("fun ");
+        self.format_accessibility(&modifiers.fun_modifiers.accessibility);
+        if modifiers.overrides {
+            self.write("override ");
+        }
+        if modifiers.fun_modifiers.is_operator {
+            self.write("operator ");
+        }
+        self.format_ignorability(&method.r#abstract.signature);
+        self.format_signature(&method.r#abstract.signature)?;
+        self.format_block(&method.scope)
+    }
+
+    fn format_comments(&mut self, comments: &[String]) {
+        for comment in comments {
+            self.write(comment);
+            self.new_line();
+        }
+    }
+
+    fn format_trailing_comments(&mut self, Comments { trailing, .. }: &Comments) {
+        for comment in trailing {
+            self.write(&format!(" {}", comment));
+        }
+    }
+
+    fn format_binding(&mut self, keyword: &str, binding: &Binding) -> Result {
+        self.format_comments(&binding.comments.leading);
+        self.write(&format!("{} ", keyword));
+        self.format_binding_body(binding)?;
+        if let Some(sydoc) = &binding.sydoc {
+            self.write(&format!(" /**{}*/", sydoc.0));
+        }
+        self.format_trailing_comments(&binding.comments);
+        Ok(())
+    }
+
+    /// A binding after its keyword and any modifiers, as in `x Int = 1`.
+    fn format_binding_body(&mut self, binding: &Binding) -> Result {
+        self.format_pattern(&binding.pattern)?;
+        if let Some(type_annotation) = &binding.explicit_type_annotation {
+            self.write(" ");
+            self.format_type_expression(type_annotation)?;
+        }
+        self.write(" = ");
+        self.format_expression(&binding.value)
+    }
+
+    fn format_pattern(&mut self, pattern: &Pattern) -> Result {
+        match &pattern.item {
+            PatternItem::Identifier(identifier) => self.write(&identifier.0),
+            PatternItem::Ignored => self.write("_"),
+            PatternItem::Literal(literal) => self.format_literal(literal)?,
+            PatternItem::Range(range) => {
+                self.format_literal(&range.start)?;
+                self.write("..");
+                self.format_literal(&range.end)?;
+            }
+            PatternItem::Tuple(elements) => {
+                self.write("(");
+                self.format_separated(elements, ", ", Self::format_pattern)?;
+                self.write(if elements.len() == 1 { ",)" } else { ")" });
+            }
+            PatternItem::Composite(composite) => self.format_composite_pattern(composite)?,
+            PatternItem::Slice(slice) => self.format_slice_pattern(slice)?,
+            PatternItem::BoundSymbol(symbol) => {
+                self.write("..");
+                self.format_symbol(symbol)?;
+            }
+        }
+        if let Some(bound_match) = &pattern.bound_match {
+            self.write(" as ");
+            self.format_pattern(bound_match)?;
+        }
+        Ok(())
+    }
+
+    /// A getter matching its own name, as in `Point(x)`, is written without
+    /// repeating the name.
+    fn format_composite_pattern(&mut self, composite: &CompositePattern) -> Result {
+        if composite.infer_enum_type {
+            return unsupported("inferred enum variants");
+        }
+        self.format_type_reference(&composite.r#type)?;
+        self.write("(");
+        self.format_separated(&composite.getters, ", ", |formatter, getter| {
+            match &getter.pattern {
+                Pattern {
+                    item: PatternItem::Identifier(name),
+                    bound_match: None,
+                } if *name == getter.name => formatter.write(&name.0),
+                pattern => {
+                    formatter.write(&format!("{}: ", getter.name.0));
+                    formatter.format_pattern(pattern)?;
+                }
+            }
+            Ok(())
+        })?;
+        if composite.ignore_rest {
+            if !composite.getters.is_empty() {
+                self.write(", ");
+            }
+            self.write("..");
+            if let Some(rest_binding) = &composite.rest_binding {
+                self.write(&rest_binding.0);
+            }
+        }
+        self.write(")");
+        Ok(())
+    }
+
+    fn format_slice_pattern(&mut self, slice: &SlicePattern) -> Result {
+        self.write("[");
+        self.format_separated(&slice.leading, ", ", Self::format_pattern)?;
+        if let Some(rest) = &slice.rest {
+            if !slice.leading.is_empty() {
+                self.write(", ");
+            }
+            self.write("..");
+            if let PatternItem::Identifier(name) = &rest.item {
+                self.write(&name.0);
+            }
+            for pattern in &slice.trailing {
+                self.write(", ");
+                self.format_pattern(pattern)?;
+            }
+        }
+        self.write("]");
+        Ok(())
+    }
+
+    fn open_brace(&mut self) {
+        match self.config.brace_style {
+            BraceStyle::SameLine => self.write(" {"),
+            BraceStyle::NextLine => {
+                self.new_line();
+                self.write("{");
+            }
+        }
+    }
+
+    /// Close a brace opened with [Formatter::open_brace] after its contents
+    /// were indented.
+    fn close_brace(&mut self) {
+        self.depth -= 1;
+        self.new_line();
+        self.write("}");
+    }
+
+    /// Bindings are written before expressions, which is only the order they
+    /// were in if no expression came before a binding.
+    fn format_block(&mut self, block: &Block) -> Result {
+        if block.interleaved {
+            return unsupported("expressions before bindings");
+        }
+        self.open_brace();
+        if block.bindings.is_empty() && block.expressions.is_empty() {
+            self.write("}");
+            return Ok(());
+        }
+
+        self.depth += 1;
+        for binding in &block.bindings {
+            self.new_line();
+            self.format_binding("var", binding)?;
+        }
+        for expression in &block.expressions {
+            self.new_line();
+            self.format_expression(expression)?;
+        }
+        self.close_brace();
+        Ok(())
+    }
+
+    /// Format an operand of `operator`, grouping it if it's an infix
+    /// expression that would otherwise reparse differently: one binding more
+    /// loosely, or as loosely but on the side `operator` doesn't associate
+    /// towards.
+    fn format_operand(
+        &mut self,
+        operand: &Expression,
+        operator: &OverloadableInfixOperator,
+        groups_at_same_precedence: bool,
+    ) -> Result {
+        let needs_grouping = match operand {
+            Expression::Operator(Operator::OverloadableInfix(_, inner, _)) => {
+                let (inner, outer) = (infix_precedence(inner), infix_precedence(operator));
+                inner < outer || (inner == outer && groups_at_same_precedence)
+            }
+            _ => false,
+        };
+        self.format_grouped_if(needs_grouping, operand)
+    }
+
+    /// Format the expression that a call, lookup, or postfix operator applies
+    /// to. Only simple expressions can be followed by those without grouping
+    /// them first.
+    fn format_target(&mut self, target: &Expression) -> Result {
+        let is_simple = match target {
+            Expression::Literal(Literal::Lambda(_)) => false,
+            Expression::Literal(_) | Expression::Symbol(_) | Expression::Hole(_) => true,
+            Expression::BranchingAndJumping(BranchingAndJumping::Call(_))
+            | Expression::BranchingAndJumping(BranchingAndJumping::ExpressionCall(_)) => true,
+            Expression::Operator(Operator::Lookup(..))
+            | Expression::Operator(Operator::OptionalChain(..))
+            | Expression::Operator(Operator::Reference(_)) => true,
+            _ => false,
+        };
+        self.format_grouped_if(!is_simple, target)
+    }
+
+    fn format_grouped_if(&mut self, grouped: bool, expression: &Expression) -> Result {
+        if grouped {
+            self.write("(");
+            self.format_expression(expression)?;
+            self.write(")");
+            Ok(())
+        } else {
+            self.format_expression(expression)
+        }
+    }
+
+    fn format_expression(&mut self, expression: &Expression) -> Result {
+        match expression {
+            Expression::Literal(literal) => self.format_literal(literal),
+            Expression::Symbol(symbol) => self.format_symbol(symbol),
+            Expression::Operator(operator) => self.format_operator(operator),
+            Expression::BranchingAndJumping(branching) => self.format_branching(branching),
+            Expression::Context(block) => {
+                self.write("with");
+                self.format_block(block)
+            }
+            Expression::Throw(Throw { exception, cause }) => {
+                self.write("throw ");
+                self.format_expression(exception)?;
+                if let Some(cause) = cause {
+                    self.write(" caused by ");
+                    self.format_expression(cause)?;
+                }
+                Ok(())
+            }
+            Expression::Use(resource) => {
+                self.write("use ");
+                self.format_expression(&resource.0)
+            }
+            Expression::Defer(defer) => {
+                self.write("defer");
+                self.format_block(&defer.0)
+            }
+            Expression::Hole(_) => {
+                self.write("???");
+                Ok(())
+            }
+            Expression::MemberHandle(symbol) => {
+                self.write(":");
+                self.format_symbol(symbol)
+            }
+            Expression::Spawn(spawn) => {
+                self.write("spawn ->");
+                self.format_block(&spawn.task)
+            }
+            Expression::NonDestructiveUpdate(_) => unsupported("non-destructive updates"),
+            Expression::ReaderMacroActivation(_) => unsupported("reader macros"),
+        }
+    }
+
+    fn format_operator(&mut self, operator: &Operator) -> Result {
+        match operator {
+            Operator::OverloadableInfix(left, operator, right) => {
+                let right_associative = is_right_associative(operator);
+                self.format_operand(left, operator, right_associative)?;
+                self.write(&format!(" {} ", infix_operator_text(operator)));
+                self.format_operand(right, operator, !right_associative)
+            }
+            Operator::Postfix(operand, operator) => {
+                self.format_target(operand)?;
+                self.write(match operator {
+                    PostfixOperator::Bind => "?",
+                    PostfixOperator::ForceUnwrap => "!",
+                });
+                Ok(())
+            }
+            Operator::Lookup(target, name) => {
+                self.format_target(target)?;
+                self.write(&format!(".{}", name.0));
+                Ok(())
+            }
+            Operator::OptionalChain(target, name) => {
+                self.format_target(target)?;
+                self.write(&format!("?.{}", name.0));
+                Ok(())
+            }
+            Operator::Reference(operator) => {
+                self.write(&format!("({})", infix_operator_text(operator)));
+                Ok(())
+            }
+            Operator::MultiSlice(..) => unsupported("slices"),
+        }
+    }
+
+    fn format_branching(&mut self, branching: &BranchingAndJumping) -> Result {
+        match branching {
+            BranchingAndJumping::Call(Call { target, arguments }) => {
+                self.format_symbol(target)?;
+                self.format_call_arguments(arguments)
+            }
+            BranchingAndJumping::ExpressionCall(ExpressionCall { target, arguments }) => {
+                self.format_target(target)?;
+                self.format_call_arguments(arguments)
+            }
+            BranchingAndJumping::If(if_node) => self.format_if(if_node),
+            BranchingAndJumping::For(r#for) => self.format_for(r#for),
+            BranchingAndJumping::While(r#while) => {
+                self.write("while ");
+                self.format_expression(&r#while.condition)?;
+                self.format_block(&r#while.scope)
+            }
+            BranchingAndJumping::WhileVar(while_var) => {
+                self.write("while var ");
+                self.format_local_binding(&while_var.binding)?;
+                self.format_block(&while_var.scope)
+            }
+            BranchingAndJumping::Switch(switch) => self.format_switch(switch),
+            BranchingAndJumping::Cond(cond) => self.format_cond(cond),
+            BranchingAndJumping::LabelledBlock(labelled) => {
+                self.write(&format!("{}:", labelled.label.0));
+                self.format_block(&labelled.scope)
+            }
+            BranchingAndJumping::Break(Break { label, value }) => {
+                self.write(&format!("break {}", label.0));
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.format_expression(value)?;
+                }
+                Ok(())
+            }
+            BranchingAndJumping::Fallthrough => {
+                self.write("fallthrough");
+                Ok(())
+            }
+            BranchingAndJumping::IfVar(_) => unsupported("`if var`s"),
+            BranchingAndJumping::Select(_) => unsupported("selects"),
+        }
+    }
+
+    /// A binding in a loop's head, which can't be annotated with a type.
+    fn format_local_binding(&mut self, binding: &Binding) -> Result {
+        self.format_pattern(&binding.pattern)?;
+        self.write(" = ");
+        self.format_expression(&binding.value)
+    }
+
+    fn format_if(&mut self, if_node: &If) -> Result {
+        self.write("if ");
+        self.format_expression(&if_node.condition)?;
+        self.format_block(&if_node.then)?;
+
+        if let Some(else_clause) = &if_node.else_clause {
+            self.format_else()?;
+            match &else_clause.expressions[..] {
+                [Expression::BranchingAndJumping(BranchingAndJumping::If(else_if))]
+                    if else_clause.bindings.is_empty() =>
+                {
+                    self.write(" ");
+                    self.format_if(else_if)?;
+                }
+                _ => self.format_block(else_clause)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn format_else(&mut self) -> Result {
+        match self.config.brace_style {
+            BraceStyle::SameLine => self.write(" else"),
+            BraceStyle::NextLine => {
+                self.new_line();
+                self.write("else");
+            }
+        }
+        Ok(())
+    }
+
+    /// The `var` is always written, although it can be left out of an
+    /// unlabelled loop's head.
+    fn format_for(&mut self, r#for: &For) -> Result {
+        self.write("for");
+        if let Some(label) = &r#for.reiteration_symbol {
+            self.write(&format!(" {}", label.0));
+        }
+        if !r#for.bindings.is_empty() {
+            self.write(" var ");
+            self.format_separated(&r#for.bindings, ", ", Self::format_local_binding)?;
+        }
+        if let Some(yield_expression) = &r#for.yield_expression {
+            self.write(" yield ");
+            self.format_expression(yield_expression)?;
+        }
+        self.format_block(&r#for.scope)?;
+        if let Some(else_clause) = &r#for.else_clause {
+            self.format_else()?;
+            self.format_block(else_clause)?;
+        }
+        Ok(())
+    }
+
+    fn format_switch(&mut self, switch: &Switch) -> Result {
+        self.write("switch ");
+        self.format_expression(&switch.expression)?;
+        if let Some(binding) = &switch.binding {
+            self.write(&format!(" as {}", binding.0));
+        }
+        self.open_brace();
+        self.depth += 1;
+        for case in &switch.cases {
+            self.new_line();
+            self.format_case(case)?;
+        }
+        self.close_brace();
+        Ok(())
+    }
+
+    fn format_case(&mut self, case: &Case) -> Result {
+        self.format_separated(&case.matches, ", ", |formatter, case_match| {
+            formatter.format_pattern(&case_match.pattern)?;
+            if let Some(guard) = &case_match.guard {
+                formatter.write(" if ");
+                formatter.format_expression(guard)?;
+            }
+            Ok(())
+        })?;
+        self.format_block(&case.body)
+    }
+
+    fn format_cond(&mut self, cond: &Cond) -> Result {
+        self.write("switch");
+        self.open_brace();
+        self.depth += 1;
+        for case in &cond.cases {
+            self.new_line();
+            self.format_separated(&case.conditions, ", ", Self::format_expression)?;
+            match &case.then {
+                Some(then) => self.format_block(then)?,
+                None => self.write(":"),
+            }
+        }
+        if let Some(else_clause) = &cond.else_clause {
+            self.new_line();
+            self.write("else");
+            self.format_block(else_clause)?;
+        }
+        self.close_brace();
+        Ok(())
+    }
+
+    fn format_lambda(&mut self, lambda: &Lambda) -> Result {
+        self.write("->");
+        if !lambda.signature.value_parameters.is_empty() {
+            self.write(" ");
+            self.format_separated(
+                &lambda.signature.value_parameters,
+                ", ",
+                Self::format_lambda_value_parameter,
+            )?;
+        }
+        self.format_block(&lambda.block)
+    }
+
+    fn format_lambda_value_parameter(&mut self, parameter: &LambdaValueParameter) -> Result {
+        if parameter.is_mutable {
+            self.write("var ");
+        }
+        if let Some(label) = &parameter.label {
+            self.write(&format!("{} ", label.0));
+        }
+        self.format_pattern(&parameter.pattern)?;
+        if let Some(default_value) = &parameter.default_value {
+            self.write(": ");
+            self.format_expression(default_value)?;
+        }
+        Ok(())
+    }
+
+    fn format_call_arguments(&mut self, arguments: &CallArguments) -> Result {
+        if !arguments.type_arguments.is_empty() {
+            self.write("[");
+            self.format_separated(&arguments.type_arguments, ", ", |formatter, argument| {
+                if let Some(label) = &argument.label {
+                    formatter.write(&format!("{}: ", label.0));
+                }
+                formatter.format_type_expression(&argument.value)
+            })?;
+            self.write("]");
+        }
+        self.write("(");
+        self.format_separated(&arguments.arguments, ", ", |formatter, argument| {
+            if let Some(label) = &argument.label {
+                formatter.write(&format!("{}: ", label.0));
+            }
+            if argument.is_spread {
+                formatter.write("..");
+            }
+            formatter.format_expression(&argument.value)
+        })?;
+        self.write(")");
+        Ok(())
+    }
+
+    fn format_signature(&mut self, signature: &FunSignature) -> Result {
+        self.write(&signature.name.0);
+        self.format_type_parameters(&signature.type_parameters)?;
+        self.write("(");
+        self.format_separated(
+            &signature.value_parameters,
+            ", ",
+            Self::format_value_parameter,
+        )?;
+        self.write(")");
+        if let Some(return_type) = &signature.return_type {
+            self.write(" ");
+            self.format_type_reference(&return_type.r#type)?;
+        }
+        Ok(())
+    }
+
+    fn format_type_parameters(&mut self, parameters: &[TypeParameter]) -> Result {
+        if !parameters.is_empty() {
+            self.write("[");
+            self.format_separated(parameters, ", ", Self::format_type_parameter)?;
+            self.write("]");
+        }
+        Ok(())
+    }
+
+    fn format_type_parameter(&mut self, parameter: &TypeParameter) -> Result {
+        if let Some(label) = &parameter.label {
+            self.write(&format!("{} ", label.0));
+        }
+        self.write(&parameter.name.0);
+        if let Some(upper_bound) = &parameter.upper_bound {
+            self.write(" extends ");
+            self.format_type_expression(upper_bound)?;
+        }
+        if let Some(default_value) = &parameter.default_value {
+            self.write(": ");
+            self.format_type_expression(default_value)?;
+        }
+        Ok(())
+    }
+
+    fn format_value_parameter(&mut self, parameter: &ValueParameter) -> Result {
+        if parameter.is_syntax {
+            self.write("syntax ");
+        }
+        if parameter.is_mutable {
+            self.write("var ");
+        }
+        if let Some(label) = &parameter.label {
+            self.write(&format!("{} ", label.0));
+        }
+        self.format_pattern(&parameter.pattern)?;
+        self.write(" ");
+        self.format_type_reference(&parameter.type_annotation)?;
+        if let Some(default_value) = &parameter.default_value {
+            self.write(": ");
+            self.format_expression(default_value)?;
+        }
+        Ok(())
+    }
+
+    fn format_type_expression(&mut self, expression: &TypeExpression) -> Result {
+        let (operands, operator) = match expression {
+            TypeExpression::Reference(reference) => return self.format_type_reference(reference),
+            TypeExpression::Optional(operand) => {
+                let grouped = !matches!(**operand, TypeExpression::Reference(_));
+                self.format_type_operand(operand, grouped)?;
+                self.write("?");
+                return Ok(());
+            }
+            TypeExpression::Function(parameters, result) => {
+                self.write("(");
+                self.format_separated(parameters, ", ", Self::format_type_expression)?;
+                self.write(") -> ");
+                return self.format_type_expression(result);
+            }
+            TypeExpression::Intersection(operands) => (operands, " & "),
+            TypeExpression::Union(operands) => (operands, " | "),
+        };
+        self.format_separated(operands, operator, |formatter, operand| {
+            // Intersections bind tighter than unions, so only unions within
+            // intersections need grouping to survive a reparse. Function
+            // types would otherwise take the operands after them as part of
+            // their results.
+            let grouped = matches!(
+                operand,
+                TypeExpression::Union(_) | TypeExpression::Function(..)
+            );
+            formatter.format_type_operand(operand, grouped)
+        })
+    }
+
+    fn format_type_operand(&mut self, operand: &TypeExpression, grouped: bool) -> Result {
+        if grouped {
+            self.write("(");
+            self.format_type_expression(operand)?;
+            self.write(")");
+            Ok(())
+        } else {
+            self.format_type_expression(operand)
+        }
+    }
+
+    fn format_type_reference(&mut self, reference: &TypeReference) -> Result {
+        self.format_symbol(&reference.symbol)?;
+        if !reference.type_arguments.is_empty() {
+            self.write("[");
+            self.format_separated(&reference.type_arguments, ", ", |formatter, argument| {
+                if let Some(label) = &argument.label {
+                    formatter.write(&format!("{}: ", label.0));
+                }
+                formatter.format_type_expression(&argument.value)
+            })?;
+            self.write("]");
+        }
+        Ok(())
+    }
+
+    fn format_symbol(&mut self, symbol: &Symbol) -> Result {
+        let (prefix, SymbolLookup(identifiers)) = match symbol {
+            Symbol::Relative(lookup) => ("", lookup),
+            Symbol::Absolute(lookup) => ("global.", lookup),
+            Symbol::ThisMember(lookup) => ("this.", lookup),
+            Symbol::Pseudo(pseudo) => {
+                self.write(pseudo_identifier_text(pseudo));
+                return Ok(());
+            }
+            Symbol::TypeMember(SymbolLookup(r#type), member) => {
+                let names = r#type
+                    .iter()
+                    .chain(std::iter::once(member))
+                    .map(|identifier| identifier.0.as_str())
+                    .collect::<Vec<&str>>();
+                self.write(&names.join("."));
+                return Ok(());
+            }
+            Symbol::InferredEnumVariant(_) => return unsupported("inferred enum variants"),
+        };
+        let names = identifiers
+            .iter()
+            .map(|identifier| identifier.0.as_str())
+            .collect::<Vec<&str>>();
+        self.write(&format!("{}{}", prefix, names.join(".")));
+        Ok(())
+    }
+
+    fn format_literal(&mut self, literal: &Literal) -> Result {
+        match literal {
+            Literal::Number(number, numeric_type) => {
+                if number.1 == 0 {
+                    self.write(&number.0.to_string())
+                } else {
+                    self.write(&format!("{}.{}", number.0, number.1))
+                }
+                if let Some(numeric_type) = numeric_type {
+                    self.write(numeric_type.suffix())
+                }
+            }
+            Literal::HexFloat(float) => self.write(&format!(
+                "{}0x{:x}p{}",
+                if float.negative { "-" } else { "" },
+                float.mantissa,
+                float.exponent
+            )),
+            Literal::Scientific(scientific) => self.write(&format!(
+                "{}{}e{}",
+                if scientific.negative { "-" } else { "" },
+                scientific.significand,
+                scientific.exponent
+            )),
+            Literal::String(string) => self.write(&format!("\"{}\"", escape(&string.0))),
+            Literal::InterpolatedString(_) => return unsupported("interpolated strings"),
+            Literal::Char(c) => self.write(&format!("'{}'", escape(&c.to_string()))),
+            Literal::Lambda(lambda) => return self.format_lambda(lambda),
+            Literal::Record(Record(fields)) => {
+                self.write("#{");
+                for (i, field) in fields.iter().enumerate() {
+                    self.write(if 0 < i { ", " } else { " " });
+                    self.write(&format!("{}: ", field.name.0));
+                    self.format_expression(&field.value)?;
+                }
+                self.write(if fields.is_empty() { "}" } else { " }" });
+            }
+            Literal::Tuple(Tuple(elements)) => {
+                self.write("(");
+                self.format_separated(elements, ", ", Self::format_expression)?;
+                self.write(if elements.len() == 1 { ",)" } else { ")" });
+            }
+            Literal::List(list) => {
+                self.write(if list.mutable { "var [" } else { "[" });
+                self.format_separated(&list.elements, ", ", Self::format_expression)?;
+                self.write("]");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\n' => "\\n".to_owned(),
+            '\r' => "\\r".to_owned(),
+            '\t' => "\\t".to_owned(),
+            '\\' => "\\\\".to_owned(),
+            '\'' => "\\'".to_owned(),
+            '"' => "\\\"".to_owned(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn pseudo_identifier_text(pseudo: &PseudoIdentifier) -> &'static str {
+    match pseudo {
+        PseudoIdentifier::Continue => "continue",
+        PseudoIdentifier::It => "it",
+        PseudoIdentifier::Super => "super",
+        PseudoIdentifier::This => "this",
+        PseudoIdentifier::ThisType => "This",
+        PseudoIdentifier::ThisPackage => "this.package",
+        PseudoIdentifier::ThisModule => "this.module",
+        PseudoIdentifier::Ellipsis => "...",
+        PseudoIdentifier::PlaceholderIdentifier => "_",
+    }
+}
+
+fn infix_operator_text(operator: &OverloadableInfixOperator) -> &'static str {
+    match operator {
+        OverloadableInfixOperator::Add => "+",
+        OverloadableInfixOperator::Ampersand => "&",
+        OverloadableInfixOperator::And => "&&",
+        OverloadableInfixOperator::BitwiseOr => "|",
+        OverloadableInfixOperator::BitwiseXor => "^",
+        OverloadableInfixOperator::Cascade => "->",
+        OverloadableInfixOperator::Compose => "~",
+        OverloadableInfixOperator::Divide => "/",
+        OverloadableInfixOperator::Equals => "==",
+        OverloadableInfixOperator::GreaterThan => ">",
+        OverloadableInfixOperator::GreaterThanOrEqual => ">=",
+        OverloadableInfixOperator::LeftShift => "<<",
+        OverloadableInfixOperator::LessThan => "<",
+        OverloadableInfixOperator::LessThanOrEqual => "<=",
+        OverloadableInfixOperator::Modulo => "%",
+        OverloadableInfixOperator::Multiply => "*",
+        OverloadableInfixOperator::NotEqual => "!=",
+        OverloadableInfixOperator::Or => "||",
+        OverloadableInfixOperator::Pipe => "|>",
+        OverloadableInfixOperator::Power => "**",
+        OverloadableInfixOperator::RightShift => ">>",
+        OverloadableInfixOperator::UnsignedRightShift => ">>>",
+        OverloadableInfixOperator::Subtract => "-",
+        OverloadableInfixOperator::MatrixAdd => "@+",
+        OverloadableInfixOperator::MatrixDivide => "@/",
+        OverloadableInfixOperator::MatrixMultiply => "@*",
+        OverloadableInfixOperator::MatrixPower => "@**",
+        OverloadableInfixOperator::MatrixSubtract => "@-",
+        OverloadableInfixOperator::MatrixTranspose => "@@",
+        OverloadableInfixOperator::Xor => "^^",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    const SOURCE: &str = r#"package main (a.{b as x, c})
+var answer = 40 + 2
+if answer == 42 { println("yes") } else if maybe { println(of: "maybe") } else { fail() }
+"#;
+
+    fn reformat(source: &str, config: FormatterConfig) -> String {
+        format(&parse_str(source).unwrap(), config).unwrap()
+    }
+
+    #[test]
+    fn default_configuration() {
+        assert_eq!(
+            reformat(SOURCE, Default::default()),
+            r#"package main (a.{b as x, c})
+
+var answer = 40 + 2
+if answer == 42 {
+    println("yes")
+} else if maybe {
+    println(of: "maybe")
+} else {
+    fail()
+}
+"#
+        );
+    }
+
+    #[test]
+    fn tabs_and_next_line_braces() {
+        let config = FormatterConfig {
+            indentation: Indentation::Tabs,
+            brace_style: BraceStyle::NextLine,
+        };
+        assert_eq!(
+            reformat(SOURCE, config),
+            "package main (a.{b as x, c})\n\nvar answer = 40 + 2\nif answer == 42\n{\n\tprintln(\"yes\")\n}\nelse if maybe\n{\n\tprintln(of: \"maybe\")\n}\nelse\n{\n\tfail()\n}\n"
+        );
+    }
+
+    #[test]
+    fn idempotent_under_each_configuration() {
+        let configs = [
+            FormatterConfig::default(),
+            FormatterConfig {
+                indentation: Indentation::Spaces(2),
+                brace_style: BraceStyle::SameLine,
+            },
+            FormatterConfig {
+                indentation: Indentation::Tabs,
+                brace_style: BraceStyle::NextLine,
+            },
+        ];
+        for &config in &configs {
+            let once = reformat(SOURCE, config);
+            assert_eq!(reformat(&once, config), once);
+        }
+    }
+
+    #[test]
+    fn records() {
+        let formatted = reformat("package main\nvar p = #{x:1,y:#{}}", Default::default());
+        assert_eq!(formatted, "package main\n\nvar p = #{ x: 1, y: #{} }\n");
+        assert_eq!(reformat(&formatted, Default::default()), formatted);
+    }
+
+    #[test]
+    fn grouping_follows_precedence() {
+        for (source, expected) in &[
+            ("(a - b) - c", "a - b - c"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("(a * b) + c", "a * b + c"),
+            ("(a + b) * c", "(a + b) * c"),
+            ("a ** (b ** c)", "a ** b ** c"),
+            ("(a ** b) ** c", "(a ** b) ** c"),
+        ] {
+            let formatted = reformat(
+                &format!("package main\nvar x = {}", source),
+                Default::default(),
+            );
+            assert_eq!(formatted, format!("package main\n\nvar x = {}\n", expected));
+            assert_eq!(reformat(&formatted, Default::default()), formatted);
+        }
+    }
+
+    #[test]
+    fn trailing_sydocs() {
+        let formatted = reformat("package main\nvar x = 1\n/** One. */", Default::default());
+        assert_eq!(formatted, "package main\n\nvar x = 1 /** One. */\n");
+        assert_eq!(reformat(&formatted, Default::default()), formatted);
+    }
+
+    /// Format `source`, checking that the result parses back to the same AST
+    /// and formats to itself.
+    fn round_trip(source: &str) -> String {
+        use crate::parsing::nodes::content_hashing::hash_main_file;
+
+        let formatted = reformat(source, Default::default());
+        let reparsed = parse_str(&formatted).unwrap();
+        assert_eq!(
+            hash_main_file(&reparsed),
+            hash_main_file(&parse_str(source).unwrap())
+        );
+        assert_eq!(format(&reparsed, Default::default()).unwrap(), formatted);
+        formatted
+    }
+
+    #[test]
+    fn classes_funs_and_typed_bindings() {
+        let source = r#"package main
+class Counter(start Int, var public step Int) implements Printable {
+    final public limit Int = 10
+    var count = start
+    fun public increment() Counter { Counter(start: count + step, step: step) }
+    fun reset() { Counter(0, step) }
+}
+fun public largest[T extends Comparable[T]](of list List[T], var fallback T) T {
+    var best = list.reduce[T](-> a, b { if a > b { a } else { b } })
+    best
+}
+final x Int = 1
+final quoted String = "say \"hi\"\n"
+"#;
+        assert_eq!(
+            round_trip(source),
+            r#"package main
+
+class Counter(start Int, var public step Int) implements Printable {
+    final public limit Int = 10
+    var count = start
+
+    fun public increment() Counter {
+        Counter(start: count + step, step: step)
+    }
+
+    fun reset() {
+        Counter(0, step)
+    }
+}
+fun public largest[T extends Comparable[T]](of list List[T], var fallback T) T {
+    var best = list.reduce[T](-> a, b {
+        if a > b {
+            a
+        } else {
+            b
+        }
+    })
+    best
+}
+final x Int = 1
+final quoted String = "say \"hi\"\n"
+"#
+        );
+    }
+
+    #[test]
+    fn loops_and_switches() {
+        let source = "package main
+for outer var i = 0, found = 0 yield i { if i < 3 { outer(i + 1, found) } }
+while var [first, ..rest] = xs { println(first) }
+switch shape as s { Circle(radius: r), Square(side) if side > 0 { r } _ { fallthrough } 0 { -1 } }
+switch { a < b: else { False } }
+search: { break search 1 }";
+        assert_eq!(
+            round_trip(source),
+            "package main
+
+for outer var i = 0, found = 0 yield i {
+    if i < 3 {
+        outer(i + 1, found)
+    }
+}
+while var [first, ..rest] = xs {
+    println(first)
+}
+switch shape as s {
+    Circle(radius: r), Square(side) if side > 0 {
+        r
+    }
+    _ {
+        fallthrough
+    }
+    0 {
+        -1
+    }
+}
+switch {
+    a < b:
+    else {
+        False
+    }
+}
+search: {
+    break search 1
+}
+"
+        );
+    }
+
+    #[test]
+    fn nodes_without_a_layout_are_unsupported() {
+        let source = "package main\ninterface Shape extends Printable { fun area() Float }";
+        assert_eq!(
+            format(&parse_str(source).unwrap(), Default::default()),
+            Err(Unsupported("interfaces"))
+        );
+    }
+
+    #[test]
+    fn statements_keep_their_order() {
+        assert_eq!(
+            reformat(
+                "package main\nfun f() { var a = 1\nvar b = 2\nprintln(a)\nprintln(b) }",
+                Default::default()
+            ),
+            "package main\n\nfun f() {\n    var a = 1\n    var b = 2\n    println(a)\n    println(b)\n}\n"
+        );
+
+        for source in &[
+            "package main\nfun f() { var a = 1\nprintln(a)\nvar b = 2\nprintln(b) }",
+            "package main\nvar a = 1\nprintln(a)\nvar b = 2\nprintln(b)",
+        ] {
+            assert_eq!(
+                format(&parse_str(source).unwrap(), Default::default()),
+                Err(Unsupported("expressions before bindings"))
+            );
+        }
+    }
+
+    fn method_signatures(source: &str) -> Vec<String> {
+        let file = parse_str(source).unwrap();
+        match &file.package.package.items[0] {
+            Item::Type(crate::parsing::nodes::Type {
+                item: crate::parsing::nodes::TypeItem::Class(class),
+                ..
+            }) => class
+                .methods
+                .iter()
+                .map(|method| format_signature(&method.r#abstract.signature).unwrap())
+                .collect(),
+            other => panic!("expected a class: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn generic_signatures() {
+        assert_eq!(
+            method_signatures(
+                "package main
+                class Lists {
+                    fun largest[T extends (Comparable[T] & Printable) | Number](of list List[T]) T {
+                        list.first()
+                    }
+                    fun repeat(times Int: 2) Lists { this }
+                    fun pick[T extends (A | B) & C?](from list List[T]) T { list.first() }
+                    fun keep[F extends ((T) -> Bool) | (T, Int) -> Bool?](matching f F) Lists { this }
+                }"
+            ),
+            vec![
+                "largest[T extends Comparable[T] & Printable | Number](of list List[T]) T",
+                "repeat(times Int: 2) Lists",
+                "pick[T extends (A | B) & C?](from list List[T]) T",
+                "keep[F extends ((T) -> Bool) | ((T, Int) -> Bool?)](matching f F) Lists",
+            ]
+        );
+    }
+}
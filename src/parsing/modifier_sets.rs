@@ -33,11 +33,11 @@ impl AccessibilityModifierExtractor {
     pub fn new() -> Self {
         let mut accessibility_tokens = HashMap::new();
         accessibility_tokens.insert(
-            Modifier::Accessibility(Accessibility::Private),
+            Modifier::Accessibility(Accessibility::Public),
             Accessibility::Public,
         );
         accessibility_tokens.insert(
-            Modifier::Accessibility(Accessibility::Private),
+            Modifier::Accessibility(Accessibility::Internal),
             Accessibility::Internal,
         );
         Self {
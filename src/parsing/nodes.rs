@@ -43,13 +43,36 @@
 //! The parser doesn't care, since refuttabillity can only be asserted with a
 //! type system. Thus, they are both just "patterns" here.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::common::multiphase::{
-    Accessibility, Identifier, InterpolatedString, Number, OverloadableInfixOperator,
-    PostfixOperator, PseudoIdentifier, Shebang, SyDoc, SylanString,
+    Accessibility, HexFloat, Identifier, InterpolatedString, Number, NumericType,
+    OverloadableInfixOperator, PostfixOperator, PseudoIdentifier, Scientific, Shebang, SyDoc,
+    SylanString,
 };
 use crate::common::version::Version;
+use crate::source::{Position, Span};
+
+pub mod checks;
+pub mod content_hashing;
+pub mod default_methods;
+pub mod divergence;
+pub mod embedding;
+pub mod entry_point;
+pub mod final_parameters;
+pub mod glob_imports;
+pub mod import_aliases;
+pub mod imports;
+pub mod interpolations;
+pub mod literal_bounds;
+pub mod name_resolution;
+pub mod optionals;
+pub mod overrides;
+pub mod reachability;
+pub mod self_bindings;
+pub mod transformation;
+pub mod traversal;
+pub mod type_parameters;
 
 /// Shebangs and source versions are special, which is why they're outside of
 /// the `PackageFile` in which all other items and expressions reside. Both
@@ -90,12 +113,69 @@ pub struct MainPackage {
     pub block: Block,
 }
 
+/// A file is an entry point if it has top-level executable code, which only the
+/// main package can have. Otherwise it only declares items for other files to
+/// import.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum FilePackage {
+    EntryPoint(MainPackage),
+    Imported(Package),
+}
+
+/// A module's definition, naming the other modules it requires, as in
+/// `module shop { requires sylan.http >= 1.2.0 }`. Resolving them against the
+/// versions actually available is left to a later pass.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ModuleDefinition {
+    pub name: SymbolLookup,
+    pub requirements: Vec<Requirement>,
+}
+
+/// A required module, which is any version of it without a constraint.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Requirement {
+    pub module: SymbolLookup,
+    pub constraint: Option<VersionConstraint>,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum VersionComparison {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+/// Limits the versions of a required module, as in the `>= 1.2.0` of
+/// `requires sylan.http >= 1.2.0`. Versions compare by their ordering, so
+/// this says nothing about which are compatible with each other.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct VersionConstraint {
+    pub comparison: VersionComparison,
+    pub version: Version,
+}
+
+impl VersionConstraint {
+    pub fn admits(&self, available: Version) -> bool {
+        match self.comparison {
+            VersionComparison::Equal => available == self.version,
+            VersionComparison::NotEqual => available != self.version,
+            VersionComparison::LessThan => available < self.version,
+            VersionComparison::LessThanOrEqual => available <= self.version,
+            VersionComparison::GreaterThan => available > self.version,
+            VersionComparison::GreaterThanOrEqual => available >= self.version,
+        }
+    }
+}
+
 /// Every node in Sylan is either an item or an expression, even the special
 /// shebang and version tokens (both of which are items).
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Node {
-    Item(Item),
-    Expression(Expression),
+    Item(Box<Item>),
+    Expression(Box<Expression>),
 }
 
 /// The declarations that make up the static structure of a Sylan program. Items
@@ -115,6 +195,10 @@ pub enum Item {
     // think of a use case for mutually recursive loop reiteration bindings.
     Var(Binding),
     Final(Binding),
+
+    // Bound at compile-time, so the value must be a constant expression. See
+    // `Expression::is_constant`.
+    Const(Binding),
 }
 
 /// The expressions that allow Turing-complete computations, i.e. allowing
@@ -128,9 +212,17 @@ pub enum Expression {
     Symbol(Symbol),
     Throw(Throw),
     Use(Use),
+    Defer(Defer),
+
+    /// A `???` standing in for code yet to be written. It fits wherever a
+    /// value of any type is expected, but fails if it's ever evaluated. The
+    /// position is where it was written, for reporting what it must become.
+    Hole(Option<Position>),
+
     MemberHandle(Symbol),
     NonDestructiveUpdate(ExpressionCall),
     ReaderMacroActivation(ReaderMacroActivation),
+    Spawn(Spawn),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -151,9 +243,25 @@ pub struct MultiSlice(pub Vec<SliceFragment>);
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Operator {
-    MultiSlice(MultiSlice),
+    /// `list[|1 : 3|]` slices the expression before it.
+    MultiSlice(Box<Expression>, MultiSlice),
+
     OverloadableInfix(Box<Expression>, OverloadableInfixOperator, Box<Expression>),
     Postfix(Box<Expression>, PostfixOperator),
+
+    /// `a.b().c` looks up `c` on the result of calling `a.b`. Lookups made
+    /// purely of names, such as the `a.b` there, are left as symbols instead,
+    /// so only lookups on the results of other expressions are operators.
+    Lookup(Box<Expression>, Identifier),
+
+    /// `a?.b` looks up `b` on an optional `a`, short-circuiting to `Null` if
+    /// `a` is `Null`.
+    OptionalChain(Box<Expression>, Identifier),
+
+    /// `(+)` refers to the function behind an infix operator, taking its two
+    /// operands as arguments, so it can be passed around like any other
+    /// function, as in `xs.reduce((+))`.
+    Reference(OverloadableInfixOperator),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -168,6 +276,12 @@ pub enum BranchingAndJumping {
     Switch(Switch),
     While(While),
     WhileVar(WhileVar),
+    LabelledBlock(LabelledBlock),
+    Break(Break),
+
+    /// Ends a `switch` arm by running the next arm's body too, as explained on
+    /// [Switch].
+    Fallthrough,
 }
 
 // One notable difference between funs and lambdas is that omitting a return
@@ -181,10 +295,46 @@ pub enum BranchingAndJumping {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct FunModifiers {
     pub accessibility: Accessibility,
-    pub is_extern: bool,
+
+    /// Set for `extern` funs, which are defined in another compiled artefact
+    /// and called with the given convention.
+    pub r#extern: Option<CallingConvention>,
+
     pub is_operator: bool,
 }
 
+/// How an `extern` fun is called, named by a string after `extern` as in
+/// `extern "C" fun`. Without one, the C convention is used, as it's the one
+/// most foreign libraries export.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// `"C"`, the platform's C calling convention.
+    #[default]
+    C,
+
+    /// `"system"`, the convention of the platform's system libraries. That's
+    /// `stdcall` for the Win32 API on 32-bit Windows, and C elsewhere.
+    System,
+}
+
+impl CallingConvention {
+    pub const ALL: [CallingConvention; 2] = [CallingConvention::C, CallingConvention::System];
+
+    pub fn from_name(name: &str) -> Option<CallingConvention> {
+        CallingConvention::ALL
+            .iter()
+            .copied()
+            .find(|convention| convention.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CallingConvention::C => "C",
+            CallingConvention::System => "system",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ValueParameter {
     /// A label is omitted if the developer passes an `_` where a label is expected.
@@ -200,6 +350,12 @@ pub struct ValueParameter {
     /// TODO: tolerate any token or grouped token to tolerate procedural macros.
     pub is_syntax: bool,
 
+    /// Parameters are final unless declared with `var`, as in `var count Int`.
+    /// Sylan has no reassignment, so only a mutable parameter can be rebound
+    /// by a `var` binding at the top of its function's body. A class's
+    /// parameters are always final, as `var` upgrades them to fields instead.
+    pub is_mutable: bool,
+
     pub pattern: Pattern,
     pub type_annotation: TypeReference,
     pub default_value: Option<Expression>,
@@ -221,8 +377,8 @@ pub struct ClassValueParameter {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ReturnType {
-    r#type: TypeReference,
-    ignorable: bool,
+    pub r#type: TypeReference,
+    pub ignorable: bool,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -241,6 +397,8 @@ pub struct FunSignature {
 pub struct Fun {
     pub modifiers: FunModifiers,
     pub signature: FunSignature,
+
+    /// Empty for `extern` funs, which are written without bodies.
     pub block: Block,
 }
 
@@ -248,6 +406,13 @@ pub struct Fun {
 pub struct ImportSingleStem {
     pub name: Identifier,
 
+    // Set when imported `as` another name, usually to avoid a clash.
+    pub alias: Option<Identifier>,
+
+    // Set when the alias is marked with `override`, as in
+    // `as override List`, to show that shadowing a builtin is intended.
+    pub overrides_builtin: bool,
+
     // Will be empty for the vast majority of imports.
     pub readers: Vec<Symbol>,
 }
@@ -256,12 +421,21 @@ pub struct ImportSingleStem {
 pub enum ImportStem {
     Single(ImportSingleStem),
     Multiple(Vec<Import>),
+
+    // Everything public in the package named by the import's root, as in
+    // `a.b.*`.
+    Glob,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Import {
     pub root: Option<Symbol>,
     pub stem: ImportStem,
+
+    // Set when marked with `exports`, as in `exports a.b`, to make the
+    // imported names part of the importing package's own API. Everything in a
+    // re-exported group is re-exported.
+    pub is_reexported: bool,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -285,10 +459,15 @@ pub struct ClassModifiers {
 
 // Concrete classes that support implementing interfaces and aliasing other
 // classes and methods inside, but cannot extend other classes directly.
+//
+// Instead, classes can embed other classes, laying them out inline. An embedded
+// class can't directly or indirectly embed the class embedding it, as it would
+// then be infinitely large.
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Class {
     pub implements: Vec<TypeReference>,
+    pub embeds: Vec<TypeReference>,
     pub methods: Vec<ConcreteMethod>,
     pub fields: Vec<Field>,
 
@@ -358,6 +537,40 @@ impl TypeReference {
     }
 }
 
+/// A type as written in annotations, bounds, and type arguments. `A & B` is a
+/// value of both types and `A | B` a value of either, as in `T | Null` for
+/// optional values. Intersections bind tighter than unions, so `A & B | C` is
+/// `(A & B) | C`; parentheses can group them otherwise. Both are associative,
+/// so chains of either operator are flattened into a single node.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TypeExpression {
+    Reference(TypeReference),
+    Intersection(Vec<TypeExpression>),
+    Union(Vec<TypeExpression>),
+
+    /// A type suffixed with `?`, such as `Int?`, can also be `Null`. Only
+    /// optional values can be chained with `?.`. The suffix binds tighter than
+    /// both operators, so `A | B?` only makes `B` optional.
+    Optional(Box<TypeExpression>),
+
+    /// The type of a lambda or fun taking the parameter types and yielding
+    /// the result type, as in `(Int, String) -> Bool`. The result extends as
+    /// far right as it can, so `(Int) -> A | B` yields either; group the
+    /// function type to use it as an operand instead.
+    Function(Vec<TypeExpression>, Box<TypeExpression>),
+}
+
+impl TypeExpression {
+    pub fn is_optional(&self) -> bool {
+        matches!(self, TypeExpression::Optional(_))
+    }
+}
+
+/// Adds methods to an existing type, as in `extend Account { ... }`. It can
+/// also make the type implement more interfaces, as in
+/// `extend Account : Printable { ... }`, which are recorded in its `item`'s
+/// `implements`. The type must then provide every method the interfaces leave
+/// abstract, either by defining them itself or in the extension.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Extension {
     pub symbol: Symbol,
@@ -369,8 +582,8 @@ pub struct Extension {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct MethodModifiers {
-    fun_modifiers: FunModifiers,
-    overrides: bool,
+    pub fun_modifiers: FunModifiers,
+    pub overrides: bool,
 }
 
 /// Methods and just bindings in a class, which can be potentially abstract (i.e. with no initial
@@ -394,24 +607,27 @@ pub struct AbstractMethod {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ConcreteMethod {
-    r#abstract: AbstractMethod,
-    scope: Block,
+    pub r#abstract: AbstractMethod,
+    pub scope: Block,
 }
 
+// In an interface, a concrete method is a default. Implementing classes inherit
+// it as-is unless they override it with their own.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Method {
     Abstract(AbstractMethod),
     Concrete(ConcreteMethod),
 }
 
-/// Type parameters are for types at compile-time and have optional upper
-/// bounds, identifiers, and optional default values.
+/// Type parameters are for types at compile-time and have an optional upper
+/// bound, identifiers, and optional default values. Multiple bounds are given
+/// as an intersection, as in `T extends A & B`.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct TypeParameter {
     pub label: Option<Identifier>,
     pub name: Identifier,
-    pub upper_bounds: Vec<TypeReference>,
-    pub default_value: Option<TypeReference>,
+    pub upper_bound: Option<TypeExpression>,
+    pub default_value: Option<TypeExpression>,
     pub sydoc: Option<SyDoc>,
 }
 
@@ -419,6 +635,12 @@ pub struct TypeParameter {
 pub struct Argument<T> {
     pub label: Option<Identifier>,
     pub value: T,
+
+    /// Spread arguments, prefixed with `..`, expand a collection into the
+    /// remaining positional parameters. They can't be labelled and must be the
+    /// final argument, so there's never any doubt about which parameters they
+    /// fill. Type arguments are never spread.
+    pub is_spread: bool,
 }
 
 /// Value arguments are for values at runtime. They support being passed as
@@ -431,7 +653,7 @@ pub type ValueArgument = Argument<Expression>;
 /// positional or keyword arguments; unlike other languages it is the choice of
 /// the caller rather than the definer. If passed as a keyword argument, an
 /// identifier is carried with it in the parse tree.
-pub type TypeArgument = Argument<TypeReference>;
+pub type TypeArgument = Argument<TypeExpression>;
 
 // Sylan's "symbol tables" are just a collection of bindings in the current
 // scope. Parent scopes can be looked up to find bindings in outer closures,
@@ -456,7 +678,28 @@ pub type TypeArgument = Argument<TypeReference>;
 pub struct Binding {
     pub pattern: Pattern,
     pub value: Box<Expression>,
-    pub explicit_type_annotation: Option<TypeReference>,
+    pub explicit_type_annotation: Option<TypeExpression>,
+    pub comments: Comments,
+
+    // Bindings are documented by a SyDoc directly after them, either on the
+    // same line or the next. SyDocs can't start a declaration, so there's
+    // never any doubt over which binding one belongs to.
+    pub sydoc: Option<SyDoc>,
+
+    // Carried along so later stages can relate what they generate back to
+    // the source. Bindings synthesised by the compiler rather than parsed have
+    // none.
+    pub span: Option<Span>,
+}
+
+// Ordinary comments are trivia, but tools that move code around want to take
+// them along too. If the parser is asked to retain them, comments on their own
+// lines just before a node lead it, and those on the same line after it trail
+// it. They are kept verbatim, delimiters included.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Comments {
+    pub leading: Vec<String>,
+    pub trailing: Vec<String>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -466,6 +709,9 @@ pub struct Final {
     pub sydoc: Option<SyDoc>,
 }
 
+// An `@`-prefixed macro in an item slot is an annotation, such as `@inline` or
+// `@config(limit: 5, retries: 3)`. Like any other call, its arguments can be
+// positional or labelled.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum MacroItem {
     Bare(Symbol),
@@ -492,10 +738,20 @@ pub enum Macro {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Field {
     pub is_extern: bool,
+    pub is_final: bool,
     pub accessibility: Accessibility,
     pub binding: Binding,
 }
 
+impl Field {
+    /// Associated constants, such as `final MAX Int = 100`, are final fields
+    /// that aren't bound to lambdas; those are closer to methods.
+    pub fn is_associated_constant(&self) -> bool {
+        let is_lambda = matches!(*self.binding.value, Expression::Literal(Literal::Lambda(_)));
+        self.is_final && !is_lambda
+    }
+}
+
 /// Expressions are seperate from bindings.
 type Expressions = Vec<Expression>;
 
@@ -521,12 +777,26 @@ type Expressions = Vec<Expression>;
 /// declare new types like packages can.
 ///
 /// All functions, concrete methods, and lambdas have an attached scope.
+///
+/// Parents are shared with `Arc` so that blocks, and the items containing
+/// them, can be parsed on other threads.
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Block {
     pub bindings: Vec<Binding>,
     pub expressions: Expressions,
-    pub parent: Option<Rc<Block>>,
+    pub parent: Option<Arc<Block>>,
+
+    /// From the opening brace to the closing one. Blocks the parser makes up
+    /// rather than reads, such as the branches of `?:`, have none. A class's
+    /// instance initialiser spans the whole class body, as its code is
+    /// written among the fields and methods.
+    pub span: Option<Span>,
+
+    /// Whether an expression came before any of the bindings in the source.
+    /// Bindings and expressions are kept apart, so the order they were
+    /// interleaved in is lost.
+    pub interleaved: bool,
 }
 
 impl Block {
@@ -535,16 +805,35 @@ impl Block {
             bindings: vec![],
             expressions: vec![],
             parent: None,
+            span: None,
+            interleaved: false,
         }
     }
 
-    pub fn within(parent: &Rc<Block>) -> Self {
+    pub fn within(parent: &Arc<Block>) -> Self {
         Block {
             bindings: vec![],
             expressions: vec![],
             parent: Some(parent.clone()),
+            span: None,
+            interleaved: false,
         }
     }
+
+    /// The expression whose value the block yields, which is its last. A
+    /// block ending in an `if` or a `switch` therefore yields the value of
+    /// whichever branch runs.
+    pub fn tail(&self) -> Option<&Expression> {
+        self.expressions.last()
+    }
+
+    /// Whether the block yields a value however its tail branches. Empty
+    /// blocks yield `Void`, which is still a value.
+    pub fn yields_value_on_every_branch(&self) -> bool {
+        self.tail()
+            .into_iter()
+            .all(Expression::yields_value_on_every_branch)
+    }
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -552,6 +841,9 @@ pub struct LambdaValueParameter {
     pub label: Option<Identifier>,
     pub pattern: Pattern,
     pub default_value: Option<Expression>,
+
+    /// As for [ValueParameter::is_mutable].
+    pub is_mutable: bool,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -569,6 +861,47 @@ pub struct Lambda {
     pub block: Block,
 }
 
+// Records are ad-hoc bundles of named values, such as for returning several
+// values without declaring a class for them. They start with `#{` rather than a
+// plain brace so they can't be mistaken for blocks. Fields are read back with
+// the usual dotted lookups.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Record(pub Vec<RecordField>);
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RecordField {
+    pub name: Identifier,
+    pub value: Expression,
+}
+
+// Tuples are positional rather than named bundles of values, mostly for returning
+// several values at once to be immediately destructured by the caller, e.g.
+// `var (quotient, remainder) = divide(7, 2)`. A parenthesised expression is only
+// a tuple if it contains a comma, so `(x)` is still just `x`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Tuple(pub Vec<Expression>);
+
+// Lists are immutable by default, so `[1, 2, 3]` is a `List`. A mutable list
+// must be asked for by prefixing the literal with `var`, as in `var [1, 2, 3]`,
+// which is a `MutableList` instead. Lists can't start statements, as they'd be
+// mistaken for type arguments or a slice of the line before.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct List {
+    pub elements: Vec<Expression>,
+    pub mutable: bool,
+}
+
+impl List {
+    /// The type of the collection the literal builds. Its type argument is
+    /// left to be inferred from the elements, as with unsuffixed numbers.
+    pub fn inferred_type(&self) -> TypeExpression {
+        let name = if self.mutable { "MutableList" } else { "List" };
+        TypeExpression::Reference(TypeReference::new(Symbol::Relative(SymbolLookup(vec![
+            Identifier::from(name),
+        ]))))
+    }
+}
+
 // Parameterised modules are still being considered; until they're committed to, just a vector of
 // identifiers is enough. Static methods don't exist in Sylan, but `Class.method` as syntactical
 // sugar for `-> object, ..args { object.method(..args)}` does, so type symbols must also be
@@ -586,6 +919,16 @@ pub enum Symbol {
     Relative(SymbolLookup),
     Absolute(SymbolLookup),
     Pseudo(PseudoIdentifier),
+
+    // A lookup on the current object, such as `this.area`. Unlike the other
+    // lookups, it can only be resolved against the enclosing type's members.
+    ThisMember(SymbolLookup),
+
+    // A method looked up on a type rather than on a value, such as
+    // `Int.parse`. The type is a relative lookup, and is resolved against the
+    // type's declaration rather than an instance.
+    TypeMember(SymbolLookup, Identifier),
+
     InferredEnumVariant(Identifier),
 }
 
@@ -593,14 +936,34 @@ pub enum Symbol {
 pub enum Literal {
     Char(char),
     InterpolatedString(InterpolatedString),
-    Number(Number),
+    Number(Number, Option<NumericType>),
+    HexFloat(HexFloat),
+    Scientific(Scientific),
     String(SylanString),
     Lambda(Lambda),
+    Record(Record),
+    Tuple(Tuple),
+    List(List),
 }
 
+// Cases are tried strictly top-to-bottom and the first to match wins, even if
+// later ones would also match. Within a case, its matches are likewise tried
+// in order, each guard only being evaluated if its pattern matches. The parser
+// must therefore keep cases and matches in source order.
+//
+// Arms don't fall through into the next by default. An arm ending with
+// `fallthrough` does, running the next arm's body without matching its
+// patterns or checking its guards. As nothing would bind them, the next arm
+// can't bind any names, and the last arm has nothing to fall through into.
+// `fallthrough` can't be used anywhere but at the end of an arm, so it's
+// unrelated to a loop's `continue`.
+//
+// `switch x as s { ... }` binds the switched value to `s` for every arm's
+// guards and body, including arms fallen through into.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Switch {
     pub expression: Box<Expression>,
+    pub binding: Option<Identifier>,
     pub cases: Vec<Case>,
 }
 
@@ -610,13 +973,22 @@ pub struct Timeout {
     pub body: Block,
 }
 
+// Cases are tried in the same order as a switch's for each message received.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Select {
-    pub message_type: TypeReference,
-    pub cases: Vec<Case>,
+    pub cases: Vec<SelectCase>,
     pub timeout: Option<Timeout>,
 }
 
+/// Each case of a `select` handles messages of one type, written before its
+/// patterns as in `Deposit amount { ... }`, so one `select` can wait on several
+/// types of message at once. A case's patterns only see messages of its type.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SelectCase {
+    pub message_type: TypeReference,
+    pub case: Case,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct CallArguments {
     pub type_arguments: Vec<TypeArgument>,
@@ -635,8 +1007,34 @@ pub struct ExpressionCall {
     pub arguments: CallArguments,
 }
 
+/// `use` yields the value of its expression while also acquiring it as a
+/// resource, to be closed by calling its `close` method when the block around
+/// it exits. That happens however the block exits, even by a `throw`, so
+/// `var file = use open(path)` is never left open. Resources are closed in
+/// the reverse of the order they were acquired in.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Use(pub Box<Expression>);
+
+/// `defer { ... }` runs its block when the block around it exits rather than
+/// straight away, however that block exits. Deferred blocks run in the reverse
+/// of the order they were deferred in, interleaved with the closing of
+/// resources acquired with `use`, so cleanup undoes setup in reverse. A
+/// `defer` itself yields `Void`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Defer(pub Block);
+
+/// `spawn -> { ... }` runs its task concurrently with the code spawning it,
+/// yielding a handle to the new task straight away. It's written like passing
+/// a lambda to `sylan.core.lang.spawn`, but is kept apart from ordinary calls
+/// so that the runtime can schedule the task itself.
+///
+/// The task is a lambda's body rather than an arbitrary expression, so it
+/// can't be confused with a lambda being returned by the task. It sees the
+/// names around it like any closure, but can't `break` out to their labels.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct Use(Box<Expression>);
+pub struct Spawn {
+    pub task: Block,
+}
 
 // Ifs must have braces for both the matching body and the else clause if one
 // exists, like any other control statement. There's one exception: if the else
@@ -649,9 +1047,72 @@ pub struct If {
     pub else_clause: Option<Block>,
 }
 
+impl Expression {
+    /// Whether the expression can be folded at compile-time, given the names
+    /// of the constants already bound. Non-interpolated literals, records of
+    /// constants, operators over constants, and references to other constants
+    /// qualify.
+    pub fn is_constant(&self, constants: &[Identifier]) -> bool {
+        match self {
+            Expression::Literal(literal) => match literal {
+                Literal::Char(_)
+                | Literal::Number(..)
+                | Literal::HexFloat(_)
+                | Literal::Scientific(_)
+                | Literal::String(_) => true,
+                Literal::Record(Record(fields)) => fields
+                    .iter()
+                    .all(|field| field.value.is_constant(constants)),
+                Literal::Tuple(Tuple(elements)) => elements
+                    .iter()
+                    .all(|element| element.is_constant(constants)),
+                Literal::List(list) => {
+                    !list.mutable
+                        && list
+                            .elements
+                            .iter()
+                            .all(|element| element.is_constant(constants))
+                }
+                Literal::InterpolatedString(_) | Literal::Lambda(_) => false,
+            },
+            Expression::Operator(Operator::OverloadableInfix(left, _, right)) => {
+                left.is_constant(constants) && right.is_constant(constants)
+            }
+            Expression::Symbol(Symbol::Relative(SymbolLookup(lookup))) => match &lookup[..] {
+                [name] => constants.contains(name),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether the expression yields a value on every branch when used for
+    /// its value. Only an `if` without an `else` can fail to, but it can be
+    /// hidden in the tail of a branch of an `if` or `switch`, or of a block.
+    pub fn yields_value_on_every_branch(&self) -> bool {
+        match self {
+            Expression::BranchingAndJumping(BranchingAndJumping::If(r#if)) => {
+                match &r#if.else_clause {
+                    Some(else_clause) => {
+                        r#if.then.yields_value_on_every_branch()
+                            && else_clause.yields_value_on_every_branch()
+                    }
+                    None => false,
+                }
+            }
+            Expression::BranchingAndJumping(BranchingAndJumping::Switch(switch)) => switch
+                .cases
+                .iter()
+                .all(|case| case.body.yields_value_on_every_branch()),
+            Expression::Context(block) => block.yields_value_on_every_branch(),
+            _ => true,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct IfVar {
-    pub binding: Binding,
+    pub binding: Box<Binding>,
     pub then: Block,
     pub else_clause: Option<Block>,
 }
@@ -659,15 +1120,30 @@ pub struct IfVar {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct CondCase {
     pub conditions: Vec<Expression>,
-    pub then: Block,
+
+    /// `None` for a bodyless arm, which yields its matched condition instead.
+    pub then: Option<Block>,
+
+    pub span: Option<Span>,
 }
 
-// The first expression yielding true is used; all others are ignored and not
-// even evaluated, regardless of whether they'd also return true.
+// Arms are evaluated top-to-bottom, as are the conditions within each arm. The
+// first expression yielding true is used; all others are ignored and not even
+// evaluated, regardless of whether they'd also return true.
 //
 // Any expression not yielding a `Boolean` type fails type checking.
+//
+// An `else` arm can be given last, which is evaluated when no other arm
+// matches. Without one, a cond matching no arms yields `Void`.
+//
+// An arm can end with a colon rather than a body, such as `a < b:`. Such an arm
+// yields the condition that matched, which saves writing `{ True }` for conds
+// that are themselves predicates.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct Cond(pub Vec<CondCase>);
+pub struct Cond {
+    pub cases: Vec<CondCase>,
+    pub else_clause: Option<Block>,
+}
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct CaseMatch {
@@ -679,6 +1155,27 @@ pub struct CaseMatch {
 pub struct Case {
     pub matches: Vec<CaseMatch>,
     pub body: Block,
+    pub span: Option<Span>,
+}
+
+impl Case {
+    /// Whether the arm ends with `fallthrough`.
+    pub fn falls_through(&self) -> bool {
+        matches!(
+            self.body.expressions.last(),
+            Some(Expression::BranchingAndJumping(
+                BranchingAndJumping::Fallthrough
+            ))
+        )
+    }
+
+    /// Whether any of the arm's patterns bind names. Falling through into
+    /// such an arm skips its patterns, so nothing would bind them.
+    pub fn binds_names(&self) -> bool {
+        self.matches
+            .iter()
+            .any(|case_match| !case_match.pattern.bound_names().is_empty())
+    }
 }
 
 // For loop "labels" are completely different to parameter labels. They are
@@ -691,19 +1188,52 @@ pub struct Case {
 //
 // For loops will halt unless continue or a label is called.
 //
+// There is no dedicated `continue@label` syntax for targeting an outer loop
+// from a nested one, as the label already _is_ that loop's `continue`. Calling
+// it reiterates the labelled loop, unwinding any loops nested within it. An
+// unqualified `continue` only ever binds to the innermost unlabelled loop.
+//
+// A `for` binding's pattern can destructure each iteration's value, as in
+// `for (key, amount) = pair { ... }`. The `var` before the bindings can be left
+// out unless the loop is labelled, as a label must be followed by one.
+//
 // `if var`, `while var`, and `for while` all allow multiple `var` bindings,
 // separated with commas. `if var` and a `while var` expect _all_ bindings to
 // match before continuing into the block. `for` won't allow refutable
 // patterns; refuttable patterns must be done inside the for loop with other
 // constructs.
+//
+// A `for` is either a statement, yielding whatever its final iteration yields,
+// or a comprehension, which has a `yield` expression before its block, e.g.
+// `for var n = 0 yield n * n { ... }`. A comprehension evaluates its yield
+// expression at the start of every iteration, including the last, and yields a
+// list of the results in order.
+//
+// A labelled loop can also be exited with `break`, as a labelled block can,
+// yielding the break's value. An `else` block after the body runs instead when
+// the loop finishes without breaking, yielding the loop's value in place of
+// the last iteration's. That suits searches, which break with what they find:
+// `for search var i = 0 { ... break search i ... } else { -1 }`.
+// Comprehensions always yield their lists, so they can't have one.
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct For {
     pub bindings: Vec<Binding>,
     pub scope: Block,
     pub reiteration_symbol: Option<Identifier>,
+    pub yield_expression: Option<Box<Expression>>,
+    pub else_clause: Option<Block>,
 }
 
+impl For {
+    pub fn is_comprehension(&self) -> bool {
+        self.yield_expression.is_some()
+    }
+}
+
+// A `while` loop yields `Void` once its condition is false. One that never
+// finishes, such as a `while True` that never reiterates an enclosing `for`
+// loop, has the bottom type instead, and can therefore be used for its value.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct While {
     pub condition: Box<Expression>,
@@ -715,17 +1245,47 @@ pub struct While {
 // irefuttable pattern bound by `for` inside the body.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct WhileVar {
-    pub binding: Binding,
+    pub binding: Box<Binding>,
     pub scope: Block,
 }
 
+/// A block that a `break` inside it can exit early with a value, as in
+/// `search: { ... break search found ... }`. It yields its last expression if
+/// nothing breaks out of it.
+///
+/// Unlike a `for` loop's label, a block's label isn't a name in scope; only
+/// `break` can refer to it. Labels don't reach into lambdas written inside the
+/// block, as they might be called after the block has finished.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LabelledBlock {
+    pub label: Identifier,
+    pub scope: Block,
+}
+
+/// Exits the enclosing block labelled `label`, which then yields `value`, or
+/// `Void` if there isn't one. Like `throw`, it never yields a value itself.
+///
+/// As there's nothing to end a `break` besides its value, one without a value
+/// must come last in its block.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Break {
+    pub label: Identifier,
+    pub value: Option<Box<Expression>>,
+}
+
 /// Throwing an expression does not yield a value as it destroys its current
 /// process. However, it is an expression and can therefore be used anywhere an
 /// expression can be used. It can throw any expression that yields a type which
 /// implements the Exception interface. In "returns" the bottom type which
 /// allows it to be used anywhere.
+///
+/// An exception thrown while handling another can name it as its cause, as in
+/// `throw e caused by original`, so that the original isn't lost.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct Throw(pub Box<Expression>);
+pub struct Throw {
+    pub exception: Box<Expression>,
+    pub cause: Option<Box<Expression>>,
+}
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct PatternGetter {
@@ -739,6 +1299,32 @@ pub struct CompositePattern {
     pub getters: Vec<PatternGetter>,
     pub infer_enum_type: bool,
     pub ignore_rest: bool,
+
+    // Binds the getters not otherwise matched, given as `..name` rather than a
+    // bare `..`. Only ever set when `ignore_rest` is.
+    pub rest_binding: Option<Identifier>,
+}
+
+// Matches a sequence such as a list by its elements, e.g. `[first, ..rest]` or
+// `[first, .., last]`. The leading and trailing patterns match the start and
+// end of the sequence, and the rest, if present, matches everything between
+// them. Without a rest the sequence must have exactly as many elements as
+// there are leading patterns.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SlicePattern {
+    pub leading: Vec<Pattern>,
+
+    // Either an identifier for `..name` or ignored for a bare `..`.
+    pub rest: Option<Box<Pattern>>,
+
+    pub trailing: Vec<Pattern>,
+}
+
+/// An inclusive range of numbers or of chars, such as `1..10` or `'a'..'z'`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RangePattern {
+    pub start: Literal,
+    pub end: Literal,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -750,9 +1336,19 @@ pub enum PatternItem {
     // Refutable unless an interpolated string is used.
     Literal(Literal),
 
+    // Refutable.
+    Range(RangePattern),
+
     // Irrefutable if all fields are also irefuttable.
     Composite(CompositePattern),
 
+    // Likewise irrefutable if all elements are, although the tuple's length
+    // must also match.
+    Tuple(Vec<Pattern>),
+
+    // Refuttable unless it has a rest and no other elements.
+    Slice(SlicePattern),
+
     // Refuttable, as it's worked out at runtime from what the symbol resolves
     // to. Irrefuttable if it can be resolved at compile-time _and_ the
     // left-hand side can also be resolved at compile-time.
@@ -768,3 +1364,61 @@ pub struct Pattern {
     // guards, fun bodies, and `if let`, `while let`, and `for` blocks.
     pub bound_match: Option<Box<Pattern>>,
 }
+
+impl Pattern {
+    /// Whether the pattern matches any value it could be given, judging by the
+    /// pattern alone. Composite patterns could be enum variants, so they're
+    /// never considered irrefutable here.
+    pub fn is_irrefutable(&self) -> bool {
+        let item = match &self.item {
+            PatternItem::Identifier(_) | PatternItem::Ignored => true,
+            PatternItem::Tuple(elements) => elements.iter().all(Pattern::is_irrefutable),
+            PatternItem::Slice(slice) => {
+                slice.leading.is_empty()
+                    && slice.trailing.is_empty()
+                    && matches!(slice.rest.as_deref(), Some(rest) if rest.is_irrefutable())
+            }
+            PatternItem::Composite(_)
+            | PatternItem::Literal(_)
+            | PatternItem::Range(_)
+            | PatternItem::BoundSymbol(_) => false,
+        };
+        item && match &self.bound_match {
+            Some(bound_match) => bound_match.is_irrefutable(),
+            None => true,
+        }
+    }
+
+    /// The names the pattern binds when it matches, including those of nested
+    /// patterns and any `as` match.
+    pub fn bound_names(&self) -> Vec<Identifier> {
+        let mut names = match &self.item {
+            PatternItem::Identifier(identifier) => vec![identifier.clone()],
+            PatternItem::Composite(composite) => composite
+                .getters
+                .iter()
+                .flat_map(|getter| getter.pattern.bound_names())
+                .chain(composite.rest_binding.clone())
+                .collect(),
+            PatternItem::Tuple(elements) => elements
+                .iter()
+                .flat_map(|element| element.bound_names())
+                .collect(),
+            PatternItem::Slice(slice) => slice
+                .leading
+                .iter()
+                .chain(slice.rest.as_deref())
+                .chain(&slice.trailing)
+                .flat_map(|element| element.bound_names())
+                .collect(),
+            PatternItem::Ignored
+            | PatternItem::Literal(_)
+            | PatternItem::Range(_)
+            | PatternItem::BoundSymbol(_) => vec![],
+        };
+        if let Some(bound_match) = &self.bound_match {
+            names.extend(bound_match.bound_names());
+        }
+        names
+    }
+}
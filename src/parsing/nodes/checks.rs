@@ -0,0 +1,125 @@
+//! Runs every check over a parsed main file, gathering their problems as
+//! diagnostics, so that tooling needn't know which checks exist.
+//!
+//! A file is checked on its own, so checks that look across packages only see
+//! those declared within it. Checks that take a block rather than a file are
+//! run over the main package's block and each of its funs' blocks.
+
+use super::default_methods::check_default_methods;
+use super::embedding::check_embedding_cycles;
+use super::entry_point::check_entry_point;
+use super::final_parameters::check_final_parameter_rebindings;
+use super::glob_imports::check_ambiguous_glob_imports;
+use super::import_aliases::check_shadowed_builtins;
+use super::imports::check_main_package_imports;
+use super::interpolations::check_interpolations;
+use super::literal_bounds::check_literal_bounds;
+use super::name_resolution::check_unresolved_names;
+use super::optionals::check_optional_chains;
+use super::overrides::{check_extension_implementations, check_implementations, check_overrides};
+use super::reachability::check_unreachable_arms;
+use super::self_bindings::check_self_bindings;
+use super::type_parameters::check_duplicate_type_parameters;
+use super::{Identifier, Item, MainFile};
+use crate::common::diagnostics::Diagnostic;
+
+pub fn check_main_file(file: &MainFile, builtins: &[Identifier]) -> Vec<Diagnostic> {
+    let package = &file.package.package;
+    let items = &package.items;
+    let blocks = std::iter::once(&file.package.block)
+        .chain(items.iter().filter_map(|item| match item {
+            Item::Fun(fun) => Some(&fun.block),
+            _ => None,
+        }))
+        .collect::<Vec<_>>();
+
+    let mut diagnostics = vec![];
+    diagnostics.extend(check_interpolations(file).iter().map(Diagnostic::from));
+    diagnostics.extend(
+        check_unresolved_names(file, builtins)
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(check_embedding_cycles(items).iter().map(Diagnostic::from));
+    diagnostics.extend(
+        check_duplicate_type_parameters(items)
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(
+        check_final_parameter_rebindings(file)
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(check_default_methods(items).iter().map(Diagnostic::from));
+    diagnostics.extend(
+        check_ambiguous_glob_imports(package, &[package])
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(check_overrides(items).iter().map(Diagnostic::from));
+    diagnostics.extend(check_implementations(items).iter().map(Diagnostic::from));
+    diagnostics.extend(
+        check_extension_implementations(items)
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(check_optional_chains(file).iter().map(Diagnostic::from));
+    diagnostics.extend(
+        check_shadowed_builtins(package, builtins)
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(
+        check_main_package_imports(&file.package, package)
+            .iter()
+            .map(Diagnostic::from),
+    );
+    diagnostics.extend(check_entry_point(file).iter().map(Diagnostic::from));
+    diagnostics.extend(check_literal_bounds(file).iter().map(Diagnostic::from));
+    for block in blocks {
+        diagnostics.extend(check_self_bindings(block).iter().map(Diagnostic::from));
+        diagnostics.extend(check_unreachable_arms(block).iter().map(Diagnostic::from));
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::diagnostics::Severity;
+    use crate::parsing::parse_str;
+
+    fn codes(source: &str) -> Vec<(Severity, &'static str)> {
+        let file = parse_str(source).unwrap();
+        check_main_file(&file, &[Identifier::from("println")])
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.code))
+            .collect()
+    }
+
+    #[test]
+    fn every_check_is_run() {
+        let source = "package main
+            class A embeds B
+            class B embeds A
+            fun main(x Int) {
+                var y = y
+                println(z)
+            }";
+        assert_eq!(
+            codes(source),
+            vec![
+                (Severity::Error, "unresolved-name"),
+                (Severity::Error, "embedding-cycle"),
+                (Severity::Error, "entry-point"),
+                (Severity::Warning, "self-binding"),
+            ]
+        );
+    }
+
+    #[test]
+    fn clean_files_have_no_diagnostics() {
+        assert!(codes("package main\nfun main() {\n    println(\"hi\")\n}").is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Hashes of what files mean rather than how they were written, so that the
+//! formatter can confirm that reformatting a file left its program alone.
+//!
+//! The derived `Hash` of a node takes in its spans, retained comments, and the
+//! parents of its blocks, so reformatting a file would change it. These hashes
+//! leave those out, as well as a file's shebang, so only changes to the
+//! program itself change them.
+//!
+//! The hasher is FNV-1a rather than the standard library's, whose algorithm can
+//! change between releases. Together with writing sizes as 64 bits whatever
+//! the platform, that keeps hashes stable enough to outlive a single run.
+
+use std::hash::{Hash, Hasher};
+
+use super::transformation::{walk_binding, walk_block, walk_expression, Transformer};
+use super::{Binding, Block, BranchingAndJumping, Comments, Expression, Item, MainFile};
+
+pub fn hash_main_file(file: &MainFile) -> u64 {
+    let package = strip_item(Item::Package(file.package.package.clone()));
+    let block = Stripper.transform_block(file.package.block.clone());
+    stable_hash(&(file.version, package, block))
+}
+
+fn strip_item(item: Item) -> Item {
+    Stripper.transform_item(item)
+}
+
+fn stable_hash(value: &impl Hash) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Clears everything left out of the hashes.
+struct Stripper;
+
+impl Transformer for Stripper {
+    fn transform_binding(&mut self, binding: Binding) -> Binding {
+        let mut binding = walk_binding(self, binding);
+        binding.comments = Comments::default();
+        binding.span = None;
+        binding
+    }
+
+    fn transform_expression(&mut self, expression: Expression) -> Expression {
+        match walk_expression(self, expression) {
+            Expression::BranchingAndJumping(BranchingAndJumping::Switch(mut switch)) => {
+                for case in &mut switch.cases {
+                    case.span = None;
+                }
+                Expression::BranchingAndJumping(BranchingAndJumping::Switch(switch))
+            }
+            Expression::BranchingAndJumping(BranchingAndJumping::Select(mut select)) => {
+                for select_case in &mut select.cases {
+                    select_case.case.span = None;
+                }
+                Expression::BranchingAndJumping(BranchingAndJumping::Select(select))
+            }
+            Expression::BranchingAndJumping(BranchingAndJumping::Cond(mut cond)) => {
+                for case in &mut cond.cases {
+                    case.span = None;
+                }
+                Expression::BranchingAndJumping(BranchingAndJumping::Cond(cond))
+            }
+            Expression::Hole(_) => Expression::Hole(None),
+            expression => expression,
+        }
+    }
+
+    fn transform_block(&mut self, block: Block) -> Block {
+        let mut block = walk_block(self, block);
+        block.parent = None;
+        block.span = None;
+        block
+    }
+}
+
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn hash(source: &str) -> u64 {
+        hash_main_file(&parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn layout_does_not_change_hashes() {
+        let compact = "package main
+var f = -> x { x + 1 }
+var answer = f(41)
+switch answer {
+    42 { \"yes\" }
+    _ { \"no\" }
+}";
+        let spread = "package main
+
+var f = -> x {
+        x
+            + 1
+    }
+
+var answer = f(  41  )
+
+switch answer
+{
+    42 {
+        \"yes\"
+    }
+
+
+    _ { \"no\" }
+}";
+        assert_eq!(hash(compact), hash(spread));
+    }
+
+    #[test]
+    fn different_programs_hash_differently() {
+        let hashes = [
+            "package main\nvar answer = f(41)",
+            "package main\nvar answer = f(42)",
+            "package main\nvar answer = g(41)",
+            "package main\nvar result = f(41)",
+            "package main\nvar answer = f(41)\nanswer",
+            "package main\nvar (answer, _) = f(41)",
+        ]
+        .iter()
+        .map(|source| hash(source))
+        .collect::<Vec<_>>();
+
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[i + 1..].contains(hash), "{} collides", i);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Checks that interface default methods only refer to methods that exist.
+//!
+//! A default method, i.e. a concrete method in an interface, can call the
+//! interface's other methods via `this`, including abstract ones that only
+//! implementors define. Interfaces have no fields, so every `this.` lookup in a
+//! default method must name a method declared either in the interface itself or
+//! in one it extends.
+//!
+//! As with override checking, only types declared among the given items are
+//! resolved. If an interface extends anything that can't be resolved, lookups
+//! that appear undeclared aren't reported, since they may be declared there.
+
+use super::overrides::{own_methods, Inherited};
+use super::traversal::NodeRef;
+use super::{
+    Block, BranchingAndJumping, Expression, Identifier, Item, Method, Symbol, SymbolLookup, Type,
+    TypeItem,
+};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UndeclaredMethod {
+    pub interface: Identifier,
+    pub method: Identifier,
+
+    /// The name looked up on `this` that has no matching declaration.
+    pub reference: Identifier,
+}
+
+pub fn check_default_methods(items: &[Item]) -> Vec<UndeclaredMethod> {
+    let types = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Type(r#type) => Some(r#type),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut problems = vec![];
+    for r#type in &types {
+        if let TypeItem::Interface(interface) = &r#type.item {
+            let inherited = Inherited::of(r#type, &types);
+            for method in &interface.methods {
+                if let Method::Concrete(method) = method {
+                    for reference in this_references(&method.scope) {
+                        if !inherited.is_complete || declares(r#type, &inherited, reference) {
+                            continue;
+                        }
+                        problems.push(UndeclaredMethod {
+                            interface: r#type.name.clone(),
+                            method: method.r#abstract.signature.name.clone(),
+                            reference: reference.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn declares(r#type: &Type, inherited: &Inherited<'_>, name: &Identifier) -> bool {
+    own_methods(r#type)
+        .into_iter()
+        .chain(inherited.methods.iter().copied())
+        .any(|(method, _)| method.signature.name == *name)
+}
+
+/// The names looked up on `this` within a method's body, in source order.
+fn this_references(block: &Block) -> Vec<&Identifier> {
+    let roots = block
+        .bindings
+        .iter()
+        .map(NodeRef::Binding)
+        .chain(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut references = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            let symbol = match node {
+                NodeRef::Expression(Expression::Symbol(symbol))
+                | NodeRef::Expression(Expression::MemberHandle(symbol)) => symbol,
+                NodeRef::Expression(Expression::BranchingAndJumping(
+                    BranchingAndJumping::Call(call),
+                )) => &call.target,
+                _ => continue,
+            };
+            if let Symbol::ThisMember(SymbolLookup(lookup)) = symbol {
+                references.extend(lookup.first());
+            }
+        }
+    }
+    references
+}
+
+impl From<&UndeclaredMethod> for Diagnostic {
+    fn from(undeclared: &UndeclaredMethod) -> Self {
+        Diagnostic::error(
+            "undeclared-method",
+            format!(
+                "the default method `{}` of `{}` refers to `this.{}`, which it doesn't declare",
+                undeclared.method.0, undeclared.interface.0, undeclared.reference.0
+            ),
+            undeclared.reference.1.map(Span::at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::multiphase::Accessibility;
+    use crate::parsing::nodes::{
+        AbstractMethod, ConcreteMethod, FunModifiers, FunSignature, Interface, MethodModifiers,
+        TypeReference,
+    };
+    use crate::parsing::parse_expression;
+
+    fn signature(name: &'static str) -> AbstractMethod {
+        AbstractMethod {
+            modifiers: MethodModifiers {
+                fun_modifiers: FunModifiers {
+                    accessibility: Accessibility::Public,
+                    r#extern: None,
+                    is_operator: false,
+                },
+                overrides: false,
+            },
+            signature: FunSignature {
+                name: Identifier::from(name),
+                sydoc: None,
+                type_parameters: vec![],
+                value_parameters: vec![],
+                return_type: None,
+            },
+        }
+    }
+
+    fn default_method(name: &'static str, body: &str) -> Method {
+        let mut scope = Block::new_root();
+        scope.expressions.push(parse_expression(body).unwrap());
+        Method::Concrete(ConcreteMethod {
+            r#abstract: signature(name),
+            scope,
+        })
+    }
+
+    fn interface(name: &'static str, extends: &[&'static str], methods: Vec<Method>) -> Item {
+        Item::Type(Type {
+            name: Identifier::from(name),
+            type_parameters: vec![],
+            item: TypeItem::Interface(Interface {
+                extends: extends
+                    .iter()
+                    .map(|name| {
+                        TypeReference::new(Symbol::Relative(SymbolLookup(vec![Identifier::from(
+                            *name,
+                        )])))
+                    })
+                    .collect(),
+                methods,
+            }),
+            sydoc: None,
+        })
+    }
+
+    #[test]
+    fn default_methods_can_call_abstract_methods() {
+        let items = vec![
+            interface("Shape", &[], vec![Method::Abstract(signature("area"))]),
+            interface(
+                "Polygon",
+                &["Shape"],
+                vec![
+                    Method::Abstract(signature("sides")),
+                    default_method("describe", "this.sides() + this.area() * 2"),
+                ],
+            ),
+        ];
+        assert_eq!(check_default_methods(&items), vec![]);
+    }
+
+    #[test]
+    fn default_methods_cannot_call_undeclared_methods() {
+        let items = vec![interface(
+            "Shape",
+            &[],
+            vec![
+                Method::Abstract(signature("area")),
+                default_method("describe", "this.area() + this.perimeter()"),
+            ],
+        )];
+        assert_eq!(
+            check_default_methods(&items),
+            vec![UndeclaredMethod {
+                interface: Identifier::from("Shape"),
+                method: Identifier::from("describe"),
+                reference: Identifier::from("perimeter"),
+            }]
+        );
+
+        let items = vec![interface(
+            "Shape",
+            &["Imported"],
+            vec![default_method("describe", "this.perimeter()")],
+        )];
+        assert_eq!(check_default_methods(&items), vec![]);
+    }
+}
@@ -0,0 +1,122 @@
+//! Recognises expressions that provably never yield, and so have the bottom
+//! type like `throw` and `???` do. Such expressions can be used wherever a value of any
+//! type is expected.
+//!
+//! Sylan's `break` only exits labelled blocks and labelled `for` loops, so the
+//! ways out of a `while` loop, besides its condition becoming false, are to
+//! reiterate a `for` loop enclosing it by calling that loop's label or
+//! `continue`, or to break out of a labelled block or loop enclosing it. A `while True` loop that can't do either never
+//! finishes. Any reference to such a reiteration symbol counts, even if it's
+//! never called, as it might be passed elsewhere to be called later. References
+//! that a `for` loop nested inside the body captures for itself don't count,
+//! and nor do breaks out of blocks nested inside the body.
+//!
+//! Like the rest of the parser, this doesn't resolve names, so a `True` shadowed
+//! by something else would fool it.
+
+use std::ptr;
+
+use super::traversal::NodeRef;
+use super::{BranchingAndJumping, Call, Expression, Identifier, Symbol, SymbolLookup, While};
+use crate::common::multiphase::PseudoIdentifier;
+
+impl Expression {
+    /// Whether the expression never yields, given the labels of the `for` loops
+    /// enclosing it.
+    pub fn is_bottom_typed(&self, enclosing_labels: &[Identifier]) -> bool {
+        match self {
+            Expression::Throw(_)
+            | Expression::Hole(_)
+            | Expression::BranchingAndJumping(BranchingAndJumping::Break(_))
+            | Expression::BranchingAndJumping(BranchingAndJumping::Fallthrough) => true,
+            Expression::BranchingAndJumping(BranchingAndJumping::While(r#while)) => {
+                r#while.is_infinite(enclosing_labels)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl While {
+    /// Whether the loop's condition is always true and nothing in its body can
+    /// reiterate any of the enclosing `for` loops with the given labels.
+    pub fn is_infinite(&self, enclosing_labels: &[Identifier]) -> bool {
+        self.condition.is_always_true() && !self.can_escape(enclosing_labels)
+    }
+
+    fn can_escape(&self, enclosing_labels: &[Identifier]) -> bool {
+        let nodes = self
+            .scope
+            .bindings
+            .iter()
+            .map(NodeRef::Binding)
+            .chain(self.scope.expressions.iter().map(NodeRef::Expression))
+            .flat_map(|root| std::iter::once(root).chain(root.descendants()))
+            .collect::<Vec<_>>();
+
+        let inner_blocks = nodes
+            .iter()
+            .filter_map(|node| match node {
+                NodeRef::Expression(Expression::BranchingAndJumping(
+                    BranchingAndJumping::LabelledBlock(labelled),
+                )) => Some(&labelled.label),
+                NodeRef::Expression(Expression::BranchingAndJumping(BranchingAndJumping::For(
+                    r#for,
+                ))) => r#for.reiteration_symbol.as_ref(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let breaks_out = nodes.iter().any(|node| match node {
+            NodeRef::Expression(Expression::BranchingAndJumping(BranchingAndJumping::Break(
+                r#break,
+            ))) => !inner_blocks.contains(&&r#break.label),
+            _ => false,
+        });
+        if breaks_out {
+            return true;
+        }
+
+        let mut captured = vec![];
+        for node in &nodes {
+            if let NodeRef::Expression(Expression::BranchingAndJumping(BranchingAndJumping::For(
+                r#for,
+            ))) = node
+            {
+                let reiteration = match &r#for.reiteration_symbol {
+                    Some(label) => Symbol::Relative(SymbolLookup(vec![label.clone()])),
+                    None => Symbol::Pseudo(PseudoIdentifier::Continue),
+                };
+                captured.extend(
+                    node.descendants()
+                        .filter_map(referenced_symbol)
+                        .filter(|symbol| **symbol == reiteration),
+                );
+            }
+        }
+
+        nodes
+            .into_iter()
+            .filter_map(referenced_symbol)
+            .any(|symbol| {
+                let reiterates = match symbol {
+                    Symbol::Pseudo(PseudoIdentifier::Continue) => true,
+                    Symbol::Relative(SymbolLookup(lookup)) => {
+                        lookup.len() == 1 && enclosing_labels.contains(&lookup[0])
+                    }
+                    _ => false,
+                };
+                reiterates && !captured.iter().any(|other| ptr::eq(*other, symbol))
+            })
+    }
+}
+
+fn referenced_symbol(node: NodeRef<'_>) -> Option<&Symbol> {
+    match node {
+        NodeRef::Expression(Expression::Symbol(symbol))
+        | NodeRef::Expression(Expression::BranchingAndJumping(BranchingAndJumping::Call(Call {
+            target: symbol,
+            ..
+        }))) => Some(symbol),
+        _ => None,
+    }
+}
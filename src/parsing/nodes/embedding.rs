@@ -0,0 +1,161 @@
+//! Checks for classes that embed themselves, directly or via other classes.
+//!
+//! As embedded classes are laid out inline, such a cycle would make a class
+//! infinitely large. Only classes declared among the given items are resolved;
+//! those embedded from elsewhere can't lead back to them anyway, as their
+//! packages would have to import each other.
+
+use std::collections::HashMap;
+
+use super::{Class, Identifier, Item, Symbol, SymbolLookup, TypeItem};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+/// A chain of embeddings that leads back to where it started. The first class
+/// is repeated at the end, e.g. `[A, B, A]` for `A embeds B` and `B embeds A`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmbeddingCycle(pub Vec<Identifier>);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+pub fn check_embedding_cycles(items: &[Item]) -> Vec<EmbeddingCycle> {
+    let classes = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Type(r#type) => match &r#type.item {
+                TypeItem::Class(class) => Some((&r#type.name, class)),
+                TypeItem::Enum(r#enum) => Some((&r#type.name, &r#enum.class)),
+                TypeItem::Interface(_) => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut search = Search {
+        classes: &classes,
+        visits: HashMap::new(),
+        path: vec![],
+        cycles: vec![],
+    };
+    for (name, _) in &classes {
+        if !search.visits.contains_key(name) {
+            search.visit(name);
+        }
+    }
+    search.cycles
+}
+
+fn embedded(class: &Class) -> impl Iterator<Item = &Identifier> {
+    class
+        .embeds
+        .iter()
+        .filter_map(|embedded| match &embedded.symbol {
+            Symbol::Relative(SymbolLookup(lookup)) if lookup.len() == 1 => Some(&lookup[0]),
+            _ => None,
+        })
+}
+
+/// A depth-first search over the embeddings, where reaching a class that's
+/// still being searched closes a cycle.
+struct Search<'a> {
+    classes: &'a [(&'a Identifier, &'a Class)],
+    visits: HashMap<&'a Identifier, Visit>,
+    path: Vec<&'a Identifier>,
+    cycles: Vec<EmbeddingCycle>,
+}
+
+impl<'a> Search<'a> {
+    fn visit(&mut self, name: &'a Identifier) {
+        let class = match self
+            .classes
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+        {
+            Some((_, class)) => *class,
+            None => return,
+        };
+        self.visits.insert(name, Visit::InProgress);
+        self.path.push(name);
+
+        for embedded in embedded(class) {
+            match self.visits.get(embedded) {
+                Some(Visit::InProgress) => {
+                    let start = self.path.iter().position(|&on_path| on_path == embedded);
+                    let mut cycle = self.path[start.unwrap()..]
+                        .iter()
+                        .map(|&name| name.clone())
+                        .collect::<Vec<_>>();
+                    cycle.push(embedded.clone());
+                    self.cycles.push(EmbeddingCycle(cycle));
+                }
+                Some(Visit::Done) => {}
+                None => self.visit(embedded),
+            }
+        }
+
+        self.path.pop();
+        self.visits.insert(name, Visit::Done);
+    }
+}
+
+impl From<&EmbeddingCycle> for Diagnostic {
+    fn from(EmbeddingCycle(names): &EmbeddingCycle) -> Self {
+        let cycle = names
+            .iter()
+            .map(|name| name.0.as_str())
+            .collect::<Vec<_>>()
+            .join(" embeds ");
+        Diagnostic::error(
+            "embedding-cycle",
+            format!("classes embed themselves in a cycle: {}", cycle),
+            names[0].1.map(Span::at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn cycles(source: &str) -> Vec<Vec<String>> {
+        let file = parse_str(source).unwrap();
+        check_embedding_cycles(&file.package.package.items)
+            .into_iter()
+            .map(|EmbeddingCycle(names)| names.iter().map(|name| name.0.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn two_class_cycles() {
+        let source = "package main
+            class A embeds B
+            class B embeds A
+            class C embeds A";
+        assert_eq!(cycles(source), vec![vec!["A", "B", "A"]]);
+    }
+
+    #[test]
+    fn longer_cycles() {
+        let source = "package main
+            class A embeds B
+            class B embeds C, Imported
+            class C embeds D
+            class D embeds B";
+        assert_eq!(cycles(source), vec![vec!["B", "C", "D", "B"]]);
+    }
+
+    #[test]
+    fn shared_and_self_embeddings() {
+        let source = "package main
+            class A embeds B, C
+            class B embeds C
+            class C
+            class D embeds D";
+        assert_eq!(cycles(source), vec![vec!["D", "D"]]);
+    }
+}
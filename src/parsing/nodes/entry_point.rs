@@ -0,0 +1,133 @@
+//! Checks that the main package's `main` fun, if it has one, can be called as
+//! the program's entry point.
+//!
+//! It takes either nothing or the program's arguments as a `List[String]`, and
+//! yields either `Void` or an `Int` for the program to exit with. Since it's
+//! called without type arguments, it can't be generic either. Only a top-level
+//! `main` counts; methods and nested packages' funs can be named anything.
+
+use super::{Item, MainFile, Symbol, SymbolLookup, TypeArgument, TypeExpression, TypeReference};
+use crate::common::diagnostics::Diagnostic;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntryPointProblem {
+    TypeParameters,
+    ValueParameters,
+    ReturnType,
+}
+
+pub fn check_entry_point(file: &MainFile) -> Vec<EntryPointProblem> {
+    let main = file
+        .package
+        .package
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Fun(fun) if fun.signature.name.0.as_str() == "main" => Some(&fun.signature),
+            _ => None,
+        });
+    let signature = match main {
+        Some(signature) => signature,
+        None => return vec![],
+    };
+
+    let mut problems = vec![];
+    if !signature.type_parameters.is_empty() {
+        problems.push(EntryPointProblem::TypeParameters);
+    }
+    let takes_arguments = match signature.value_parameters.as_slice() {
+        [] => true,
+        [arguments] => {
+            arguments.default_value.is_none() && is_list_of_strings(&arguments.type_annotation)
+        }
+        _ => false,
+    };
+    if !takes_arguments {
+        problems.push(EntryPointProblem::ValueParameters);
+    }
+    let returns_exit_code = match &signature.return_type {
+        None => true,
+        Some(return_type) => {
+            names(&return_type.r#type, "Void") || names(&return_type.r#type, "Int")
+        }
+    };
+    if !returns_exit_code {
+        problems.push(EntryPointProblem::ReturnType);
+    }
+    problems
+}
+
+fn names(reference: &TypeReference, name: &str) -> bool {
+    reference.type_arguments.is_empty() && is_named(&reference.symbol, name)
+}
+
+fn is_named(symbol: &Symbol, name: &str) -> bool {
+    match symbol {
+        Symbol::Relative(SymbolLookup(lookup)) => lookup.len() == 1 && lookup[0].0.as_str() == name,
+        _ => false,
+    }
+}
+
+fn is_list_of_strings(reference: &TypeReference) -> bool {
+    is_named(&reference.symbol, "List")
+        && match reference.type_arguments.as_slice() {
+            [TypeArgument {
+                label: None,
+                value: TypeExpression::Reference(element),
+                is_spread: false,
+            }] => names(element, "String"),
+            _ => false,
+        }
+}
+
+impl From<&EntryPointProblem> for Diagnostic {
+    fn from(problem: &EntryPointProblem) -> Self {
+        let message = match problem {
+            EntryPointProblem::TypeParameters => "`main` can't take type parameters",
+            EntryPointProblem::ValueParameters => {
+                "`main` must take either nothing or a `List[String]` of arguments"
+            }
+            EntryPointProblem::ReturnType => "`main` must yield either `Void` or an `Int`",
+        };
+        Diagnostic::error("entry-point", message, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn problems_in(source: &str) -> Vec<EntryPointProblem> {
+        check_entry_point(&parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn valid_mains() {
+        assert_eq!(problems_in("package main\nfun main() { }"), vec![]);
+        assert_eq!(
+            problems_in("package main\nfun main(arguments List[String]) Int { 0 }"),
+            vec![]
+        );
+        assert_eq!(problems_in("package main\nvar answer = 42"), vec![]);
+    }
+
+    #[test]
+    fn invalid_mains() {
+        assert_eq!(
+            problems_in("package main\nfun main(count Int) String { \"\" }"),
+            vec![
+                EntryPointProblem::ValueParameters,
+                EntryPointProblem::ReturnType
+            ]
+        );
+        assert_eq!(
+            problems_in("package main\nfun main(arguments List[Int], verbose Boolean) { }"),
+            vec![EntryPointProblem::ValueParameters]
+        );
+        assert_eq!(
+            problems_in("package main\nfun main[T]() { }"),
+            vec![EntryPointProblem::TypeParameters]
+        );
+    }
+}
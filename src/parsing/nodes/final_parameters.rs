@@ -0,0 +1,141 @@
+//! Checks that final parameters aren't rebound.
+//!
+//! Sylan has no reassignment, so changing a parameter's value means rebinding
+//! its name with a `var` binding at the top of the function's body. That's
+//! only allowed for parameters declared with `var`; the rest are final.
+//! Bindings in nested blocks are left alone, as they only shadow the parameter
+//! within those blocks.
+
+use super::traversal::NodeRef;
+use super::{Block, Expression, Identifier, Item, Literal, MainFile, Pattern};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalParameterRebinding {
+    pub name: Identifier,
+    pub span: Option<Span>,
+}
+
+pub fn check_final_parameter_rebindings(file: &MainFile) -> Vec<FinalParameterRebinding> {
+    let block = &file.package.block;
+    let roots = file
+        .package
+        .package
+        .items
+        .iter()
+        .map(NodeRef::Item)
+        .chain(block.bindings.iter().map(NodeRef::Binding))
+        .chain(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut found = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            match node {
+                NodeRef::Item(Item::Fun(fun)) => {
+                    let parameters = fun
+                        .signature
+                        .value_parameters
+                        .iter()
+                        .map(|parameter| (&parameter.pattern, parameter.is_mutable));
+                    check_body(parameters, &fun.block, &mut found);
+                }
+                NodeRef::Expression(Expression::Literal(Literal::Lambda(lambda))) => {
+                    let parameters = lambda
+                        .signature
+                        .value_parameters
+                        .iter()
+                        .map(|parameter| (&parameter.pattern, parameter.is_mutable));
+                    check_body(parameters, &lambda.block, &mut found);
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+fn check_body<'a>(
+    parameters: impl Iterator<Item = (&'a Pattern, bool)>,
+    body: &Block,
+    found: &mut Vec<FinalParameterRebinding>,
+) {
+    let finals = parameters
+        .filter(|(_, is_mutable)| !is_mutable)
+        .flat_map(|(pattern, _)| pattern.bound_names())
+        .collect::<Vec<_>>();
+    for binding in &body.bindings {
+        for name in binding.pattern.bound_names() {
+            if finals.contains(&name) {
+                found.push(FinalParameterRebinding {
+                    name,
+                    span: binding.span,
+                });
+            }
+        }
+    }
+}
+
+impl From<&FinalParameterRebinding> for Diagnostic {
+    fn from(rebinding: &FinalParameterRebinding) -> Self {
+        Diagnostic::error(
+            "final-parameter-rebinding",
+            format!(
+                "`{}` is a final parameter, so only a `var` parameter can be rebound",
+                rebinding.name.0
+            ),
+            rebinding.span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn rebindings_in(source: &str) -> Vec<FinalParameterRebinding> {
+        check_final_parameter_rebindings(&parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn final_parameters_cannot_be_rebound() {
+        let found = rebindings_in(
+            "package main
+            var step = -> count, var total_so_far {
+                var count = count + 1
+                var total_so_far = total_so_far + count
+                total_so_far
+            }
+            var reset = -> count {
+                if count > 3 {
+                    var count = 0
+                    count
+                } else {
+                    count
+                }
+            }",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, Identifier::from("count"));
+        assert_eq!(found[0].span.unwrap().start.line(), 3);
+    }
+
+    #[test]
+    fn mutable_parameters_can_be_rebound() {
+        let source = "package main
+            var step = -> var count {
+                var count = count + 1
+                count
+            }";
+        assert_eq!(rebindings_in(source), vec![]);
+
+        let file = parse_str(source).unwrap();
+        match &*file.package.block.bindings[0].value {
+            Expression::Literal(Literal::Lambda(lambda)) => {
+                assert!(lambda.signature.value_parameters[0].is_mutable)
+            }
+            other => panic!("expected a lambda: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Expands glob imports such as `a.b.*`, and checks that no two of a package's
+//! glob imports bring in the same name.
+//!
+//! A name imported by two globs is ambiguous, as nothing says which of the two
+//! packages it should come from. Importing it explicitly, as in
+//! `a.b.*, c.d.*, c.d.name`, settles it.
+//!
+//! Functions and packages count as members of their package only if they're
+//! declared public. Types and bindings always count, as the AST doesn't record
+//! their modifiers yet. Names a package
+//! re-exports with `exports` are members too, so a glob of a facade package
+//! brings in what it re-exports.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{Accessibility, Identifier, Import, ImportStem, Item, Package, Symbol, SymbolLookup};
+use crate::common::diagnostics::Diagnostic;
+
+/// A name brought in by more than one of the glob imports written in the
+/// package named by `importer`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmbiguousGlobImport {
+    pub importer: Identifier,
+    pub name: Identifier,
+
+    /// The paths of the packages the globs import from, in the order they're
+    /// imported.
+    pub packages: Vec<Vec<Identifier>>,
+}
+
+/// The public members of the package at `path`, looked up from the given
+/// top-level packages. `None` if there's no such package.
+pub fn glob_members(path: &[Identifier], packages: &[&Package]) -> Option<Vec<Identifier>> {
+    let (first, rest) = path.split_first()?;
+    let mut package = *packages.iter().find(|package| package.name == *first)?;
+    for name in rest {
+        package = package.items.iter().find_map(|item| match item {
+            Item::Package(nested) if nested.name == *name => Some(nested),
+            _ => None,
+        })?;
+    }
+
+    let mut members = vec![];
+    for item in &package.items {
+        match item {
+            Item::Fun(fun) if fun.modifiers.accessibility == Accessibility::Public => {
+                members.push(fun.signature.name.clone())
+            }
+            Item::Package(nested) if nested.accessibility == Accessibility::Public => {
+                members.push(nested.name.clone())
+            }
+            Item::Type(r#type) => members.push(r#type.name.clone()),
+            Item::Var(binding) | Item::Final(binding) | Item::Const(binding) => {
+                members.extend(binding.pattern.bound_names())
+            }
+            Item::Fun(_) | Item::Package(_) | Item::Extension(_) | Item::Macro(_) => {}
+        }
+    }
+    for import in &package.imports {
+        reexported_names(import, false, &mut members);
+    }
+    Some(members)
+}
+
+fn reexported_names(import: &Import, within_reexport: bool, names: &mut Vec<Identifier>) {
+    let is_reexported = within_reexport || import.is_reexported;
+    match &import.stem {
+        ImportStem::Single(stem) if is_reexported => {
+            names.push(stem.alias.as_ref().unwrap_or(&stem.name).clone())
+        }
+        ImportStem::Multiple(imports) => {
+            for nested in imports {
+                reexported_names(nested, is_reexported, names);
+            }
+        }
+        ImportStem::Single(_) | ImportStem::Glob => {}
+    }
+}
+
+/// Globs importing from packages that aren't among `packages` can't be
+/// expanded, so they're assumed not to clash.
+pub fn check_ambiguous_glob_imports(
+    package: &Package,
+    packages: &[&Package],
+) -> Vec<AmbiguousGlobImport> {
+    let mut problems = vec![];
+    check_package(package, packages, &mut problems);
+    problems
+}
+
+fn check_package(
+    package: &Package,
+    packages: &[&Package],
+    problems: &mut Vec<AmbiguousGlobImport>,
+) {
+    let mut globs = vec![];
+    let mut explicit = HashSet::new();
+    for import in &package.imports {
+        collect(&[], import, &mut globs, &mut explicit);
+    }
+
+    let mut sources: HashMap<Identifier, Vec<Vec<Identifier>>> = HashMap::new();
+    let mut order = vec![];
+    for glob in globs {
+        for member in glob_members(&glob, packages).unwrap_or_default() {
+            let paths = sources.entry(member.clone()).or_insert_with(|| {
+                order.push(member);
+                vec![]
+            });
+            if !paths.contains(&glob) {
+                paths.push(glob.clone());
+            }
+        }
+    }
+
+    for name in order {
+        let paths = &sources[&name];
+        if 1 < paths.len() && !explicit.contains(&name) {
+            problems.push(AmbiguousGlobImport {
+                importer: package.name.clone(),
+                name,
+                packages: paths.clone(),
+            });
+        }
+    }
+
+    for item in &package.items {
+        if let Item::Package(nested) = item {
+            check_package(nested, packages, problems);
+        }
+    }
+}
+
+/// Gather the paths of glob imports, along with the names that non-glob
+/// imports bring in under.
+fn collect(
+    prefix: &[Identifier],
+    import: &Import,
+    globs: &mut Vec<Vec<Identifier>>,
+    explicit: &mut HashSet<Identifier>,
+) {
+    let mut path = prefix.to_vec();
+    match &import.root {
+        Some(Symbol::Relative(SymbolLookup(root))) | Some(Symbol::Absolute(SymbolLookup(root))) => {
+            path.extend(root.iter().cloned())
+        }
+        _ => {}
+    }
+    match &import.stem {
+        ImportStem::Single(stem) => {
+            explicit.insert(stem.alias.as_ref().unwrap_or(&stem.name).clone());
+        }
+        ImportStem::Multiple(imports) => {
+            for import in imports {
+                collect(&path, import, globs, explicit);
+            }
+        }
+        ImportStem::Glob => globs.push(path),
+    }
+}
+
+impl From<&AmbiguousGlobImport> for Diagnostic {
+    fn from(ambiguous: &AmbiguousGlobImport) -> Self {
+        let packages = ambiguous
+            .packages
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|name| name.0.as_str())
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Diagnostic::error(
+            "ambiguous-glob-import",
+            format!(
+                "`{}` is imported into `{}` by the globs of more than one package: {}",
+                ambiguous.name.0, ambiguous.importer.0, packages
+            ),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn path(names: &[&'static str]) -> Vec<Identifier> {
+        names.iter().map(|&name| Identifier::from(name)).collect()
+    }
+
+    const LIBRARIES: &str = "package libraries
+        package text {
+            class Format
+            final width = 80
+        }
+        package numbers {
+            class Format
+            class Rational
+        }
+        package output {
+            final width = 120
+        }";
+
+    #[test]
+    fn glob_imports_expand_to_public_members() {
+        let libraries = parse_str(LIBRARIES).unwrap();
+        let packages = [&libraries.package.package];
+
+        let members = glob_members(&path(&["libraries", "numbers"]), &packages).unwrap();
+        assert_eq!(members, path(&["Format", "Rational"]));
+        assert_eq!(
+            glob_members(&path(&["libraries"]), &packages).unwrap(),
+            path(&["text", "numbers", "output"])
+        );
+        assert!(glob_members(&path(&["libraries", "missing"]), &packages).is_none());
+    }
+
+    #[test]
+    fn globs_bring_in_reexports() {
+        let facade = parse_str(
+            "package facade (exports libraries.numbers.Rational, libraries.text.Format)
+            class Canvas",
+        )
+        .unwrap();
+        let packages = [&facade.package.package];
+        assert_eq!(
+            glob_members(&path(&["facade"]), &packages).unwrap(),
+            path(&["Canvas", "Rational"])
+        );
+    }
+
+    #[test]
+    fn names_from_two_globs_are_ambiguous() {
+        let libraries = parse_str(LIBRARIES).unwrap();
+        let packages = [&libraries.package.package];
+
+        let app = parse_str(
+            "package app (libraries.text.*, libraries.{numbers.*, output.*}, unknown.*)
+            package inner (libraries.numbers.*, libraries.text.*, libraries.text.Format) {
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            check_ambiguous_glob_imports(&app.package.package, &packages),
+            vec![
+                AmbiguousGlobImport {
+                    importer: Identifier::from("app"),
+                    name: Identifier::from("Format"),
+                    packages: vec![
+                        path(&["libraries", "text"]),
+                        path(&["libraries", "numbers"]),
+                    ],
+                },
+                AmbiguousGlobImport {
+                    importer: Identifier::from("app"),
+                    name: Identifier::from("width"),
+                    packages: vec![path(&["libraries", "text"]), path(&["libraries", "output"]),],
+                },
+            ]
+        );
+
+        let app = parse_str("package app (libraries.text.*, libraries.text.*)").unwrap();
+        assert_eq!(
+            check_ambiguous_glob_imports(&app.package.package, &packages),
+            vec![]
+        );
+    }
+}
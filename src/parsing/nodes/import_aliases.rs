@@ -0,0 +1,133 @@
+//! Warns about imports renamed to the name of a builtin, as in
+//! `collections.Vector as List`, which shadows the builtin for the rest of the
+//! package.
+//!
+//! Writing `override` after `as` marks the shadowing as intended and silences
+//! the warning, as in `collections.Vector as override List`. Only aliases are
+//! checked: an alias is a name the importer picks, so colliding with a builtin
+//! is more likely an accident than importing something that happens to share
+//! a builtin's name.
+//!
+//! Builtins are given by the caller, as with
+//! [name_resolution](super::name_resolution).
+
+use super::{Identifier, Import, ImportStem, Item, Package};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+/// An import in the package named by `importer` that renames `name` to a
+/// builtin's name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShadowedBuiltin {
+    pub importer: Identifier,
+    pub name: Identifier,
+    pub alias: Identifier,
+}
+
+pub fn check_shadowed_builtins(package: &Package, builtins: &[Identifier]) -> Vec<ShadowedBuiltin> {
+    let mut problems = vec![];
+    check_package(package, builtins, &mut problems);
+    problems
+}
+
+fn check_package(package: &Package, builtins: &[Identifier], problems: &mut Vec<ShadowedBuiltin>) {
+    for import in &package.imports {
+        check_import(&package.name, import, builtins, problems);
+    }
+    for item in &package.items {
+        if let Item::Package(nested) = item {
+            check_package(nested, builtins, problems);
+        }
+    }
+}
+
+fn check_import(
+    importer: &Identifier,
+    import: &Import,
+    builtins: &[Identifier],
+    problems: &mut Vec<ShadowedBuiltin>,
+) {
+    match &import.stem {
+        ImportStem::Single(stem) => match &stem.alias {
+            Some(alias) if !stem.overrides_builtin && builtins.contains(alias) => {
+                problems.push(ShadowedBuiltin {
+                    importer: importer.clone(),
+                    name: stem.name.clone(),
+                    alias: alias.clone(),
+                })
+            }
+            _ => {}
+        },
+        ImportStem::Multiple(imports) => {
+            for import in imports {
+                check_import(importer, import, builtins, problems);
+            }
+        }
+        ImportStem::Glob => {}
+    }
+}
+
+impl From<&ShadowedBuiltin> for Diagnostic {
+    fn from(shadowed: &ShadowedBuiltin) -> Self {
+        Diagnostic::warning(
+            "shadowed-builtin",
+            format!(
+                "`{}` is imported into `{}` as `{}`, shadowing the builtin; write `as override {}` \
+                 if that's intended",
+                shadowed.name.0, shadowed.importer.0, shadowed.alias.0, shadowed.alias.0
+            ),
+            shadowed.alias.1.map(Span::at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn shadowed_in(source: &str) -> Vec<ShadowedBuiltin> {
+        let builtins = [Identifier::from("List"), Identifier::from("println")];
+        check_shadowed_builtins(&parse_str(source).unwrap().package.package, &builtins)
+    }
+
+    #[test]
+    fn aliases_shadowing_builtins_are_reported() {
+        assert_eq!(
+            shadowed_in(
+                "package app (collections.Vector as List, io.{write as println, scan as input})
+                package inner (collections.Deque as List) {
+                }"
+            ),
+            vec![
+                ShadowedBuiltin {
+                    importer: Identifier::from("app"),
+                    name: Identifier::from("Vector"),
+                    alias: Identifier::from("List"),
+                },
+                ShadowedBuiltin {
+                    importer: Identifier::from("app"),
+                    name: Identifier::from("write"),
+                    alias: Identifier::from("println"),
+                },
+                ShadowedBuiltin {
+                    importer: Identifier::from("inner"),
+                    name: Identifier::from("Deque"),
+                    alias: Identifier::from("List"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn other_aliases_and_marked_shadowing_are_clean() {
+        assert_eq!(
+            shadowed_in("package app (collections.Vector as Vec, collections.List, io.*)"),
+            vec![]
+        );
+        assert_eq!(
+            shadowed_in("package app (collections.Vector as override List)"),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Checks that no package imports the main package.
+//!
+//! The main package can contain executable code as well as items, which is run
+//! when Sylan invokes its file directly. Importing it from elsewhere would
+//! either run that code a second time or silently skip it, so it's disallowed
+//! altogether, as is importing anything declared within it.
+
+use super::{Identifier, Import, ImportStem, Item, MainPackage, Package, Symbol, SymbolLookup};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+/// An import of the main package or something within it, written in the
+/// package named by `importer`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MainPackageImport {
+    pub importer: Identifier,
+    pub path: Vec<Identifier>,
+}
+
+pub fn check_main_package_imports(main: &MainPackage, package: &Package) -> Vec<MainPackageImport> {
+    let mut problems = vec![];
+    check_package(&main.package.name, package, &mut problems);
+    problems
+}
+
+fn check_package(main: &Identifier, package: &Package, problems: &mut Vec<MainPackageImport>) {
+    for import in &package.imports {
+        check_import(main, &package.name, &[], import, problems);
+    }
+    for item in &package.items {
+        if let Item::Package(nested) = item {
+            check_package(main, nested, problems);
+        }
+    }
+}
+
+fn check_import(
+    main: &Identifier,
+    importer: &Identifier,
+    prefix: &[Identifier],
+    import: &Import,
+    problems: &mut Vec<MainPackageImport>,
+) {
+    let mut path = prefix.to_vec();
+    match &import.root {
+        Some(Symbol::Relative(SymbolLookup(root))) | Some(Symbol::Absolute(SymbolLookup(root))) => {
+            path.extend(root.iter().cloned())
+        }
+        _ => {}
+    }
+    match &import.stem {
+        ImportStem::Single(stem) => {
+            path.push(stem.name.clone());
+            if path.first() == Some(main) {
+                problems.push(MainPackageImport {
+                    importer: importer.clone(),
+                    path,
+                });
+            }
+        }
+        ImportStem::Multiple(imports) => {
+            for import in imports {
+                check_import(main, importer, &path, import, problems);
+            }
+        }
+        ImportStem::Glob => {
+            if path.first() == Some(main) {
+                problems.push(MainPackageImport {
+                    importer: importer.clone(),
+                    path,
+                });
+            }
+        }
+    }
+}
+
+impl From<&MainPackageImport> for Diagnostic {
+    fn from(import: &MainPackageImport) -> Self {
+        let path = import
+            .path
+            .iter()
+            .map(|name| name.0.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        Diagnostic::error(
+            "main-package-import",
+            format!(
+                "`{}` imports `{}`, but the main package can't be imported",
+                import.importer.0, path
+            ),
+            import.path.last().and_then(|name| name.1).map(Span::at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn path(names: &[&'static str]) -> Vec<Identifier> {
+        names.iter().map(|&name| Identifier::from(name)).collect()
+    }
+
+    #[test]
+    fn main_package_cannot_be_imported() {
+        let main = parse_str("package app\n var greeting = \"hello\"").unwrap();
+        let other = parse_str(
+            "package tools (app, util.{strings, app}, app.{greeting, helpers.format})
+            package inner (app.greeting) {
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            check_main_package_imports(&main.package, &other.package.package),
+            vec![
+                MainPackageImport {
+                    importer: Identifier::from("tools"),
+                    path: path(&["app"]),
+                },
+                MainPackageImport {
+                    importer: Identifier::from("tools"),
+                    path: path(&["app", "greeting"]),
+                },
+                MainPackageImport {
+                    importer: Identifier::from("tools"),
+                    path: path(&["app", "helpers", "format"]),
+                },
+                MainPackageImport {
+                    importer: Identifier::from("inner"),
+                    path: path(&["app", "greeting"]),
+                },
+            ]
+        );
+
+        let other = parse_str("package tools (util.app, application)").unwrap();
+        assert_eq!(
+            check_main_package_imports(&main.package, &other.package.package),
+            vec![]
+        );
+    }
+}
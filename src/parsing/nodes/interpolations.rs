@@ -0,0 +1,119 @@
+//! Checks that each interpolation in a string holds a valid expression, as in
+//! the `count + 1` of `$"{count + 1} left"`.
+//!
+//! The lexer can't parse expressions, so it leaves the source between an
+//! interpolation's braces for this pass to parse. Without it, a mistake such as
+//! `$"{1 +}"` would only surface once the string was evaluated.
+
+use super::traversal::NodeRef;
+use super::{Expression, Literal, MainFile};
+use crate::common::diagnostics::Diagnostic;
+use crate::common::multiphase::Interpolation;
+use crate::parsing::{parse_expression, Error};
+
+#[derive(Debug)]
+pub struct MalformedInterpolation {
+    pub source: String,
+
+    /// Where the error is, in characters from the start of the string's
+    /// contents. It's the start of the interpolation if the error has no
+    /// position of its own.
+    pub offset: usize,
+
+    pub error: Error,
+}
+
+pub fn check_interpolations(file: &MainFile) -> Vec<MalformedInterpolation> {
+    let block = &file.package.block;
+    let roots = file
+        .package
+        .package
+        .items
+        .iter()
+        .map(NodeRef::Item)
+        .chain(block.bindings.iter().map(NodeRef::Binding))
+        .chain(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut malformed = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            if let NodeRef::Expression(Expression::Literal(Literal::InterpolatedString(string))) =
+                node
+            {
+                malformed.extend(string.interpolations.iter().filter_map(check));
+            }
+        }
+    }
+    malformed
+}
+
+fn check(interpolation: &Interpolation) -> Option<MalformedInterpolation> {
+    let error = parse_expression(&interpolation.source).err()?;
+    let within = match &error {
+        Error::Parser(error) => error
+            .position
+            .map(|position| position.absolute_character_index()),
+        Error::Lexer(_) => None,
+    };
+    Some(MalformedInterpolation {
+        source: interpolation.source.clone(),
+        offset: interpolation.offset + within.unwrap_or(0),
+        error,
+    })
+}
+
+impl From<&MalformedInterpolation> for Diagnostic {
+    fn from(malformed: &MalformedInterpolation) -> Self {
+        let cause = malformed
+            .error
+            .diagnostics()
+            .into_iter()
+            .next()
+            .map(|diagnostic| diagnostic.message)
+            .unwrap_or_default();
+        Diagnostic::error(
+            "malformed-interpolation",
+            format!(
+                "`{}` isn't a valid interpolation: {}",
+                malformed.source, cause
+            ),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn malformed_in(source: &str) -> Vec<(String, usize)> {
+        check_interpolations(&parse_str(source).unwrap())
+            .into_iter()
+            .map(|malformed| (malformed.source, malformed.offset))
+            .collect()
+    }
+
+    #[test]
+    fn valid_interpolations() {
+        assert_eq!(
+            malformed_in(
+                "package main
+                var count = 2
+                var message = $\"{count + 1} left, {f(count)} today\""
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn malformed_interpolations() {
+        let found = malformed_in(
+            "package main
+            var f = -> { $\"sum: {1 +}, ok: {x}, {)}\" }",
+        );
+        // The first runs out of input at the end of its region, whereas the
+        // second fails on its first character.
+        assert_eq!(found, vec![("1 +".to_owned(), 9), (")".to_owned(), 22)]);
+    }
+}
@@ -0,0 +1,129 @@
+//! Checks that integer literals bound with an explicit numeric type, whether
+//! given by a suffix or a type annotation, are within that type's range.
+//!
+//! The lexer already rejects a suffixed literal that doesn't fit its suffix, so
+//! this mostly catches those that don't fit an annotation, as with
+//! `final big Byte = 256`. Only literals bound directly are checked, as the
+//! types of other expressions aren't inferred yet.
+
+use super::traversal::NodeRef;
+use super::{
+    Binding, Expression, Item, Literal, MainFile, Symbol, SymbolLookup, TypeExpression,
+    TypeReference,
+};
+use crate::common::diagnostics::Diagnostic;
+use crate::common::multiphase::NumericType;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutOfRangeLiteral {
+    pub description: String,
+    pub span: Option<Span>,
+}
+
+pub fn check_literal_bounds(file: &MainFile) -> Vec<OutOfRangeLiteral> {
+    let block = &file.package.block;
+    let roots = file
+        .package
+        .package
+        .items
+        .iter()
+        .map(NodeRef::Item)
+        .chain(block.bindings.iter().map(NodeRef::Binding))
+        .chain(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut problems = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            let binding = match node {
+                NodeRef::Binding(binding)
+                | NodeRef::Item(Item::Var(binding))
+                | NodeRef::Item(Item::Final(binding))
+                | NodeRef::Item(Item::Const(binding)) => binding,
+                _ => continue,
+            };
+            if let Err(description) = check_binding(binding) {
+                problems.push(OutOfRangeLiteral {
+                    description,
+                    span: binding.span,
+                });
+            }
+        }
+    }
+    problems
+}
+
+fn check_binding(binding: &Binding) -> Result<(), String> {
+    let (number, suffix) = match &*binding.value {
+        Expression::Literal(Literal::Number(number, suffix)) => (number, suffix),
+        _ => return Ok(()),
+    };
+    let numeric_type = suffix.or_else(|| {
+        binding
+            .explicit_type_annotation
+            .as_ref()
+            .and_then(annotated_numeric_type)
+    });
+    match numeric_type {
+        Some(numeric_type) => numeric_type.check_bounds(number),
+        None => Ok(()),
+    }
+}
+
+fn annotated_numeric_type(annotation: &TypeExpression) -> Option<NumericType> {
+    match annotation {
+        TypeExpression::Reference(TypeReference {
+            symbol: Symbol::Relative(SymbolLookup(lookup)),
+            ..
+        }) if lookup.len() == 1 => NumericType::from_type_name(&lookup[0].0),
+        _ => None,
+    }
+}
+
+impl From<&OutOfRangeLiteral> for Diagnostic {
+    fn from(literal: &OutOfRangeLiteral) -> Self {
+        Diagnostic::error("literal-out-of-range", &literal.description, literal.span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::multiphase::Number;
+    use crate::parsing::parse_str;
+
+    #[test]
+    fn integer_literals_must_fit_their_types() {
+        let file = parse_str(
+            "package main
+            final small Byte = 255
+            final big Byte = 256
+            final negative UInt32 = -1
+            final unknown Imported = 256
+            var suffixed = 255u8",
+        )
+        .unwrap();
+        let problems = check_literal_bounds(&file);
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| problem.description.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "256 is out of range for u8, which holds 0 to 255",
+                "-1 is out of range for u32, which holds 0 to 4294967295",
+            ]
+        );
+        assert_eq!(problems[0].span.unwrap().start.line(), 3);
+    }
+
+    #[test]
+    fn suffixes_are_checked_too() {
+        // Out-of-range suffixed literals are already rejected by the lexer, but
+        // can still be built directly.
+        let mut file = parse_str("package main\nvar suffixed = 255u8").unwrap();
+        *file.package.block.bindings[0].value =
+            Expression::Literal(Literal::Number(Number(300, 0), Some(NumericType::U8)));
+        assert_eq!(check_literal_bounds(&file).len(), 1);
+    }
+}
@@ -0,0 +1,492 @@
+//! Finds references to names that nothing in scope declares, usually typos, for
+//! "unknown name" diagnostics.
+//!
+//! A name is in scope if a block, parameter list, pattern, or loop label
+//! enclosing the reference binds it, or if an enclosing package declares or
+//! imports it. Builtins are given by the caller, as they depend on what runs the
+//! code.
+//!
+//! A block's bindings are in scope throughout the block, even above their
+//! declarations, as delayed computations such as lambdas can use them from
+//! there. Only the first name of a lookup such as `a.b.c` is resolved, as the
+//! rest are members that need types to resolve, and types aren't checked
+//! either. A glob import from a package outside the file can't be expanded, so
+//! nothing within a package that has one is reported.
+//!
+//! Within a method, the other methods of the enclosing type are in scope too,
+//! so `area()` can be written for `this.area()`. Only the type's own methods
+//! are in scope this way; inherited ones still need `this.`.
+
+use std::mem;
+
+use super::glob_imports::glob_members;
+use super::traversal::NodeRef;
+use super::{
+    Block, BranchingAndJumping, Case, Class, ConcreteMethod, Defer, Expression, Identifier, Import,
+    ImportStem, Item, Literal, MainFile, Method, Package, Symbol, SymbolLookup, TypeItem,
+    ValueParameter,
+};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnresolvedName {
+    pub name: Identifier,
+    pub span: Option<Span>,
+}
+
+pub fn check_unresolved_names(file: &MainFile, builtins: &[Identifier]) -> Vec<UnresolvedName> {
+    resolve(file, builtins).unresolved
+}
+
+fn resolve<'a>(file: &'a MainFile, builtins: &[Identifier]) -> Resolver<'a> {
+    let main = &file.package;
+    let mut resolver = Resolver {
+        names: builtins.to_vec(),
+        packages: vec![&main.package],
+        unresolved: vec![],
+        methods: vec![],
+    };
+
+    if let Some(imported) = resolver.imported_names(&main.package) {
+        resolver.names.extend(imported);
+        resolver.declare_items(&main.package.items);
+        for binding in &main.block.bindings {
+            resolver.names.extend(binding.pattern.bound_names());
+        }
+        resolver.items(&main.package.items);
+        for binding in &main.block.bindings {
+            resolver.expression(&binding.value);
+        }
+        for expression in &main.block.expressions {
+            resolver.expression(expression);
+        }
+    }
+    resolver
+}
+
+struct Resolver<'a> {
+    /// Names in scope at the current point of the walk, innermost last.
+    names: Vec<Identifier>,
+
+    /// The packages that glob imports can be expanded from.
+    packages: Vec<&'a Package>,
+
+    unresolved: Vec<UnresolvedName>,
+
+    /// The methods of the type whose method is being walked, if any.
+    methods: Vec<Identifier>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&mut self, name: &Identifier) {
+        if !self.names.contains(name) && !self.methods.contains(name) {
+            self.unresolved.push(UnresolvedName {
+                name: name.clone(),
+                span: name.span(),
+            });
+        }
+    }
+
+    fn symbol(&mut self, symbol: &Symbol) {
+        if let Symbol::Relative(SymbolLookup(lookup)) = symbol {
+            if let Some(first) = lookup.first() {
+                self.resolve(first);
+            }
+        }
+    }
+
+    /// The names the package's imports bring in, or `None` if a glob import
+    /// can't be expanded.
+    fn imported_names(&self, package: &Package) -> Option<Vec<Identifier>> {
+        let mut names = vec![];
+        for import in &package.imports {
+            self.import(&[], import, &mut names)?;
+        }
+        Some(names)
+    }
+
+    fn import(
+        &self,
+        prefix: &[Identifier],
+        import: &Import,
+        names: &mut Vec<Identifier>,
+    ) -> Option<()> {
+        let mut path = prefix.to_vec();
+        if let Some(Symbol::Relative(SymbolLookup(root)))
+        | Some(Symbol::Absolute(SymbolLookup(root))) = &import.root
+        {
+            path.extend(root.iter().cloned());
+        }
+        match &import.stem {
+            ImportStem::Single(stem) => {
+                names.push(stem.alias.as_ref().unwrap_or(&stem.name).clone())
+            }
+            ImportStem::Multiple(imports) => {
+                for import in imports {
+                    self.import(&path, import, names)?;
+                }
+            }
+            ImportStem::Glob => names.extend(glob_members(&path, &self.packages)?),
+        }
+        Some(())
+    }
+
+    fn declare_items(&mut self, items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Fun(fun) => self.names.push(fun.signature.name.clone()),
+                Item::Type(r#type) => self.names.push(r#type.name.clone()),
+                Item::Package(package) => self.names.push(package.name.clone()),
+                Item::Var(binding) | Item::Final(binding) | Item::Const(binding) => {
+                    self.names.extend(binding.pattern.bound_names())
+                }
+                Item::Extension(_) | Item::Macro(_) => {}
+            }
+        }
+    }
+
+    fn items(&mut self, items: &[Item]) {
+        for item in items {
+            self.item(item);
+        }
+    }
+
+    fn item(&mut self, item: &Item) {
+        match item {
+            Item::Fun(fun) => {
+                let outer = self.names.len();
+                self.parameters(&fun.signature.value_parameters);
+                self.block(&fun.block);
+                self.names.truncate(outer);
+            }
+            Item::Type(r#type) => match &r#type.item {
+                TypeItem::Class(class) => self.class(class),
+                TypeItem::Enum(r#enum) => self.class(&r#enum.class),
+                TypeItem::Interface(interface) => {
+                    let methods = interface
+                        .methods
+                        .iter()
+                        .map(|method| match method {
+                            Method::Abstract(method) => &method.signature.name,
+                            Method::Concrete(method) => &method.r#abstract.signature.name,
+                        })
+                        .cloned()
+                        .collect();
+                    let outer_methods = mem::replace(&mut self.methods, methods);
+                    for method in &interface.methods {
+                        if let Method::Concrete(method) = method {
+                            self.method(method);
+                        }
+                    }
+                    self.methods = outer_methods;
+                }
+            },
+            Item::Extension(extension) => self.class(&extension.item),
+            Item::Package(package) => {
+                let outer = self.names.len();
+                if let Some(imported) = self.imported_names(package) {
+                    self.names.extend(imported);
+                    self.declare_items(&package.items);
+                    self.items(&package.items);
+                }
+                self.names.truncate(outer);
+            }
+            Item::Var(binding) | Item::Final(binding) | Item::Const(binding) => {
+                self.expression(&binding.value)
+            }
+            Item::Macro(_) => {}
+        }
+    }
+
+    fn class(&mut self, class: &Class) {
+        let outer = self.names.len();
+        for parameter in &class.value_parameters {
+            if let Some(default_value) = &parameter.parameter.default_value {
+                self.expression(default_value);
+            }
+            self.names.extend(parameter.parameter.pattern.bound_names());
+        }
+        for field in &class.fields {
+            self.names.extend(field.binding.pattern.bound_names());
+        }
+        for field in &class.fields {
+            self.expression(&field.binding.value);
+        }
+        let methods = class
+            .methods
+            .iter()
+            .map(|method| method.r#abstract.signature.name.clone())
+            .collect();
+        let outer_methods = mem::replace(&mut self.methods, methods);
+        for method in &class.methods {
+            self.method(method);
+        }
+        self.methods = outer_methods;
+        self.block(&class.instance_initialiser);
+        self.names.truncate(outer);
+    }
+
+    fn method(&mut self, method: &ConcreteMethod) {
+        let outer = self.names.len();
+        self.parameters(&method.r#abstract.signature.value_parameters);
+        self.block(&method.scope);
+        self.names.truncate(outer);
+    }
+
+    /// Resolve the parameters' default values and bring the parameters into
+    /// scope. Each default value can see the parameters before it.
+    fn parameters(&mut self, parameters: &[ValueParameter]) {
+        for parameter in parameters {
+            if let Some(default_value) = &parameter.default_value {
+                self.expression(default_value);
+            }
+            self.names.extend(parameter.pattern.bound_names());
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        let outer = self.names.len();
+        for binding in &block.bindings {
+            self.names.extend(binding.pattern.bound_names());
+        }
+        for binding in &block.bindings {
+            self.expression(&binding.value);
+        }
+        for expression in &block.expressions {
+            self.expression(expression);
+        }
+        self.names.truncate(outer);
+    }
+
+    fn case(&mut self, case: &Case) {
+        let outer = self.names.len();
+        for case_match in &case.matches {
+            let before_match = self.names.len();
+            self.names.extend(case_match.pattern.bound_names());
+            if let Some(guard) = &case_match.guard {
+                self.expression(guard);
+            }
+            self.names.truncate(before_match);
+        }
+        for case_match in &case.matches {
+            self.names.extend(case_match.pattern.bound_names());
+        }
+        self.block(&case.body);
+        self.names.truncate(outer);
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Symbol(symbol) => self.symbol(symbol),
+            Expression::Context(block) | Expression::Defer(Defer(block)) => self.block(block),
+            Expression::Spawn(spawn) => self.block(&spawn.task),
+            Expression::Literal(Literal::Lambda(lambda)) => {
+                let outer = self.names.len();
+                for parameter in &lambda.signature.value_parameters {
+                    if let Some(default_value) = &parameter.default_value {
+                        self.expression(default_value);
+                    }
+                    self.names.extend(parameter.pattern.bound_names());
+                }
+                self.block(&lambda.block);
+                self.names.truncate(outer);
+            }
+            Expression::BranchingAndJumping(branching) => self.branching(branching),
+            other => self.children(other),
+        }
+    }
+
+    fn branching(&mut self, branching: &BranchingAndJumping) {
+        let outer = self.names.len();
+        match branching {
+            BranchingAndJumping::Call(call) => {
+                self.symbol(&call.target);
+                for argument in &call.arguments.arguments {
+                    self.expression(&argument.value);
+                }
+            }
+            BranchingAndJumping::Cond(cond) => {
+                for case in &cond.cases {
+                    for condition in &case.conditions {
+                        self.expression(condition);
+                    }
+                    if let Some(then) = &case.then {
+                        self.block(then);
+                    }
+                }
+                if let Some(else_clause) = &cond.else_clause {
+                    self.block(else_clause);
+                }
+            }
+            BranchingAndJumping::For(r#for) => {
+                for binding in &r#for.bindings {
+                    self.expression(&binding.value);
+                }
+                if let Some(else_clause) = &r#for.else_clause {
+                    self.block(else_clause);
+                }
+                for binding in &r#for.bindings {
+                    self.names.extend(binding.pattern.bound_names());
+                }
+                self.names.extend(r#for.reiteration_symbol.clone());
+                if let Some(yield_expression) = &r#for.yield_expression {
+                    self.expression(yield_expression);
+                }
+                self.block(&r#for.scope);
+            }
+            BranchingAndJumping::If(r#if) => {
+                self.expression(&r#if.condition);
+                self.block(&r#if.then);
+                if let Some(else_clause) = &r#if.else_clause {
+                    self.block(else_clause);
+                }
+            }
+            BranchingAndJumping::IfVar(if_var) => {
+                self.expression(&if_var.binding.value);
+                if let Some(else_clause) = &if_var.else_clause {
+                    self.block(else_clause);
+                }
+                self.names.extend(if_var.binding.pattern.bound_names());
+                self.block(&if_var.then);
+            }
+            BranchingAndJumping::Select(select) => {
+                for select_case in &select.cases {
+                    self.case(&select_case.case);
+                }
+                if let Some(timeout) = &select.timeout {
+                    self.expression(&timeout.nanoseconds);
+                    self.block(&timeout.body);
+                }
+            }
+            BranchingAndJumping::Switch(switch) => {
+                self.expression(&switch.expression);
+                let outer = self.names.len();
+                self.names.extend(switch.binding.clone());
+                for case in &switch.cases {
+                    self.case(case);
+                }
+                self.names.truncate(outer);
+            }
+            BranchingAndJumping::While(r#while) => {
+                self.expression(&r#while.condition);
+                self.block(&r#while.scope);
+            }
+            BranchingAndJumping::WhileVar(while_var) => {
+                self.expression(&while_var.binding.value);
+                self.names.extend(while_var.binding.pattern.bound_names());
+                self.block(&while_var.scope);
+            }
+            BranchingAndJumping::LabelledBlock(labelled) => self.block(&labelled.scope),
+            BranchingAndJumping::ExpressionCall(call) => {
+                self.expression(&call.target);
+                for argument in &call.arguments.arguments {
+                    self.expression(&argument.value);
+                }
+            }
+            BranchingAndJumping::Break(r#break) => {
+                if let Some(value) = &r#break.value {
+                    self.expression(value);
+                }
+            }
+            BranchingAndJumping::Fallthrough => {}
+        }
+        self.names.truncate(outer);
+    }
+
+    /// Resolve the subexpressions of an expression that neither binds names nor
+    /// contains blocks of its own, such as an operator or a record literal.
+    fn children(&mut self, expression: &Expression) {
+        for child in NodeRef::Expression(expression).children() {
+            if let NodeRef::Expression(child) = child {
+                self.expression(child);
+            }
+        }
+    }
+}
+
+impl From<&UnresolvedName> for Diagnostic {
+    fn from(unresolved: &UnresolvedName) -> Self {
+        Diagnostic::error(
+            "unresolved-name",
+            format!("nothing called `{}` is in scope", unresolved.name.0),
+            unresolved.span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn unresolved_in(source: &str) -> Vec<UnresolvedName> {
+        let builtins = [Identifier::from("True"), Identifier::from("println")];
+        check_unresolved_names(&parse_str(source).unwrap(), &builtins)
+    }
+
+    #[test]
+    fn typos_are_unresolved() {
+        let unresolved = unresolved_in(
+            "package main
+            var amount = 10
+            var double = -> n { n * 2 }
+            println(double(amount))
+            println(double(amont))",
+        );
+        let found = unresolved
+            .iter()
+            .map(|unresolved| {
+                let start = unresolved.span.unwrap().start;
+                (
+                    unresolved.name.0.as_str(),
+                    (start.line(), start.character_position_in_line()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![("amont", (5, 28))]);
+    }
+
+    #[test]
+    fn scoped_names_builtins_and_imports_resolve() {
+        let unresolved = unresolved_in(
+            "package main (libraries.text.Format, libraries.numbers.{Rational as R})
+            var limit = True
+            for var n = 0 {
+                switch n {
+                    m if m < limit { continue(m + 1) }
+                    _ { Format(R(n)) }
+                }
+            }
+            var later = -> { n }",
+        );
+        assert_eq!(
+            unresolved,
+            vec![UnresolvedName {
+                name: Identifier::from("n"),
+                span: unresolved[0].span,
+            }]
+        );
+    }
+
+    #[test]
+    fn methods_call_each_other_without_this() {
+        let source = "package main
+            class Square {
+                fun describe() String { println(area()) }
+                fun area() Int { side() * side() }
+                fun side() Int { 2 }
+            }
+            var outside = -> { area() }";
+        let unresolved = unresolved_in(source)
+            .into_iter()
+            .map(|unresolved| {
+                (
+                    unresolved.name.0.as_str().to_owned(),
+                    unresolved.span.unwrap().start.line(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(unresolved, vec![("area".to_owned(), 7)]);
+    }
+}
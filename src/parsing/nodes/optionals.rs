@@ -0,0 +1,134 @@
+//! Checks that optional chaining is only used on optional values.
+//!
+//! Looking up a member with `?.` only makes sense if the value it's looked up
+//! on might be `Null`, i.e. if its type is optional as in `Int?`. Using it on
+//! anything else hides a mistake about what a value can hold.
+//!
+//! Types aren't inferred yet, so only chains on names bound at the top of the
+//! main package with an explicit type annotation are checked. Names rebound by
+//! the main package's local bindings are ignored, since their types are
+//! unknown.
+
+use std::collections::HashMap;
+
+use super::traversal::NodeRef;
+use super::{Expression, Identifier, Item, MainFile, Operator, PatternItem, Symbol, SymbolLookup};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NonOptionalChain {
+    /// The name whose annotated type isn't optional.
+    pub receiver: Identifier,
+
+    pub member: Identifier,
+}
+
+pub fn check_optional_chains(file: &MainFile) -> Vec<NonOptionalChain> {
+    let mut annotations = HashMap::new();
+    let mut roots = vec![];
+    for item in &file.package.package.items {
+        if let Item::Var(binding) | Item::Final(binding) | Item::Const(binding) = item {
+            if let (PatternItem::Identifier(name), Some(annotation)) =
+                (&binding.pattern.item, &binding.explicit_type_annotation)
+            {
+                annotations.insert(name, annotation);
+            }
+            roots.push(NodeRef::Binding(binding));
+        }
+    }
+
+    let block = &file.package.block;
+    for binding in &block.bindings {
+        for name in binding.pattern.bound_names() {
+            annotations.remove(&name);
+        }
+    }
+    roots.extend(block.bindings.iter().map(NodeRef::Binding));
+    roots.extend(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut problems = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            if let NodeRef::Expression(Expression::Operator(Operator::OptionalChain(
+                receiver,
+                member,
+            ))) = node
+            {
+                if let Some(name) = annotated_name(receiver) {
+                    if let Some(annotation) = annotations.get(name) {
+                        if !annotation.is_optional() {
+                            problems.push(NonOptionalChain {
+                                receiver: name.clone(),
+                                member: member.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn annotated_name(expression: &Expression) -> Option<&Identifier> {
+    match expression {
+        Expression::Symbol(Symbol::Relative(SymbolLookup(lookup))) if lookup.len() == 1 => {
+            lookup.first()
+        }
+        _ => None,
+    }
+}
+
+impl From<&NonOptionalChain> for Diagnostic {
+    fn from(chain: &NonOptionalChain) -> Self {
+        Diagnostic::warning(
+            "non-optional-chain",
+            format!(
+                "`{}` isn't optional, so `?.{}` should be `.{}`",
+                chain.receiver.0, chain.member.0, chain.member.0
+            ),
+            chain.receiver.1.map(Span::at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn chains_in(source: &str) -> Vec<NonOptionalChain> {
+        check_optional_chains(&parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn optional_chaining_requires_an_optional() {
+        let problems = chains_in(
+            "package main
+            final maybe Account? = find()
+            final definitely Account = create()
+            final unknown = create()
+            var balance = maybe?.balance
+            var owner = definitely?.owner",
+        );
+        assert_eq!(
+            problems,
+            vec![NonOptionalChain {
+                receiver: Identifier::from("definitely"),
+                member: Identifier::from("owner"),
+            }]
+        );
+
+        assert_eq!(
+            chains_in(
+                "package main
+                final definitely Account = create()
+                var definitely = find()
+                var owner = definitely?.owner
+                var name = unknown?.name",
+            ),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,649 @@
+//! Checks that `override` is used exactly where a method specialises one it
+//! inherits.
+//!
+//! A class inherits methods from the interfaces it implements, and an interface
+//! from those it extends, transitively. Implementing an abstract method doesn't
+//! need `override`, but using it there is harmless. Replacing an
+//! already-defined method should be marked with `override`, although leaving it
+//! off is only a warning. Marking a method that has nothing to override is an
+//! error, as is overriding with an incompatible signature.
+//!
+//! Only types declared among the given items are resolved. If a type inherits
+//! from anything that can't be resolved, such as an imported interface, methods
+//! that appear to have nothing to override aren't reported, since they may well
+//! override something there.
+//!
+//! A class with no implementation of an inherited abstract method, neither its
+//! own nor an inherited default, is an error. So is an extension that makes a type implement an interface without
+//! providing the interface's abstract methods.
+
+use std::collections::HashSet;
+
+use super::{
+    AbstractMethod, Extension, FunSignature, Identifier, Item, Method, Symbol, SymbolLookup, Type,
+    TypeItem, TypeReference,
+};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OverrideProblem {
+    NoBase {
+        r#type: Identifier,
+        method: Identifier,
+    },
+    IncompatibleSignature {
+        r#type: Identifier,
+        method: Identifier,
+    },
+    MissingModifier {
+        r#type: Identifier,
+        method: Identifier,
+    },
+}
+
+impl OverrideProblem {
+    /// Whether the problem should fail compilation rather than just warn.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, OverrideProblem::MissingModifier { .. })
+    }
+}
+
+pub fn check_overrides(items: &[Item]) -> Vec<OverrideProblem> {
+    let types = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Type(r#type) => Some(r#type),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut problems = vec![];
+    for r#type in &types {
+        let inherited = Inherited::of(r#type, &types);
+        for (method, _) in own_methods(r#type) {
+            check_method(r#type, method, &inherited, &mut problems);
+        }
+    }
+    problems
+}
+
+/// An abstract method that a class or enum inherits but doesn't implement.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnimplementedMethod {
+    pub r#type: Identifier,
+    pub method: Identifier,
+}
+
+/// Check that every class and enum implements the abstract methods it
+/// inherits, either itself or via an inherited default. Class methods always
+/// have bodies and Sylan has no abstract classes, so these are the only
+/// methods that can be left unimplemented.
+pub fn check_implementations(items: &[Item]) -> Vec<UnimplementedMethod> {
+    let types = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Type(r#type) => Some(r#type),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut problems = vec![];
+    for r#type in &types {
+        if let TypeItem::Interface(_) = r#type.item {
+            continue;
+        }
+        let inherited = Inherited::of(r#type, &types);
+        if !inherited.is_complete {
+            continue;
+        }
+        let implemented = own_methods(r#type)
+            .into_iter()
+            .chain(inherited.methods.iter().copied())
+            .filter(|&(_, is_concrete)| is_concrete)
+            .map(|(method, _)| &method.signature.name)
+            .collect::<HashSet<_>>();
+
+        let mut reported = HashSet::new();
+        for (method, _) in &inherited.methods {
+            let name = &method.signature.name;
+            if !implemented.contains(name) && reported.insert(name) {
+                problems.push(UnimplementedMethod {
+                    r#type: r#type.name.clone(),
+                    method: name.clone(),
+                });
+            }
+        }
+    }
+    problems
+}
+
+/// Check that every extension adding interfaces to a type provides the
+/// abstract methods they bring, whether in the extension, in the type itself,
+/// or via a default. Extensions of types that can't be resolved aren't
+/// checked, nor are those adding interfaces that can't be.
+pub fn check_extension_implementations(items: &[Item]) -> Vec<UnimplementedMethod> {
+    let types = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Type(r#type) => Some(r#type),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut problems = vec![];
+    for item in items {
+        if let Item::Extension(extension) = item {
+            check_extension(extension, &types, &mut problems);
+        }
+    }
+    problems
+}
+
+fn check_extension(
+    extension: &Extension,
+    types: &[&Type],
+    problems: &mut Vec<UnimplementedMethod>,
+) {
+    let extended = match resolve(&TypeReference::new(extension.symbol.clone()), types) {
+        Some(extended) => extended,
+        None => return,
+    };
+    let mut added = Inherited {
+        methods: vec![],
+        is_complete: true,
+    };
+    let mut visited = HashSet::new();
+    visited.insert(&extended.name);
+    added.collect(&extension.item.implements, types, &mut visited);
+    if !added.is_complete {
+        return;
+    }
+
+    let existing = Inherited::of(extended, types);
+    let implemented = extension
+        .item
+        .methods
+        .iter()
+        .map(|method| (&method.r#abstract, true))
+        .chain(own_methods(extended))
+        .chain(existing.methods.iter().copied())
+        .chain(added.methods.iter().copied())
+        .filter(|&(_, is_concrete)| is_concrete)
+        .map(|(method, _)| &method.signature.name)
+        .collect::<HashSet<_>>();
+
+    let mut reported = HashSet::new();
+    for (method, _) in &added.methods {
+        let name = &method.signature.name;
+        if !implemented.contains(name) && reported.insert(name) {
+            problems.push(UnimplementedMethod {
+                r#type: extended.name.clone(),
+                method: name.clone(),
+            });
+        }
+    }
+}
+
+fn resolve<'a>(reference: &TypeReference, types: &[&'a Type]) -> Option<&'a Type> {
+    match &reference.symbol {
+        Symbol::Relative(SymbolLookup(lookup)) if lookup.len() == 1 => types
+            .iter()
+            .find(|candidate| candidate.name == lookup[0])
+            .copied(),
+        _ => None,
+    }
+}
+
+fn check_method(
+    r#type: &Type,
+    method: &AbstractMethod,
+    inherited: &Inherited<'_>,
+    problems: &mut Vec<OverrideProblem>,
+) {
+    let name = &method.signature.name;
+    let bases = inherited
+        .methods
+        .iter()
+        .filter(|(base, _)| base.signature.name == *name)
+        .collect::<Vec<_>>();
+
+    let problem = if method.modifiers.overrides {
+        if bases.is_empty() {
+            if inherited.is_complete {
+                Some(OverrideProblem::NoBase {
+                    r#type: r#type.name.clone(),
+                    method: name.clone(),
+                })
+            } else {
+                None
+            }
+        } else if bases
+            .iter()
+            .any(|(base, _)| compatible(&base.signature, &method.signature))
+        {
+            None
+        } else {
+            Some(OverrideProblem::IncompatibleSignature {
+                r#type: r#type.name.clone(),
+                method: name.clone(),
+            })
+        }
+    } else if bases.iter().any(|&&(_, is_concrete)| is_concrete) {
+        Some(OverrideProblem::MissingModifier {
+            r#type: r#type.name.clone(),
+            method: name.clone(),
+        })
+    } else {
+        None
+    };
+    problems.extend(problem);
+}
+
+/// Overriding keeps the same type parameters, parameter labels and types, and
+/// return type. Parameter names and defaults can differ.
+fn compatible(base: &FunSignature, method: &FunSignature) -> bool {
+    (base.type_parameters.len() == method.type_parameters.len())
+        && (base.value_parameters.len() == method.value_parameters.len())
+        && base
+            .value_parameters
+            .iter()
+            .zip(&method.value_parameters)
+            .all(|(base, parameter)| {
+                (base.label == parameter.label)
+                    && (base.type_annotation == parameter.type_annotation)
+            })
+        && (base.return_type == method.return_type)
+}
+
+/// A type's methods, each paired with whether it is concrete.
+pub(super) fn own_methods(r#type: &Type) -> Vec<(&AbstractMethod, bool)> {
+    match &r#type.item {
+        TypeItem::Class(class) => class
+            .methods
+            .iter()
+            .map(|method| (&method.r#abstract, true))
+            .collect(),
+        TypeItem::Enum(r#enum) => r#enum
+            .class
+            .methods
+            .iter()
+            .map(|method| (&method.r#abstract, true))
+            .collect(),
+        TypeItem::Interface(interface) => interface
+            .methods
+            .iter()
+            .map(|method| match method {
+                Method::Abstract(method) => (method, false),
+                Method::Concrete(method) => (&method.r#abstract, true),
+            })
+            .collect(),
+    }
+}
+
+fn bases(r#type: &Type) -> &[TypeReference] {
+    match &r#type.item {
+        TypeItem::Class(class) => &class.implements,
+        TypeItem::Enum(r#enum) => &r#enum.class.implements,
+        TypeItem::Interface(interface) => &interface.extends,
+    }
+}
+
+pub(super) struct Inherited<'a> {
+    pub(super) methods: Vec<(&'a AbstractMethod, bool)>,
+
+    /// Whether every base type could be resolved.
+    pub(super) is_complete: bool,
+}
+
+impl<'a> Inherited<'a> {
+    pub(super) fn of(r#type: &'a Type, types: &[&'a Type]) -> Self {
+        let mut inherited = Inherited {
+            methods: vec![],
+            is_complete: true,
+        };
+        let mut visited = HashSet::new();
+        visited.insert(&r#type.name);
+        inherited.collect(bases(r#type), types, &mut visited);
+        inherited
+    }
+
+    fn collect(
+        &mut self,
+        bases: &[TypeReference],
+        types: &[&'a Type],
+        visited: &mut HashSet<&'a Identifier>,
+    ) {
+        for base in bases {
+            match resolve(base, types) {
+                Some(base) => {
+                    // Interfaces can be reached along several paths, or even
+                    // cyclically in invalid code.
+                    if visited.insert(&base.name) {
+                        self.methods.extend(own_methods(base));
+                        self.collect(self::bases(base), types, visited);
+                    }
+                }
+                None => self.is_complete = false,
+            }
+        }
+    }
+}
+
+impl From<&OverrideProblem> for Diagnostic {
+    fn from(problem: &OverrideProblem) -> Self {
+        let (code, r#type, method, message) = match problem {
+            OverrideProblem::NoBase { r#type, method } => (
+                "override-without-base",
+                r#type,
+                method,
+                "is marked `override` but overrides nothing",
+            ),
+            OverrideProblem::IncompatibleSignature { r#type, method } => (
+                "incompatible-override",
+                r#type,
+                method,
+                "has a different signature from the method it overrides",
+            ),
+            OverrideProblem::MissingModifier { r#type, method } => (
+                "missing-override",
+                r#type,
+                method,
+                "replaces an inherited method without being marked `override`",
+            ),
+        };
+        let message = format!("`{}.{}` {}", r#type.0, method.0, message);
+        let span = method.1.map(Span::at);
+        if problem.is_error() {
+            Diagnostic::error(code, message, span)
+        } else {
+            Diagnostic::warning(code, message, span)
+        }
+    }
+}
+
+impl From<&UnimplementedMethod> for Diagnostic {
+    fn from(unimplemented: &UnimplementedMethod) -> Self {
+        Diagnostic::error(
+            "unimplemented-method",
+            format!(
+                "`{}` doesn't implement the inherited method `{}`",
+                unimplemented.r#type.0, unimplemented.method.0
+            ),
+            unimplemented.r#type.1.map(Span::at),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::multiphase::Accessibility;
+    use crate::parsing::nodes::{
+        Block, Class, ConcreteMethod, FunModifiers, Interface, MethodModifiers, Pattern,
+        PatternItem, ValueParameter,
+    };
+
+    fn type_reference(name: &'static str) -> TypeReference {
+        TypeReference::new(Symbol::Relative(SymbolLookup(vec![Identifier::from(name)])))
+    }
+
+    fn signature(name: &'static str, parameter_type: &'static str) -> AbstractMethod {
+        let parameter = ValueParameter {
+            label: None,
+            is_syntax: false,
+            is_mutable: false,
+            pattern: Pattern {
+                item: PatternItem::Identifier(Identifier::from("x")),
+                bound_match: None,
+            },
+            type_annotation: type_reference(parameter_type),
+            default_value: None,
+            sydoc: None,
+        };
+        AbstractMethod {
+            modifiers: MethodModifiers {
+                fun_modifiers: FunModifiers {
+                    accessibility: Accessibility::Public,
+                    r#extern: None,
+                    is_operator: false,
+                },
+                overrides: false,
+            },
+            signature: FunSignature {
+                name: Identifier::from(name),
+                sydoc: None,
+                type_parameters: vec![],
+                value_parameters: vec![parameter],
+                return_type: None,
+            },
+        }
+    }
+
+    fn concrete(
+        name: &'static str,
+        parameter_type: &'static str,
+        overrides: bool,
+    ) -> ConcreteMethod {
+        let mut method = signature(name, parameter_type);
+        method.modifiers.overrides = overrides;
+        ConcreteMethod {
+            r#abstract: method,
+            scope: Block::new_root(),
+        }
+    }
+
+    fn interface(name: &'static str, extends: &[&'static str], methods: Vec<Method>) -> Item {
+        Item::Type(Type {
+            name: Identifier::from(name),
+            type_parameters: vec![],
+            item: TypeItem::Interface(Interface {
+                extends: extends.iter().map(|name| type_reference(name)).collect(),
+                methods,
+            }),
+            sydoc: None,
+        })
+    }
+
+    fn class(
+        name: &'static str,
+        implements: &[&'static str],
+        methods: Vec<ConcreteMethod>,
+    ) -> Item {
+        Item::Type(Type {
+            name: Identifier::from(name),
+            type_parameters: vec![],
+            item: TypeItem::Class(Class {
+                implements: implements.iter().map(|name| type_reference(name)).collect(),
+                embeds: vec![],
+                methods,
+                fields: vec![],
+                value_parameters: vec![],
+                instance_initialiser: Block::new_root(),
+            }),
+            sydoc: None,
+        })
+    }
+
+    fn shapes() -> Vec<Item> {
+        vec![
+            interface(
+                "Shape",
+                &[],
+                vec![
+                    Method::Abstract(signature("area", "Number")),
+                    Method::Concrete(concrete("describe", "String", false)),
+                ],
+            ),
+            interface("Polygon", &["Shape"], vec![]),
+        ]
+    }
+
+    #[test]
+    fn valid_overrides() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![
+                concrete("area", "Number", false),
+                concrete("describe", "String", true),
+                concrete("side", "Number", false),
+            ],
+        ));
+        assert_eq!(check_overrides(&items), vec![]);
+    }
+
+    #[test]
+    fn overrides_need_a_compatible_base() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![
+                concrete("area", "Number", false),
+                concrete("side", "Number", true),
+                concrete("describe", "Number", true),
+            ],
+        ));
+        let problems = check_overrides(&items);
+        assert_eq!(
+            problems,
+            vec![
+                OverrideProblem::NoBase {
+                    r#type: Identifier::from("Square"),
+                    method: Identifier::from("side"),
+                },
+                OverrideProblem::IncompatibleSignature {
+                    r#type: Identifier::from("Square"),
+                    method: Identifier::from("describe"),
+                },
+            ]
+        );
+        assert!(problems.iter().all(OverrideProblem::is_error));
+    }
+
+    #[test]
+    fn missing_overrides_are_warnings() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![concrete("describe", "String", false)],
+        ));
+        let problems = check_overrides(&items);
+        assert_eq!(
+            problems,
+            vec![OverrideProblem::MissingModifier {
+                r#type: Identifier::from("Square"),
+                method: Identifier::from("describe"),
+            }]
+        );
+        assert!(!problems[0].is_error());
+    }
+
+    #[test]
+    fn classes_can_override_defaults() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![
+                concrete("area", "Number", false),
+                concrete("describe", "String", true),
+            ],
+        ));
+        assert_eq!(check_overrides(&items), vec![]);
+    }
+
+    #[test]
+    fn classes_inherit_defaults_unchanged() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![concrete("area", "Number", false)],
+        ));
+        assert_eq!(check_implementations(&items), vec![]);
+    }
+
+    #[test]
+    fn unresolved_bases_might_have_anything() {
+        let items = vec![class(
+            "Square",
+            &["Imported"],
+            vec![concrete("area", "Number", true)],
+        )];
+        assert_eq!(check_overrides(&items), vec![]);
+    }
+
+    #[test]
+    fn classes_must_implement_abstract_methods() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![concrete("side", "Number", false)],
+        ));
+        assert_eq!(
+            check_implementations(&items),
+            vec![UnimplementedMethod {
+                r#type: Identifier::from("Square"),
+                method: Identifier::from("area"),
+            }]
+        );
+    }
+
+    fn extension(source: &str) -> Item {
+        let source = format!("package main\n{}", source);
+        let mut file = crate::parsing::parse_str(&source).unwrap();
+        file.package.package.items.pop().unwrap()
+    }
+
+    #[test]
+    fn extensions_must_implement_added_interfaces() {
+        let mut items = shapes();
+        items.push(class("Square", &[], vec![]));
+        items.push(extension(
+            "extend Square : Polygon {
+                fun side() Number { 4 }
+            }",
+        ));
+        assert_eq!(
+            check_extension_implementations(&items),
+            vec![UnimplementedMethod {
+                r#type: Identifier::from("Square"),
+                method: Identifier::from("area"),
+            }]
+        );
+
+        // The type itself can provide them too.
+        items.pop();
+        items.pop();
+        items.push(class(
+            "Square",
+            &[],
+            vec![concrete("area", "Number", false)],
+        ));
+        items.push(extension("extend Square : Polygon"));
+        items.push(extension("extend Unresolved : Polygon"));
+        items.push(extension("extend Square : Unresolved"));
+        assert_eq!(check_extension_implementations(&items), vec![]);
+    }
+
+    #[test]
+    fn fully_implemented_classes() {
+        let mut items = shapes();
+        items.push(class(
+            "Square",
+            &["Polygon"],
+            vec![concrete("area", "Number", false)],
+        ));
+        items.push(class(
+            "Imported",
+            &["Unresolved"],
+            vec![concrete("area", "Number", false)],
+        ));
+        assert_eq!(check_implementations(&items), vec![]);
+    }
+}
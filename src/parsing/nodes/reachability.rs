@@ -0,0 +1,194 @@
+//! Flags `switch` and `cond` arms that can never be reached because an earlier
+//! arm catches everything.
+//!
+//! A `switch` arm catches everything if any of its matches has an irrefutable
+//! pattern and no guard. A `cond` arm does if any of its conditions is `True`.
+//! Arms are tried top-to-bottom, so every arm after a catch-all is dead code,
+//! which is usually a sign that the arms are in the wrong order.
+
+use super::traversal::NodeRef;
+use super::{Block, BranchingAndJumping, Cond, Expression, Switch, Symbol, SymbolLookup};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnreachableArm {
+    /// The arm's index within its `switch` or `cond`. A `cond`'s `else` arm
+    /// comes after all of its other arms.
+    pub index: usize,
+
+    /// Where the arm is in the source. A `cond`'s `else` arm has no span of its
+    /// own.
+    pub span: Option<Span>,
+}
+
+impl Expression {
+    /// Whether the expression is simply `True`. Names aren't resolved, so this
+    /// is fooled by anything shadowing it.
+    pub fn is_always_true(&self) -> bool {
+        matches!(
+            self,
+            Expression::Symbol(Symbol::Relative(SymbolLookup(lookup)))
+                if lookup.len() == 1 && *lookup[0].0 == "True"
+        )
+    }
+}
+
+impl Switch {
+    pub fn unreachable_arms(&self) -> Vec<UnreachableArm> {
+        let catch_all = self.cases.iter().position(|case| {
+            case.matches
+                .iter()
+                .any(|case_match| case_match.guard.is_none() && case_match.pattern.is_irrefutable())
+        });
+        match catch_all {
+            Some(catch_all) => self.cases[catch_all + 1..]
+                .iter()
+                .enumerate()
+                .map(|(offset, case)| UnreachableArm {
+                    index: catch_all + 1 + offset,
+                    span: case.span,
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+impl Cond {
+    pub fn unreachable_arms(&self) -> Vec<UnreachableArm> {
+        let catch_all = self
+            .cases
+            .iter()
+            .position(|case| case.conditions.iter().any(Expression::is_always_true));
+        let catch_all = match catch_all {
+            Some(catch_all) => catch_all,
+            None => return vec![],
+        };
+
+        let mut arms = self.cases[catch_all + 1..]
+            .iter()
+            .enumerate()
+            .map(|(offset, case)| UnreachableArm {
+                index: catch_all + 1 + offset,
+                span: case.span,
+            })
+            .collect::<Vec<_>>();
+        if self.else_clause.is_some() {
+            arms.push(UnreachableArm {
+                index: self.cases.len(),
+                span: None,
+            });
+        }
+        arms
+    }
+}
+
+/// Every unreachable arm of every `switch` and `cond` within a block, in source
+/// order.
+pub fn check_unreachable_arms(block: &Block) -> Vec<UnreachableArm> {
+    let roots = block
+        .bindings
+        .iter()
+        .map(NodeRef::Binding)
+        .chain(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut arms = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            match node {
+                NodeRef::Expression(Expression::BranchingAndJumping(
+                    BranchingAndJumping::Switch(switch),
+                )) => arms.extend(switch.unreachable_arms()),
+                NodeRef::Expression(Expression::BranchingAndJumping(
+                    BranchingAndJumping::Cond(cond),
+                )) => arms.extend(cond.unreachable_arms()),
+                _ => {}
+            }
+        }
+    }
+    arms
+}
+
+impl From<&UnreachableArm> for Diagnostic {
+    fn from(arm: &UnreachableArm) -> Self {
+        Diagnostic::warning(
+            "unreachable-arm",
+            "the arm can never be reached, as an earlier one catches everything",
+            arm.span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn unreachable_in(source: &str) -> Vec<UnreachableArm> {
+        check_unreachable_arms(&parse_str(source).unwrap().package.block)
+    }
+
+    #[test]
+    fn arms_after_a_wildcard_are_unreachable() {
+        let arms = unreachable_in(
+            "package main
+            switch x {
+                0 { \"zero\" }
+                _ { \"other\" }
+                1 { \"one\" }
+            }",
+        );
+        assert_eq!(arms.len(), 1);
+        assert_eq!(arms[0].index, 2);
+        let span = arms[0].span.unwrap();
+        assert_eq!((span.start.line(), span.end.line()), (5, 5));
+        assert_eq!(span.start.character_position_in_line(), 17);
+
+        // Binding the value is as much a catch-all as ignoring it, unless it's
+        // guarded.
+        let arms = unreachable_in(
+            "package main
+            switch x {
+                n if n < 0 { \"negative\" }
+                (a, b) { \"pair\" }
+                0 { \"zero\" }
+                _ { \"other\" }
+            }",
+        );
+        assert_eq!(
+            arms.iter().map(|arm| arm.index).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        assert_eq!(
+            unreachable_in(
+                "package main
+                switch x {
+                    0 { \"zero\" }
+                    _ { \"other\" }
+                }"
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn cond_arms_after_true_are_unreachable() {
+        let arms = unreachable_in(
+            "package main
+            switch {
+                x < 0 { \"negative\" }
+                True { \"anything\" }
+                x == 0 { \"zero\" }
+                else { \"positive\" }
+            }",
+        );
+        assert_eq!(
+            arms.iter().map(|arm| arm.index).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(arms[0].span.unwrap().start.line(), 5);
+        assert_eq!(arms[1].span, None);
+    }
+}
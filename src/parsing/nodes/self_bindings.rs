@@ -0,0 +1,133 @@
+//! Flags bindings of a name to itself, such as `var x = x`.
+//!
+//! Sylan has no reassignment, so a self-assignment can only be written as a
+//! binding. If it shadows nothing, the name is used in its own temporal dead
+//! zone; if it shadows an outer `x`, it's a no-op that just copies it. Either
+//! way it's almost always a mistake, such as a typo in one of the names.
+//!
+//! `for` loops are the exception. Binding a loop variable to an outer value of
+//! the same name is how a loop starts from where an outer one left off.
+
+use std::ptr;
+
+use super::traversal::NodeRef;
+use super::{Binding, Block, BranchingAndJumping, Expression, PatternItem, Symbol, SymbolLookup};
+use crate::common::diagnostics::Diagnostic;
+use crate::common::multiphase::Identifier;
+use crate::source::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelfBinding {
+    pub name: Identifier,
+    pub span: Option<Span>,
+}
+
+impl Binding {
+    /// The name the binding binds to itself, if it does.
+    pub fn self_bound_name(&self) -> Option<&Identifier> {
+        let name = match (&self.pattern.item, &self.pattern.bound_match) {
+            (PatternItem::Identifier(name), None) => name,
+            _ => return None,
+        };
+        match &*self.value {
+            Expression::Symbol(Symbol::Relative(SymbolLookup(lookup)))
+                if lookup.len() == 1 && lookup[0] == *name =>
+            {
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub fn check_self_bindings(block: &Block) -> Vec<SelfBinding> {
+    let roots = block
+        .bindings
+        .iter()
+        .map(NodeRef::Binding)
+        .chain(block.expressions.iter().map(NodeRef::Expression));
+
+    let mut loop_bindings: Vec<&Binding> = vec![];
+    let mut found = vec![];
+    for root in roots {
+        for node in std::iter::once(root).chain(root.descendants()) {
+            match node {
+                // Loops come before their bindings, as nodes are in source
+                // order.
+                NodeRef::Expression(Expression::BranchingAndJumping(BranchingAndJumping::For(
+                    r#for,
+                ))) => loop_bindings.extend(&r#for.bindings),
+                NodeRef::Binding(binding)
+                    if !loop_bindings.iter().any(|&other| ptr::eq(other, binding)) =>
+                {
+                    if let Some(name) = binding.self_bound_name() {
+                        found.push(SelfBinding {
+                            name: name.clone(),
+                            span: binding.span,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+impl From<&SelfBinding> for Diagnostic {
+    fn from(binding: &SelfBinding) -> Self {
+        Diagnostic::warning(
+            "self-binding",
+            format!("`{}` is bound to itself", binding.name.0),
+            binding.span,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn self_bindings_in(source: &str) -> Vec<SelfBinding> {
+        check_self_bindings(&parse_str(source).unwrap().package.block)
+    }
+
+    #[test]
+    fn self_bindings_are_flagged() {
+        let found = self_bindings_in(
+            "package main
+            var limit = limit
+            var f = -> {
+                var count = 1
+                var x = x
+                x
+            }",
+        );
+        let names = found
+            .iter()
+            .map(|found| found.name.0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["limit", "x"]);
+
+        let lines = found
+            .iter()
+            .map(|found| found.span.unwrap().start.line())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 5]);
+    }
+
+    #[test]
+    fn other_bindings_are_not_flagged() {
+        let found = self_bindings_in(
+            "package main
+            var limit = 5
+            var copy = limit
+            var doubled = limit * 2
+            for var limit = limit {
+                limit
+            }",
+        );
+        assert_eq!(found, vec![]);
+    }
+}
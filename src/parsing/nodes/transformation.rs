@@ -0,0 +1,471 @@
+//! Rewriting the AST, for passes such as desugaring, folding, and lowering.
+//!
+//! A [Transformer] takes nodes by value and gives back their replacements. Each
+//! of its methods defaults to rebuilding its node from the node's transformed
+//! children, so a transformer only overrides the methods for the nodes it
+//! rewrites, calling the matching `walk_` function to carry on into the
+//! children of those it keeps. Nodes are moved rather than cloned as they're
+//! rebuilt, so unchanged subtrees are never copied.
+//!
+//! Like [traversal](super::traversal), it reaches every expression and binding
+//! however deeply they're nested, but doesn't descend into patterns or types.
+//! Blocks keep their `parent` as it was, so a rewrite isn't seen through them.
+
+use super::{
+    Binding, Block, BranchingAndJumping, CallArguments, Case, Class, Defer, Expression,
+    ExpressionCall, Item, Literal, Macro, MacroItem, Method, Operator, TypeItem,
+};
+
+pub trait Transformer {
+    fn transform_item(&mut self, item: Item) -> Item {
+        walk_item(self, item)
+    }
+
+    fn transform_binding(&mut self, binding: Binding) -> Binding {
+        walk_binding(self, binding)
+    }
+
+    fn transform_expression(&mut self, expression: Expression) -> Expression {
+        walk_expression(self, expression)
+    }
+
+    fn transform_block(&mut self, block: Block) -> Block {
+        walk_block(self, block)
+    }
+}
+
+pub fn walk_item<T: Transformer + ?Sized>(transformer: &mut T, item: Item) -> Item {
+    match item {
+        Item::Extension(mut extension) => {
+            extension.item = class(transformer, extension.item);
+            Item::Extension(extension)
+        }
+        Item::Fun(mut fun) => {
+            for parameter in &mut fun.signature.value_parameters {
+                parameter.default_value = optional(transformer, parameter.default_value.take());
+            }
+            fun.block = transformer.transform_block(fun.block);
+            Item::Fun(fun)
+        }
+        Item::Package(mut package) => {
+            package.items = package
+                .items
+                .into_iter()
+                .map(|item| transformer.transform_item(item))
+                .collect();
+            Item::Package(package)
+        }
+        Item::Type(mut r#type) => {
+            r#type.item = match r#type.item {
+                TypeItem::Class(class_item) => TypeItem::Class(class(transformer, class_item)),
+                TypeItem::Enum(mut r#enum) => {
+                    r#enum.class = class(transformer, r#enum.class);
+                    TypeItem::Enum(r#enum)
+                }
+                TypeItem::Interface(mut interface) => {
+                    interface.methods = interface
+                        .methods
+                        .into_iter()
+                        .map(|method| match method {
+                            Method::Concrete(mut method) => {
+                                method.scope = transformer.transform_block(method.scope);
+                                Method::Concrete(method)
+                            }
+                            method => method,
+                        })
+                        .collect();
+                    TypeItem::Interface(interface)
+                }
+            };
+            Item::Type(r#type)
+        }
+        Item::Macro(Macro::Item(MacroItem::Call(mut call))) => {
+            call.arguments = arguments(transformer, call.arguments);
+            Item::Macro(Macro::Item(MacroItem::Call(call)))
+        }
+        Item::Macro(r#macro) => Item::Macro(r#macro),
+        Item::Var(binding) => Item::Var(transformer.transform_binding(binding)),
+        Item::Final(binding) => Item::Final(transformer.transform_binding(binding)),
+        Item::Const(binding) => Item::Const(transformer.transform_binding(binding)),
+    }
+}
+
+pub fn walk_binding<T: Transformer + ?Sized>(transformer: &mut T, mut binding: Binding) -> Binding {
+    binding.value = boxed(transformer, binding.value);
+    binding
+}
+
+pub fn walk_block<T: Transformer + ?Sized>(transformer: &mut T, mut block: Block) -> Block {
+    block.bindings = block
+        .bindings
+        .into_iter()
+        .map(|binding| transformer.transform_binding(binding))
+        .collect();
+    block.expressions = expressions(transformer, block.expressions);
+    block
+}
+
+pub fn walk_expression<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    expression: Expression,
+) -> Expression {
+    match expression {
+        Expression::BranchingAndJumping(branching) => {
+            Expression::BranchingAndJumping(match branching {
+                BranchingAndJumping::ExpressionCall(call) => {
+                    BranchingAndJumping::ExpressionCall(expression_call(transformer, call))
+                }
+                BranchingAndJumping::Call(mut call) => {
+                    call.arguments = arguments(transformer, call.arguments);
+                    BranchingAndJumping::Call(call)
+                }
+                BranchingAndJumping::Cond(mut cond) => {
+                    cond.cases = cond
+                        .cases
+                        .into_iter()
+                        .map(|mut case| {
+                            case.conditions = expressions(transformer, case.conditions);
+                            case.then = case.then.map(|then| transformer.transform_block(then));
+                            case
+                        })
+                        .collect();
+                    cond.else_clause = cond
+                        .else_clause
+                        .map(|else_clause| transformer.transform_block(else_clause));
+                    BranchingAndJumping::Cond(cond)
+                }
+                BranchingAndJumping::For(mut r#for) => {
+                    r#for.bindings = r#for
+                        .bindings
+                        .into_iter()
+                        .map(|binding| transformer.transform_binding(binding))
+                        .collect();
+                    r#for.yield_expression = r#for
+                        .yield_expression
+                        .map(|yield_expression| boxed(transformer, yield_expression));
+                    r#for.scope = transformer.transform_block(r#for.scope);
+                    r#for.else_clause = r#for
+                        .else_clause
+                        .map(|else_clause| transformer.transform_block(else_clause));
+                    BranchingAndJumping::For(r#for)
+                }
+                BranchingAndJumping::If(mut r#if) => {
+                    r#if.condition = boxed(transformer, r#if.condition);
+                    r#if.then = transformer.transform_block(r#if.then);
+                    r#if.else_clause = r#if
+                        .else_clause
+                        .map(|else_clause| transformer.transform_block(else_clause));
+                    BranchingAndJumping::If(r#if)
+                }
+                BranchingAndJumping::IfVar(mut if_var) => {
+                    *if_var.binding = transformer.transform_binding(*if_var.binding);
+                    if_var.then = transformer.transform_block(if_var.then);
+                    if_var.else_clause = if_var
+                        .else_clause
+                        .map(|else_clause| transformer.transform_block(else_clause));
+                    BranchingAndJumping::IfVar(if_var)
+                }
+                BranchingAndJumping::Select(mut select) => {
+                    select.cases = select
+                        .cases
+                        .into_iter()
+                        .map(|mut select_case| {
+                            select_case.case = case(transformer, select_case.case);
+                            select_case
+                        })
+                        .collect();
+                    select.timeout = select.timeout.map(|mut timeout| {
+                        timeout.nanoseconds = boxed(transformer, timeout.nanoseconds);
+                        timeout.body = transformer.transform_block(timeout.body);
+                        timeout
+                    });
+                    BranchingAndJumping::Select(select)
+                }
+                BranchingAndJumping::Switch(mut switch) => {
+                    switch.expression = boxed(transformer, switch.expression);
+                    switch.cases = switch
+                        .cases
+                        .into_iter()
+                        .map(|switch_case| case(transformer, switch_case))
+                        .collect();
+                    BranchingAndJumping::Switch(switch)
+                }
+                BranchingAndJumping::While(mut r#while) => {
+                    r#while.condition = boxed(transformer, r#while.condition);
+                    r#while.scope = transformer.transform_block(r#while.scope);
+                    BranchingAndJumping::While(r#while)
+                }
+                BranchingAndJumping::WhileVar(mut while_var) => {
+                    *while_var.binding = transformer.transform_binding(*while_var.binding);
+                    while_var.scope = transformer.transform_block(while_var.scope);
+                    BranchingAndJumping::WhileVar(while_var)
+                }
+                BranchingAndJumping::LabelledBlock(mut labelled) => {
+                    labelled.scope = transformer.transform_block(labelled.scope);
+                    BranchingAndJumping::LabelledBlock(labelled)
+                }
+                BranchingAndJumping::Break(mut r#break) => {
+                    r#break.value = r#break.value.map(|value| boxed(transformer, value));
+                    BranchingAndJumping::Break(r#break)
+                }
+                BranchingAndJumping::Fallthrough => BranchingAndJumping::Fallthrough,
+            })
+        }
+        Expression::Context(context) => Expression::Context(transformer.transform_block(context)),
+        Expression::Spawn(mut spawn) => {
+            spawn.task = transformer.transform_block(spawn.task);
+            Expression::Spawn(spawn)
+        }
+        Expression::Literal(Literal::Lambda(mut lambda)) => {
+            for parameter in &mut lambda.signature.value_parameters {
+                parameter.default_value = optional(transformer, parameter.default_value.take());
+            }
+            lambda.block = transformer.transform_block(lambda.block);
+            Expression::Literal(Literal::Lambda(lambda))
+        }
+        Expression::Literal(Literal::Record(mut record)) => {
+            record.0 = record
+                .0
+                .into_iter()
+                .map(|mut field| {
+                    field.value = transformer.transform_expression(field.value);
+                    field
+                })
+                .collect();
+            Expression::Literal(Literal::Record(record))
+        }
+        Expression::Literal(Literal::Tuple(mut tuple)) => {
+            tuple.0 = expressions(transformer, tuple.0);
+            Expression::Literal(Literal::Tuple(tuple))
+        }
+        Expression::Literal(Literal::List(mut list)) => {
+            list.elements = expressions(transformer, list.elements);
+            Expression::Literal(Literal::List(list))
+        }
+        Expression::Operator(Operator::OverloadableInfix(left, operator, right)) => {
+            let left = boxed(transformer, left);
+            let right = boxed(transformer, right);
+            Expression::Operator(Operator::OverloadableInfix(left, operator, right))
+        }
+        Expression::Operator(Operator::Postfix(operand, operator)) => {
+            Expression::Operator(Operator::Postfix(boxed(transformer, operand), operator))
+        }
+        Expression::Operator(Operator::Lookup(operand, member)) => {
+            Expression::Operator(Operator::Lookup(boxed(transformer, operand), member))
+        }
+        Expression::Operator(Operator::OptionalChain(operand, member)) => {
+            Expression::Operator(Operator::OptionalChain(boxed(transformer, operand), member))
+        }
+        Expression::Operator(Operator::MultiSlice(operand, slice)) => {
+            Expression::Operator(Operator::MultiSlice(boxed(transformer, operand), slice))
+        }
+        Expression::Throw(mut throw) => {
+            throw.exception = boxed(transformer, throw.exception);
+            throw.cause = throw.cause.map(|cause| boxed(transformer, cause));
+            Expression::Throw(throw)
+        }
+        Expression::Use(mut r#use) => {
+            r#use.0 = boxed(transformer, r#use.0);
+            Expression::Use(r#use)
+        }
+        Expression::Defer(defer) => Expression::Defer(Defer(transformer.transform_block(defer.0))),
+        Expression::NonDestructiveUpdate(call) => {
+            Expression::NonDestructiveUpdate(expression_call(transformer, call))
+        }
+        Expression::Literal(_)
+        | Expression::Symbol(_)
+        | Expression::Hole(_)
+        | Expression::MemberHandle(_)
+        | Expression::Operator(Operator::Reference(_))
+        | Expression::ReaderMacroActivation(_) => expression,
+    }
+}
+
+/// Transform a boxed expression, reusing its box.
+fn boxed<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    mut expression: Box<Expression>,
+) -> Box<Expression> {
+    *expression = transformer.transform_expression(*expression);
+    expression
+}
+
+fn optional<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    expression: Option<Expression>,
+) -> Option<Expression> {
+    expression.map(|expression| transformer.transform_expression(expression))
+}
+
+fn expressions<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    expressions: Vec<Expression>,
+) -> Vec<Expression> {
+    expressions
+        .into_iter()
+        .map(|expression| transformer.transform_expression(expression))
+        .collect()
+}
+
+fn arguments<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    mut arguments: CallArguments,
+) -> CallArguments {
+    arguments.arguments = arguments
+        .arguments
+        .into_iter()
+        .map(|mut argument| {
+            argument.value = transformer.transform_expression(argument.value);
+            argument
+        })
+        .collect();
+    arguments
+}
+
+fn expression_call<T: Transformer + ?Sized>(
+    transformer: &mut T,
+    mut call: ExpressionCall,
+) -> ExpressionCall {
+    call.target = boxed(transformer, call.target);
+    call.arguments = arguments(transformer, call.arguments);
+    call
+}
+
+fn case<T: Transformer + ?Sized>(transformer: &mut T, mut case: Case) -> Case {
+    for case_match in &mut case.matches {
+        case_match.guard = optional(transformer, case_match.guard.take());
+    }
+    case.body = transformer.transform_block(case.body);
+    case
+}
+
+fn class<T: Transformer + ?Sized>(transformer: &mut T, mut class: Class) -> Class {
+    for parameter in &mut class.value_parameters {
+        parameter.parameter.default_value =
+            optional(transformer, parameter.parameter.default_value.take());
+    }
+    class.fields = class
+        .fields
+        .into_iter()
+        .map(|mut field| {
+            field.binding = transformer.transform_binding(field.binding);
+            field
+        })
+        .collect();
+    class.methods = class
+        .methods
+        .into_iter()
+        .map(|mut method| {
+            method.scope = transformer.transform_block(method.scope);
+            method
+        })
+        .collect();
+    class.instance_initialiser = transformer.transform_block(class.instance_initialiser);
+    class
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::multiphase::{Number, OverloadableInfixOperator};
+    use crate::parsing::parse_str;
+
+    /// Rewrites every number `n` to `n + 1`.
+    struct Increment;
+
+    impl Transformer for Increment {
+        fn transform_expression(&mut self, expression: Expression) -> Expression {
+            match expression {
+                Expression::Literal(Literal::Number(..)) => {
+                    let one = Expression::Literal(Literal::Number(Number(1, 0), None));
+                    Expression::Operator(Operator::OverloadableInfix(
+                        Box::new(expression),
+                        OverloadableInfixOperator::Add,
+                        Box::new(one),
+                    ))
+                }
+                other => walk_expression(self, other),
+            }
+        }
+    }
+
+    /// Clears the spans of blocks, which differ between sources of different
+    /// lengths.
+    struct Unspanned;
+
+    impl Transformer for Unspanned {
+        fn transform_block(&mut self, block: Block) -> Block {
+            let mut block = walk_block(self, block);
+            block.span = None;
+            block
+        }
+    }
+
+    #[test]
+    fn numbers_are_rewritten_throughout() {
+        let file = parse_str(
+            "package main
+            final limit = 10
+            var x = f(1 * 2, g(3))
+            var h = -> { 7 }
+            if x > 4 { #{ y: 5 } } else { 6 }",
+        )
+        .unwrap();
+        let expected = parse_str(
+            "package main
+            final limit = 10 + 1
+            var x = f((1 + 1) * (2 + 1), g(3 + 1))
+            var h = -> { 7 + 1 }
+            if x > 4 + 1 { #{ y: 5 + 1 } } else { 6 + 1 }",
+        )
+        .unwrap();
+
+        // Spans differ as the expected source is longer, so only values are
+        // compared.
+        let values = |bindings: Vec<&Binding>| {
+            bindings
+                .into_iter()
+                .map(|binding| Unspanned.transform_expression((*binding.value).clone()))
+                .collect::<Vec<_>>()
+        };
+        let item_values = |items: &[Item]| {
+            values(
+                items
+                    .iter()
+                    .map(|item| match item {
+                        Item::Final(binding) => binding,
+                        other => panic!("expected a final binding: {:?}", other),
+                    })
+                    .collect(),
+            )
+        };
+
+        let items = file
+            .package
+            .package
+            .items
+            .into_iter()
+            .map(|item| Increment.transform_item(item))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            item_values(&items),
+            item_values(&expected.package.package.items)
+        );
+
+        let block = Increment.transform_block(file.package.block);
+        assert_eq!(
+            values(block.bindings.iter().collect()),
+            values(expected.package.block.bindings.iter().collect())
+        );
+        let unspanned = |expressions: Vec<Expression>| {
+            expressions
+                .into_iter()
+                .map(|expression| Unspanned.transform_expression(expression))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            unspanned(block.expressions),
+            unspanned(expected.package.block.expressions)
+        );
+    }
+}
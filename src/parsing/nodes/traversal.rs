@@ -0,0 +1,346 @@
+//! Iterators over the AST, for analyses that just want to see every node of a
+//! kind rather than handle each variant's structure themselves.
+//!
+//! Walking borrows the tree via [NodeRef] rather than cloning subtrees into
+//! owned [Node]s. Bindings get their own kind of node as, unlike items and
+//! expressions, they appear directly inside blocks and loops.
+
+use std::vec;
+
+use super::{
+    Binding, Block, BranchingAndJumping, CallArguments, Case, Class, Expression, ExpressionCall,
+    Item, Literal, Macro, MacroItem, Method, Node, Operator, TypeItem,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeRef<'a> {
+    Item(&'a Item),
+    Binding(&'a Binding),
+    Expression(&'a Expression),
+}
+
+impl<'a> From<&'a Node> for NodeRef<'a> {
+    fn from(node: &'a Node) -> Self {
+        match node {
+            Node::Item(item) => NodeRef::Item(item),
+            Node::Expression(expression) => NodeRef::Expression(expression),
+        }
+    }
+}
+
+impl Node {
+    pub fn children(&self) -> vec::IntoIter<NodeRef<'_>> {
+        NodeRef::from(self).children()
+    }
+
+    pub fn descendants(&self) -> Descendants<'_> {
+        NodeRef::from(self).descendants()
+    }
+}
+
+impl<'a> NodeRef<'a> {
+    /// The nodes directly within this one, in source order.
+    pub fn children(self) -> vec::IntoIter<NodeRef<'a>> {
+        let mut children = Children(vec![]);
+        match self {
+            NodeRef::Item(item) => children.item(item),
+            NodeRef::Binding(binding) => children.expression(&binding.value),
+            NodeRef::Expression(expression) => children.expression_parts(expression),
+        }
+        children.0.into_iter()
+    }
+
+    /// Every node within this one, depth-first and in source order. The node
+    /// itself is not included.
+    pub fn descendants(self) -> Descendants<'a> {
+        let mut stack = self.children().collect::<Vec<_>>();
+        stack.reverse();
+        Descendants { stack }
+    }
+}
+
+pub struct Descendants<'a> {
+    stack: Vec<NodeRef<'a>>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<NodeRef<'a>> {
+        let next = self.stack.pop()?;
+        let start = self.stack.len();
+        self.stack.extend(next.children());
+        self.stack[start..].reverse();
+        Some(next)
+    }
+}
+
+/// Gathers the children of a single node.
+struct Children<'a>(Vec<NodeRef<'a>>);
+
+impl<'a> Children<'a> {
+    fn expression(&mut self, expression: &'a Expression) {
+        self.0.push(NodeRef::Expression(expression));
+    }
+
+    fn binding(&mut self, binding: &'a Binding) {
+        self.0.push(NodeRef::Binding(binding));
+    }
+
+    fn block(&mut self, block: &'a Block) {
+        for binding in &block.bindings {
+            self.binding(binding);
+        }
+        for expression in &block.expressions {
+            self.expression(expression);
+        }
+    }
+
+    fn arguments(&mut self, arguments: &'a CallArguments) {
+        for argument in &arguments.arguments {
+            self.expression(&argument.value);
+        }
+    }
+
+    fn expression_call(&mut self, call: &'a ExpressionCall) {
+        self.expression(&call.target);
+        self.arguments(&call.arguments);
+    }
+
+    fn cases(&mut self, cases: &'a [Case]) {
+        for case in cases {
+            self.case(case);
+        }
+    }
+
+    fn case(&mut self, case: &'a Case) {
+        for case_match in &case.matches {
+            if let Some(guard) = &case_match.guard {
+                self.expression(guard);
+            }
+        }
+        self.block(&case.body);
+    }
+
+    fn class(&mut self, class: &'a Class) {
+        for parameter in &class.value_parameters {
+            if let Some(default_value) = &parameter.parameter.default_value {
+                self.expression(default_value);
+            }
+        }
+        for field in &class.fields {
+            self.binding(&field.binding);
+        }
+        for method in &class.methods {
+            self.block(&method.scope);
+        }
+        self.block(&class.instance_initialiser);
+    }
+
+    fn item(&mut self, item: &'a Item) {
+        match item {
+            Item::Extension(extension) => self.class(&extension.item),
+            Item::Fun(fun) => {
+                for parameter in &fun.signature.value_parameters {
+                    if let Some(default_value) = &parameter.default_value {
+                        self.expression(default_value);
+                    }
+                }
+                self.block(&fun.block);
+            }
+            Item::Package(package) => {
+                for item in &package.items {
+                    self.0.push(NodeRef::Item(item));
+                }
+            }
+            Item::Type(r#type) => match &r#type.item {
+                TypeItem::Class(class) => self.class(class),
+                TypeItem::Enum(r#enum) => self.class(&r#enum.class),
+                TypeItem::Interface(interface) => {
+                    for method in &interface.methods {
+                        if let Method::Concrete(method) = method {
+                            self.block(&method.scope);
+                        }
+                    }
+                }
+            },
+            Item::Macro(Macro::Item(MacroItem::Call(call))) => self.arguments(&call.arguments),
+            Item::Macro(_) => {}
+            Item::Var(binding) | Item::Final(binding) | Item::Const(binding) => {
+                self.expression(&binding.value)
+            }
+        }
+    }
+
+    fn expression_parts(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::BranchingAndJumping(branching) => match branching {
+                BranchingAndJumping::ExpressionCall(call) => self.expression_call(call),
+                BranchingAndJumping::Call(call) => self.arguments(&call.arguments),
+                BranchingAndJumping::Cond(cond) => {
+                    for case in &cond.cases {
+                        for condition in &case.conditions {
+                            self.expression(condition);
+                        }
+                        if let Some(then) = &case.then {
+                            self.block(then);
+                        }
+                    }
+                    if let Some(else_clause) = &cond.else_clause {
+                        self.block(else_clause);
+                    }
+                }
+                BranchingAndJumping::For(r#for) => {
+                    for binding in &r#for.bindings {
+                        self.binding(binding);
+                    }
+                    if let Some(yield_expression) = &r#for.yield_expression {
+                        self.expression(yield_expression);
+                    }
+                    self.block(&r#for.scope);
+                    if let Some(else_clause) = &r#for.else_clause {
+                        self.block(else_clause);
+                    }
+                }
+                BranchingAndJumping::If(r#if) => {
+                    self.expression(&r#if.condition);
+                    self.block(&r#if.then);
+                    if let Some(else_clause) = &r#if.else_clause {
+                        self.block(else_clause);
+                    }
+                }
+                BranchingAndJumping::IfVar(if_var) => {
+                    self.binding(&if_var.binding);
+                    self.block(&if_var.then);
+                    if let Some(else_clause) = &if_var.else_clause {
+                        self.block(else_clause);
+                    }
+                }
+                BranchingAndJumping::Select(select) => {
+                    for select_case in &select.cases {
+                        self.case(&select_case.case);
+                    }
+                    if let Some(timeout) = &select.timeout {
+                        self.expression(&timeout.nanoseconds);
+                        self.block(&timeout.body);
+                    }
+                }
+                BranchingAndJumping::Switch(switch) => {
+                    self.expression(&switch.expression);
+                    self.cases(&switch.cases);
+                }
+                BranchingAndJumping::While(r#while) => {
+                    self.expression(&r#while.condition);
+                    self.block(&r#while.scope);
+                }
+                BranchingAndJumping::WhileVar(while_var) => {
+                    self.binding(&while_var.binding);
+                    self.block(&while_var.scope);
+                }
+                BranchingAndJumping::LabelledBlock(labelled) => self.block(&labelled.scope),
+                BranchingAndJumping::Break(r#break) => {
+                    if let Some(value) = &r#break.value {
+                        self.expression(value);
+                    }
+                }
+                BranchingAndJumping::Fallthrough => {}
+            },
+            Expression::Context(block) => self.block(block),
+            Expression::Spawn(spawn) => self.block(&spawn.task),
+            Expression::Literal(Literal::Lambda(lambda)) => {
+                for parameter in &lambda.signature.value_parameters {
+                    if let Some(default_value) = &parameter.default_value {
+                        self.expression(default_value);
+                    }
+                }
+                self.block(&lambda.block);
+            }
+            Expression::Literal(Literal::Record(record)) => {
+                for field in &record.0 {
+                    self.expression(&field.value);
+                }
+            }
+            Expression::Literal(Literal::Tuple(tuple)) => {
+                for element in &tuple.0 {
+                    self.expression(element);
+                }
+            }
+            Expression::Literal(Literal::List(list)) => {
+                for element in &list.elements {
+                    self.expression(element);
+                }
+            }
+            Expression::Literal(_) => {}
+            Expression::Operator(Operator::OverloadableInfix(left, _, right)) => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::Operator(Operator::Postfix(operand, _))
+            | Expression::Operator(Operator::Lookup(operand, _))
+            | Expression::Operator(Operator::OptionalChain(operand, _))
+            | Expression::Operator(Operator::MultiSlice(operand, _)) => self.expression(operand),
+            Expression::Symbol(_)
+            | Expression::Hole(_)
+            | Expression::MemberHandle(_)
+            | Expression::Operator(Operator::Reference(_)) => {}
+            Expression::Throw(throw) => {
+                self.expression(&throw.exception);
+                if let Some(cause) = &throw.cause {
+                    self.expression(cause);
+                }
+            }
+            Expression::Use(r#use) => self.expression(&r#use.0),
+            Expression::Defer(defer) => self.block(&defer.0),
+            Expression::NonDestructiveUpdate(call) => self.expression_call(call),
+            Expression::ReaderMacroActivation(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn binding_of(source: &str) -> Binding {
+        let file = parse_str(source).unwrap();
+        file.package.block.bindings[0].clone()
+    }
+
+    #[test]
+    fn children() {
+        let binding = binding_of("package main\n var x = f(1 + 2, g(3))");
+        let children = NodeRef::Binding(&binding).children().collect::<Vec<_>>();
+        assert_eq!(children, vec![NodeRef::Expression(&binding.value)]);
+
+        let call = Node::Expression(binding.value.clone());
+        assert_eq!(call.children().count(), 2);
+    }
+
+    #[test]
+    fn descendants() {
+        let binding = binding_of("package main\n var x = f(1 + 2, g(3))");
+        assert_eq!(NodeRef::Binding(&binding).descendants().count(), 6);
+
+        let literals = NodeRef::Binding(&binding)
+            .descendants()
+            .filter_map(|node| match node {
+                NodeRef::Expression(Expression::Literal(Literal::Number(number, _))) => {
+                    Some(number.0)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(literals, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn descendants_through_blocks() {
+        let file = parse_str("package main\n if a { var b = 1\n f(b) } else { g() }").unwrap();
+        let node = Node::Expression(Box::new(file.package.block.expressions[0].clone()));
+
+        // The condition, the binding and its value, the call and its argument,
+        // and the else branch's call.
+        assert_eq!(node.descendants().count(), 6);
+    }
+}
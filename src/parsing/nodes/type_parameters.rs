@@ -0,0 +1,154 @@
+//! Checks that no generic declaration repeats a type parameter's name, as in
+//! `class Pair[T, T]`, since uses of the name couldn't tell the two apart.
+//!
+//! Classes, interfaces, and enums are checked along with their methods, as are
+//! functions and extensions. An extension's own type parameters share a scope
+//! with those of the type it extends, so they're checked together.
+
+use super::traversal::NodeRef;
+use super::{Class, FunSignature, Identifier, Item, Method, TypeItem, TypeParameter};
+use crate::common::diagnostics::Diagnostic;
+use crate::source::Span;
+
+/// A type parameter named the same as an earlier one in the same declaration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DuplicateTypeParameter {
+    pub name: Identifier,
+    pub first: Option<Span>,
+    pub duplicate: Option<Span>,
+}
+
+pub fn check_duplicate_type_parameters(items: &[Item]) -> Vec<DuplicateTypeParameter> {
+    let mut duplicates = vec![];
+    for item in items {
+        let root = NodeRef::Item(item);
+        for node in std::iter::once(root).chain(root.descendants()) {
+            if let NodeRef::Item(item) = node {
+                check_item(item, &mut duplicates);
+            }
+        }
+    }
+    duplicates
+}
+
+fn check_item(item: &Item, duplicates: &mut Vec<DuplicateTypeParameter>) {
+    match item {
+        Item::Fun(fun) => check_signature(&fun.signature, duplicates),
+        Item::Type(r#type) => {
+            check(r#type.type_parameters.iter(), duplicates);
+            match &r#type.item {
+                TypeItem::Class(class) => check_class(class, duplicates),
+                TypeItem::Enum(r#enum) => check_class(&r#enum.class, duplicates),
+                TypeItem::Interface(interface) => {
+                    for method in &interface.methods {
+                        let signature = match method {
+                            Method::Abstract(method) => &method.signature,
+                            Method::Concrete(method) => &method.r#abstract.signature,
+                        };
+                        check_signature(signature, duplicates);
+                    }
+                }
+            }
+        }
+        Item::Extension(extension) => {
+            let parameters = extension
+                .extension_parameters
+                .iter()
+                .chain(&extension.type_parameters);
+            check(parameters, duplicates);
+            check_class(&extension.item, duplicates);
+        }
+        Item::Package(_) | Item::Macro(_) | Item::Var(_) | Item::Final(_) | Item::Const(_) => {}
+    }
+}
+
+fn check_class(class: &Class, duplicates: &mut Vec<DuplicateTypeParameter>) {
+    for method in &class.methods {
+        check_signature(&method.r#abstract.signature, duplicates);
+    }
+}
+
+fn check_signature(signature: &FunSignature, duplicates: &mut Vec<DuplicateTypeParameter>) {
+    check(signature.type_parameters.iter(), duplicates);
+}
+
+fn check<'a>(
+    parameters: impl Iterator<Item = &'a TypeParameter>,
+    duplicates: &mut Vec<DuplicateTypeParameter>,
+) {
+    let mut seen: Vec<&Identifier> = vec![];
+    for parameter in parameters {
+        match seen.iter().find(|&&name| *name == parameter.name) {
+            Some(first) => duplicates.push(DuplicateTypeParameter {
+                name: parameter.name.clone(),
+                first: first.span(),
+                duplicate: parameter.name.span(),
+            }),
+            None => seen.push(&parameter.name),
+        }
+    }
+}
+
+impl From<&DuplicateTypeParameter> for Diagnostic {
+    fn from(duplicate: &DuplicateTypeParameter) -> Self {
+        Diagnostic::error(
+            "duplicate-type-parameter",
+            format!(
+                "the type parameter `{}` is declared more than once",
+                duplicate.name.0
+            ),
+            duplicate.duplicate,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_str;
+
+    fn duplicates_in(source: &str) -> Vec<DuplicateTypeParameter> {
+        let file = parse_str(source).unwrap();
+        check_duplicate_type_parameters(&file.package.package.items)
+    }
+
+    #[test]
+    fn duplicate_type_parameters_are_flagged() {
+        let duplicates = duplicates_in(
+            "package main
+            class Pair[T, T]
+            package inner {
+                class Table[K, V, K]
+            }",
+        );
+        let found = duplicates
+            .iter()
+            .map(|duplicate| {
+                let first = duplicate.first.unwrap().start;
+                let second = duplicate.duplicate.unwrap().start;
+                (
+                    duplicate.name.0.as_str(),
+                    (first.line(), first.character_position_in_line()),
+                    (second.line(), second.character_position_in_line()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![("T", (2, 24), (2, 27)), ("K", (4, 29), (4, 35))]
+        );
+    }
+
+    #[test]
+    fn distinct_type_parameters_are_allowed() {
+        assert_eq!(
+            duplicates_in(
+                "package main
+                class Pair[A, B]
+                class Other[A]
+                class Bounded[T extends Comparable[T]]"
+            ),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! The parser reads tokens through a `TokenStream` rather than directly from `Tokens`. It behaves
+//! identically, but remembers where the last consumed token was so that parser errors can report
+//! a position without every sub-parser having to carry one around.
+
+use crate::common::peekable_buffer::PeekableBuffer;
+use crate::lexing::lexer::{LexedToken, LexerTaskError};
+use crate::lexing::{LexedTokenReadMany, Tokens};
+use crate::source::Position;
+
+pub struct TokenStream {
+    tokens: Tokens,
+    last_position: Option<Position>,
+}
+
+impl From<Tokens> for TokenStream {
+    fn from(tokens: Tokens) -> Self {
+        Self {
+            tokens,
+            last_position: None,
+        }
+    }
+}
+
+impl TokenStream {
+    /// The position of the next token to be consumed, if there is one.
+    pub fn current_position(&mut self) -> Option<Position> {
+        self.tokens.peek().map(|lexed| lexed.position)
+    }
+
+    /// The position of the most recently consumed token, if any have been consumed yet.
+    pub fn last_position(&self) -> Option<Position> {
+        self.last_position
+    }
+
+    pub fn join_lexer_thread(self) -> Result<(), LexerTaskError> {
+        self.tokens.join_lexer_thread()
+    }
+}
+
+impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for TokenStream {
+    fn peek_many(&mut self, n: usize) -> Option<&[LexedToken]> {
+        self.tokens.peek_many(n)
+    }
+
+    fn read_many(&mut self, n: usize) -> Option<LexedTokenReadMany> {
+        let read = self.tokens.read_many(n)?;
+        if 0 < n {
+            self.last_position = Some(read[n - 1].position);
+        }
+        Some(read)
+    }
+
+    fn discard_many(&mut self, n: usize) -> bool {
+        // Peek each token before discarding it, as `Tokens` doesn't hand back what it discards.
+        (0..n).all(|_| match self.tokens.peek() {
+            Some(lexed) => {
+                self.last_position = Some(lexed.position);
+                self.tokens.discard()
+            }
+            None => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexing::lexer::Lexer;
+    use crate::lexing::tokens::Token;
+    use crate::source::in_memory::Source;
+
+    fn token_stream(source: &str) -> TokenStream {
+        let lexer = Lexer::from(Source::from(source.chars().collect::<Vec<char>>()));
+        TokenStream::from(Tokens::from(lexer).unwrap())
+    }
+
+    #[test]
+    fn positions_advance_as_tokens_are_consumed() {
+        let mut tokens = token_stream("first\nsecond third");
+
+        assert!(tokens.last_position().is_none());
+        let current = tokens.current_position().unwrap();
+        assert_eq!(current.line(), 1);
+        assert_eq!(current.character_position_in_line(), 1);
+
+        tokens.read();
+        let last = tokens.last_position().unwrap();
+        assert_eq!(last.line(), 1);
+        assert_eq!(last.character_position_in_line(), 1);
+        let current = tokens.current_position().unwrap();
+        assert_eq!(current.line(), 2);
+        assert_eq!(current.character_position_in_line(), 1);
+
+        tokens.discard();
+        let last = tokens.last_position().unwrap();
+        assert_eq!(last.line(), 2);
+        assert_eq!(last.character_position_in_line(), 1);
+        let current = tokens.current_position().unwrap();
+        assert_eq!(current.line(), 2);
+        assert_eq!(current.character_position_in_line(), 8);
+
+        assert!(tokens.match_next(|lexed| lexed.token == Token::Identifier("third".into())));
+        tokens.read_many(2);
+        assert!(tokens.current_position().is_none());
+        assert_eq!(
+            tokens.last_position().unwrap().character_position_in_line(),
+            13
+        );
+    }
+}
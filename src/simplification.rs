@@ -0,0 +1,200 @@
+//! # Simplification
+//!
+//! Simplification lowers the AST into Kernel Sylan, a strict subset of Sylan
+//! without the conveniences of the full language. Type checking and Sylan IL
+//! generation both work from Kernel Sylan, so anything they report or generate
+//! must be traceable back to the original source. Kernel nodes therefore keep
+//! the span of the AST node they came from, ready for source maps and for
+//! pointing diagnostics at the right place.
+//!
+//! Only conds are simplified so far. They are lowered into nested `if`s, which
+//! is already how `else if` ladders are parsed, leaving `if` as the only
+//! conditional for later stages.
+
+use crate::common::multiphase::{Identifier, OverloadableInfixOperator};
+use crate::parsing::nodes::transformation::{walk_expression, Transformer};
+use crate::parsing::nodes::{
+    self, BranchingAndJumping, Cond, CondCase, Expression, If, MainFile, Node, Operator, Symbol,
+    SymbolLookup,
+};
+
+/// The file with every cond, however deeply nested, lowered into `if`s.
+///
+/// Each arm becomes an `if` whose `else` holds the `if`s of the arms after it,
+/// with the cond's own `else` innermost. An arm's conditions are joined with
+/// `||`, which evaluates them in order and stops at the first true one, just as
+/// the cond would. A bodyless arm yields `True`, as the condition it yields
+/// could only have been true.
+pub fn lower_conds(file: &MainFile) -> MainFile {
+    let mut file = file.clone();
+    file.package.package.items = file
+        .package
+        .package
+        .items
+        .into_iter()
+        .map(|item| CondLowering.transform_item(item))
+        .collect();
+    file.package.block = CondLowering.transform_block(file.package.block);
+    file
+}
+
+/// A node, such as a line entered at the REPL, with its conds lowered as in
+/// [lower_conds].
+pub fn lower_node_conds(node: &Node) -> Node {
+    match node.clone() {
+        Node::Item(item) => Node::Item(Box::new(CondLowering.transform_item(*item))),
+        Node::Expression(expression) => {
+            Node::Expression(Box::new(CondLowering.transform_expression(*expression)))
+        }
+    }
+}
+
+struct CondLowering;
+
+impl Transformer for CondLowering {
+    fn transform_expression(&mut self, expression: Expression) -> Expression {
+        match walk_expression(self, expression) {
+            Expression::BranchingAndJumping(BranchingAndJumping::Cond(cond)) => lower_cond(cond),
+            expression => expression,
+        }
+    }
+}
+
+/// A cond without arms just runs its `else`, if it has one.
+fn lower_cond(cond: Cond) -> Expression {
+    let Cond {
+        mut cases,
+        else_clause,
+    } = cond;
+    let last = match cases.pop() {
+        Some(last) => last,
+        None => return Expression::Context(else_clause.unwrap_or_else(nodes::Block::new_root)),
+    };
+    let mut lowered = lower_case(last, else_clause);
+    for case in cases.into_iter().rev() {
+        lowered = lower_case(case, Some(synthesised_block(lowered)));
+    }
+    lowered
+}
+
+fn lower_case(case: CondCase, else_clause: Option<nodes::Block>) -> Expression {
+    let CondCase {
+        conditions, then, ..
+    } = case;
+    let condition = conditions
+        .into_iter()
+        .reduce(|left, right| {
+            Expression::Operator(Operator::OverloadableInfix(
+                Box::new(left),
+                OverloadableInfixOperator::Or,
+                Box::new(right),
+            ))
+        })
+        .expect("cond arms have at least one condition");
+    let then = then.unwrap_or_else(|| {
+        synthesised_block(Expression::Symbol(Symbol::Relative(SymbolLookup(vec![
+            Identifier::from("True"),
+        ]))))
+    });
+    Expression::BranchingAndJumping(BranchingAndJumping::If(If {
+        condition: Box::new(condition),
+        then,
+        else_clause,
+    }))
+}
+
+/// A block made up by lowering rather than parsed, so it has no span.
+fn synthesised_block(expression: Expression) -> nodes::Block {
+    nodes::Block {
+        expressions: vec![expression],
+        ..nodes::Block::new_root()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intepreter::values::Value;
+    use crate::intepreter::{Error, Interpreter};
+    use crate::parsing::nodes::content_hashing::hash_main_file;
+    use crate::parsing::parse_str;
+
+    #[test]
+    fn conds_lower_to_nested_ifs() {
+        let lowered = lower_conds(
+            &parse_str(
+                "package main
+                switch {
+                    x < 0 { \"negative\" }
+                    x == 0, x == 1 { \"small\" }
+                    x > 100:
+                    else { \"other\" }
+                }",
+            )
+            .unwrap(),
+        );
+        let nested = parse_str(
+            "package main
+            if x < 0 {
+                \"negative\"
+            } else if (x == 0) || (x == 1) {
+                \"small\"
+            } else if x > 100 {
+                True
+            } else {
+                \"other\"
+            }",
+        )
+        .unwrap();
+        assert_eq!(hash_main_file(&lowered), hash_main_file(&nested));
+
+        let empty = lower_conds(&parse_str("package main\nswitch { else { 1 } }").unwrap());
+        assert!(matches!(
+            empty.package.block.expressions[0],
+            Expression::Context(_)
+        ));
+    }
+
+    #[test]
+    fn lowered_conds_evaluate_in_order() {
+        // Dividing by zero fails, so the results show which conditions were
+        // evaluated as well as which arm was taken.
+        let source = |x: i64| {
+            format!(
+                "package main
+                var x = {}
+                var f = -> {{
+                    switch {{
+                        x < 0 {{ \"negative\" }}
+                        x == 0, x == 1 {{ \"small\" }}
+                        x == 2:
+                        x > 100 {{ switch {{ x > 1000 {{ \"huge\" }} }} }}
+                        x == 3, 1 / 0 == 0:
+                    }}
+                }}
+                f()",
+                x
+            )
+        };
+        let string = |s: &str| Ok(Value::String(s.to_owned()));
+        let division_by_zero = Err(Error::Described("division by zero".to_owned()));
+        for (x, expected) in [
+            (-1, string("negative")),
+            (0, string("small")),
+            (1, string("small")),
+            (2, Ok(Value::Boolean(true))),
+            (3, Ok(Value::Boolean(true))),
+            (4, division_by_zero),
+            (200, Ok(Value::Void)),
+            (2000, string("huge")),
+        ] {
+            let file = parse_str(&source(x)).unwrap();
+            assert_eq!(
+                Interpreter::default().run(&lower_conds(&file)),
+                expected,
+                "x = {}",
+                x
+            );
+        }
+    }
+}
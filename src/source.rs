@@ -10,6 +10,7 @@
 //! commences on already-streamed fragments without breaking compatibility.
 
 use crate::common::newlines::{check_newline, NewLine};
+use std::cmp::Ordering;
 use std::ops::Index;
 
 pub mod in_memory;
@@ -26,7 +27,7 @@ impl<'a> Index<usize> for CharReadMany<'a> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Position {
     /// Suitable for calculating offsets in a lexer.
     absolute_character_index: usize,
@@ -42,6 +43,37 @@ impl Position {
         self.absolute_character_index + 1
     }
 
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn absolute_character_index(&self) -> usize {
+        self.absolute_character_index
+    }
+
+    pub fn character_position_in_line(&self) -> usize {
+        self.character_position_in_line
+    }
+
+    /// The first character of a line, for when it's worked out from a later
+    /// position rather than found by reading up to it.
+    pub fn start_of_line(line: usize, absolute_character_index: usize) -> Self {
+        Self {
+            absolute_character_index,
+            character_position_in_line: 1,
+            line,
+        }
+    }
+
+    /// The position a number of characters further along the same line.
+    pub fn along_line(self, characters: usize) -> Self {
+        Self {
+            absolute_character_index: self.absolute_character_index + characters,
+            character_position_in_line: self.character_position_in_line + characters,
+            line: self.line,
+        }
+    }
+
     fn increment_position_line(&mut self) {
         self.character_position_in_line = 1;
         self.line += 1;
@@ -62,12 +94,30 @@ impl Position {
                 }
                 if newline.is_some() {
                     self.increment_position_line()
+                } else {
+                    self.character_position_in_line += 1;
                 }
             }
         }
     }
 }
 
+// Positions are ordered by where they are in the source. Their lines and
+// characters follow from their indices, so only the indices are compared.
+
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Position {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.absolute_character_index
+            .cmp(&other.absolute_character_index)
+    }
+}
+
 impl Default for Position {
     fn default() -> Self {
         Self {
@@ -78,6 +128,25 @@ impl Default for Position {
     }
 }
 
+/// Where a node came from in the source, from its first token to its last.
+/// Both ends are where those tokens start, so the last token is included.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    /// A span of the single position, for things found at a point rather than
+    /// across a node, such as where the lexer gave up.
+    pub fn at(position: Position) -> Self {
+        Self {
+            start: position,
+            end: position,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::common::peekable_buffer::PeekableBuffer;
@@ -146,6 +215,11 @@ mod tests {
         assert_eq!(source.position.line, 4);
         assert_eq!(source.position.character_position_in_line, 1);
 
+        // Test tracking within a line.
+        source.discard_many(4);
+        assert_eq!(source.position.line(), 4);
+        assert_eq!(source.position.character_position_in_line(), 5);
+
         assert_eq!(
             source.position.absolute_character_index + 1,
             source.position.character_position()
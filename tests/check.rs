@@ -0,0 +1,49 @@
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+fn check(name: &str, source: &str) -> Output {
+    let path = env::temp_dir().join(format!("sylan-check-{}.sy", name));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_sylan"))
+        .arg("--check")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+fn lines(output: &Output) -> Vec<String> {
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn problems_are_reported_in_source_order() {
+    let output = check(
+        "problems",
+        "package main\n\nfun main() {\n    var y = y\n    println(z)\n}\n",
+    );
+    assert!(!output.status.success());
+    assert_eq!(
+        lines(&output),
+        vec![
+            "4:5: warning: `y` is bound to itself [self-binding]",
+            "5:13: error: nothing called `z` is in scope [unresolved-name]",
+        ]
+    );
+}
+
+#[test]
+fn warnings_alone_pass() {
+    let output = check("warnings", "package main\n \tprintln(\"hi\")\n");
+    assert!(output.status.success());
+    assert_eq!(
+        lines(&output),
+        vec!["2:1: warning: line 2 mixes tabs and spaces in its indentation [mixed-indentation]"]
+    );
+}
@@ -0,0 +1,43 @@
+use std::env;
+use std::fs;
+use std::process::{Command, Output};
+
+fn format(name: &str, source: &str) -> Output {
+    let path = env::temp_dir().join(format!("sylan-format-{}.sy", name));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_sylan"))
+        .arg("--format")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn sources_are_printed_reformatted() {
+    let output = format(
+        "reformatted",
+        "package main\nfun main() {   var x = 1 +  2\n\tprintln( x ) }\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "package main\n\nfun main() {\n    var x = 1 + 2\n    println(x)\n}\n"
+    );
+}
+
+#[test]
+fn unformattable_sources_print_nothing() {
+    for (name, source) in &[
+        ("unparsable", "package main\nfun main( {\n"),
+        (
+            "unsupported",
+            "package main\ninterface Shape { fun area() Float }\n",
+        ),
+    ] {
+        let output = format(name, source);
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+}
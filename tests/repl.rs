@@ -0,0 +1,61 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn repl(lines: &[&str]) -> Vec<String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sylan"))
+        .arg("--repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for line in lines {
+        writeln!(stdin, "{}", line).unwrap();
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .split("> ")
+        .map(|output| output.trim().to_owned())
+        .filter(|output| !output.is_empty())
+        .collect()
+}
+
+#[test]
+fn bindings_persist_across_lines() {
+    let outputs = repl(&[
+        "var x = 1",
+        "x + 1",
+        "var add = -> a, b { a + b }",
+        "add(x, 41)",
+    ]);
+    assert_eq!(outputs, vec!["2", "42"]);
+}
+
+#[test]
+fn errors_do_not_end_the_session() {
+    let outputs = repl(&["var x = 1", "y + 1", "var x = 2", "x +", "x * 10"]);
+    assert_eq!(outputs.len(), 4);
+    assert_eq!(outputs[0], "error: unknown name: y");
+    assert_eq!(outputs[1], "error: x is already bound");
+    assert!(outputs[2].starts_with("error: failed to parse"));
+    assert_eq!(outputs[3], "10");
+}
+
+#[test]
+fn integers_can_be_shown_in_other_radixes() {
+    let outputs = repl(&[
+        "255",
+        ":radix hex",
+        "255",
+        ":radix bin",
+        "5",
+        ":radix dec",
+        "255",
+    ]);
+    assert_eq!(outputs, vec!["255", "0xff", "0b101", "255"]);
+}